@@ -36,6 +36,13 @@ pub struct Cli {
 	/// telemetry, if telemetry is enabled.
 	#[clap(long)]
 	pub no_hardware_benchmarks: bool,
+
+	/// Directory holding the ink! metadata of contract codes.
+	///
+	/// The metadata of a code is read from the file named after its hex encoded code hash,
+	/// e.g. `0x1234…cdef.json`, unless metadata is registered on chain for the code.
+	#[clap(long, value_name = "PATH", parse(from_os_str))]
+	pub contracts_metadata_dir: Option<std::path::PathBuf>,
 }
 
 /// Possible subcommands of the main binary.
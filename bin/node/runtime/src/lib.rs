@@ -1036,6 +1036,11 @@ parameter_types! {
 			<Runtime as pallet_contracts::Config>::WeightInfo::on_initialize_per_queue_item(0)
 		)) / 5) as u32;
 	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+	pub const ContractsChainId: &'static str = "substrate-node";
+	pub const ContractsNativeDenom: &'static str = "unit";
+	pub const ContractsCapabilities: &'static [&'static str] = &["iterator", "stargate"];
+	pub const ContractsAssetDenomPrefix: &'static str = "asset/";
+	pub const ContractsWeightPerGas: Weight = 140_000;
 }
 
 impl pallet_contracts::Config for Runtime {
@@ -1061,6 +1066,14 @@ impl pallet_contracts::Config for Runtime {
 	type DeletionWeightLimit = DeletionWeightLimit;
 	type Schedule = Schedule;
 	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+	type AddressCodec = pallet_contracts::Ss58AddressCodec;
+	type ChainId = ContractsChainId;
+	type NativeDenom = ContractsNativeDenom;
+	type Capabilities = ContractsCapabilities;
+	type Assets = Assets;
+	type DenomResolver = pallet_contracts::PrefixedDenomResolver<ContractsAssetDenomPrefix>;
+	type GasConverter = pallet_contracts::FixedGasRatio<ContractsWeightPerGas>;
+	type ContractsPauseOrigin = EnsureRootOrHalfCouncil;
 }
 
 impl pallet_sudo::Config for Runtime {
@@ -1748,6 +1761,50 @@ impl_runtime_apis! {
 			Contracts::bare_call(origin, dest, value, gas_limit, storage_deposit_limit, input_data, true)
 		}
 
+		fn trace_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractTraceResult<AccountId, Balance> {
+			Contracts::bare_trace_call(
+				origin,
+				dest,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				input_data,
+			)
+		}
+
+		fn estimate_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractEstimateResult<Balance> {
+			Contracts::bare_estimate_call(
+				origin,
+				dest,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				input_data,
+			)
+		}
+
+		fn query(
+			dest: AccountId,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult<Balance> {
+			Contracts::bare_query(dest, gas_limit, input_data, true)
+		}
+
 		fn instantiate(
 			origin: AccountId,
 			value: Balance,
@@ -1776,6 +1833,22 @@ impl_runtime_apis! {
 		) -> pallet_contracts_primitives::GetStorageResult {
 			Contracts::get_storage(address, key)
 		}
+
+		fn contracts_by_label(label: Vec<u8>) -> Vec<AccountId> {
+			Contracts::contracts_by_label(&label)
+		}
+
+		fn contract_events() -> Vec<pallet_contracts_primitives::ContractEvent<AccountId, Hash>> {
+			Contracts::contract_events()
+		}
+
+		fn code_hash(address: AccountId) -> Option<Hash> {
+			Contracts::code_hash(&address)
+		}
+
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>> {
+			Contracts::code_metadata(&code_hash)
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
@@ -18,11 +18,16 @@
 
 //! This module implements sandboxing support in the runtime.
 //!
-//! Sandboxing is backed by wasmi and wasmer, depending on the configuration.
+//! Sandboxing is backed by wasmi, wasmer and wasmtime, depending on the configuration.
 
 #[cfg(feature = "wasmer-sandbox")]
 mod wasmer_backend;
 mod wasmi_backend;
+#[cfg(feature = "wasmtime-sandbox")]
+mod wasmtime_backend;
+
+#[cfg(test)]
+mod tests;
 
 use std::{collections::HashMap, rc::Rc};
 
@@ -37,13 +42,21 @@ use crate::{
 
 #[cfg(feature = "wasmer-sandbox")]
 use self::wasmer_backend::{
-	get_global as wasmer_get_global, instantiate as wasmer_instantiate, invoke as wasmer_invoke,
-	new_memory as wasmer_new_memory, Backend as WasmerBackend,
-	MemoryWrapper as WasmerMemoryWrapper,
+	get_global as wasmer_get_global, get_memory as wasmer_get_memory,
+	instantiate as wasmer_instantiate, invoke as wasmer_invoke, new_memory as wasmer_new_memory,
+	Backend as WasmerBackend, MemoryWrapper as WasmerMemoryWrapper,
 };
 use self::wasmi_backend::{
-	get_global as wasmi_get_global, instantiate as wasmi_instantiate, invoke as wasmi_invoke,
-	new_memory as wasmi_new_memory, MemoryWrapper as WasmiMemoryWrapper,
+	get_global as wasmi_get_global, get_memory as wasmi_get_memory,
+	instantiate as wasmi_instantiate, invoke as wasmi_invoke, new_memory as wasmi_new_memory,
+	MemoryWrapper as WasmiMemoryWrapper,
+};
+#[cfg(feature = "wasmtime-sandbox")]
+use self::wasmtime_backend::{
+	get_global as wasmtime_get_global, get_memory as wasmtime_get_memory,
+	instantiate as wasmtime_instantiate, invoke as wasmtime_invoke,
+	new_memory as wasmtime_new_memory, Backend as WasmtimeBackend, Instance as WasmtimeInstance,
+	MemoryWrapper as WasmtimeMemoryWrapper,
 };
 
 /// Index of a function inside the supervisor.
@@ -163,6 +176,10 @@ enum BackendInstance {
 	/// Wasmer module instance
 	#[cfg(feature = "wasmer-sandbox")]
 	Wasmer(wasmer::Instance),
+
+	/// Wasmtime module instance
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(WasmtimeInstance),
 }
 
 /// Sandboxed instance of a wasm module.
@@ -206,6 +223,10 @@ impl SandboxInstance {
 			#[cfg(feature = "wasmer-sandbox")]
 			BackendInstance::Wasmer(wasmer_instance) =>
 				wasmer_invoke(wasmer_instance, export_name, args, state, sandbox_context),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendInstance::Wasmtime(wasmtime_instance) =>
+				wasmtime_invoke(wasmtime_instance, export_name, args, state, sandbox_context),
 		}
 	}
 
@@ -218,6 +239,26 @@ impl SandboxInstance {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			BackendInstance::Wasmer(wasmer_instance) => wasmer_get_global(wasmer_instance, name),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendInstance::Wasmtime(wasmtime_instance) =>
+				wasmtime_get_global(wasmtime_instance, name),
+		}
+	}
+
+	/// Get the memory exported under the given `name`.
+	///
+	/// Returns `Some(_)` if the memory could be found.
+	pub fn get_memory(&self, name: &str) -> Option<Memory> {
+		match &self.backend_instance {
+			BackendInstance::Wasmi(wasmi_instance) => wasmi_get_memory(wasmi_instance, name),
+
+			#[cfg(feature = "wasmer-sandbox")]
+			BackendInstance::Wasmer(wasmer_instance) => wasmer_get_memory(wasmer_instance, name),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendInstance::Wasmtime(wasmtime_instance) =>
+				wasmtime_get_memory(wasmtime_instance, name),
 		}
 	}
 }
@@ -324,6 +365,13 @@ pub enum SandboxBackend {
 
 	/// Use wasmer backend if available. Fall back to wasmi otherwise.
 	TryWasmer,
+
+	/// Wasmtime environment
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime,
+
+	/// Use wasmtime backend if available. Fall back to `TryWasmer` otherwise.
+	TryWasmtime,
 }
 
 /// Memory reference in terms of a selected backend
@@ -335,6 +383,10 @@ pub enum Memory {
 	/// Wasmer memory refernce
 	#[cfg(feature = "wasmer-sandbox")]
 	Wasmer(WasmerMemoryWrapper),
+
+	/// Wasmtime memory reference
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(WasmtimeMemoryWrapper),
 }
 
 impl Memory {
@@ -345,6 +397,9 @@ impl Memory {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			Memory::Wasmer(_) => None,
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(_) => None,
 		}
 	}
 
@@ -354,6 +409,21 @@ impl Memory {
 		match self {
 			Memory::Wasmer(memory) => Some(memory.clone()),
 			Memory::Wasmi(_) => None,
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(_) => None,
+		}
+	}
+
+	/// View as wasmtime memory
+	#[cfg(feature = "wasmtime-sandbox")]
+	pub fn as_wasmtime(&self) -> Option<WasmtimeMemoryWrapper> {
+		match self {
+			Memory::Wasmtime(memory) => Some(memory.clone()),
+			Memory::Wasmi(_) => None,
+
+			#[cfg(feature = "wasmer-sandbox")]
+			Memory::Wasmer(_) => None,
 		}
 	}
 }
@@ -365,6 +435,9 @@ impl util::MemoryTransfer for Memory {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			Memory::Wasmer(sandboxed_memory) => sandboxed_memory.read(source_addr, size),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(sandboxed_memory) => sandboxed_memory.read(source_addr, size),
 		}
 	}
 
@@ -374,6 +447,10 @@ impl util::MemoryTransfer for Memory {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			Memory::Wasmer(sandboxed_memory) => sandboxed_memory.read_into(source_addr, destination),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(sandboxed_memory) =>
+				sandboxed_memory.read_into(source_addr, destination),
 		}
 	}
 
@@ -383,6 +460,9 @@ impl util::MemoryTransfer for Memory {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			Memory::Wasmer(sandboxed_memory) => sandboxed_memory.write_from(dest_addr, source),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			Memory::Wasmtime(sandboxed_memory) => sandboxed_memory.write_from(dest_addr, source),
 		}
 	}
 }
@@ -395,6 +475,10 @@ enum BackendContext {
 	/// Wasmer specific context
 	#[cfg(feature = "wasmer-sandbox")]
 	Wasmer(WasmerBackend),
+
+	/// Wasmtime specific context
+	#[cfg(feature = "wasmtime-sandbox")]
+	Wasmtime(WasmtimeBackend),
 }
 
 impl BackendContext {
@@ -408,6 +492,13 @@ impl BackendContext {
 			#[cfg(feature = "wasmer-sandbox")]
 			SandboxBackend::Wasmer | SandboxBackend::TryWasmer =>
 				BackendContext::Wasmer(WasmerBackend::new()),
+
+			#[cfg(not(feature = "wasmtime-sandbox"))]
+			SandboxBackend::TryWasmtime => BackendContext::new(SandboxBackend::TryWasmer),
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			SandboxBackend::Wasmtime | SandboxBackend::TryWasmtime =>
+				BackendContext::Wasmtime(WasmtimeBackend::new()),
 		}
 	}
 }
@@ -455,6 +546,9 @@ impl<DT: Clone> Store<DT> {
 
 			#[cfg(feature = "wasmer-sandbox")]
 			BackendContext::Wasmer(context) => wasmer_new_memory(context, initial, maximum)?,
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendContext::Wasmtime(context) => wasmtime_new_memory(context, initial, maximum)?,
 		};
 
 		let mem_idx = memories.len();
@@ -508,6 +602,27 @@ impl<DT: Clone> Store<DT> {
 			.ok_or_else(|| "Trying to access a torndown sandboxed memory".into())
 	}
 
+	/// Register the memory exported under `name` by the instance at `instance_idx` and return
+	/// its index.
+	///
+	/// Returns `None` if the instance doesn't export a memory under that name.
+	///
+	/// # Errors
+	///
+	/// Returns `Err` If `instance_idx` isn't a valid index of an instance or
+	/// instance is already torndown.
+	pub fn instance_memory(&mut self, instance_idx: u32, name: &str) -> Result<Option<u32>> {
+		let memory = match self.instance(instance_idx)?.get_memory(name) {
+			Some(memory) => memory,
+			None => return Ok(None),
+		};
+
+		let mem_idx = self.memories.len();
+		self.memories.push(Some(memory));
+
+		Ok(Some(mem_idx as u32))
+	}
+
 	/// Tear down the memory at the specified index.
 	///
 	/// # Errors
@@ -565,6 +680,10 @@ impl<DT: Clone> Store<DT> {
 			#[cfg(feature = "wasmer-sandbox")]
 			BackendContext::Wasmer(ref context) =>
 				wasmer_instantiate(&context, wasm, guest_env, state, sandbox_context)?,
+
+			#[cfg(feature = "wasmtime-sandbox")]
+			BackendContext::Wasmtime(ref context) =>
+				wasmtime_instantiate(context, wasm, guest_env, state, sandbox_context)?,
 		};
 
 		Ok(UnregisteredInstance { sandbox_instance })
@@ -0,0 +1,331 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Differential tests of the sandbox backends against wasmi.
+//!
+//! Contracts are instrumented the way `pallet-contracts` does it and executed under every
+//! enabled backend, which must agree with wasmi on the gas left, the results and the kind of
+//! traps.
+
+use codec::{Decode, Encode};
+use sp_sandbox::{
+	env::{Entry, EnvironmentDefinition, ExternEntity},
+	HostError,
+};
+use sp_wasm_interface::{FunctionContext, Pointer, ReturnValue, Sandbox, Value, WordSize};
+use wasm_instrument::{
+	gas_metering::{self, ConstantCostRules},
+	parity_wasm::{deserialize_buffer, elements::Module, serialize},
+};
+
+use super::{GuestEnvironment, SandboxBackend, SandboxContext, Store, SupervisorFuncIndex};
+use crate::error::{Error, Result};
+
+/// Index of the supervisor function which charges the gas passed to it.
+const GAS_FUNC_INDEX: u32 = 0;
+
+/// Stack height limit the contracts are instrumented with.
+const STACK_HEIGHT_LIMIT: u32 = 128;
+
+/// Supervisor of the sandboxed contracts, which meters their gas.
+struct Supervisor {
+	memory: Vec<u8>,
+	gas_left: u64,
+}
+
+impl Supervisor {
+	fn charge_gas(&mut self, amount: u64) -> std::result::Result<ReturnValue, HostError> {
+		self.gas_left = self.gas_left.checked_sub(amount).ok_or(HostError)?;
+		Ok(ReturnValue::Unit)
+	}
+}
+
+impl FunctionContext for Supervisor {
+	fn read_memory_into(
+		&self,
+		address: Pointer<u8>,
+		dest: &mut [u8],
+	) -> sp_wasm_interface::Result<()> {
+		let start = u32::from(address) as usize;
+		let source = self.memory.get(start..start + dest.len()).ok_or("read out of bounds")?;
+		dest.copy_from_slice(source);
+		Ok(())
+	}
+
+	fn write_memory(&mut self, address: Pointer<u8>, data: &[u8]) -> sp_wasm_interface::Result<()> {
+		let start = u32::from(address) as usize;
+		let dest = self.memory.get_mut(start..start + data.len()).ok_or("write out of bounds")?;
+		dest.copy_from_slice(data);
+		Ok(())
+	}
+
+	fn allocate_memory(&mut self, size: WordSize) -> sp_wasm_interface::Result<Pointer<u8>> {
+		let ptr = Pointer::new(self.memory.len() as u32);
+		self.memory.resize(self.memory.len() + size as usize, 0);
+		Ok(ptr)
+	}
+
+	fn deallocate_memory(&mut self, _ptr: Pointer<u8>) -> sp_wasm_interface::Result<()> {
+		Ok(())
+	}
+
+	fn sandbox(&mut self) -> &mut dyn Sandbox {
+		unimplemented!("the supervisor doesn't create sandboxes of its own")
+	}
+
+	fn register_panic_error_message(&mut self, _message: &str) {}
+}
+
+impl SandboxContext for Supervisor {
+	fn invoke(
+		&mut self,
+		invoke_args_ptr: Pointer<u8>,
+		invoke_args_len: WordSize,
+		_state: u32,
+		func_idx: SupervisorFuncIndex,
+	) -> Result<i64> {
+		let args = self.read_memory(invoke_args_ptr, invoke_args_len)?;
+		let args = Vec::<Value>::decode(&mut &args[..]).map_err(|_| "invalid arguments")?;
+		let result = match (usize::from(func_idx) as u32, args.as_slice()) {
+			(GAS_FUNC_INDEX, [Value::I32(amount)]) => self.charge_gas(*amount as u32 as u64),
+			_ => return Err("unknown supervisor function".into()),
+		}
+		.encode();
+
+		let ptr = self.allocate_memory(result.len() as WordSize)?;
+		self.write_memory(ptr, &result)?;
+		Ok((u32::from(ptr) as i64) << 32 | result.len() as i64)
+	}
+
+	fn supervisor_context(&mut self) -> &mut dyn FunctionContext {
+		self
+	}
+}
+
+/// Kind of a trap, as the error messages of the backends differ.
+#[derive(Debug, PartialEq)]
+enum TrapKind {
+	/// A supervisor function returned an error, e.g. because the gas ran out.
+	Host,
+	Unreachable,
+	DivisionByZero,
+	MemoryOutOfBounds,
+	Other(String),
+}
+
+impl From<Error> for TrapKind {
+	fn from(error: Error) -> Self {
+		let message = error.to_string();
+		if message.contains("HostError") {
+			TrapKind::Host
+		} else if message.contains("nreachable") {
+			TrapKind::Unreachable
+		} else if message.contains("DivisionByZero") || message.contains("divide by zero") {
+			TrapKind::DivisionByZero
+		} else if message.contains("MemoryAccessOutOfBounds") ||
+			message.contains("out of bounds memory access")
+		{
+			TrapKind::MemoryOutOfBounds
+		} else {
+			TrapKind::Other(message)
+		}
+	}
+}
+
+/// What executing a contract amounts to.
+#[derive(Debug, PartialEq)]
+struct Outcome {
+	result: std::result::Result<Option<Value>, TrapKind>,
+	gas_left: u64,
+}
+
+/// Inject gas metering and stack height limiting into the module `wat`.
+fn instrument(wat: &str) -> Vec<u8> {
+	let module: Module = deserialize_buffer(&wat::parse_str(wat).unwrap()).unwrap();
+	let module = gas_metering::inject(module, &ConstantCostRules::default(), "env").unwrap();
+	let module = wasm_instrument::inject_stack_limiter(module, STACK_HEIGHT_LIMIT).unwrap();
+	serialize(module).unwrap()
+}
+
+/// Call the export `call` of `code` with `gas` under `backend`.
+fn execute(backend: SandboxBackend, code: &[u8], gas: u64, args: &[Value]) -> Outcome {
+	let mut store = Store::<()>::new(backend);
+	let env_def = EnvironmentDefinition {
+		entries: vec![Entry {
+			module_name: b"env".to_vec(),
+			field_name: b"gas".to_vec(),
+			entity: ExternEntity::Function(GAS_FUNC_INDEX),
+		}],
+	};
+	let guest_env = GuestEnvironment::decode(&store, &env_def.encode())
+		.unwrap_or_else(|_| panic!("the environment definition is valid"));
+
+	let mut supervisor = Supervisor { memory: vec![0; 8], gas_left: gas };
+	let instance_idx = store
+		.instantiate(code, guest_env, 0, &mut supervisor)
+		.unwrap_or_else(|_| panic!("the contract can be instantiated"))
+		.register(&mut store, ());
+	let result = store
+		.instance(instance_idx)
+		.unwrap()
+		.invoke("call", args, 0, &mut supervisor)
+		.map_err(TrapKind::from);
+
+	Outcome { result, gas_left: supervisor.gas_left }
+}
+
+/// Execute the instrumented `wat` under every enabled backend, check that they agree with wasmi
+/// and return the outcome.
+fn differential(wat: &str, gas: u64, args: &[Value]) -> Outcome {
+	let code = instrument(wat);
+	let wasmi = execute(SandboxBackend::Wasmi, &code, gas, args);
+	#[cfg(feature = "wasmer-sandbox")]
+	assert_eq!(execute(SandboxBackend::Wasmer, &code, gas, args), wasmi, "wasmer differs");
+	#[cfg(feature = "wasmtime-sandbox")]
+	assert_eq!(execute(SandboxBackend::Wasmtime, &code, gas, args), wasmi, "wasmtime differs");
+	wasmi
+}
+
+const SUM: &str = r#"
+(module
+	(func (export "call") (param $n i32) (result i32)
+		(local $sum i32)
+		(block $done
+			(loop $next
+				(br_if $done (i32.eqz (get_local $n)))
+				(set_local $sum (i32.add (get_local $sum) (get_local $n)))
+				(set_local $n (i32.sub (get_local $n) (i32.const 1)))
+				(br $next)
+			)
+		)
+		(get_local $sum)
+	)
+)
+"#;
+
+#[test]
+fn results_and_gas_match() {
+	let outcome = differential(SUM, 100_000, &[Value::I32(100)]);
+	assert_eq!(outcome.result, Ok(Some(Value::I32(5050))));
+	assert!(outcome.gas_left < 100_000);
+
+	// The gas depends on the number of iterations.
+	let more_iterations = differential(SUM, 100_000, &[Value::I32(200)]);
+	assert!(more_iterations.gas_left < outcome.gas_left);
+}
+
+#[test]
+fn out_of_gas_traps_the_same_way() {
+	let outcome = differential(SUM, 1_000, &[Value::I32(1_000)]);
+	assert_eq!(outcome.result, Err(TrapKind::Host));
+	assert!(outcome.gas_left < 1_000);
+
+	let outcome = differential(
+		r#"
+		(module
+			(func (export "call")
+				(loop $forever (br $forever))
+			)
+		)
+		"#,
+		10_000,
+		&[],
+	);
+	assert_eq!(outcome.result, Err(TrapKind::Host));
+}
+
+#[test]
+fn stack_height_exhaustion_traps_the_same_way() {
+	let outcome = differential(
+		r#"
+		(module
+			(func $recurse (export "call") (param $depth i32) (result i32)
+				(if (result i32) (i32.eqz (get_local $depth))
+					(then (i32.const 0))
+					(else
+						(i32.add
+							(call $recurse (i32.sub (get_local $depth) (i32.const 1)))
+							(i32.const 1)
+						)
+					)
+				)
+			)
+		)
+		"#,
+		1_000_000,
+		&[Value::I32(10)],
+	);
+	assert_eq!(outcome.result, Ok(Some(Value::I32(10))));
+
+	let outcome = differential(
+		r#"
+		(module
+			(func $recurse (export "call")
+				(call $recurse)
+			)
+		)
+		"#,
+		1_000_000,
+		&[],
+	);
+	assert_eq!(outcome.result, Err(TrapKind::Unreachable));
+	assert!(outcome.gas_left > 0);
+}
+
+#[test]
+fn wasm_traps_match() {
+	let outcome = differential(
+		r#"
+		(module
+			(func (export "call") (result i32)
+				(i32.div_u (i32.const 1) (i32.const 0))
+			)
+		)
+		"#,
+		1_000,
+		&[],
+	);
+	assert_eq!(outcome.result, Err(TrapKind::DivisionByZero));
+
+	let outcome = differential(
+		r#"
+		(module
+			(memory 1 1)
+			(func (export "call") (result i32)
+				(i32.load (i32.const 65536))
+			)
+		)
+		"#,
+		1_000,
+		&[],
+	);
+	assert_eq!(outcome.result, Err(TrapKind::MemoryOutOfBounds));
+
+	let outcome = differential(
+		r#"
+		(module
+			(func (export "call")
+				(unreachable)
+			)
+		)
+		"#,
+		1_000,
+		&[],
+	);
+	assert_eq!(outcome.result, Err(TrapKind::Unreachable));
+}
@@ -447,3 +447,9 @@ pub fn get_global(instance: &wasmer::Instance, name: &str) -> Option<Value> {
 
 	Some(wasmtime_value)
 }
+
+/// Get exported memory by name
+pub fn get_memory(instance: &wasmer::Instance, name: &str) -> Option<Memory> {
+	let memory = instance.exports.get_memory(name).ok()?.clone();
+	Some(Memory::Wasmer(MemoryWrapper::new(memory)))
+}
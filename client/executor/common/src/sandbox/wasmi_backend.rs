@@ -326,3 +326,9 @@ pub fn invoke(
 pub fn get_global(instance: &wasmi::ModuleRef, name: &str) -> Option<Value> {
 	Some(instance.export_by_name(name)?.as_global()?.get().into())
 }
+
+/// Get exported memory by name
+pub fn get_memory(instance: &wasmi::ModuleRef, name: &str) -> Option<Memory> {
+	let memory = instance.export_by_name(name)?.as_memory()?.clone();
+	Some(Memory::Wasmi(MemoryWrapper::new(memory)))
+}
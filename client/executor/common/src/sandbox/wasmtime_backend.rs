@@ -0,0 +1,537 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Wasmtime specific impls for sandbox
+
+use std::{
+	cell::RefCell,
+	collections::HashMap,
+	rc::Rc,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use wasmtime::{AsContextMut, Trap};
+
+use codec::{Decode, Encode};
+use sp_sandbox::HostError;
+use sp_wasm_interface::{FunctionContext, Pointer, ReturnValue, Value, WordSize};
+
+use crate::{
+	error::{Error, Result},
+	sandbox::{
+		BackendInstance, GuestEnvironment, InstantiationError, Memory, SandboxContext,
+		SandboxInstance, SupervisorFuncIndex,
+	},
+	util::{checked_range, MemoryTransfer},
+};
+
+environmental::environmental!(SandboxContextStore: trait SandboxContext);
+
+/// Maximum number of pages a 32-bit linear memory can have.
+const MAX_PAGES: u32 = 65536;
+
+/// Source of the ids used to tell which store a `Caller` belongs to.
+static NEXT_STORE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Configuration of the sandbox engine.
+///
+/// Only the extensions understood by wasmi are enabled, so that both backends accept the
+/// same modules and behave the same way when executing them.
+fn config() -> wasmtime::Config {
+	let mut config = wasmtime::Config::new();
+	config.cranelift_opt_level(wasmtime::OptLevel::SpeedAndSize);
+	config.cranelift_nan_canonicalization(true);
+
+	config.wasm_reference_types(false);
+	config.wasm_simd(false);
+	config.wasm_bulk_memory(false);
+	config.wasm_multi_value(false);
+	config.wasm_multi_memory(false);
+	config.wasm_module_linking(false);
+	config.wasm_threads(false);
+	config.wasm_memory64(false);
+
+	config
+}
+
+/// Wasmtime specific context
+pub struct Backend {
+	engine: wasmtime::Engine,
+	/// Modules compiled by this backend keyed by the blake2-256 hash of their code.
+	///
+	/// The cache lives as long as the sandbox store which owns the backend, so that code
+	/// instantiated repeatedly during one runtime call is only compiled once.
+	modules: RefCell<HashMap<[u8; 32], wasmtime::Module>>,
+}
+
+impl Backend {
+	pub fn new() -> Self {
+		let engine = wasmtime::Engine::new(&config())
+			.expect("the configuration only disables features; thus it is valid; qed");
+		Backend { engine, modules: RefCell::new(HashMap::new()) }
+	}
+}
+
+/// Gives access to the store of the guest that called into the supervisor.
+trait CallerContext {
+	fn context(&mut self) -> wasmtime::StoreContextMut<'_, u64>;
+}
+
+impl CallerContext for wasmtime::Caller<'_, u64> {
+	fn context(&mut self) -> wasmtime::StoreContextMut<'_, u64> {
+		self.as_context_mut()
+	}
+}
+
+/// Keeps track of the innermost `Caller` while host functions execute.
+///
+/// This lives in its own module as `environmental` can only be used once per module.
+mod caller_context {
+	use super::CallerContext;
+
+	environmental::environmental!(CallerContextStore: trait CallerContext);
+
+	pub(super) fn using<R>(caller: &mut dyn CallerContext, f: impl FnOnce() -> R) -> R {
+		CallerContextStore::using(caller, f)
+	}
+
+	pub(super) fn with<R>(f: impl FnOnce(&mut dyn CallerContext) -> R) -> Option<R> {
+		CallerContextStore::with(f)
+	}
+}
+
+/// The store owning a sandboxed instance and the memories it uses.
+///
+/// Every instance gets a store of its own, unless it imports a memory, in which case it is
+/// placed in the store of that memory. This way everything is released as soon as the instance
+/// and its memories are torn down.
+#[derive(Debug)]
+struct SandboxStore {
+	/// Unique id of the store, kept as the store data to recognize its callers.
+	id: u64,
+	store: RefCell<wasmtime::Store<u64>>,
+}
+
+impl SandboxStore {
+	fn new(engine: &wasmtime::Engine) -> Rc<Self> {
+		let id = NEXT_STORE_ID.fetch_add(1, Ordering::Relaxed);
+		Rc::new(SandboxStore { id, store: RefCell::new(wasmtime::Store::new(engine, id)) })
+	}
+
+	/// Run `f` with the context of this store.
+	///
+	/// While a guest is executing, its store is borrowed by the call and is only reachable
+	/// through the `Caller` that was passed to the host function being executed.
+	fn with_context<R>(&self, f: impl FnOnce(wasmtime::StoreContextMut<u64>) -> R) -> Result<R> {
+		if let Ok(mut store) = self.store.try_borrow_mut() {
+			return Ok(f(store.as_context_mut()))
+		}
+
+		caller_context::with(|caller| {
+			let context = caller.context();
+			if *context.data() == self.id {
+				Some(f(context))
+			} else {
+				None
+			}
+		})
+		.flatten()
+		.ok_or_else(|| Error::Other("sandbox store is in use by another guest".into()))
+	}
+}
+
+/// Module instance along with the store that owns it
+pub struct Instance {
+	instance: wasmtime::Instance,
+	store: Rc<SandboxStore>,
+}
+
+/// Invoke a function within a sandboxed module
+pub fn invoke(
+	instance: &Instance,
+	export_name: &str,
+	args: &[Value],
+	_state: u32,
+	sandbox_context: &mut dyn SandboxContext,
+) -> std::result::Result<Option<Value>, Error> {
+	let args: Vec<wasmtime::Val> = args
+		.iter()
+		.map(|v| match *v {
+			Value::I32(val) => wasmtime::Val::I32(val),
+			Value::I64(val) => wasmtime::Val::I64(val),
+			Value::F32(val) => wasmtime::Val::F32(val),
+			Value::F64(val) => wasmtime::Val::F64(val),
+		})
+		.collect();
+
+	let wasmtime_result = instance.store.with_context(|mut context| -> Result<_> {
+		let function = instance
+			.instance
+			.get_func(&mut context, export_name)
+			.ok_or_else(|| Error::Sandbox(format!("function {} is not exported", export_name)))?;
+
+		let mut results = vec![wasmtime::Val::I32(0); function.ty(&context).results().len()];
+		SandboxContextStore::using(sandbox_context, || {
+			function.call(&mut context, &args, &mut results)
+		})
+		.map_err(|error| Error::Sandbox(error.to_string()))?;
+
+		Ok(results)
+	})??;
+
+	match wasmtime_result.as_slice() {
+		[] => Ok(None),
+
+		[wasm_value] => {
+			let wasmtime_value = match *wasm_value {
+				wasmtime::Val::I32(val) => Value::I32(val),
+				wasmtime::Val::I64(val) => Value::I64(val),
+				wasmtime::Val::F32(val) => Value::F32(val),
+				wasmtime::Val::F64(val) => Value::F64(val),
+				_ =>
+					return Err(Error::Sandbox(format!(
+						"Unsupported return value: {:?}",
+						wasm_value,
+					))),
+			};
+
+			Ok(Some(wasmtime_value))
+		},
+
+		_ => Err(Error::Sandbox("multiple return types are not supported yet".into())),
+	}
+}
+
+/// Compile a module, reusing the compiled artifact of identical code if it is cached.
+fn compile(
+	context: &Backend,
+	wasm: &[u8],
+) -> std::result::Result<wasmtime::Module, InstantiationError> {
+	let code_hash = sp_core_hashing::blake2_256(wasm);
+	if let Some(module) = context.modules.borrow().get(&code_hash) {
+		return Ok(module.clone())
+	}
+
+	let module = wasmtime::Module::new(&context.engine, wasm)
+		.map_err(|_| InstantiationError::ModuleDecoding)?;
+	context.modules.borrow_mut().insert(code_hash, module.clone());
+
+	Ok(module)
+}
+
+/// Instantiate a module within a sandbox context
+pub fn instantiate(
+	context: &Backend,
+	wasm: &[u8],
+	guest_env: GuestEnvironment,
+	state: u32,
+	sandbox_context: &mut dyn SandboxContext,
+) -> std::result::Result<Rc<SandboxInstance>, InstantiationError> {
+	let module = compile(context, wasm)?;
+
+	let mut linker = wasmtime::Linker::new(&context.engine);
+	// A module may import the same function more than once.
+	linker.allow_shadowing(true);
+
+	let mut store = None;
+	let mut functions = Vec::new();
+	for import in module.imports() {
+		let name = import.name().ok_or(InstantiationError::ModuleDecoding)?;
+		match import.ty() {
+			wasmtime::ExternType::Memory(_) => {
+				let memory = guest_env
+					.imports
+					.memory_by_name(import.module(), name)
+					.ok_or(InstantiationError::ModuleDecoding)?;
+
+				let memory = memory.as_wasmtime().expect(
+					"memory is created by wasmtime; \
+					exported by the same module and backend; \
+					thus the operation can't fail; \
+					qed",
+				);
+
+				// Memories can only be used within the store they were created in.
+				match &store {
+					Some(store) if !Rc::ptr_eq(store, &memory.store) =>
+						return Err(InstantiationError::EnvironmentDefinitionCorrupted),
+					Some(_) => (),
+					None => store = Some(memory.store.clone()),
+				}
+
+				linker
+					.define(import.module(), name, memory.memory)
+					.map_err(|_| InstantiationError::Instantiation)?;
+			},
+
+			wasmtime::ExternType::Func(func_ty) => {
+				let guest_func_index = guest_env.imports.func_by_name(import.module(), name);
+
+				let guest_func_index = if let Some(index) = guest_func_index {
+					index
+				} else {
+					// Missing import (should we abort here?)
+					continue
+				};
+
+				let supervisor_func_index = guest_env
+					.guest_to_supervisor_mapping
+					.func_by_guest_index(guest_func_index)
+					.ok_or(InstantiationError::ModuleDecoding)?;
+
+				functions.push((import.module(), name, func_ty, supervisor_func_index));
+			},
+
+			// Nothing to do here
+			_ => (),
+		}
+	}
+
+	let store = store.unwrap_or_else(|| SandboxStore::new(&context.engine));
+	let instance = store
+		.with_context(|mut store_context| {
+			for (module_name, name, func_ty, supervisor_func_index) in functions {
+				let function =
+					dispatch_function(supervisor_func_index, &mut store_context, func_ty, state);
+				linker
+					.define(module_name, name, function)
+					.map_err(|_| InstantiationError::Instantiation)?;
+			}
+
+			SandboxContextStore::using(sandbox_context, || {
+				linker.instantiate(&mut store_context, &module).map_err(|error| {
+					if error.is::<Trap>() {
+						InstantiationError::StartTrapped
+					} else {
+						InstantiationError::Instantiation
+					}
+				})
+			})
+		})
+		.map_err(|_| InstantiationError::EnvironmentDefinitionCorrupted)??;
+
+	Ok(Rc::new(SandboxInstance {
+		backend_instance: BackendInstance::Wasmtime(Instance { instance, store }),
+		guest_to_supervisor_mapping: guest_env.guest_to_supervisor_mapping,
+	}))
+}
+
+fn dispatch_function(
+	supervisor_func_index: SupervisorFuncIndex,
+	store: impl AsContextMut<Data = u64>,
+	func_ty: wasmtime::FuncType,
+	state: u32,
+) -> wasmtime::Func {
+	wasmtime::Func::new(store, func_ty, move |mut caller, params, results| {
+		caller_context::using(&mut caller, || {
+			SandboxContextStore::with(|sandbox_context| {
+				// Serialize arguments into a byte vector.
+				let invoke_args_data = params
+					.iter()
+					.map(|val| match val {
+						wasmtime::Val::I32(val) => Ok(Value::I32(*val)),
+						wasmtime::Val::I64(val) => Ok(Value::I64(*val)),
+						wasmtime::Val::F32(val) => Ok(Value::F32(*val)),
+						wasmtime::Val::F64(val) => Ok(Value::F64(*val)),
+						_ => Err(Trap::new(format!("Unsupported function argument: {:?}", val))),
+					})
+					.collect::<std::result::Result<Vec<_>, _>>()?
+					.encode();
+
+				// Move serialized arguments inside the memory, invoke dispatch thunk and
+				// then free allocated memory.
+				let invoke_args_len = invoke_args_data.len() as WordSize;
+				let invoke_args_ptr =
+					sandbox_context.supervisor_context().allocate_memory(invoke_args_len).map_err(
+						|_| Trap::new("Can't allocate memory in supervisor for the arguments"),
+					)?;
+
+				let deallocate = |fe: &mut dyn FunctionContext, ptr, fail_msg| {
+					fe.deallocate_memory(ptr).map_err(|_| Trap::new(fail_msg))
+				};
+
+				if sandbox_context
+					.supervisor_context()
+					.write_memory(invoke_args_ptr, &invoke_args_data)
+					.is_err()
+				{
+					deallocate(
+						sandbox_context.supervisor_context(),
+						invoke_args_ptr,
+						"Failed dealloction after failed write of invoke arguments",
+					)?;
+
+					return Err(Trap::new("Can't write invoke args into memory"))
+				}
+
+				// Perform the actuall call
+				let serialized_result = sandbox_context
+					.invoke(invoke_args_ptr, invoke_args_len, state, supervisor_func_index)
+					.map_err(|e| Trap::new(e.to_string()));
+
+				deallocate(
+					sandbox_context.supervisor_context(),
+					invoke_args_ptr,
+					"Failed dealloction after invoke",
+				)?;
+
+				let serialized_result = serialized_result?;
+
+				// dispatch_thunk returns pointer to serialized arguments.
+				// Unpack pointer and len of the serialized result data.
+				let (serialized_result_val_ptr, serialized_result_val_len) = {
+					// Cast to u64 to use zero-extension.
+					let v = serialized_result as u64;
+					let ptr = (v >> 32) as u32;
+					let len = (v & 0xFFFFFFFF) as u32;
+					(Pointer::new(ptr), len)
+				};
+
+				let serialized_result_val = sandbox_context
+					.supervisor_context()
+					.read_memory(serialized_result_val_ptr, serialized_result_val_len)
+					.map_err(|_| Trap::new("Can't read the serialized result from dispatch thunk"));
+
+				deallocate(
+					sandbox_context.supervisor_context(),
+					serialized_result_val_ptr,
+					"Can't deallocate memory for dispatch thunk's result",
+				)?;
+
+				let serialized_result_val = serialized_result_val?;
+
+				let deserialized_result = std::result::Result::<ReturnValue, HostError>::decode(
+					&mut serialized_result_val.as_slice(),
+				)
+				.map_err(|_| Trap::new("Decoding Result<ReturnValue, HostError> failed!"))?
+				.map_err(|_| Trap::new("Supervisor function returned sandbox::HostError"))?;
+
+				let result = match deserialized_result {
+					ReturnValue::Value(Value::I32(val)) => Some(wasmtime::Val::I32(val)),
+					ReturnValue::Value(Value::I64(val)) => Some(wasmtime::Val::I64(val)),
+					ReturnValue::Value(Value::F32(val)) => Some(wasmtime::Val::F32(val)),
+					ReturnValue::Value(Value::F64(val)) => Some(wasmtime::Val::F64(val)),
+
+					ReturnValue::Unit => None,
+				};
+
+				match (result, results) {
+					(Some(value), [result]) => *result = value,
+					(None, []) => (),
+					_ => return Err(Trap::new("Supervisor function returned an unexpected value")),
+				}
+
+				Ok(())
+			})
+			.expect("SandboxContextStore is set when invoking sandboxed functions; qed")
+		})
+	})
+}
+
+/// Allocate new memory region
+pub fn new_memory(context: &Backend, initial: u32, maximum: Option<u32>) -> Result<Memory> {
+	// Wasmtime expects the limits to be validated beforehand, unlike wasmi.
+	if initial > MAX_PAGES ||
+		matches!(maximum, Some(maximum) if maximum > MAX_PAGES || initial > maximum)
+	{
+		return Err(Error::InvalidMemoryReference)
+	}
+
+	let store = SandboxStore::new(&context.engine);
+	let ty = wasmtime::MemoryType::new(initial, maximum);
+	let memory = store
+		.with_context(|store_context| wasmtime::Memory::new(store_context, ty))?
+		.map_err(|_| Error::InvalidMemoryReference)?;
+
+	Ok(Memory::Wasmtime(MemoryWrapper { memory, store }))
+}
+
+/// A memory along with the store that owns it.
+///
+/// All memory operations go through the store, which enforces the memory access protocol.
+#[derive(Debug, Clone)]
+pub struct MemoryWrapper {
+	memory: wasmtime::Memory,
+	store: Rc<SandboxStore>,
+}
+
+impl MemoryTransfer for MemoryWrapper {
+	fn read(&self, source_addr: Pointer<u8>, size: usize) -> Result<Vec<u8>> {
+		self.store.with_context(|store_context| {
+			let source = self.memory.data(&store_context);
+
+			let range = checked_range(source_addr.into(), size, source.len())
+				.ok_or_else(|| Error::Other("memory read is out of bounds".into()))?;
+
+			Ok(source[range].to_vec())
+		})?
+	}
+
+	fn read_into(&self, source_addr: Pointer<u8>, destination: &mut [u8]) -> Result<()> {
+		self.store.with_context(|store_context| {
+			let source = self.memory.data(&store_context);
+
+			let range = checked_range(source_addr.into(), destination.len(), source.len())
+				.ok_or_else(|| Error::Other("memory read is out of bounds".into()))?;
+
+			destination.copy_from_slice(&source[range]);
+			Ok(())
+		})?
+	}
+
+	fn write_from(&self, dest_addr: Pointer<u8>, source: &[u8]) -> Result<()> {
+		self.store.with_context(|store_context| {
+			let destination = self.memory.data_mut(store_context);
+
+			let range = checked_range(dest_addr.into(), source.len(), destination.len())
+				.ok_or_else(|| Error::Other("memory write is out of bounds".into()))?;
+
+			destination[range].copy_from_slice(source);
+			Ok(())
+		})?
+	}
+}
+
+/// Get global value by name
+pub fn get_global(instance: &Instance, name: &str) -> Option<Value> {
+	instance
+		.store
+		.with_context(|mut store_context| {
+			let global = instance.instance.get_global(&mut store_context, name)?;
+			let wasmtime_value = match global.get(&mut store_context) {
+				wasmtime::Val::I32(val) => Value::I32(val),
+				wasmtime::Val::I64(val) => Value::I64(val),
+				wasmtime::Val::F32(val) => Value::F32(val),
+				wasmtime::Val::F64(val) => Value::F64(val),
+				_ => None?,
+			};
+
+			Some(wasmtime_value)
+		})
+		.ok()?
+}
+
+/// Get exported memory by name
+pub fn get_memory(instance: &Instance, name: &str) -> Option<Memory> {
+	let memory = instance
+		.store
+		.with_context(|store_context| instance.instance.get_memory(store_context, name))
+		.ok()??;
+
+	Some(Memory::Wasmtime(MemoryWrapper { memory, store: instance.store.clone() }))
+}
@@ -463,6 +463,37 @@ wasm_export_sandbox_test_functions! {
 			_ => 40,
 		}
 	}
+
+	fn test_sandbox_get_memory<T>(code: Vec<u8>) -> u8
+	where
+		T: SandboxInstance<()>,
+	{
+		let env_builder = T::EnvironmentBuilder::new();
+		let mut instance = if let Ok(i) = T::new(&code, &env_builder, &mut ()) {
+			i
+		} else {
+			return 20
+		};
+
+		let memory = if let Some(memory) = instance.get_memory("memory") {
+			memory
+		} else {
+			return 30
+		};
+
+		let mut data = [0u8; 5];
+		if memory.get(0, &mut data).is_err() || &data != b"hello" {
+			return 40
+		}
+		if memory.set(5, &[42]).is_err() {
+			return 50
+		}
+
+		match instance.invoke("get", &[], &mut ()) {
+			Ok(sp_sandbox::ReturnValue::Value(Value::I32(val))) => val as u8,
+			_ => 60,
+		}
+	}
 }
 
 #[cfg(not(feature = "std"))]
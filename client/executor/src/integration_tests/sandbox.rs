@@ -337,3 +337,34 @@ fn get_global_val_works(wasm_method: WasmExecutionMethod, fn_suffix: &str) {
 		500i64.encode(),
 	);
 }
+
+test_wasm_execution_sandbox!(get_memory_works);
+fn get_memory_works(wasm_method: WasmExecutionMethod, fn_suffix: &str) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+
+	let code = wat::parse_str(
+		r#"
+		(module
+			(memory (export "memory") 1 1)
+			(data (i32.const 0) "hello")
+			(func (export "get") (result i32)
+				(i32.load8_u (i32.const 5))
+			)
+		)
+		"#,
+	)
+	.unwrap()
+	.encode();
+
+	assert_eq!(
+		call_in_wasm(
+			&format!("test_sandbox_get_memory{}", fn_suffix),
+			&code,
+			wasm_method,
+			&mut ext,
+		)
+		.unwrap(),
+		42u8.encode(),
+	);
+}
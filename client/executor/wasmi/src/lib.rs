@@ -314,6 +314,13 @@ impl Sandbox for FunctionExecutor {
 			.map(|i| i.get_global_val(name))
 			.map_err(|e| e.to_string())
 	}
+
+	fn instance_memory(&mut self, instance_idx: u32, name: &str) -> WResult<Option<MemoryId>> {
+		self.sandbox_store
+			.borrow_mut()
+			.instance_memory(instance_idx, name)
+			.map_err(|e| e.to_string())
+	}
 }
 
 /// Will be used on initialization of a module to resolve function and memory imports.
@@ -334,6 +334,16 @@ impl<'a> Sandbox for HostContext<'a> {
 			.map(|i| i.get_global_val(name))
 			.map_err(|e| e.to_string())
 	}
+
+	fn instance_memory(
+		&mut self,
+		instance_idx: u32,
+		name: &str,
+	) -> sp_wasm_interface::Result<Option<MemoryId>> {
+		self.sandbox_store_mut()
+			.instance_memory(instance_idx, name)
+			.map_err(|e| e.to_string())
+	}
 }
 
 struct SandboxContext<'a, 'b> {
@@ -94,6 +94,51 @@ pub type ContractExecResult<Balance> =
 pub type ContractInstantiateResult<AccountId, Balance> =
 	ContractResult<Result<InstantiateReturnValue<AccountId>, DispatchError>, Balance>;
 
+/// Result type of a `bare_trace_call` call.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(
+	feature = "std",
+	serde(
+		rename_all = "camelCase",
+		bound(serialize = "AccountId: Serialize, Balance: Copy + Into<NumberOrHex>"),
+		bound(deserialize = "AccountId: Deserialize<'de>, Balance: TryFrom<NumberOrHex>")
+	)
+)]
+pub struct ContractTraceResult<AccountId, Balance> {
+	/// The result of the call, as returned by `bare_call`.
+	pub call: ContractExecResult<Balance>,
+	/// The frames which were executed by the call.
+	///
+	/// This is `None` when the call failed before any contract code was executed.
+	pub trace: Option<CallTrace<AccountId, Balance>>,
+}
+
+/// Result type of a `bare_estimate_call` call.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(
+	feature = "std",
+	serde(
+		rename_all = "camelCase",
+		bound(serialize = "Balance: Copy + Into<NumberOrHex>"),
+		bound(deserialize = "Balance: TryFrom<NumberOrHex>")
+	)
+)]
+pub struct ContractEstimateResult<Balance> {
+	/// The smallest gas limit with which the call succeeds.
+	///
+	/// If the call does not succeed with the supplied gas limit this is the
+	/// [`ContractResult::gas_required`] of [`Self::call`] instead.
+	pub gas_required: u64,
+	/// The largest storage deposit charged by any of the executions of the call.
+	///
+	/// This value should be used to determine the storage deposit limit for on-chain execution.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// The result of the call when executed with [`Self::gas_required`] as gas limit.
+	pub call: ContractExecResult<Balance>,
+}
+
 /// Result type of a `bare_code_upload` call.
 pub type CodeUploadResult<CodeHash, Balance> =
 	Result<CodeUploadReturnValue<CodeHash, Balance>, DispatchError>;
@@ -120,7 +165,7 @@ bitflags! {
 }
 
 /// Output of a contract call or instantiation which ran to completion.
-#[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct ExecReturnValue {
@@ -137,6 +182,82 @@ impl ExecReturnValue {
 	}
 }
 
+/// The entry point of a contract which is executed by a frame of a [`CallTrace`].
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum EntryPoint {
+	/// The constructor of a newly instantiated contract.
+	Constructor,
+	/// The call entry point of a contract.
+	Call,
+	/// The call entry point of some code, executed on behalf of the calling contract.
+	DelegateCall,
+	/// The query entry point of a CosmWasm contract.
+	Query,
+	/// The migrate entry point of a CosmWasm contract.
+	Migrate,
+	/// The `ibc_channel_open` entry point of a CosmWasm contract.
+	IbcChannelOpen,
+	/// The `ibc_channel_connect` entry point of a CosmWasm contract.
+	IbcChannelConnect,
+	/// The `ibc_channel_close` entry point of a CosmWasm contract.
+	IbcChannelClose,
+	/// The `ibc_packet_receive` entry point of a CosmWasm contract.
+	IbcPacketReceive,
+	/// The `ibc_packet_ack` entry point of a CosmWasm contract.
+	IbcPacketAck,
+	/// The `ibc_packet_timeout` entry point of a CosmWasm contract.
+	IbcPacketTimeout,
+}
+
+/// A frame that was executed as part of a contract call, together with the frames it spawned.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(
+	feature = "std",
+	serde(
+		rename_all = "camelCase",
+		bound(serialize = "AccountId: Serialize, Balance: Copy + Into<NumberOrHex>"),
+		bound(deserialize = "AccountId: Deserialize<'de>, Balance: TryFrom<NumberOrHex>")
+	)
+)]
+pub struct CallTrace<AccountId, Balance> {
+	/// The contract which was executed by the frame.
+	pub contract: AccountId,
+	/// The entry point which was executed.
+	pub entry_point: EntryPoint,
+	/// The input data passed to the entry point.
+	pub input: Bytes,
+	/// The output of the entry point or the error the frame failed with.
+	pub result: Result<ExecReturnValue, DispatchError>,
+	/// How much gas was consumed by the frame, including the frames it spawned.
+	pub gas_consumed: u64,
+	/// The storage deposit of the frame, including the frames it spawned.
+	///
+	/// Changes made by frames which failed or reverted are not included.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// The frames which were spawned by this frame, in execution order.
+	pub calls: Vec<CallTrace<AccountId, Balance>>,
+}
+
+/// An event which was emitted by a contract through `seal_deposit_event`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ContractEvent<AccountId, Hash> {
+	/// The index of the extrinsic which emitted the event.
+	///
+	/// This is `None` if the event was emitted outside of an extrinsic.
+	pub extrinsic_index: Option<u32>,
+	/// The contract which emitted the event.
+	pub contract: AccountId,
+	/// The data passed to `seal_deposit_event`.
+	pub data: Bytes,
+	/// The topics passed to `seal_deposit_event`.
+	pub topics: Vec<Hash>,
+}
+
 /// The result of a successful contract instantiation.
 #[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -167,7 +288,7 @@ pub struct CodeUploadReturnValue<CodeHash, Balance> {
 	pub deposit: Balance,
 }
 
-/// Reference to an existing code hash or a new wasm module.
+/// Reference to an existing code hash, an existing code id or a new wasm module.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
@@ -176,6 +297,8 @@ pub enum Code<Hash> {
 	Upload(Bytes),
 	/// The code hash of an on-chain wasm blob.
 	Existing(Hash),
+	/// The sequential id which was assigned to an on-chain wasm blob when it was uploaded.
+	Id(u64),
 }
 
 impl<T: Into<Vec<u8>>, Hash> From<T> for Code<Hash> {
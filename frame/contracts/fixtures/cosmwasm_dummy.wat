@@ -0,0 +1,37 @@
+;; A valid CosmWasm contract which does nothing at all
+(module
+	(memory (export "memory") 1)
+	(global $heap (mut i32) (i32.const 0x1000))
+
+	;; Region of the empty response.
+	(data (i32.const 0x100) "\00\02\00\00\3e\00\00\00\3e\00\00\00")
+	(data (i32.const 0x200) "{\"ok\":{\"messages\":[],\"attributes\":[],\"events\":[],\"data\":null}}")
+
+	;; Region of the query result `{}`.
+	(data (i32.const 0x110) "\00\03\00\00\0c\00\00\00\0c\00\00\00")
+	(data (i32.const 0x300) "{\"ok\":\"e30=\"}")
+
+	(func (export "allocate") (param $len i32) (result i32)
+		(local $ptr i32)
+		(set_local $ptr (get_global $heap))
+		(i32.store (get_local $ptr) (i32.add (get_local $ptr) (i32.const 12)))
+		(i32.store (i32.add (get_local $ptr) (i32.const 4)) (get_local $len))
+		(i32.store (i32.add (get_local $ptr) (i32.const 8)) (i32.const 0))
+		(set_global $heap
+			(i32.add (get_local $ptr) (i32.add (get_local $len) (i32.const 12)))
+		)
+		(get_local $ptr)
+	)
+	(func (export "deallocate") (param i32))
+	(func (export "interface_version_8"))
+
+	(func (export "instantiate") (param i32 i32 i32) (result i32)
+		(i32.const 0x100)
+	)
+	(func (export "execute") (param i32 i32 i32) (result i32)
+		(i32.const 0x100)
+	)
+	(func (export "query") (param i32 i32) (result i32)
+		(i32.const 0x110)
+	)
+)
@@ -0,0 +1,91 @@
+;; An IBC enabled CosmWasm contract which stores the messages of the IBC entry points.
+;;
+;; Executing it with `{}` sends "ping" over `channel-0` and any other message requests
+;; `channel-0` to be closed. Received packets are acknowledged with "pong".
+(module
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+
+	(memory (export "memory") 1)
+	(global $heap (mut i32) (i32.const 0x1000))
+
+	;; Region of the response of `execute` with `{}`, which sends "ping" over `channel-0`.
+	(data (i32.const 0x100) "\00\04\00\00\f5\00\00\00\f5\00\00\00")
+	(data (i32.const 0x400) "{\"ok\":{\"messages\":[{\"id\":0,\"msg\":{\"ibc\":{\"send_packet\":{\"channel_id\":\"channel-0\",\"data\":\"cGluZw==\",\"timeout\":{\"block\":{\"revision\":0,\"height\":10},\"timestamp\":null}}}},\"gas_limit\":null,\"reply_on\":\"never\"}],\"attributes\":[],\"events\":[],\"data\":null}}")
+	;; Region of the response of `execute` with any other message, which closes `channel-0`.
+	(data (i32.const 0x110) "\00\06\00\00\a5\00\00\00\a5\00\00\00")
+	(data (i32.const 0x600) "{\"ok\":{\"messages\":[{\"id\":0,\"msg\":{\"ibc\":{\"close_channel\":{\"channel_id\":\"channel-0\"}}},\"gas_limit\":null,\"reply_on\":\"never\"}],\"attributes\":[],\"events\":[],\"data\":null}}")
+	;; Region of the empty response of `instantiate`.
+	(data (i32.const 0x120) "\00\07\00\00\3e\00\00\00\3e\00\00\00")
+	(data (i32.const 0x700) "{\"ok\":{\"messages\":[],\"attributes\":[],\"events\":[],\"data\":null}}")
+	;; Region of the response of `ibc_channel_open`, which accepts the proposed version.
+	(data (i32.const 0x130) "\80\07\00\00\0b\00\00\00\0b\00\00\00")
+	(data (i32.const 0x780) "{\"ok\":null}")
+	;; Region of the response of the other IBC entry points.
+	(data (i32.const 0x140) "\a0\07\00\00\32\00\00\00\32\00\00\00")
+	(data (i32.const 0x7a0) "{\"ok\":{\"messages\":[],\"attributes\":[],\"events\":[]}}")
+	;; Region of the response of `ibc_packet_receive`, which acknowledges with "pong".
+	(data (i32.const 0x150) "\00\08\00\00\4f\00\00\00\4f\00\00\00")
+	(data (i32.const 0x800) "{\"ok\":{\"acknowledgement\":\"cG9uZw==\",\"messages\":[],\"attributes\":[],\"events\":[]}}")
+	;; Region of the key "connected".
+	(data (i32.const 0x200) "\00\09\00\00\09\00\00\00\09\00\00\00")
+	;; Region of the key "received".
+	(data (i32.const 0x210) "\09\09\00\00\08\00\00\00\08\00\00\00")
+	;; Region of the key "acked".
+	(data (i32.const 0x220) "\11\09\00\00\05\00\00\00\05\00\00\00")
+	;; Region of the key "timed_out".
+	(data (i32.const 0x230) "\16\09\00\00\09\00\00\00\09\00\00\00")
+	;; Region of the key "closed".
+	(data (i32.const 0x240) "\1f\09\00\00\06\00\00\00\06\00\00\00")
+	(data (i32.const 0x900) "connectedreceivedackedtimed_outclosed")
+
+	(func (export "allocate") (param $len i32) (result i32)
+		(local $ptr i32)
+		(set_local $ptr (get_global $heap))
+		(i32.store (get_local $ptr) (i32.add (get_local $ptr) (i32.const 12)))
+		(i32.store (i32.add (get_local $ptr) (i32.const 4)) (get_local $len))
+		(i32.store (i32.add (get_local $ptr) (i32.const 8)) (i32.const 0))
+		(set_global $heap
+			(i32.add (get_local $ptr) (i32.add (get_local $len) (i32.const 12)))
+		)
+		(get_local $ptr)
+	)
+	(func (export "deallocate") (param i32))
+	(func (export "interface_version_8"))
+
+	(func (export "instantiate") (param i32 i32 i32) (result i32)
+		(i32.const 0x120)
+	)
+	(func (export "execute") (param i32 i32 i32) (result i32)
+		(if (result i32) (i32.eq (i32.load offset=8 (get_local 2)) (i32.const 2))
+			(then (i32.const 0x100))
+			(else (i32.const 0x110))
+		)
+	)
+	(func (export "query") (param i32 i32) (result i32)
+		(unreachable)
+	)
+
+	(func (export "ibc_channel_open") (param i32 i32) (result i32)
+		(i32.const 0x130)
+	)
+	(func (export "ibc_channel_connect") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x200) (get_local 1))
+		(i32.const 0x140)
+	)
+	(func (export "ibc_channel_close") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x240) (get_local 1))
+		(i32.const 0x140)
+	)
+	(func (export "ibc_packet_receive") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x210) (get_local 1))
+		(i32.const 0x150)
+	)
+	(func (export "ibc_packet_ack") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x220) (get_local 1))
+		(i32.const 0x140)
+	)
+	(func (export "ibc_packet_timeout") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x230) (get_local 1))
+		(i32.const 0x140)
+	)
+)
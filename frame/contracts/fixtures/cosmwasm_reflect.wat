@@ -0,0 +1,103 @@
+;; A reflect contract in the spirit of the one of CosmWasm.
+;;
+;; `execute` dispatches the JSON array of sub-messages it is passed and `query` forwards the
+;; `QueryRequest` it is passed to the chain. Replies and migration messages are stored under the
+;; keys "reply" and "migrated".
+(module
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+	(import "env" "query_chain" (func $query_chain (param i32) (result i32)))
+
+	(memory (export "memory") 1)
+	(global $heap (mut i32) (i32.const 0x1000))
+
+	;; Regions of the start and the end of the response of `execute`.
+	(data (i32.const 0x100) "\00\02\00\00\12\00\00\00\12\00\00\00")
+	(data (i32.const 0x110) "\80\02\00\00\4c\00\00\00\4c\00\00\00")
+	(data (i32.const 0x200) "{\"ok\":{\"messages\":")
+	(data (i32.const 0x280) ",\"attributes\":[{\"key\":\"action\",\"value\":\"reflect\"}],\"events\":[],\"data\":null}}")
+
+	;; Region of the empty response.
+	(data (i32.const 0x120) "\00\03\00\00\3e\00\00\00\3e\00\00\00")
+	(data (i32.const 0x300) "{\"ok\":{\"messages\":[],\"attributes\":[],\"events\":[],\"data\":null}}")
+
+	;; Regions of the keys "reply" and "migrated".
+	(data (i32.const 0x130) "\00\04\00\00\05\00\00\00\05\00\00\00")
+	(data (i32.const 0x140) "\05\04\00\00\08\00\00\00\08\00\00\00")
+	(data (i32.const 0x400) "replymigrated")
+
+	(func $allocate (export "allocate") (param $len i32) (result i32)
+		(local $ptr i32)
+		(set_local $ptr (get_global $heap))
+		(i32.store (get_local $ptr) (i32.add (get_local $ptr) (i32.const 12)))
+		(i32.store (i32.add (get_local $ptr) (i32.const 4)) (get_local $len))
+		(i32.store (i32.add (get_local $ptr) (i32.const 8)) (i32.const 0))
+		(set_global $heap
+			(i32.add (get_local $ptr) (i32.add (get_local $len) (i32.const 12)))
+		)
+		(get_local $ptr)
+	)
+	(func (export "deallocate") (param i32))
+	(func (export "interface_version_8"))
+
+	;; Append the data of the region at `$src` to the region at `$dst`.
+	(func $append (param $dst i32) (param $src i32)
+		(local $from i32)
+		(local $to i32)
+		(local $end i32)
+		(set_local $from (i32.load (get_local $src)))
+		(set_local $to
+			(i32.add (i32.load (get_local $dst)) (i32.load offset=8 (get_local $dst)))
+		)
+		(set_local $end (i32.add (get_local $from) (i32.load offset=8 (get_local $src))))
+		(block $done
+			(loop $copy
+				(br_if $done (i32.ge_u (get_local $from) (get_local $end)))
+				(i32.store8 (get_local $to) (i32.load8_u (get_local $from)))
+				(set_local $from (i32.add (get_local $from) (i32.const 1)))
+				(set_local $to (i32.add (get_local $to) (i32.const 1)))
+				(br $copy)
+			)
+		)
+		(i32.store offset=8 (get_local $dst)
+			(i32.add (i32.load offset=8 (get_local $dst)) (i32.load offset=8 (get_local $src)))
+		)
+	)
+
+	(func (export "instantiate") (param i32 i32 i32) (result i32)
+		(i32.const 0x120)
+	)
+	(func (export "execute") (param i32 i32 i32) (result i32)
+		(local $response i32)
+		(set_local $response
+			(call $allocate (i32.add (i32.load offset=8 (get_local 2)) (i32.const 0x100)))
+		)
+		(call $append (get_local $response) (i32.const 0x100))
+		(call $append (get_local $response) (get_local 2))
+		(call $append (get_local $response) (i32.const 0x110))
+		(get_local $response)
+	)
+	;; The `ContractResult` of the query is the one wrapped in the `SystemResult` of the chain.
+	(func (export "query") (param i32 i32) (result i32)
+		(local $result i32)
+		(local $response i32)
+		(set_local $result (call $query_chain (get_local 1)))
+		(set_local $response (call $allocate (i32.const 0)))
+		;; Strip `{"ok":` and `}`.
+		(i32.store (get_local $response) (i32.add (i32.load (get_local $result)) (i32.const 6)))
+		(i32.store offset=4 (get_local $response)
+			(i32.sub (i32.load offset=8 (get_local $result)) (i32.const 7))
+		)
+		(i32.store offset=8 (get_local $response)
+			(i32.sub (i32.load offset=8 (get_local $result)) (i32.const 7))
+		)
+		(get_local $response)
+	)
+	(func (export "reply") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x130) (get_local 1))
+		(i32.const 0x120)
+	)
+	(func (export "migrate") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x140) (get_local 1))
+		(i32.const 0x120)
+	)
+)
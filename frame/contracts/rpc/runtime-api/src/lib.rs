@@ -25,7 +25,8 @@
 
 use codec::Codec;
 use pallet_contracts_primitives::{
-	Code, CodeUploadResult, ContractExecResult, ContractInstantiateResult, GetStorageResult,
+	Code, CodeUploadResult, ContractEstimateResult, ContractEvent, ContractExecResult,
+	ContractInstantiateResult, ContractTraceResult, GetStorageResult,
 };
 use sp_std::vec::Vec;
 
@@ -49,6 +50,41 @@ sp_api::decl_runtime_apis! {
 			input_data: Vec<u8>,
 		) -> ContractExecResult<Balance>;
 
+		/// Perform a call from a specified account to a given contract and record the frames
+		/// it executes.
+		///
+		/// See `pallet_contracts::Pallet::bare_trace_call`.
+		fn trace_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> ContractTraceResult<AccountId, Balance>;
+
+		/// Estimate the gas limit and storage deposit limit required by a call from a specified
+		/// account to a given contract.
+		///
+		/// See `pallet_contracts::Pallet::bare_estimate_call`.
+		fn estimate_call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> ContractEstimateResult<Balance>;
+
+		/// Query a CosmWasm contract without changing any state.
+		///
+		/// See `pallet_contracts::Pallet::bare_query`.
+		fn query(
+			dest: AccountId,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractExecResult<Balance>;
+
 		/// Instantiate a new contract.
 		///
 		/// See `pallet_contracts::Pallet::instantiate`.
@@ -81,5 +117,25 @@ sp_api::decl_runtime_apis! {
 			address: AccountId,
 			key: [u8; 32],
 		) -> GetStorageResult;
+
+		/// Returns the contracts which were given `label` on instantiation.
+		///
+		/// See `pallet_contracts::Pallet::contracts_by_label`.
+		fn contracts_by_label(label: Vec<u8>) -> Vec<AccountId>;
+
+		/// Returns the events which were emitted by contracts in the block.
+		///
+		/// See `pallet_contracts::Pallet::contract_events`.
+		fn contract_events() -> Vec<ContractEvent<AccountId, Hash>>;
+
+		/// Returns the code hash of the contract at `address`.
+		///
+		/// See `pallet_contracts::Pallet::code_hash`.
+		fn code_hash(address: AccountId) -> Option<Hash>;
+
+		/// Returns the metadata registered for the code stored under `code_hash`.
+		///
+		/// See `pallet_contracts::Pallet::code_metadata`.
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>>;
 	}
 }
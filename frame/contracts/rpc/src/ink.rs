@@ -0,0 +1,778 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of ink! messages and decoding of their return values using the metadata of
+//! a contract.
+//!
+//! Only version 3 of the ink! metadata is supported. Values are represented in JSON as
+//! follows:
+//!
+//! - Integers are numbers or decimal strings. 128 bit integers are always decoded as strings
+//!   because they don't fit into a JSON number.
+//! - Structs with named fields are objects and structs with unnamed fields are arrays. A struct
+//!   with a single unnamed field, e.g. `AccountId`, is represented by the value of its field.
+//! - Enum variants without fields are strings and other variants are objects with the name of the
+//!   variant as their only key, e.g. `{ "Some": 42 }`. `null` is accepted for `None`.
+//! - Byte arrays and vectors are hex strings. A 32 byte array, e.g. `AccountId`, can also be given
+//!   in SS58.
+//! - Tuples are arrays and the unit type is `null`.
+
+use codec::{Compact, Decode, Encode};
+use scale_info::{form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{AccountId32, Ss58Codec},
+	Bytes,
+};
+
+/// The maximum nesting of the types of an encoded or decoded value.
+///
+/// This guards against recursive types, which are allowed by the metadata.
+const MAX_DEPTH: u32 = 64;
+
+/// The metadata generated by ink! for a contract.
+#[derive(Deserialize)]
+pub struct InkMetadata {
+	#[serde(rename = "V3")]
+	project: InkProject,
+}
+
+#[derive(Deserialize)]
+struct InkProject {
+	spec: ContractSpec,
+	#[serde(flatten)]
+	registry: PortableRegistry,
+}
+
+#[derive(Deserialize)]
+struct ContractSpec {
+	messages: Vec<MessageSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageSpec {
+	label: String,
+	selector: Bytes,
+	args: Vec<MessageParamSpec>,
+	return_type: Option<TypeSpec>,
+}
+
+#[derive(Deserialize)]
+struct MessageParamSpec {
+	label: String,
+	#[serde(rename = "type")]
+	ty: TypeSpec,
+}
+
+#[derive(Deserialize)]
+struct TypeSpec {
+	#[serde(rename = "type")]
+	ty: u32,
+}
+
+/// A message encoded as the input data of a call.
+pub struct EncodedMessage {
+	/// The selector of the message followed by its encoded arguments.
+	pub input: Vec<u8>,
+	/// The type of the value returned by the message, if any.
+	pub return_type: Option<u32>,
+}
+
+impl InkMetadata {
+	/// Parse the JSON metadata of a contract.
+	pub fn from_json(json: &[u8]) -> Result<Self, String> {
+		serde_json::from_slice(json).map_err(|e| format!("Invalid ink! metadata: {}", e))
+	}
+
+	/// Encode a call of the message with the label `label`.
+	///
+	/// `args` is either an object with the labels of the arguments as keys, an array of the
+	/// arguments in order or `null` for a message without arguments.
+	pub fn encode_message(&self, label: &str, args: &Value) -> Result<EncodedMessage, String> {
+		let message = self
+			.project
+			.spec
+			.messages
+			.iter()
+			.find(|message| message.label == label)
+			.ok_or_else(|| format!("The contract has no message `{}`", label))?;
+
+		let mut input = message.selector.to_vec();
+		match args {
+			Value::Null if message.args.is_empty() => (),
+			Value::Object(args) => {
+				if let Some(unknown) =
+					args.keys().find(|key| message.args.iter().all(|arg| arg.label != **key))
+				{
+					return Err(format!("The message `{}` has no argument `{}`", label, unknown))
+				}
+				for arg in &message.args {
+					let value = args
+						.get(&arg.label)
+						.ok_or_else(|| format!("Missing argument `{}`", arg.label))?;
+					self.encode_value(arg.ty.ty, value, &mut input, 0)?;
+				}
+			},
+			Value::Array(args) if args.len() == message.args.len() =>
+				for (arg, value) in message.args.iter().zip(args) {
+					self.encode_value(arg.ty.ty, value, &mut input, 0)?;
+				},
+			_ =>
+				return Err(format!(
+					"The message `{}` expects {} arguments",
+					label,
+					message.args.len()
+				)),
+		}
+
+		Ok(EncodedMessage { input, return_type: message.return_type.as_ref().map(|ty| ty.ty) })
+	}
+
+	/// Decode `data` as a value of the type `ty`.
+	pub fn decode(&self, ty: u32, mut data: &[u8]) -> Result<Value, String> {
+		let value = self.decode_value(ty, &mut data, 0)?;
+		if !data.is_empty() {
+			return Err(format!("{} bytes are left after decoding the value", data.len()))
+		}
+		Ok(value)
+	}
+
+	fn resolve(&self, ty: u32) -> Result<&Type<PortableForm>, String> {
+		self.project
+			.registry
+			.resolve(ty)
+			.ok_or_else(|| format!("The type {} is not in the metadata", ty))
+	}
+
+	fn is_byte(&self, ty: u32) -> Result<bool, String> {
+		Ok(matches!(self.resolve(ty)?.type_def(), TypeDef::Primitive(TypeDefPrimitive::U8)))
+	}
+
+	fn encode_value(
+		&self,
+		ty: u32,
+		value: &Value,
+		out: &mut Vec<u8>,
+		depth: u32,
+	) -> Result<(), String> {
+		let depth = nested(depth)?;
+		match self.resolve(ty)?.type_def() {
+			TypeDef::Composite(composite) =>
+				self.encode_fields(composite.fields(), value, out, depth),
+			TypeDef::Variant(variant) => {
+				let (name, fields) = match value {
+					Value::Null => ("None", &Value::Null),
+					Value::String(name) => (name.as_str(), &Value::Null),
+					Value::Object(object) if object.len() == 1 => object
+						.iter()
+						.next()
+						.map(|(name, fields)| (name.as_str(), fields))
+						.expect("The object has exactly one entry; qed"),
+					_ => return Err(unexpected("an enum variant", value)),
+				};
+				let variant = variant
+					.variants()
+					.iter()
+					.find(|variant| variant.name() == name)
+					.ok_or_else(|| format!("Unknown enum variant `{}`", name))?;
+				out.push(variant.index());
+				self.encode_fields(variant.fields(), fields, out, depth)
+			},
+			TypeDef::Sequence(sequence) => {
+				let ty = sequence.type_param().id();
+				if value.is_string() && self.is_byte(ty)? {
+					parse_bytes(value, None)?.encode_to(out);
+					return Ok(())
+				}
+				let values = value.as_array().ok_or_else(|| unexpected("an array", value))?;
+				Compact(values.len() as u32).encode_to(out);
+				values.iter().try_for_each(|value| self.encode_value(ty, value, out, depth))
+			},
+			TypeDef::Array(array) => {
+				let ty = array.type_param().id();
+				let len = array.len() as usize;
+				if value.is_string() && self.is_byte(ty)? {
+					out.extend(parse_bytes(value, Some(len))?);
+					return Ok(())
+				}
+				match value.as_array() {
+					Some(values) if values.len() == len =>
+						values.iter().try_for_each(|value| self.encode_value(ty, value, out, depth)),
+					_ => Err(unexpected(&format!("an array of {} elements", len), value)),
+				}
+			},
+			TypeDef::Tuple(tuple) => match (tuple.fields(), value) {
+				([], Value::Null) => Ok(()),
+				(fields, Value::Array(values)) if fields.len() == values.len() => fields
+					.iter()
+					.zip(values)
+					.try_for_each(|(ty, value)| self.encode_value(ty.id(), value, out, depth)),
+				(fields, _) =>
+					Err(unexpected(&format!("a tuple of {} elements", fields.len()), value)),
+			},
+			TypeDef::Primitive(primitive) => encode_primitive(primitive, value, out),
+			TypeDef::Compact(compact) => {
+				// The value is encoded as its type in order to check its range and then
+				// converted to an integer, which works for newtypes of integers as well.
+				let mut encoded = Vec::new();
+				self.encode_value(compact.type_param().id(), value, &mut encoded, depth)?;
+				let mut bytes = [0u8; 16];
+				if encoded.len() > bytes.len() {
+					return Err(unexpected("an integer of at most 128 bits", value))
+				}
+				bytes[..encoded.len()].copy_from_slice(&encoded);
+				Compact(u128::from_le_bytes(bytes)).encode_to(out);
+				Ok(())
+			},
+			TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+		}
+	}
+
+	fn encode_fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		value: &Value,
+		out: &mut Vec<u8>,
+		depth: u32,
+	) -> Result<(), String> {
+		let named = fields.iter().all(|field| field.name().is_some());
+		match (fields, value) {
+			([], Value::Null) => Ok(()),
+			// A newtype is represented by the value of its field.
+			([field], value) if field.name().is_none() =>
+				self.encode_value(field.ty().id(), value, out, depth),
+			(fields, Value::Object(object)) if named => fields.iter().try_for_each(|field| {
+				let name = field.name().expect("All fields are named; qed");
+				let value = object.get(name).ok_or_else(|| format!("Missing field `{}`", name))?;
+				self.encode_value(field.ty().id(), value, out, depth)
+			}),
+			(fields, Value::Array(values)) if fields.len() == values.len() =>
+				fields.iter().zip(values).try_for_each(|(field, value)| {
+					self.encode_value(field.ty().id(), value, out, depth)
+				}),
+			(fields, _) => Err(unexpected(&format!("{} fields", fields.len()), value)),
+		}
+	}
+
+	fn decode_value(&self, ty: u32, input: &mut &[u8], depth: u32) -> Result<Value, String> {
+		let depth = nested(depth)?;
+		match self.resolve(ty)?.type_def() {
+			TypeDef::Composite(composite) => self.decode_fields(composite.fields(), input, depth),
+			TypeDef::Variant(variant) => {
+				let index = u8::decode(input).map_err(invalid_data)?;
+				let variant = variant
+					.variants()
+					.iter()
+					.find(|variant| variant.index() == index)
+					.ok_or_else(|| format!("Invalid enum variant index {}", index))?;
+				if variant.fields().is_empty() {
+					return Ok(Value::String(variant.name().clone()))
+				}
+				let mut object = Map::new();
+				let fields = self.decode_fields(variant.fields(), input, depth)?;
+				object.insert(variant.name().clone(), fields);
+				Ok(Value::Object(object))
+			},
+			TypeDef::Sequence(sequence) => {
+				let len = <Compact<u32>>::decode(input).map_err(invalid_data)?.0;
+				self.decode_elements(sequence.type_param().id(), len, input, depth)
+			},
+			TypeDef::Array(array) =>
+				self.decode_elements(array.type_param().id(), array.len(), input, depth),
+			TypeDef::Tuple(tuple) => match tuple.fields() {
+				[] => Ok(Value::Null),
+				fields => fields
+					.iter()
+					.map(|ty| self.decode_value(ty.id(), input, depth))
+					.collect::<Result<_, _>>()
+					.map(Value::Array),
+			},
+			TypeDef::Primitive(primitive) => decode_primitive(primitive, input),
+			TypeDef::Compact(compact) => {
+				let int = <Compact<u128>>::decode(input).map_err(invalid_data)?.0;
+				let bytes = int.to_le_bytes();
+				let mut remaining = &bytes[..];
+				let value = self.decode_value(compact.type_param().id(), &mut remaining, depth)?;
+				if remaining.iter().any(|byte| *byte != 0) {
+					return Err(format!("The compact integer {} is out of range", int))
+				}
+				Ok(value)
+			},
+			TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+		}
+	}
+
+	fn decode_fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		input: &mut &[u8],
+		depth: u32,
+	) -> Result<Value, String> {
+		match fields {
+			[] => Ok(Value::Null),
+			[field] if field.name().is_none() => self.decode_value(field.ty().id(), input, depth),
+			fields if fields.iter().all(|field| field.name().is_some()) => fields
+				.iter()
+				.map(|field| {
+					let name = field.name().expect("All fields are named; qed").clone();
+					Ok((name, self.decode_value(field.ty().id(), input, depth)?))
+				})
+				.collect::<Result<_, _>>()
+				.map(Value::Object),
+			fields => fields
+				.iter()
+				.map(|field| self.decode_value(field.ty().id(), input, depth))
+				.collect::<Result<_, _>>()
+				.map(Value::Array),
+		}
+	}
+
+	fn decode_elements(
+		&self,
+		ty: u32,
+		len: u32,
+		input: &mut &[u8],
+		depth: u32,
+	) -> Result<Value, String> {
+		// Every element is required to take at least one byte, even if its type is zero-sized,
+		// so that a bogus length can't make us build billions of values.
+		let len = len as usize;
+		if input.len() < len {
+			return Err(invalid_data("Not enough data to fill buffer".into()))
+		}
+		if self.is_byte(ty)? {
+			let (bytes, remaining) = input.split_at(len);
+			*input = remaining;
+			return Ok(Value::String(to_hex(bytes, false)))
+		}
+		(0..len)
+			.map(|_| self.decode_value(ty, input, depth))
+			.collect::<Result<_, _>>()
+			.map(Value::Array)
+	}
+}
+
+fn encode_primitive(
+	primitive: &TypeDefPrimitive,
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<(), String> {
+	match primitive {
+		TypeDefPrimitive::Bool =>
+			value.as_bool().ok_or_else(|| unexpected("a boolean", value))?.encode_to(out),
+		TypeDefPrimitive::Char => {
+			let mut chars = value.as_str().map(str::chars).into_iter().flatten();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) => (c as u32).encode_to(out),
+				_ => return Err(unexpected("a character", value)),
+			}
+		},
+		TypeDefPrimitive::Str =>
+			value.as_str().ok_or_else(|| unexpected("a string", value))?.encode_to(out),
+		TypeDefPrimitive::U8 => unsigned::<u8>(value)?.encode_to(out),
+		TypeDefPrimitive::U16 => unsigned::<u16>(value)?.encode_to(out),
+		TypeDefPrimitive::U32 => unsigned::<u32>(value)?.encode_to(out),
+		TypeDefPrimitive::U64 => unsigned::<u64>(value)?.encode_to(out),
+		TypeDefPrimitive::U128 => unsigned::<u128>(value)?.encode_to(out),
+		TypeDefPrimitive::I8 => signed::<i8>(value)?.encode_to(out),
+		TypeDefPrimitive::I16 => signed::<i16>(value)?.encode_to(out),
+		TypeDefPrimitive::I32 => signed::<i32>(value)?.encode_to(out),
+		TypeDefPrimitive::I64 => signed::<i64>(value)?.encode_to(out),
+		TypeDefPrimitive::I128 => signed::<i128>(value)?.encode_to(out),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			return Err("256 bit integers are not supported".into()),
+	}
+	Ok(())
+}
+
+fn decode_primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Value, String> {
+	let value = match primitive {
+		TypeDefPrimitive::Bool => bool::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::Char => {
+			let c = u32::decode(input).map_err(invalid_data)?;
+			char::from_u32(c)
+				.ok_or_else(|| format!("{} is not a valid character", c))?
+				.to_string()
+				.into()
+		},
+		TypeDefPrimitive::Str => String::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::U8 => u8::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::U16 => u16::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::U32 => u32::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::U64 => u64::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::U128 => u128::decode(input).map_err(invalid_data)?.to_string().into(),
+		TypeDefPrimitive::I8 => i8::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::I16 => i16::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::I32 => i32::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::I64 => i64::decode(input).map_err(invalid_data)?.into(),
+		TypeDefPrimitive::I128 => i128::decode(input).map_err(invalid_data)?.to_string().into(),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			return Err("256 bit integers are not supported".into()),
+	};
+	Ok(value)
+}
+
+/// Parse a hex string or, if `len` is 32, an SS58 address.
+fn parse_bytes(value: &Value, len: Option<usize>) -> Result<Vec<u8>, String> {
+	let string = value.as_str().ok_or_else(|| unexpected("a hex string", value))?;
+	let bytes = match (string.starts_with("0x"), len) {
+		(false, Some(32)) => AccountId32::from_ss58check(string)
+			.map(|account| AsRef::<[u8]>::as_ref(&account).to_vec())
+			.map_err(|_| unexpected("a hex string or an SS58 address", value))?,
+		_ => from_hex(string).map_err(|_| unexpected("a hex string", value))?,
+	};
+	match len {
+		Some(len) if bytes.len() != len => Err(unexpected(&format!("{} bytes", len), value)),
+		_ => Ok(bytes),
+	}
+}
+
+fn unsigned<T: TryFrom<u128>>(value: &Value) -> Result<T, String> {
+	let int = match value {
+		Value::Number(number) => number.as_u64().map(Into::into),
+		Value::String(string) => string.parse().ok(),
+		_ => None,
+	};
+	int.and_then(|int: u128| T::try_from(int).ok())
+		.ok_or_else(|| unexpected(&format!("a {}", std::any::type_name::<T>()), value))
+}
+
+fn signed<T: TryFrom<i128>>(value: &Value) -> Result<T, String> {
+	let int = match value {
+		Value::Number(number) => number.as_i64().map(Into::into),
+		Value::String(string) => string.parse().ok(),
+		_ => None,
+	};
+	int.and_then(|int: i128| T::try_from(int).ok())
+		.ok_or_else(|| unexpected(&format!("a {}", std::any::type_name::<T>()), value))
+}
+
+/// The depth of the types nested in a type at depth `depth`.
+fn nested(depth: u32) -> Result<u32, String> {
+	if depth >= MAX_DEPTH {
+		return Err(format!("Types nested more than {} levels deep are not supported", MAX_DEPTH))
+	}
+	Ok(depth + 1)
+}
+
+fn unexpected(expected: &str, value: &Value) -> String {
+	format!("Expected {}, found {}", expected, value)
+}
+
+fn invalid_data(err: codec::Error) -> String {
+	format!("Invalid return value: {}", err)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+	use sp_core::crypto::AccountId32;
+
+	const METADATA: &str = r#"{
+		"metadataVersion": "0.1.0",
+		"contract": { "name": "erc20", "version": "3.0.0", "authors": [] },
+		"V3": {
+			"spec": {
+				"constructors": [],
+				"docs": [],
+				"events": [],
+				"messages": [
+					{
+						"args": [
+							{ "label": "to", "type": { "displayName": ["AccountId"], "type": 1 } },
+							{ "label": "value", "type": { "displayName": ["Balance"], "type": 0 } }
+						],
+						"docs": [],
+						"label": "transfer",
+						"mutates": true,
+						"payable": false,
+						"returnType": { "displayName": ["Result"], "type": 5 },
+						"selector": "0x84a15da1"
+					},
+					{
+						"args": [],
+						"docs": [],
+						"label": "info",
+						"mutates": false,
+						"payable": false,
+						"returnType": { "displayName": ["Info"], "type": 7 },
+						"selector": "0xa0d4a2c3"
+					},
+					{
+						"args": [
+							{ "label": "data", "type": { "displayName": ["Vec"], "type": 8 } },
+							{ "label": "limit", "type": { "displayName": ["Option"], "type": 9 } },
+							{ "label": "ids", "type": { "displayName": ["Vec"], "type": 12 } }
+						],
+						"docs": [],
+						"label": "set_data",
+						"mutates": true,
+						"payable": false,
+						"returnType": null,
+						"selector": "0x0a1b2c3d"
+					}
+				]
+			},
+			"storage": { "struct": { "fields": [] } },
+			"types": [
+				{ "id": 0, "type": { "def": { "primitive": "u128" } } },
+				{
+					"id": 1,
+					"type": {
+						"def": {
+							"composite": { "fields": [{ "type": 2, "typeName": "[u8; 32]" }] }
+						},
+						"path": ["ink_env", "types", "AccountId"]
+					}
+				},
+				{ "id": 2, "type": { "def": { "array": { "len": 32, "type": 3 } } } },
+				{ "id": 3, "type": { "def": { "primitive": "u8" } } },
+				{ "id": 4, "type": { "def": { "tuple": [] } } },
+				{
+					"id": 5,
+					"type": {
+						"def": {
+							"variant": {
+								"variants": [
+									{ "fields": [{ "type": 4 }], "index": 0, "name": "Ok" },
+									{ "fields": [{ "type": 6 }], "index": 1, "name": "Err" }
+								]
+							}
+						},
+						"params": [{ "name": "T", "type": 4 }, { "name": "E", "type": 6 }],
+						"path": ["Result"]
+					}
+				},
+				{
+					"id": 6,
+					"type": {
+						"def": {
+							"variant": {
+								"variants": [
+									{ "index": 0, "name": "InsufficientBalance" },
+									{ "index": 1, "name": "InsufficientAllowance" }
+								]
+							}
+						},
+						"path": ["erc20", "erc20", "Error"]
+					}
+				},
+				{
+					"id": 7,
+					"type": {
+						"def": {
+							"composite": {
+								"fields": [
+									{ "name": "owner", "type": 1, "typeName": "AccountId" },
+									{ "name": "total_supply", "type": 0, "typeName": "Balance" }
+								]
+							}
+						},
+						"path": ["erc20", "erc20", "Info"]
+					}
+				},
+				{ "id": 8, "type": { "def": { "sequence": { "type": 3 } } } },
+				{
+					"id": 9,
+					"type": {
+						"def": {
+							"variant": {
+								"variants": [
+									{ "index": 0, "name": "None" },
+									{ "fields": [{ "type": 10 }], "index": 1, "name": "Some" }
+								]
+							}
+						},
+						"params": [{ "name": "T", "type": 10 }],
+						"path": ["Option"]
+					}
+				},
+				{ "id": 10, "type": { "def": { "primitive": "u32" } } },
+				{ "id": 11, "type": { "def": { "compact": { "type": 10 } } } },
+				{ "id": 12, "type": { "def": { "sequence": { "type": 11 } } } }
+			]
+		}
+	}"#;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	fn alice() -> AccountId32 {
+		AccountId32::from_ss58check(ALICE).unwrap()
+	}
+
+	#[test]
+	fn encode_message_works() {
+		let metadata = InkMetadata::from_json(METADATA.as_bytes()).unwrap();
+		let mut expected = vec![0x84, 0xa1, 0x5d, 0xa1];
+		(alice(), 1_000_000_000_000_000_000_000u128).encode_to(&mut expected);
+
+		let message = metadata
+			.encode_message("transfer", &json!({ "to": ALICE, "value": "1000000000000000000000" }))
+			.unwrap();
+		assert_eq!(message.input, expected);
+		assert_eq!(message.return_type, Some(5));
+
+		// Arguments can be passed in order and accounts as hex.
+		let to = to_hex(alice().as_ref(), false);
+		let message = metadata
+			.encode_message("transfer", &json!([to, "1000000000000000000000"]))
+			.unwrap();
+		assert_eq!(message.input, expected);
+
+		let message = metadata.encode_message("info", &Value::Null).unwrap();
+		assert_eq!(message.input, vec![0xa0, 0xd4, 0xa2, 0xc3]);
+
+		let message = metadata
+			.encode_message(
+				"set_data",
+				&json!({ "data": "0x1234", "limit": { "Some": 7 }, "ids": [1, 1000] }),
+			)
+			.unwrap();
+		let mut expected = vec![0x0a, 0x1b, 0x2c, 0x3d];
+		(vec![0x12u8, 0x34], Some(7u32), vec![Compact(1u32), Compact(1000u32)])
+			.encode_to(&mut expected);
+		assert_eq!(message.input, expected);
+		assert_eq!(message.return_type, None);
+
+		let message = metadata
+			.encode_message("set_data", &json!({ "data": [1, 2], "limit": null, "ids": [] }))
+			.unwrap();
+		assert_eq!(&message.input[4..], (vec![1u8, 2], None::<u32>, Vec::<u32>::new()).encode());
+	}
+
+	#[test]
+	fn encode_message_rejects_invalid_args() {
+		let metadata = InkMetadata::from_json(METADATA.as_bytes()).unwrap();
+		let err = |message: &str, args: Value| {
+			metadata.encode_message(message, &args).err().expect("Encoding must fail")
+		};
+
+		assert_eq!(err("approve", Value::Null), "The contract has no message `approve`");
+		assert_eq!(err("transfer", Value::Null), "The message `transfer` expects 2 arguments");
+		assert_eq!(err("transfer", json!([ALICE])), "The message `transfer` expects 2 arguments");
+		assert_eq!(err("transfer", json!({ "to": ALICE })), "Missing argument `value`");
+		assert_eq!(
+			err("transfer", json!({ "to": ALICE, "value": 1, "from": ALICE })),
+			"The message `transfer` has no argument `from`",
+		);
+		assert_eq!(
+			err("transfer", json!({ "to": "0x1234", "value": 1 })),
+			r#"Expected 32 bytes, found "0x1234""#,
+		);
+		assert_eq!(
+			err("transfer", json!({ "to": ALICE, "value": -1 })),
+			"Expected a u128, found -1",
+		);
+		assert_eq!(
+			err("set_data", json!({ "data": [256], "limit": null, "ids": [] })),
+			"Expected a u8, found 256",
+		);
+		assert_eq!(
+			err("set_data", json!({ "data": [], "limit": "Any", "ids": [] })),
+			"Unknown enum variant `Any`",
+		);
+		assert_eq!(
+			err("set_data", json!({ "data": [], "limit": null, "ids": [4294967296u64] })),
+			"Expected a u32, found 4294967296",
+		);
+	}
+
+	#[test]
+	fn decode_works() {
+		let metadata = InkMetadata::from_json(METADATA.as_bytes()).unwrap();
+
+		assert_eq!(metadata.decode(5, &Ok::<(), u8>(()).encode()).unwrap(), json!({ "Ok": null }));
+		assert_eq!(
+			metadata.decode(5, &Err::<(), u8>(0).encode()).unwrap(),
+			json!({ "Err": "InsufficientBalance" }),
+		);
+		assert_eq!(
+			metadata.decode(7, &(alice(), u128::MAX).encode()).unwrap(),
+			json!({
+				"owner": to_hex(alice().as_ref(), false),
+				"total_supply": "340282366920938463463374607431768211455",
+			}),
+		);
+		assert_eq!(metadata.decode(9, &None::<u32>.encode()).unwrap(), json!("None"));
+		assert_eq!(metadata.decode(9, &Some(7u32).encode()).unwrap(), json!({ "Some": 7 }));
+		assert_eq!(metadata.decode(8, &vec![1u8, 2].encode()).unwrap(), json!("0x0102"));
+		assert_eq!(
+			metadata.decode(12, &vec![Compact(1u32), Compact(1000u32)].encode()).unwrap(),
+			json!([1, 1000]),
+		);
+
+		assert_eq!(
+			metadata.decode(10, &[1, 0, 0, 0, 0]).err().unwrap(),
+			"1 bytes are left after decoding the value",
+		);
+		assert_eq!(metadata.decode(6, &[2]).err().unwrap(), "Invalid enum variant index 2");
+		assert_eq!(
+			metadata.decode(12, &vec![Compact(u64::MAX)].encode()).err().unwrap(),
+			"The compact integer 18446744073709551615 is out of range",
+		);
+		assert_eq!(metadata.decode(13, &[]).err().unwrap(), "The type 13 is not in the metadata");
+	}
+
+	#[test]
+	fn recursive_types_and_bogus_lengths_are_rejected() {
+		let metadata = InkMetadata::from_json(
+			br#"{
+				"V3": {
+					"spec": {
+						"messages": [
+							{
+								"args": [{ "label": "node", "type": { "type": 0 } }],
+								"label": "visit",
+								"returnType": null,
+								"selector": "0x00000000"
+							}
+						]
+					},
+					"types": [
+						{
+							"id": 0,
+							"type": { "def": { "composite": { "fields": [{ "type": 0 }] } } }
+						},
+						{ "id": 1, "type": { "def": { "tuple": [] } } },
+						{ "id": 2, "type": { "def": { "sequence": { "type": 1 } } } }
+					]
+				}
+			}"#,
+		)
+		.unwrap();
+
+		let too_deep = "Types nested more than 64 levels deep are not supported";
+		assert_eq!(metadata.encode_message("visit", &json!([null])).err().unwrap(), too_deep);
+		assert_eq!(metadata.decode(0, &[]).err().unwrap(), too_deep);
+		assert_eq!(
+			metadata.decode(2, &Compact(u32::MAX).encode()).err().unwrap(),
+			"Invalid return value: Not enough data to fill buffer",
+		);
+	}
+
+	#[test]
+	fn only_metadata_v3_is_supported() {
+		let err = InkMetadata::from_json(br#"{ "V1": { "spec": {}, "types": [] } }"#)
+			.err()
+			.unwrap();
+		assert!(err.starts_with("Invalid ink! metadata: missing field `V3`"), "{}", err);
+	}
+}
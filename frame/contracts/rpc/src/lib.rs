@@ -17,28 +17,39 @@
 
 //! Node-specific RPC methods for interaction with contracts.
 
-use std::sync::Arc;
+mod ink;
 
-use codec::Codec;
+use std::{io::ErrorKind, path::PathBuf, sync::Arc};
+
+use codec::{Codec, Encode};
+use futures::{future, stream, task::Spawn, FutureExt, SinkExt, StreamExt};
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
 use pallet_contracts_primitives::{
-	Code, CodeUploadResult, ContractExecResult, ContractInstantiateResult,
+	Code, CodeUploadResult, ContractEstimateResult, ContractEvent, ContractExecResult,
+	ContractInstantiateResult, ContractTraceResult,
 };
+use sc_client_api::BlockchainEvents;
 use serde::{Deserialize, Serialize};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
-use sp_core::{Bytes, H256};
+use sp_core::{bytes::to_hex, Bytes, H256};
 use sp_rpc::number::NumberOrHex;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{Block as BlockT, Header as HeaderT},
 };
 
+use ink::InkMetadata;
+
 pub use pallet_contracts_rpc_runtime_api::ContractsApi as ContractsRuntimeApi;
 
 const RUNTIME_ERROR: i64 = 1;
 const CONTRACT_DOESNT_EXIST: i64 = 2;
+const METADATA_NOT_FOUND: i64 = 3;
+const INVALID_METADATA: i64 = 4;
 
 pub type Weight = u64;
 
@@ -84,6 +95,47 @@ pub struct CallRequest<AccountId> {
 	input_data: Bytes,
 }
 
+/// A struct that encodes RPC parameters required for a call to a message of an ink! contract.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct MessageCallRequest<AccountId> {
+	origin: AccountId,
+	dest: AccountId,
+	value: NumberOrHex,
+	gas_limit: NumberOrHex,
+	storage_deposit_limit: Option<NumberOrHex>,
+	message: String,
+	#[serde(default)]
+	args: serde_json::Value,
+}
+
+/// The result of a call to a message of an ink! contract.
+#[derive(Serialize, Deserialize)]
+#[serde(
+	rename_all = "camelCase",
+	bound(serialize = "Balance: Copy + Into<NumberOrHex>"),
+	bound(deserialize = "Balance: TryFrom<NumberOrHex>")
+)]
+pub struct MessageCallResult<Balance> {
+	/// The result of the call.
+	pub call: ContractExecResult<Balance>,
+	/// The return value of the message decoded into JSON.
+	///
+	/// `None` if the message returns nothing or the call failed or reverted.
+	pub output: Option<serde_json::Value>,
+}
+
+/// A struct that encodes RPC parameters required for a query to a CosmWasm contract.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct QueryRequest<AccountId> {
+	dest: AccountId,
+	gas_limit: NumberOrHex,
+	input_data: Bytes,
+}
+
 /// A struct that encodes RPC parameters required to instantiate a new smart-contract.
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -108,12 +160,59 @@ pub struct CodeUploadRequest<AccountId> {
 	storage_deposit_limit: Option<NumberOrHex>,
 }
 
+/// A struct that encodes RPC parameters to select the contract events a subscriber receives.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+#[serde(bound(deserialize = "AccountId: Deserialize<'de>, Hash: Deserialize<'de>"))]
+pub struct EventFilter<AccountId, Hash> {
+	contract: Option<AccountId>,
+	#[serde(default)]
+	topics: Vec<Option<Hash>>,
+	#[serde(default)]
+	finalized: bool,
+}
+
+impl<AccountId: PartialEq, Hash: PartialEq> EventFilter<AccountId, Hash> {
+	/// Whether `event` was emitted by the contract of the filter and with its topics.
+	///
+	/// The topics are matched by position and a `None` matches any topic.
+	fn matches(&self, event: &ContractEvent<AccountId, Hash>) -> bool {
+		self.contract.iter().all(|contract| *contract == event.contract) &&
+			self.topics.len() <= event.topics.len() &&
+			self.topics
+				.iter()
+				.zip(&event.topics)
+				.all(|(filter, topic)| filter.iter().all(|filter| filter == topic))
+	}
+}
+
+/// An event emitted by a contract which is pushed to the subscribers of
+/// `contracts_subscribeEvents`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventNotification<BlockHash, AccountId, Hash> {
+	/// The block in which the event was emitted.
+	pub block_hash: BlockHash,
+	/// The index of the extrinsic which emitted the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// The contract which emitted the event.
+	pub contract: AccountId,
+	/// The data of the event.
+	pub data: Bytes,
+	/// The topics of the event.
+	pub topics: Vec<Hash>,
+}
+
 /// Contracts RPC methods.
 #[rpc]
 pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance, Hash>
 where
 	Balance: Copy + TryFrom<NumberOrHex> + Into<NumberOrHex>,
 {
+	/// RPC Metadata
+	type Metadata;
+
 	/// Executes a call to a contract.
 	///
 	/// This call is performed locally without submitting any transactions. Thus executing this
@@ -128,6 +227,64 @@ where
 		at: Option<BlockHash>,
 	) -> Result<ContractExecResult<Balance>>;
 
+	/// Executes a call to a contract and returns the frames it executed.
+	///
+	/// Same as `contracts_call`, but the result additionally contains the tree of the frames
+	/// executed by the call. Every frame shows the called contract, the entry point, the input,
+	/// the output, the consumed gas, the storage deposit and the error it failed with, if any.
+	///
+	/// This method is useful to find out where a call failed when it spans multiple contracts.
+	#[rpc(name = "contracts_traceCall")]
+	fn trace_call(
+		&self,
+		call_request: CallRequest<AccountId>,
+		at: Option<BlockHash>,
+	) -> Result<ContractTraceResult<AccountId, Balance>>;
+
+	/// Estimates the gas limit and storage deposit limit required by a call to a contract.
+	///
+	/// The call is executed repeatedly without submitting any transactions, bisecting the gas
+	/// limit between zero and the `gasLimit` of the request. The result contains the smallest
+	/// gas limit with which the call succeeds, the largest storage deposit charged by any of
+	/// the executions and the result of the call when executed with the estimated gas limit.
+	///
+	/// This method is useful for wallets to set accurate limits before submitting a call.
+	#[rpc(name = "contracts_estimateCall")]
+	fn estimate_call(
+		&self,
+		call_request: CallRequest<AccountId>,
+		at: Option<BlockHash>,
+	) -> Result<ContractEstimateResult<Balance>>;
+
+	/// Executes a call to a message of an ink! contract.
+	///
+	/// Same as `contracts_call`, but instead of `inputData` the request contains the label of
+	/// the `message` and its `args` as JSON. The arguments are given as an object keyed by
+	/// their labels or as an array. They are encoded using the ink! metadata registered for the
+	/// code of the contract through `set_code_metadata` or, if none is registered, the metadata
+	/// found in the metadata directory of the node. The return value of the message is decoded
+	/// into `output`.
+	///
+	/// This method makes it possible to call ink! contracts without encoding their messages.
+	#[rpc(name = "contracts_callMessage")]
+	fn call_message(
+		&self,
+		call_request: MessageCallRequest<AccountId>,
+		at: Option<BlockHash>,
+	) -> Result<MessageCallResult<Balance>>;
+
+	/// Queries a CosmWasm contract.
+	///
+	/// The `query` entry point of the contract is called with `input_data` as message. Contrary
+	/// to `contracts_call` the contract is not allowed to change any state and the call fails
+	/// if it tries to.
+	#[rpc(name = "contracts_query")]
+	fn query(
+		&self,
+		query_request: QueryRequest<AccountId>,
+		at: Option<BlockHash>,
+	) -> Result<ContractExecResult<Balance>>;
+
 	/// Instantiate a new contract.
 	///
 	/// This instantiate is performed locally without submitting any transactions. Thus the contract
@@ -163,18 +320,89 @@ where
 		key: H256,
 		at: Option<BlockHash>,
 	) -> Result<Option<Bytes>>;
+
+	/// Returns the addresses of the contracts which were given `label` on instantiation.
+	///
+	/// Labels are not unique. Contracts instantiated without a label can't be looked up.
+	#[rpc(name = "contracts_getContractsByLabel")]
+	fn get_contracts_by_label(
+		&self,
+		label: String,
+		at: Option<BlockHash>,
+	) -> Result<Vec<AccountId>>;
+
+	/// Subscribes to the events emitted by contracts through `seal_deposit_event`.
+	///
+	/// The events of every new best block are pushed as it is imported or, if `finalized` is
+	/// set in the filter, of every block as it is finalized. Only the events emitted by the
+	/// `contract` of the filter are pushed if it is set. The `topics` of the filter are
+	/// matched by position against the topics of the events, where `null` matches any topic.
+	#[pubsub(subscription = "contracts_events", subscribe, name = "contracts_subscribeEvents")]
+	fn subscribe_events(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<EventNotification<BlockHash, AccountId, Hash>>,
+		filter: Option<EventFilter<AccountId, Hash>>,
+	);
+
+	/// Unsubscribes from the events emitted by contracts.
+	#[pubsub(
+		subscription = "contracts_events",
+		unsubscribe,
+		name = "contracts_unsubscribeEvents"
+	)]
+	fn unsubscribe_events(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
 }
 
 /// An implementation of contract specific RPC methods.
 pub struct Contracts<C, B> {
 	client: Arc<C>,
+	manager: SubscriptionManager,
+	metadata_dir: Option<PathBuf>,
 	_marker: std::marker::PhantomData<B>,
 }
 
 impl<C, B> Contracts<C, B> {
-	/// Create new `Contracts` with the given reference to the client.
-	pub fn new(client: Arc<C>) -> Self {
-		Contracts { client, _marker: Default::default() }
+	/// Create new `Contracts` with the given reference to the client and the executor
+	/// which runs the subscriptions.
+	pub fn new<E>(client: Arc<C>, executor: E) -> Self
+	where
+		E: Spawn + Send + Sync + 'static,
+	{
+		let manager = SubscriptionManager::new(Arc::new(executor));
+		Contracts { client, manager, metadata_dir: None, _marker: Default::default() }
+	}
+
+	/// Read the ink! metadata of codes for which no metadata is registered from `dir`.
+	///
+	/// The metadata of a code is expected in a file named after its hex encoded code hash,
+	/// e.g. `0x1234…cdef.json`.
+	pub fn with_metadata_dir(mut self, dir: PathBuf) -> Self {
+		self.metadata_dir = Some(dir);
+		self
+	}
+
+	/// Read the metadata of the code stored under `code_hash` from the metadata directory.
+	fn read_metadata(&self, code_hash: &impl Encode) -> Result<Vec<u8>> {
+		let not_found = || Error {
+			code: ErrorCode::ServerError(METADATA_NOT_FOUND),
+			message: "No metadata is available for the code of the contract.".into(),
+			data: None,
+		};
+		let dir = self.metadata_dir.as_ref().ok_or_else(not_found)?;
+		let path = dir.join(format!("{}.json", to_hex(&code_hash.encode(), false)));
+		std::fs::read(&path).map_err(|e| match e.kind() {
+			ErrorKind::NotFound => not_found(),
+			_ => Error {
+				code: ErrorCode::ServerError(METADATA_NOT_FOUND),
+				message: format!("Failed to read the metadata from {}", path.display()),
+				data: Some(e.to_string().into()),
+			},
+		})
 	}
 }
 impl<C, Block, AccountId, Balance, Hash>
@@ -187,7 +415,12 @@ impl<C, Block, AccountId, Balance, Hash>
 	> for Contracts<C, Block>
 where
 	Block: BlockT,
-	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C: Send
+		+ Sync
+		+ 'static
+		+ ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ BlockchainEvents<Block>,
 	C::Api: ContractsRuntimeApi<
 		Block,
 		AccountId,
@@ -195,10 +428,13 @@ where
 		<<Block as BlockT>::Header as HeaderT>::Number,
 		Hash,
 	>,
-	AccountId: Codec,
+	Block::Hash: Unpin,
+	AccountId: Codec + Clone + PartialEq + Serialize + Unpin + Send + Sync + 'static,
 	Balance: Codec + Copy + TryFrom<NumberOrHex> + Into<NumberOrHex>,
-	Hash: Codec,
+	Hash: Codec + Clone + PartialEq + Serialize + Unpin + Send + Sync + 'static,
 {
+	type Metadata = sc_rpc_api::Metadata;
+
 	fn call(
 		&self,
 		call_request: CallRequest<AccountId>,
@@ -222,6 +458,140 @@ where
 			.map_err(runtime_error_into_rpc_err)
 	}
 
+	fn trace_call(
+		&self,
+		call_request: CallRequest<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ContractTraceResult<AccountId, Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let CallRequest { origin, dest, value, gas_limit, storage_deposit_limit, input_data } =
+			call_request;
+
+		let value: Balance = decode_hex(value, "balance")?;
+		let gas_limit: Weight = decode_hex(gas_limit, "weight")?;
+		let storage_deposit_limit: Option<Balance> =
+			storage_deposit_limit.map(|l| decode_hex(l, "balance")).transpose()?;
+		limit_gas(gas_limit)?;
+
+		api.trace_call(
+			&at,
+			origin,
+			dest,
+			value,
+			gas_limit,
+			storage_deposit_limit,
+			input_data.to_vec(),
+		)
+		.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn estimate_call(
+		&self,
+		call_request: CallRequest<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ContractEstimateResult<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let CallRequest { origin, dest, value, gas_limit, storage_deposit_limit, input_data } =
+			call_request;
+
+		let value: Balance = decode_hex(value, "balance")?;
+		let gas_limit: Weight = decode_hex(gas_limit, "weight")?;
+		let storage_deposit_limit: Option<Balance> =
+			storage_deposit_limit.map(|l| decode_hex(l, "balance")).transpose()?;
+		limit_gas(gas_limit)?;
+
+		api.estimate_call(
+			&at,
+			origin,
+			dest,
+			value,
+			gas_limit,
+			storage_deposit_limit,
+			input_data.to_vec(),
+		)
+		.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn call_message(
+		&self,
+		call_request: MessageCallRequest<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<MessageCallResult<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let MessageCallRequest {
+			origin,
+			dest,
+			value,
+			gas_limit,
+			storage_deposit_limit,
+			message,
+			args,
+		} = call_request;
+
+		let value: Balance = decode_hex(value, "balance")?;
+		let gas_limit: Weight = decode_hex(gas_limit, "weight")?;
+		let storage_deposit_limit: Option<Balance> =
+			storage_deposit_limit.map(|l| decode_hex(l, "balance")).transpose()?;
+		limit_gas(gas_limit)?;
+
+		let code_hash =
+			api.code_hash(&at, dest.clone()).map_err(runtime_error_into_rpc_err)?.ok_or(
+				ContractAccessError(pallet_contracts_primitives::ContractAccessError::DoesntExist),
+			)?;
+		let metadata =
+			match api.code_metadata(&at, code_hash.clone()).map_err(runtime_error_into_rpc_err)? {
+				Some(metadata) => metadata,
+				None => self.read_metadata(&code_hash)?,
+			};
+		let metadata = InkMetadata::from_json(&metadata).map_err(invalid_metadata_into_rpc_err)?;
+		let message = metadata.encode_message(&message, &args).map_err(|e| Error {
+			code: ErrorCode::InvalidParams,
+			message: e,
+			data: None,
+		})?;
+
+		let call = api
+			.call(&at, origin, dest, value, gas_limit, storage_deposit_limit, message.input)
+			.map_err(runtime_error_into_rpc_err)?;
+		let output = match (&call.result, message.return_type) {
+			(Ok(result), Some(ty)) if !result.did_revert() =>
+				Some(metadata.decode(ty, &result.data).map_err(invalid_metadata_into_rpc_err)?),
+			_ => None,
+		};
+
+		Ok(MessageCallResult { call, output })
+	}
+
+	fn query(
+		&self,
+		query_request: QueryRequest<AccountId>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ContractExecResult<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let QueryRequest { dest, gas_limit, input_data } = query_request;
+
+		let gas_limit: Weight = decode_hex(gas_limit, "weight")?;
+		limit_gas(gas_limit)?;
+
+		api.query(&at, dest, gas_limit, input_data.to_vec()).map_err(runtime_error_into_rpc_err)
+	}
+
 	fn instantiate(
 		&self,
 		instantiate_request: InstantiateRequest<AccountId, Hash>,
@@ -299,6 +669,95 @@ where
 
 		Ok(result)
 	}
+
+	fn get_contracts_by_label(
+		&self,
+		label: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		api.contracts_by_label(&at, label.into_bytes()).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn subscribe_events(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<EventNotification<<Block as BlockT>::Hash, AccountId, Hash>>,
+		filter: Option<EventFilter<AccountId, Hash>>,
+	) {
+		let filter =
+			filter.unwrap_or(EventFilter { contract: None, topics: Vec::new(), finalized: false });
+
+		let blocks = if filter.finalized {
+			self.client
+				.finality_notification_stream()
+				// Blocks which were finalized along with the notified one are not notified.
+				.flat_map(|notification| {
+					let mut hashes = notification.tree_route.to_vec();
+					hashes.push(notification.hash);
+					stream::iter(hashes)
+				})
+				.boxed()
+		} else {
+			self.client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				// Blocks which were enacted by a re-org are not notified.
+				.flat_map(|notification| {
+					let mut hashes: Vec<_> = notification
+						.tree_route
+						.iter()
+						.flat_map(|route| route.enacted().iter().map(|block| block.hash))
+						.collect();
+					hashes.push(notification.hash);
+					stream::iter(hashes)
+				})
+				.boxed()
+		};
+
+		let client = self.client.clone();
+		let notifications = blocks.flat_map(move |block_hash| {
+			let events = client
+				.runtime_api()
+				.contract_events(&BlockId::hash(block_hash))
+				.unwrap_or_else(|e| {
+					warn!("Failed to read the contract events of block {:?}: {}", block_hash, e);
+					Vec::new()
+				});
+			let notifications: Vec<_> = events
+				.into_iter()
+				.filter(|event| filter.matches(event))
+				.map(|event| {
+					Ok(Ok::<_, Error>(EventNotification {
+						block_hash,
+						extrinsic_index: event.extrinsic_index,
+						contract: event.contract,
+						data: event.data,
+						topics: event.topics,
+					}))
+				})
+				.collect();
+			stream::iter(notifications)
+		});
+
+		self.manager.add(subscriber, |sink| {
+			notifications
+				.forward(sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_events(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
 }
 
 /// Converts a runtime trap into an RPC error.
@@ -310,6 +769,15 @@ fn runtime_error_into_rpc_err(err: impl std::fmt::Display) -> Error {
 	}
 }
 
+/// Converts an error caused by metadata which does not match the contract into an RPC error.
+fn invalid_metadata_into_rpc_err(err: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(INVALID_METADATA),
+		message: "The metadata does not match the contract".into(),
+		data: Some(err.into()),
+	}
+}
+
 fn decode_hex<H: std::fmt::Debug + Copy, T: TryFrom<H>>(from: H, name: &str) -> Result<T> {
 	from.try_into().map_err(|_| Error {
 		code: ErrorCode::InvalidParams,
@@ -363,6 +831,60 @@ mod tests {
 		assert_eq!(req.value.into_u256(), U256::from(1234567890987654321u128));
 	}
 
+	#[test]
+	fn message_call_request_should_serialize_deserialize_properly() {
+		type Req = MessageCallRequest<String>;
+		let req: Req = serde_json::from_str(
+			r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"dest": "5DRakbLVnjVrW6niwLfHGW24EeCEvDAFGEXrtaYS5M4ynoom",
+			"value": 0,
+			"gasLimit": 1000000000000,
+			"message": "transfer",
+			"args": { "to": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL", "value": "42" }
+		}
+		"#,
+		)
+		.unwrap();
+		assert_eq!(req.message, "transfer");
+		assert_eq!(req.args["value"], "42");
+		assert_eq!(req.storage_deposit_limit, None);
+
+		// The arguments can be omitted for messages without arguments.
+		let req: Req = serde_json::from_str(
+			r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"dest": "5DRakbLVnjVrW6niwLfHGW24EeCEvDAFGEXrtaYS5M4ynoom",
+			"value": 0,
+			"gasLimit": 1000000000000,
+			"message": "total_supply"
+		}
+		"#,
+		)
+		.unwrap();
+		assert_eq!(req.args, serde_json::Value::Null);
+	}
+
+	#[test]
+	fn query_request_should_serialize_deserialize_properly() {
+		type Req = QueryRequest<String>;
+		let req: Req = serde_json::from_str(
+			r#"
+		{
+			"dest": "5DRakbLVnjVrW6niwLfHGW24EeCEvDAFGEXrtaYS5M4ynoom",
+			"gasLimit": 1000000000000,
+			"inputData": "0x7b7d"
+		}
+		"#,
+		)
+		.unwrap();
+		assert_eq!(req.dest, "5DRakbLVnjVrW6niwLfHGW24EeCEvDAFGEXrtaYS5M4ynoom");
+		assert_eq!(req.gas_limit.into_u256(), U256::from(0xe8d4a51000u64));
+		assert_eq!(&*req.input_data, b"{}".as_ref());
+	}
+
 	#[test]
 	fn instantiate_request_should_serialize_deserialize_properly() {
 		type Req = InstantiateRequest<String, String>;
@@ -393,6 +915,26 @@ mod tests {
 		assert_eq!(&code, "0x1122");
 	}
 
+	#[test]
+	fn instantiate_request_with_code_id_should_deserialize_properly() {
+		type Req = InstantiateRequest<String, String>;
+		let req: Req = serde_json::from_str(
+			r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"value": "0x88",
+			"gasLimit": 42,
+			"code": { "id": 7 },
+			"data": "0x4299",
+			"salt": "0x9988"
+		}
+		"#,
+		)
+		.unwrap();
+
+		assert_eq!(req.code, Code::Id(7));
+	}
+
 	#[test]
 	fn code_upload_request_should_serialize_deserialize_properly() {
 		type Req = CodeUploadRequest<String>;
@@ -411,6 +953,53 @@ mod tests {
 		assert_eq!(req.storage_deposit_limit.map(|l| l.into_u256()), Some(5000.into()));
 	}
 
+	#[test]
+	fn event_filter_should_deserialize_and_match_properly() {
+		type Filter = EventFilter<String, u32>;
+		let filter: Filter = serde_json::from_str(
+			r#"
+		{
+			"contract": "5CiPP",
+			"topics": [null, 42],
+			"finalized": true
+		}
+		"#,
+		)
+		.unwrap();
+		assert!(filter.finalized);
+
+		let event = |contract: &str, topics: Vec<u32>| ContractEvent {
+			extrinsic_index: Some(1),
+			contract: contract.to_string(),
+			data: Bytes(vec![]),
+			topics,
+		};
+		assert!(filter.matches(&event("5CiPP", vec![1, 42])));
+		assert!(filter.matches(&event("5CiPP", vec![2, 42, 3])));
+		assert!(!filter.matches(&event("5CiPP", vec![42, 1])));
+		assert!(!filter.matches(&event("5CiPP", vec![1])));
+		assert!(!filter.matches(&event("5DRak", vec![1, 42])));
+
+		// An empty filter matches all events.
+		let filter: Filter = serde_json::from_str("{}").unwrap();
+		assert!(!filter.finalized);
+		assert!(filter.matches(&event("5DRak", vec![])));
+	}
+
+	#[test]
+	fn event_notification_should_serialize_deserialize_properly() {
+		let expected = r#"{
+			"blockHash": "0x1234",
+			"extrinsicIndex": 2,
+			"contract": "5CiPP",
+			"data": "0x0400",
+			"topics": [42]
+		}"#;
+		let res: EventNotification<String, String, u32> = serde_json::from_str(expected).unwrap();
+		let actual = serde_json::to_string(&res).unwrap();
+		assert_eq!(actual, trim(expected).as_str());
+	}
+
 	#[test]
 	fn call_result_should_serialize_deserialize_properly() {
 		fn test(expected: &str) {
@@ -483,6 +1072,133 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn trace_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: ContractTraceResult<String, u32> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, trim(expected).as_str());
+		}
+		test(
+			r#"{
+			"call": {
+				"gasConsumed": 5000,
+				"gasRequired": 8000,
+				"storageDeposit": {"charge": 42000},
+				"debugMessage": "HelloWorld",
+				"result": {
+				  "Err": "BadOrigin"
+				}
+			},
+			"trace": {
+				"contract": "5CiPP",
+				"entryPoint": "call",
+				"input": "0x1234",
+				"result": {
+				  "Ok": {
+					"flags": 0,
+					"data": "0x"
+				  }
+				},
+				"gasConsumed": 4000,
+				"storageDeposit": {"charge": 42000},
+				"calls": [{
+					"contract": "5DRak",
+					"entryPoint": "delegateCall",
+					"input": "0x",
+					"result": {
+					  "Err": "BadOrigin"
+					},
+					"gasConsumed": 1000,
+					"storageDeposit": {"charge": 0},
+					"calls": []
+				}]
+			}
+		}"#,
+		);
+		test(
+			r#"{
+			"call": {
+				"gasConsumed": 3400,
+				"gasRequired": 5200,
+				"storageDeposit": {"charge": 0},
+				"debugMessage": "",
+				"result": {
+				  "Err": "BadOrigin"
+				}
+			},
+			"trace": null
+		}"#,
+		);
+	}
+
+	#[test]
+	fn estimate_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: ContractEstimateResult<u32> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, trim(expected).as_str());
+		}
+		test(
+			r#"{
+			"gasRequired": 7000,
+			"storageDeposit": {"charge": 42000},
+			"call": {
+				"gasConsumed": 5000,
+				"gasRequired": 8000,
+				"storageDeposit": {"refund": 12000},
+				"debugMessage": "HelloWorld",
+				"result": {
+				  "Ok": {
+					"flags": 0,
+					"data": "0x1234"
+				  }
+				}
+			}
+		}"#,
+		);
+	}
+
+	#[test]
+	fn message_call_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: MessageCallResult<u32> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, trim(expected).as_str());
+		}
+		test(
+			r#"{
+			"call": {
+				"gasConsumed": 5000,
+				"gasRequired": 8000,
+				"storageDeposit": {"charge": 0},
+				"debugMessage": "",
+				"result": {
+				  "Ok": {
+					"flags": 0,
+					"data": "0x00"
+				  }
+				}
+			},
+			"output": {"Ok": null}
+		}"#,
+		);
+		test(
+			r#"{
+			"call": {
+				"gasConsumed": 3400,
+				"gasRequired": 5200,
+				"storageDeposit": {"charge": 0},
+				"debugMessage": "",
+				"result": {
+				  "Err": "BadOrigin"
+				}
+			},
+			"output": null
+		}"#,
+		);
+	}
+
 	#[test]
 	fn code_upload_result_should_serialize_deserialize_properly() {
 		fn test(expected: &str) {
@@ -396,6 +396,153 @@ where
 		.into()
 	}
 
+	/// Creates a CosmWasm contract whose `execute` entry point runs `execute_body` and then
+	/// returns an empty successful response. `instantiate`, `query` and `migrate` return that
+	/// response right away.
+	///
+	/// The contract defines and exports its own linear memory of maximum size. The lower half
+	/// of it is available to the `data_segments`. `allocate` always hands out the same region
+	/// at the start of the upper half so that the memory can't be exhausted by repeated calls.
+	pub fn cosmwasm(
+		imported_functions: Vec<ImportedFunction>,
+		data_segments: Vec<DataSegment>,
+		execute_body: FuncBody,
+	) -> Self {
+		Self::cosmwasm_with(imported_functions, data_segments, execute_body, false)
+	}
+
+	/// Creates a CosmWasm contract like [`Self::cosmwasm`] with an empty `execute` entry point
+	/// which additionally exports the IBC entry points.
+	///
+	/// `ibc_channel_open` accepts the proposed version and `ibc_packet_receive` returns an
+	/// empty acknowledgement. The other IBC entry points return an empty response.
+	#[cfg(feature = "stargate")]
+	pub fn cosmwasm_ibc() -> Self {
+		let execute_body = body::plain(vec![Instruction::End]);
+		Self::cosmwasm_with(vec![], vec![], execute_body, true)
+	}
+
+	fn cosmwasm_with(
+		imported_functions: Vec<ImportedFunction>,
+		data_segments: Vec<DataSegment>,
+		execute_body: FuncBody,
+		ibc: bool,
+	) -> Self {
+		use self::elements::Instruction::{End, GetLocal, I32Const, I32Store};
+		const RESPONSE: &[u8] =
+			br#"{"ok":{"messages":[],"attributes":[],"events":[],"data":null}}"#;
+		const OPEN_RESPONSE: &[u8] = br#"{"ok":null}"#;
+		const RECEIVE_RESPONSE: &[u8] =
+			br#"{"ok":{"acknowledgement":"","messages":[],"attributes":[],"events":[]}}"#;
+		let pages = max_pages::<T>();
+		let heap = (pages * 64 * 1024 / 2) as i32;
+		let response = heap - 0x100;
+		let func_offset = u32::try_from(imported_functions.len()).unwrap();
+		let response_body = || body::plain(vec![I32Const(response), End]);
+		// Return the response instead of ending the supplied body.
+		let execute_body = {
+			let mut code = execute_body.code().elements().to_vec();
+			code.pop();
+			code.extend([I32Const(response), End]);
+			FuncBody::new(execute_body.locals().to_vec(), Instructions::new(code))
+		};
+
+		let mut contract = builder::module()
+			.memory()
+			.with_min(pages)
+			.with_max(Some(pages))
+			.build()
+			.export()
+			.field("memory")
+			.internal()
+			.memory(0)
+			.build();
+
+		let mut entry_points: Vec<(&str, Vec<ValueType>, bool, FuncBody)> = vec![
+			(
+				"allocate",
+				vec![ValueType::I32],
+				true,
+				body::plain(vec![
+					I32Const(heap),
+					I32Const(heap + 12),
+					I32Store(2, 0),
+					I32Const(heap),
+					GetLocal(0),
+					I32Store(2, 4),
+					I32Const(heap),
+					I32Const(0),
+					I32Store(2, 8),
+					I32Const(heap),
+					End,
+				]),
+			),
+			("deallocate", vec![ValueType::I32], false, body::plain(vec![End])),
+			("interface_version_8", vec![], false, body::plain(vec![End])),
+			("instantiate", vec![ValueType::I32; 3], true, response_body()),
+			("execute", vec![ValueType::I32; 3], true, execute_body),
+			("query", vec![ValueType::I32; 2], true, response_body()),
+			("migrate", vec![ValueType::I32; 2], true, response_body()),
+		];
+		if ibc {
+			// The responses of `ibc_channel_open` and `ibc_packet_receive` follow the one of
+			// the other entry points.
+			let open_body = body::plain(vec![I32Const(response + 12), End]);
+			let receive_body = body::plain(vec![I32Const(response + 24), End]);
+			entry_points.extend([
+				("ibc_channel_open", vec![ValueType::I32; 2], true, open_body),
+				("ibc_channel_connect", vec![ValueType::I32; 2], true, response_body()),
+				("ibc_channel_close", vec![ValueType::I32; 2], true, response_body()),
+				("ibc_packet_receive", vec![ValueType::I32; 2], true, receive_body),
+				("ibc_packet_ack", vec![ValueType::I32; 2], true, response_body()),
+				("ibc_packet_timeout", vec![ValueType::I32; 2], true, response_body()),
+			]);
+		}
+		for (index, (name, params, returns, body)) in entry_points.into_iter().enumerate() {
+			let mut signature = contract.function().signature().with_params(params);
+			if returns {
+				signature = signature.with_result(ValueType::I32);
+			}
+			contract = signature
+				.build()
+				.with_body(body)
+				.build()
+				.export()
+				.field(name)
+				.internal()
+				.func(func_offset + index as u32)
+				.build();
+		}
+
+		for func in imported_functions {
+			let sig = builder::signature()
+				.with_params(func.params)
+				.with_results(func.return_type.into_iter().collect())
+				.build_sig();
+			let sig = contract.push_signature(sig);
+			contract = contract
+				.import()
+				.module(func.module)
+				.field(func.name)
+				.with_external(elements::External::Function(sig))
+				.build();
+		}
+
+		let responses = [RESPONSE.to_vec(), OPEN_RESPONSE.to_vec(), RECEIVE_RESPONSE.to_vec()];
+		let response_segment = regions(response as u32, &responses);
+		for data in data_segments.into_iter().chain(sp_std::iter::once(response_segment)) {
+			contract = contract
+				.data()
+				.offset(Instruction::I32Const(data.offset as i32))
+				.value(data.value)
+				.build()
+		}
+
+		let code = contract.build().to_bytes().unwrap();
+		let hash = T::Hashing::hash(&code);
+		Self { code, hash, memory: None }
+	}
+
 	/// Creates a memory instance for use in a sandbox with dimensions declared in this module
 	/// and adds it to `env`. A reference to that memory is returned so that it can be used to
 	/// access the memory contents from the supervisor.
@@ -543,6 +690,12 @@ pub mod body {
 		FuncBody::new(Vec::new(), Instructions::new(body))
 	}
 
+	/// Insert the `prefix` instructions at the start of the supplied `body`.
+	pub fn prefixed(prefix: &[Instruction], mut body: FuncBody) -> FuncBody {
+		body.code_mut().elements_mut().splice(0..0, prefix.iter().cloned());
+		body
+	}
+
 	/// Replace the locals of the supplied `body` with `num` i64 locals.
 	pub fn inject_locals(body: &mut FuncBody, num: u32) {
 		use self::elements::Local;
@@ -550,6 +703,22 @@ pub mod body {
 	}
 }
 
+/// Creates a data segment at `offset` holding one CosmWasm region per item followed by the
+/// items themselves.
+///
+/// The region of the `i`-th item is located at `offset + 12 * i`.
+pub fn regions(offset: u32, items: &[Vec<u8>]) -> DataSegment {
+	let mut data_offset = offset + 12 * items.len() as u32;
+	let mut value = Vec::new();
+	for item in items {
+		let len = item.len() as u32;
+		value.extend([data_offset.to_le_bytes(), len.to_le_bytes(), len.to_le_bytes()].concat());
+		data_offset += len;
+	}
+	value.extend(items.iter().flatten());
+	DataSegment { offset, value }
+}
+
 /// The maximum amount of pages any contract is allowed to have according to the current `Schedule`.
 pub fn max_pages<T: Config>() -> u32
 where
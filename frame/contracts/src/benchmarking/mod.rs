@@ -38,7 +38,13 @@ use crate::{
 };
 use codec::{Encode, MaxEncodedLen};
 use frame_benchmarking::{account, benchmarks, whitelisted_caller};
-use frame_support::weights::Weight;
+use frame_support::{
+	traits::{
+		fungibles::{self, Create, Inspect, Mutate},
+		EnsureOrigin,
+	},
+	weights::Weight,
+};
 use frame_system::RawOrigin;
 use sp_runtime::{
 	traits::{Bounded, Hash},
@@ -101,6 +107,8 @@ where
 			module.hash,
 			data,
 			salt,
+			None,
+			vec![],
 		)?;
 
 		let result =
@@ -195,11 +203,114 @@ macro_rules! load_benchmark {
 	}};
 }
 
-benchmarks! {
+/// Expands to `benchmarks!` extended by the benchmarks of the IBC extrinsics. Those only exist
+/// with the `stargate` feature and `benchmarks!` can't gate individual benchmarks.
+#[cfg(all(feature = "runtime-benchmarks", feature = "stargate"))]
+macro_rules! contracts_benchmarks {
+	($($benchmarks:tt)*) => {
+		benchmarks! {
+			$($benchmarks)*
+
+			// We open a channel between two CosmWasm contracts whose IBC entry points merely
+			// return in order to measure the overhead of the handshake. The costs for executing
+			// the entry points are covered by the gas meter.
+			ibc_open_channel {
+				let instance = Contract::<T>::with_caller(
+					whitelisted_caller(), WasmModule::cosmwasm_ibc(), vec![],
+				)?;
+				let counterparty =
+					Contract::<T>::with_index(1, WasmModule::cosmwasm_ibc(), vec![])?;
+				let channel_id = <NextIbcChannelId<T>>::get();
+				let origin = RawOrigin::Signed(instance.caller.clone());
+			}: _(
+				origin,
+				instance.addr.clone(),
+				counterparty.addr.clone(),
+				IbcOrder::Ordered,
+				b"ics20-1".to_vec(),
+				Weight::MAX,
+				None
+			)
+			verify {
+				assert!(<IbcChannels<T>>::contains_key(channel_id));
+			}
+
+			// The worst case is a packet sent over an ordered channel which is received and
+			// acknowledged rather than timed out.
+			ibc_relay_packet {
+				let instance = Contract::<T>::with_caller(
+					whitelisted_caller(), WasmModule::cosmwasm_ibc(), vec![],
+				)?;
+				let counterparty =
+					Contract::<T>::with_index(1, WasmModule::cosmwasm_ibc(), vec![])?;
+				let channel_id = <NextIbcChannelId<T>>::get();
+				Contracts::<T>::ibc_open_channel(
+					RawOrigin::Signed(instance.caller.clone()).into(),
+					instance.addr.clone(),
+					counterparty.addr.clone(),
+					IbcOrder::Ordered,
+					b"ics20-1".to_vec(),
+					Weight::MAX,
+					None,
+				)
+				.map_err(|err| err.error)?;
+				let timeout = T::BlockNumber::max_value();
+				let packet = ibc::RawPacket { data: b"ping".to_vec(), timeout };
+				<IbcPackets<T>>::insert(channel_id, 1, packet);
+				let origin = RawOrigin::Signed(instance.caller.clone());
+			}: _(origin, channel_id, 1, Weight::MAX, None)
+			verify {
+				assert!(!<IbcPackets<T>>::contains_key(channel_id, 1));
+			}
+
+			ibc_close_channel {
+				let instance = Contract::<T>::with_caller(
+					whitelisted_caller(), WasmModule::cosmwasm_ibc(), vec![],
+				)?;
+				let counterparty =
+					Contract::<T>::with_index(1, WasmModule::cosmwasm_ibc(), vec![])?;
+				let channel_id = <NextIbcChannelId<T>>::get();
+				Contracts::<T>::ibc_open_channel(
+					RawOrigin::Signed(instance.caller.clone()).into(),
+					instance.addr.clone(),
+					counterparty.addr.clone(),
+					IbcOrder::Ordered,
+					b"ics20-1".to_vec(),
+					Weight::MAX,
+					None,
+				)
+				.map_err(|err| err.error)?;
+				<IbcChannels<T>>::mutate(channel_id, |end| {
+					if let Some(end) = end {
+						end.state = ibc::ChannelState::Closing;
+					}
+				});
+				let origin = RawOrigin::Signed(instance.caller.clone());
+			}: _(origin, channel_id, Weight::MAX, None)
+			verify {
+				let end = <IbcChannels<T>>::get(channel_id)
+					.ok_or("Expected the channel to exist.")?;
+				assert_eq!(end.state, ibc::ChannelState::Closed);
+			}
+		}
+	};
+}
+
+/// Expands to `benchmarks!`.
+#[cfg(not(feature = "stargate"))]
+macro_rules! contracts_benchmarks {
+	($($benchmarks:tt)*) => {
+		benchmarks! { $($benchmarks)* }
+	};
+}
+
+contracts_benchmarks! {
 	where_clause { where
 		T::AccountId: UncheckedFrom<T::Hash>,
 		T::AccountId: AsRef<[u8]>,
 		<BalanceOf<T> as codec::HasCompact>::Type: Clone + Eq + PartialEq + sp_std::fmt::Debug + scale_info::TypeInfo + codec::Encode,
+		T::Assets: fungibles::Create<T::AccountId> + fungibles::Mutate<T::AccountId>,
+		AssetIdOf<T>: From<u32>,
 	}
 
 	// The base weight without any actual work performed apart from the setup costs.
@@ -280,7 +391,7 @@ benchmarks! {
 		let WasmModule { code, hash, .. } = WasmModule::<T>::sized(c, Location::Call);
 		let origin = RawOrigin::Signed(caller.clone());
 		let addr = Contracts::<T>::contract_address(&caller, &hash, &salt);
-	}: _(origin, value, Weight::MAX, None, code, vec![], salt)
+	}: _(origin, value, Weight::MAX, None, code, vec![], salt, None, vec![])
 	verify {
 		// the contract itself does not trigger any reserves
 		let deposit = T::Currency::reserved_balance(&addr);
@@ -308,7 +419,7 @@ benchmarks! {
 		let origin = RawOrigin::Signed(caller.clone());
 		let addr = Contracts::<T>::contract_address(&caller, &hash, &salt);
 		Contracts::<T>::store_code_raw(code, caller.clone())?;
-	}: _(origin, value, Weight::MAX, None, hash, vec![], salt)
+	}: _(origin, value, Weight::MAX, None, hash, vec![], salt, None, vec![])
 	verify {
 		// the contract itself does not trigger any reserves
 		let deposit = T::Currency::reserved_balance(&addr);
@@ -351,6 +462,31 @@ benchmarks! {
 		instance.info()?;
 	}
 
+	// Same as `call` but `a` different assets are transferred to the contract in addition to
+	// the balance.
+	// `a`: Number of assets transferred.
+	call_with_assets {
+		let a in 0 .. T::Schedule::get().limits.transferred_assets;
+		let instance = Contract::<T>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let mut assets = Vec::new();
+		for id in 0 .. a {
+			let id = AssetIdOf::<T>::from(id);
+			T::Assets::create(id, instance.caller.clone(), true, 1u32.into())?;
+			T::Assets::mint_into(id, &instance.caller, 1_000u32.into())?;
+			assets.push((id, 100u32.into()));
+		}
+		let value = T::Currency::minimum_balance();
+		let origin = RawOrigin::Signed(instance.caller.clone());
+		let callee = instance.addr.clone();
+	}: _(origin, callee, value, assets.clone(), Weight::MAX, None, vec![])
+	verify {
+		for (id, amount) in assets {
+			assert_eq!(T::Assets::balance(id, &instance.account_id), amount);
+		}
+	}
+
 	// This constructs a contract that is maximal expensive to instrument.
 	// It creates a maximum number of metering blocks per byte.
 	// `c`: Size of the code in kilobytes.
@@ -391,6 +527,124 @@ benchmarks! {
 		assert!(<Contract<T>>::code_removed(&hash));
 	}
 
+	// We migrate a CosmWasm contract to another code whose `migrate` function merely returns
+	// in order to measure the overhead of the migrate extrinsic. The costs for executing the
+	// `migrate` function are covered by the gas meter.
+	migrate {
+		let empty = || body::plain(vec![Instruction::End]);
+		let instance = Contract::<T>::with_caller(
+			whitelisted_caller(), WasmModule::cosmwasm(vec![], vec![], empty()), vec![],
+		)?;
+		let mut info = instance.info()?;
+		info.admin = Some(instance.caller.clone());
+		<ContractInfoOf<T>>::insert(&instance.account_id, info);
+		// The data segment only makes the code differ from the code of the contract.
+		let data_segments = vec![DataSegment { offset: 0, value: vec![42] }];
+		let WasmModule { code, hash, .. } =
+			WasmModule::<T>::cosmwasm(vec![], data_segments, empty());
+		Contracts::<T>::store_code_raw(code, instance.caller.clone())?;
+		let code_id = <CodeHashToId<T>>::get(&hash).ok_or("Expected the code to have an id.")?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: _(origin, instance.addr.clone(), Weight::MAX, None, code_id, vec![])
+	verify {
+		assert_eq!(instance.info()?.code_hash, hash);
+	}
+
+	update_admin {
+		let instance = Contract::<T>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let mut info = instance.info()?;
+		info.admin = Some(instance.caller.clone());
+		<ContractInfoOf<T>>::insert(&instance.account_id, info);
+		let admin: T::AccountId = account("admin", 0, 0);
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: _(origin, instance.addr.clone(), T::Lookup::unlookup(admin.clone()))
+	verify {
+		assert_eq!(instance.info()?.admin, Some(admin));
+	}
+
+	clear_admin {
+		let instance = Contract::<T>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let mut info = instance.info()?;
+		info.admin = Some(instance.caller.clone());
+		<ContractInfoOf<T>>::insert(&instance.account_id, info);
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: _(origin, instance.addr.clone())
+	verify {
+		assert_eq!(instance.info()?.admin, None);
+	}
+
+	pause_contract {
+		let instance = Contract::<T>::new(WasmModule::dummy(), vec![])?;
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin, instance.addr.clone())
+	verify {
+		assert!(<PausedContracts<T>>::contains_key(&instance.account_id));
+	}
+
+	unpause_contract {
+		let instance = Contract::<T>::new(WasmModule::dummy(), vec![])?;
+		<PausedContracts<T>>::insert(&instance.account_id, ());
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin, instance.addr.clone())
+	verify {
+		assert!(!<PausedContracts<T>>::contains_key(&instance.account_id));
+	}
+
+	pause_code {
+		let WasmModule { code, hash, .. } = WasmModule::<T>::dummy();
+		Contracts::<T>::store_code_raw(code, whitelisted_caller())?;
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin, hash)
+	verify {
+		assert!(<PausedCodes<T>>::contains_key(&hash));
+	}
+
+	unpause_code {
+		let WasmModule { code, hash, .. } = WasmModule::<T>::dummy();
+		Contracts::<T>::store_code_raw(code, whitelisted_caller())?;
+		<PausedCodes<T>>::insert(&hash, ());
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin, hash)
+	verify {
+		assert!(!<PausedCodes<T>>::contains_key(&hash));
+	}
+
+	freeze_contracts {
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin)
+	verify {
+		assert!(<ContractsFrozen<T>>::get());
+	}
+
+	unfreeze_contracts {
+		<ContractsFrozen<T>>::put(true);
+		let origin = T::ContractsPauseOrigin::successful_origin();
+	}: _<T::Origin>(origin)
+	verify {
+		assert!(!<ContractsFrozen<T>>::get());
+	}
+
+	// The code already has metadata which is replaced because the length of the previous
+	// metadata is read in order to determine the deposit.
+	// `c`: Size of the metadata in bytes.
+	set_code_metadata {
+		let c in 0 .. T::Schedule::get().limits.metadata_len;
+		let caller = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, caller_funding::<T>());
+		let WasmModule { code, hash, .. } = WasmModule::<T>::dummy();
+		Contracts::<T>::store_code_raw(code, caller.clone())?;
+		<CodeMetadata<T>>::insert(&hash, vec![42u8]);
+		let metadata = vec![42u8; c as usize];
+		let origin = RawOrigin::Signed(caller);
+	}: _(origin, hash, metadata.clone())
+	verify {
+		assert_eq!(Contracts::<T>::code_metadata(&hash), (c > 0).then(|| metadata));
+	}
+
 	seal_caller {
 		let r in 0 .. API_BENCHMARK_BATCHES;
 		let instance = Contract::<T>::new(WasmModule::getter(
@@ -2041,6 +2295,588 @@ benchmarks! {
 		let origin = RawOrigin::Signed(instance.caller.clone());
 	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
 
+	// The CosmWasm storage benchmarks mirror their seal counterparts. The keys, values and
+	// any other argument are passed to the host functions as regions.
+	#[skip_meta]
+	db_read {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_read",
+				params: vec![ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &keys)],
+			body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::Call(0)),
+				Regular(Instruction::Drop),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(&info.trie_id, &key, Some(vec![]), None, false)
+				.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_read_per_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let keys = (0 .. API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_read",
+				params: vec![ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &keys)],
+			body::repeated_dyn(API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::Call(0)),
+				Regular(Instruction::Drop),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(
+				&info.trie_id,
+				&key,
+				Some(vec![42u8; (n * 1024) as usize]),
+				None,
+				false,
+			)
+			.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_write {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let mut items = (0 .. r * API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let value_ptr = items.len() as u32 * 12;
+		items.push(vec![]);
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_write",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: None,
+			}],
+			vec![code::regions(0, &items)],
+			body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::I32Const(value_ptr as i32)), // value_ptr
+				Regular(Instruction::Call(0)),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in &items[.. items.len() - 1] {
+			Storage::<T>::write_raw(&info.trie_id, key, Some(vec![]), None, false)
+				.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_write_per_new_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let mut items = (0 .. API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let value_ptr = items.len() as u32 * 12;
+		items.push(vec![42u8; (n * 1024) as usize]);
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_write",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: None,
+			}],
+			vec![code::regions(0, &items)],
+			body::repeated_dyn(API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::I32Const(value_ptr as i32)), // value_ptr
+				Regular(Instruction::Call(0)),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in &items[.. items.len() - 1] {
+			Storage::<T>::write_raw(&info.trie_id, key, Some(vec![]), None, false)
+				.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_write_per_old_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let mut items = (0 .. API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let value_ptr = items.len() as u32 * 12;
+		items.push(vec![]);
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_write",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: None,
+			}],
+			vec![code::regions(0, &items)],
+			body::repeated_dyn(API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::I32Const(value_ptr as i32)), // value_ptr
+				Regular(Instruction::Call(0)),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in &items[.. items.len() - 1] {
+			Storage::<T>::write_raw(
+				&info.trie_id,
+				key,
+				Some(vec![42u8; (n * 1024) as usize]),
+				None,
+				false,
+			)
+			.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_remove {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_remove",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}],
+			vec![code::regions(0, &keys)],
+			body::repeated_dyn(r * API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::Call(0)),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(&info.trie_id, &key, Some(vec![]), None, false)
+				.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_remove_per_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let keys = (0 .. API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_remove",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}],
+			vec![code::regions(0, &keys)],
+			body::repeated_dyn(API_BENCHMARK_BATCH_SIZE, vec![
+				Counter(0, 12), // key_ptr
+				Regular(Instruction::Call(0)),
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(
+				&info.trie_id,
+				&key,
+				Some(vec![42u8; (n * 1024) as usize]),
+				None,
+				false,
+			)
+			.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// Creating an ascending iterator with open bounds does not visit any key.
+	db_scan {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "db_scan",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // start_ptr
+				Instruction::I32Const(0), // end_ptr
+				Instruction::I32Const(1), // order
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// A single iterator is created upfront which is then advanced over all the keys.
+	#[skip_meta]
+	db_next {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let keys = (0 .. r * API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![
+				ImportedFunction {
+					module: "env",
+					name: "db_scan",
+					params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+					return_type: Some(ValueType::I32),
+				},
+				ImportedFunction {
+					module: "env",
+					name: "db_next",
+					params: vec![ValueType::I32],
+					return_type: Some(ValueType::I32),
+				},
+			],
+			vec![],
+			body::prefixed(
+				&[
+					Instruction::I32Const(0), // start_ptr
+					Instruction::I32Const(0), // end_ptr
+					Instruction::I32Const(1), // order
+					Instruction::Call(0),
+					Instruction::Drop,
+				],
+				body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+					Instruction::I32Const(0), // iterator_id
+					Instruction::Call(1),
+					Instruction::Drop,
+				]),
+			),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(&info.trie_id, &key, Some(vec![]), None, false)
+				.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	#[skip_meta]
+	db_next_per_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let keys = (0 .. API_BENCHMARK_BATCH_SIZE)
+			.map(|n| T::Hashing::hash_of(&n).as_ref().to_vec())
+			.collect::<Vec<_>>();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![
+				ImportedFunction {
+					module: "env",
+					name: "db_scan",
+					params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+					return_type: Some(ValueType::I32),
+				},
+				ImportedFunction {
+					module: "env",
+					name: "db_next",
+					params: vec![ValueType::I32],
+					return_type: Some(ValueType::I32),
+				},
+			],
+			vec![],
+			body::prefixed(
+				&[
+					Instruction::I32Const(0), // start_ptr
+					Instruction::I32Const(0), // end_ptr
+					Instruction::I32Const(1), // order
+					Instruction::Call(0),
+					Instruction::Drop,
+				],
+				body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+					Instruction::I32Const(0), // iterator_id
+					Instruction::Call(1),
+					Instruction::Drop,
+				]),
+			),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let info = instance.info()?;
+		for key in keys {
+			Storage::<T>::write_raw(
+				&info.trie_id,
+				&key,
+				Some(vec![42u8; (n * 1024) as usize]),
+				None,
+				false,
+			)
+			.map_err(|_| "Failed to write to storage during setup.")?;
+		}
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	addr_validate {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let address = T::AddressCodec::encode(&whitelisted_caller())?;
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "addr_validate",
+				params: vec![ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[address.into_bytes()])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // source_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	addr_canonicalize {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let account: T::AccountId = whitelisted_caller();
+		let address = T::AddressCodec::encode(&account)?;
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "addr_canonicalize",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[address.into_bytes(), vec![0; account.encode().len()]])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // source_ptr
+				Instruction::I32Const(12), // destination_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	addr_humanize {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let account: T::AccountId = whitelisted_caller();
+		let address = T::AddressCodec::encode(&account)?;
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "addr_humanize",
+				params: vec![ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[account.encode(), vec![0; address.len()]])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // source_ptr
+				Instruction::I32Const(12), // destination_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// Only calling the function itself with a valid signature of the message "Hello world".
+	secp256k1_verify {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let message_hash = sp_io::hashing::sha2_256("Hello world".as_bytes());
+		let key_type = sp_core::crypto::KeyTypeId(*b"code");
+		let pub_key = sp_io::crypto::ecdsa_generate(key_type, None);
+		let sig = sp_io::crypto::ecdsa_sign_prehashed(key_type, &pub_key, &message_hash)
+			.expect("Generates signature");
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "secp256k1_verify",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(
+				0,
+				&[message_hash.to_vec(), sig.0[..64].to_vec(), pub_key.0.to_vec()],
+			)],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // message_hash_ptr
+				Instruction::I32Const(12), // signature_ptr
+				Instruction::I32Const(24), // public_key_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	secp256k1_recover_pubkey {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let message_hash = sp_io::hashing::sha2_256("Hello world".as_bytes());
+		let key_type = sp_core::crypto::KeyTypeId(*b"code");
+		let pub_key = sp_io::crypto::ecdsa_generate(key_type, None);
+		let sig = sp_io::crypto::ecdsa_sign_prehashed(key_type, &pub_key, &message_hash)
+			.expect("Generates signature");
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "secp256k1_recover_pubkey",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I64),
+			}],
+			vec![code::regions(0, &[message_hash.to_vec(), sig.0[..64].to_vec()])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // message_hash_ptr
+				Instruction::I32Const(12), // signature_ptr
+				Instruction::I32Const(sig.0[64] as i32), // recovery_param
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// Only calling the function itself with a valid signature of the message "Hello world".
+	ed25519_verify {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let message = "Hello world".as_bytes().to_vec();
+		let key_type = sp_core::crypto::KeyTypeId(*b"code");
+		let pub_key = sp_io::crypto::ed25519_generate(key_type, None);
+		let sig = sp_io::crypto::ed25519_sign(key_type, &pub_key, &message)
+			.expect("Generates signature");
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "ed25519_verify",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[message, sig.0.to_vec(), pub_key.0.to_vec()])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // message_ptr
+				Instruction::I32Const(12), // signature_ptr
+				Instruction::I32Const(24), // public_key_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	ed25519_verify_per_kb {
+		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let message = vec![42u8; (n * 1024) as usize];
+		let key_type = sp_core::crypto::KeyTypeId(*b"code");
+		let pub_key = sp_io::crypto::ed25519_generate(key_type, None);
+		let sig = sp_io::crypto::ed25519_sign(key_type, &pub_key, &message)
+			.expect("Generates signature");
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "ed25519_verify",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[message, sig.0.to_vec(), pub_key.0.to_vec()])],
+			body::repeated(API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // message_ptr
+				Instruction::I32Const(12), // signature_ptr
+				Instruction::I32Const(24), // public_key_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// The signatures of a batch are charged through `ed25519_verify`: only the overhead of
+	// verifying an empty batch is measured.
+	ed25519_batch_verify {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "ed25519_batch_verify",
+				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[vec![]])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // messages_ptr
+				Instruction::I32Const(0), // signatures_ptr
+				Instruction::I32Const(0), // public_keys_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	// Query the balance of the native currency of the caller.
+	query_chain {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let address = T::AddressCodec::encode(&whitelisted_caller())?;
+		let request = [
+			r#"{"bank":{"balance":{"address":""#,
+			&address,
+			r#"","denom":""#,
+			T::NativeDenom::get(),
+			r#""}}}"#,
+		].concat();
+		let code = WasmModule::<T>::cosmwasm(
+			vec![ImportedFunction {
+				module: "env",
+				name: "query_chain",
+				params: vec![ValueType::I32],
+				return_type: Some(ValueType::I32),
+			}],
+			vec![code::regions(0, &[request.into_bytes()])],
+			body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(0), // request_ptr
+				Instruction::Call(0),
+				Instruction::Drop,
+			]),
+		);
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
 	// We make the assumption that pushing a constant and dropping a value takes roughly
 	// the same amount of time. We follow that `t.load` and `drop` both have the weight
 	// of this benchmark / 2. We need to make this assumption because there is no way
@@ -16,24 +16,30 @@
 // limitations under the License.
 
 use crate::{
-	gas::GasMeter,
+	gas::{GasMeter, Token},
 	storage::{self, Storage, WriteOutcome},
-	BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf, Error, Event, Nonce,
-	Pallet as Contracts, Schedule,
+	AssetIdOf, BalanceOf, CodeChecksum, CodeHash, CodeHashToId, CodeId, CodeIdToHash, Config,
+	ContractInfo, ContractInfoOf, ContractsByLabel, ContractsFrozen, Error, Event, Nonce,
+	OwnerInfoOf, Pallet as Contracts, PausedCodes, PausedContracts, Schedule,
 };
 use frame_support::{
 	crypto::ecdsa::ECDSAExt,
 	dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo, Dispatchable},
+	ensure,
 	storage::{with_transaction, TransactionOutcome},
-	traits::{Contains, Currency, ExistenceRequirement, OriginTrait, Randomness, Time},
+	traits::{
+		fungibles::{Inspect, Transfer},
+		Contains, Currency, ExistenceRequirement, Get, OriginTrait, Randomness, Time,
+	},
 	weights::Weight,
+	DefaultNoBound,
 };
 use frame_system::RawOrigin;
-use pallet_contracts_primitives::ExecReturnValue;
+use pallet_contracts_primitives::{CallTrace, EntryPoint, ExecReturnValue, ReturnFlags};
 use smallvec::{Array, SmallVec};
-use sp_core::{crypto::UncheckedFrom, ecdsa::Public as ECDSAPublic};
+use sp_core::{crypto::UncheckedFrom, ecdsa::Public as ECDSAPublic, Bytes};
 use sp_io::crypto::secp256k1_ecdsa_recover_compressed;
-use sp_runtime::traits::Convert;
+use sp_runtime::traits::{Convert, Hash, Zero};
 use sp_std::{marker::PhantomData, mem, prelude::*};
 
 pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
@@ -42,6 +48,12 @@ pub type SeedOf<T> = <T as frame_system::Config>::Hash;
 pub type BlockNumberOf<T> = <T as frame_system::Config>::BlockNumber;
 pub type StorageKey = [u8; 32];
 pub type ExecResult = Result<ExecReturnValue, ExecError>;
+/// Amounts of [`Config::Assets`] which are transferred in addition to the native currency.
+pub type AssetsOf<T> = Vec<(AssetIdOf<T>, BalanceOf<T>)>;
+/// The key-value attributes of an event emitted by a CosmWasm contract.
+pub type EventAttributes = Vec<(Vec<u8>, Vec<u8>)>;
+/// The trace of a frame recorded by a [`CallTracer`].
+pub type CallTraceOf<T> = CallTrace<AccountIdOf<T>, BalanceOf<T>>;
 
 /// A type that represents a topic of an event. At the moment a hash is used.
 pub type TopicOf<T> = <T as frame_system::Config>::Hash;
@@ -92,15 +104,28 @@ pub trait Ext: sealing::Sealed {
 	/// Call (possibly transferring some amount of funds) into the specified account.
 	///
 	/// Returns the original code size of the called contract.
+	/// `assets` are transferred to the called account along with the `value`.
 	fn call(
 		&mut self,
 		gas_limit: Weight,
 		to: AccountIdOf<Self::T>,
 		value: BalanceOf<Self::T>,
+		assets: AssetsOf<Self::T>,
 		input_data: Vec<u8>,
 		allows_reentry: bool,
 	) -> Result<ExecReturnValue, ExecError>;
 
+	/// Query the specified contract.
+	///
+	/// The query is executed in a new frame which isn't allowed to change any state. The
+	/// querying contract can't be reentered while the query is executed.
+	fn query(
+		&mut self,
+		gas_limit: Weight,
+		to: AccountIdOf<Self::T>,
+		input_data: Vec<u8>,
+	) -> Result<ExecReturnValue, ExecError>;
+
 	/// Execute code in the current frame.
 	///
 	/// Returns the original code size of the called contract.
@@ -114,14 +139,21 @@ pub trait Ext: sealing::Sealed {
 	///
 	/// Returns the original code size of the called contract.
 	/// The newly created account will be associated with `code`. `value` specifies the amount of
-	/// value transferred from this to the newly created account.
+	/// value transferred from this to the newly created account, `assets` are transferred along
+	/// with it. `admin` is the account which is allowed to migrate the new contract and `label`
+	/// is the human readable label it is given. `derivation` selects how the address of the new
+	/// contract is derived from `salt`.
 	fn instantiate(
 		&mut self,
 		gas_limit: Weight,
 		code: CodeHash<Self::T>,
 		value: BalanceOf<Self::T>,
+		assets: AssetsOf<Self::T>,
 		input_data: Vec<u8>,
 		salt: &[u8],
+		admin: Option<AccountIdOf<Self::T>>,
+		label: Vec<u8>,
+		derivation: AddressDerivation,
 	) -> Result<(AccountIdOf<Self::T>, ExecReturnValue), ExecError>;
 
 	/// Transfer all funds to `beneficiary` and delete the contract.
@@ -136,6 +168,16 @@ pub trait Ext: sealing::Sealed {
 	/// Transfer some amount of funds into the specified account.
 	fn transfer(&mut self, to: &AccountIdOf<Self::T>, value: BalanceOf<Self::T>) -> DispatchResult;
 
+	/// Transfer some `value` and `assets` into the specified account.
+	///
+	/// Either all the transfers succeed or none of them is applied.
+	fn transfer_funds(
+		&mut self,
+		to: &AccountIdOf<Self::T>,
+		value: BalanceOf<Self::T>,
+		assets: AssetsOf<Self::T>,
+	) -> DispatchResult;
+
 	/// Returns the storage entry of the executing account by the given `key`.
 	///
 	/// Returns `None` if the `key` wasn't previously set by `set_storage` or
@@ -157,6 +199,32 @@ pub trait Ext: sealing::Sealed {
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError>;
 
+	/// Returns the storage entry of the executing account by the given unhashed `key`.
+	///
+	/// Returns `None` if the `key` wasn't previously set by `set_storage_raw` or
+	/// was deleted.
+	fn get_storage_raw(&mut self, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Returns the storage entry of the contract at `address` by the given unhashed `key`.
+	///
+	/// Returns `None` if `address` isn't a contract or the `key` isn't set.
+	fn get_storage_raw_of(&mut self, address: &AccountIdOf<Self::T>, key: &[u8])
+		-> Option<Vec<u8>>;
+
+	/// Returns the unhashed key following `key` in the storage of the executing account.
+	///
+	/// Returns `None` if there is no such key.
+	fn next_storage_key_raw(&mut self, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Sets the storage entry by the given unhashed key to the specified value. If `value` is
+	/// `None` then the storage entry is deleted.
+	fn set_storage_raw(
+		&mut self,
+		key: Vec<u8>,
+		value: Option<Vec<u8>>,
+		take_old: bool,
+	) -> Result<WriteOutcome, DispatchError>;
+
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
 
@@ -168,6 +236,24 @@ pub trait Ext: sealing::Sealed {
 	/// Returns `None` if the `address` does not belong to a contract.
 	fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>>;
 
+	/// Returns the code hash of the code which was assigned the given `code_id`.
+	///
+	/// Returns `None` if no code was assigned this id.
+	fn code_hash_by_id(&self, code_id: CodeId) -> Option<CodeHash<Self::T>>;
+
+	/// Returns the id, the owner and the sha256 checksum of the code with the given `code_hash`.
+	///
+	/// Returns `None` if no such code exists.
+	fn code_info(
+		&self,
+		code_hash: &CodeHash<Self::T>,
+	) -> Option<(CodeId, AccountIdOf<Self::T>, [u8; 32])>;
+
+	/// Returns the admin of the contract for the given `address`.
+	///
+	/// Returns `None` if the contract has no admin or the `address` does not belong to a contract.
+	fn contract_admin(&self, address: &AccountIdOf<Self::T>) -> Option<AccountIdOf<Self::T>>;
+
 	/// Returns the code hash of the contract being executed.
 	fn own_code_hash(&mut self) -> &CodeHash<Self::T>;
 
@@ -185,9 +271,22 @@ pub trait Ext: sealing::Sealed {
 	/// The `value_transferred` is already added.
 	fn balance(&self) -> BalanceOf<Self::T>;
 
+	/// Returns the free balance of the given `address`.
+	fn balance_of(&self, address: &AccountIdOf<Self::T>) -> BalanceOf<Self::T>;
+
+	/// Returns the balance of `asset` held by the given `address`.
+	fn asset_balance_of(
+		&self,
+		address: &AccountIdOf<Self::T>,
+		asset: AssetIdOf<Self::T>,
+	) -> BalanceOf<Self::T>;
+
 	/// Returns the value transferred along with this call.
 	fn value_transferred(&self) -> BalanceOf<Self::T>;
 
+	/// Returns the assets transferred along with this call.
+	fn assets_transferred(&self) -> &[(AssetIdOf<Self::T>, BalanceOf<Self::T>)];
+
 	/// Returns a reference to the timestamp of the current block
 	fn now(&self) -> &MomentOf<Self::T>;
 
@@ -202,6 +301,32 @@ pub trait Ext: sealing::Sealed {
 	/// There should not be any duplicates in `topics`.
 	fn deposit_event(&mut self, topics: Vec<TopicOf<Self::T>>, data: Vec<u8>);
 
+	/// Deposit an event of type `ty` on behalf of a CosmWasm contract.
+	///
+	/// The event is also recorded in the current frame so that it can be reported to the
+	/// caller once the frame returned successfully.
+	fn deposit_cosmwasm_event(&mut self, ty: Vec<u8>, attributes: EventAttributes);
+
+	/// Returns the CosmWasm events emitted by the current frame and by the calls it made.
+	fn cosmwasm_events(&self) -> &[(Vec<u8>, EventAttributes)];
+
+	/// Send an IBC packet holding `data` over the channel `channel_id` of the executing
+	/// contract.
+	///
+	/// The packet times out once the block number reached `timeout`. Returns the sequence
+	/// number of the packet.
+	#[cfg(feature = "stargate")]
+	fn ibc_send_packet(
+		&mut self,
+		channel_id: &str,
+		data: Vec<u8>,
+		timeout: BlockNumberOf<Self::T>,
+	) -> Result<u64, DispatchError>;
+
+	/// Request the channel `channel_id` of the executing contract to be closed.
+	#[cfg(feature = "stargate")]
+	fn ibc_close_channel(&mut self, channel_id: &str) -> Result<(), DispatchError>;
+
 	/// Returns the current block number.
 	fn block_number(&self) -> BlockNumberOf<Self::T>;
 
@@ -244,6 +369,17 @@ pub trait Ext: sealing::Sealed {
 	fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError>;
 }
 
+/// Describes how the address of a new contract is derived.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressDerivation {
+	/// The address is derived by [`Config::AddressGenerator`].
+	Generator,
+	/// The address is derived like CosmWasm's `instantiate2_address`.
+	///
+	/// See [`Pallet::instantiate2_address`](crate::Pallet::instantiate2_address).
+	Instantiate2,
+}
+
 /// Describes the different functions that can be exported by an [`Executable`].
 #[derive(Clone, Copy, PartialEq)]
 pub enum ExportedFunction {
@@ -251,6 +387,53 @@ pub enum ExportedFunction {
 	Constructor,
 	/// The function which is executed when a contract is called.
 	Call,
+	/// The function which is executed when a contract is queried.
+	///
+	/// It is not allowed to change any state.
+	Query,
+	/// The function which is executed when a contract is migrated to a new code.
+	Migrate,
+	/// The function which is executed during the handshake opening an IBC channel.
+	#[cfg(feature = "stargate")]
+	IbcChannelOpen,
+	/// The function which is executed once an IBC channel is established.
+	#[cfg(feature = "stargate")]
+	IbcChannelConnect,
+	/// The function which is executed when an IBC channel is closed.
+	#[cfg(feature = "stargate")]
+	IbcChannelClose,
+	/// The function which is executed when an IBC packet is received.
+	#[cfg(feature = "stargate")]
+	IbcPacketReceive,
+	/// The function which is executed when a sent IBC packet is acknowledged.
+	#[cfg(feature = "stargate")]
+	IbcPacketAck,
+	/// The function which is executed when a sent IBC packet timed out.
+	#[cfg(feature = "stargate")]
+	IbcPacketTimeout,
+}
+
+impl From<ExportedFunction> for EntryPoint {
+	fn from(function: ExportedFunction) -> Self {
+		match function {
+			ExportedFunction::Constructor => Self::Constructor,
+			ExportedFunction::Call => Self::Call,
+			ExportedFunction::Query => Self::Query,
+			ExportedFunction::Migrate => Self::Migrate,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcChannelOpen => Self::IbcChannelOpen,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcChannelConnect => Self::IbcChannelConnect,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcChannelClose => Self::IbcChannelClose,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcPacketReceive => Self::IbcPacketReceive,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcPacketAck => Self::IbcPacketAck,
+			#[cfg(feature = "stargate")]
+			ExportedFunction::IbcPacketTimeout => Self::IbcPacketTimeout,
+		}
+	}
 }
 
 /// A trait that represents something that can be executed.
@@ -303,6 +486,63 @@ pub trait Executable<T: Config>: Sized {
 	fn code_len(&self) -> u32;
 }
 
+/// Records the frames executed by a call stack as a tree of [`CallTrace`]s.
+///
+/// This is only used when executing as an RPC because it copies the input and output of
+/// every frame.
+#[derive(DefaultNoBound)]
+pub struct CallTracer<T: Config> {
+	/// The traces of the frames which are still executing. The last one is the top frame.
+	active: Vec<CallTraceOf<T>>,
+	/// The trace of the first frame once it finished executing.
+	root: Option<CallTraceOf<T>>,
+}
+
+impl<T: Config> CallTracer<T> {
+	/// Start recording a frame which executes `entry_point` of `contract` with `input`.
+	fn enter(&mut self, contract: T::AccountId, entry_point: EntryPoint, input: Vec<u8>) {
+		self.active.push(CallTrace {
+			contract,
+			entry_point,
+			input: Bytes(input),
+			result: Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Bytes(Vec::new()) }),
+			gas_consumed: 0,
+			storage_deposit: Default::default(),
+			calls: Vec::new(),
+		});
+	}
+
+	/// Finish recording the top frame and attach it to the frame which spawned it.
+	fn exit(
+		&mut self,
+		result: &ExecResult,
+		gas_consumed: Weight,
+		storage_deposit: storage::meter::DepositOf<T>,
+	) {
+		let mut trace = match self.active.pop() {
+			Some(trace) => trace,
+			None => return,
+		};
+		trace.result = match result {
+			Ok(output) => Ok(output.clone()),
+			Err(e) => Err(e.error),
+		};
+		trace.gas_consumed = gas_consumed;
+		trace.storage_deposit = storage_deposit;
+		match self.active.last_mut() {
+			Some(parent) => parent.calls.push(trace),
+			None => self.root = Some(trace),
+		}
+	}
+
+	/// The trace of the first frame of the call stack.
+	///
+	/// Returns `None` if the call stack failed before executing any frame.
+	pub fn into_trace(self) -> Option<CallTraceOf<T>> {
+		self.root
+	}
+}
+
 /// The complete call stack of a contract execution.
 ///
 /// The call stack is initiated by either a signed origin or one of the contract RPC calls.
@@ -341,6 +581,8 @@ pub struct Stack<'a, T: Config, E> {
 	/// All the bytes added to this field should be valid UTF-8. The buffer has no defined
 	/// structure and is intended to be shown to users as-is for debugging purposes.
 	debug_message: Option<&'a mut Vec<u8>>,
+	/// Records the executed frames when tracing a call.
+	tracer: Option<&'a mut CallTracer<T>>,
 	/// No executable is held by the struct but influences its behaviour.
 	_phantom: PhantomData<E>,
 }
@@ -361,6 +603,10 @@ pub struct Frame<T: Config> {
 	contract_info: CachedContract<T>,
 	/// The amount of balance transferred by the caller as part of the call.
 	value_transferred: BalanceOf<T>,
+	/// The assets transferred by the caller as part of the call.
+	assets_transferred: AssetsOf<T>,
+	/// The CosmWasm events emitted by this frame and by the frames that it spawned.
+	cosmwasm_events: Vec<(Vec<u8>, EventAttributes)>,
 	/// Determines whether this is a call or instantiate frame.
 	entry_point: ExportedFunction,
 	/// The gas meter capped to the supplied gas limit.
@@ -394,6 +640,8 @@ enum FrameArgs<'a, T: Config, E> {
 		/// what is stored at [`Self::dest`]. Its caller ([`Frame::delegated_caller`]) is the
 		/// account which called the caller contract
 		delegated_call: Option<DelegatedCall<T, E>>,
+		/// The function of the contract which is executed.
+		entry_point: ExportedFunction,
 	},
 	Instantiate {
 		/// The contract or signed origin which instantiates the new contract.
@@ -404,6 +652,12 @@ enum FrameArgs<'a, T: Config, E> {
 		executable: E,
 		/// A salt used in the contract address deriviation of the new contract.
 		salt: &'a [u8],
+		/// The account which is allowed to migrate the new contract.
+		admin: Option<T::AccountId>,
+		/// The human readable label of the new contract.
+		label: Vec<u8>,
+		/// How the address of the new contract is derived from the salt.
+		derivation: AddressDerivation,
 	},
 }
 
@@ -532,8 +786,8 @@ where
 	///
 	/// # Note
 	///
-	/// `debug_message` should only ever be set to `Some` when executing as an RPC because
-	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	/// `debug_message` and `tracer` should only ever be set to `Some` when executing as an RPC
+	/// because they add allocations and could be abused to drive the runtime into an OOM panic.
 	///
 	/// # Return Value
 	///
@@ -545,17 +799,134 @@ where
 		storage_meter: &'a mut storage::meter::Meter<T>,
 		schedule: &'a Schedule<T>,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		input_data: Vec<u8>,
 		debug_message: Option<&'a mut Vec<u8>>,
+		tracer: Option<&'a mut CallTracer<T>>,
 	) -> Result<ExecReturnValue, ExecError> {
 		let (mut stack, executable) = Self::new(
-			FrameArgs::Call { dest, cached_info: None, delegated_call: None },
+			FrameArgs::Call {
+				dest,
+				cached_info: None,
+				delegated_call: None,
+				entry_point: ExportedFunction::Call,
+			},
 			origin,
 			gas_meter,
 			storage_meter,
 			schedule,
 			value,
+			assets,
+			debug_message,
+			tracer,
+		)?;
+		stack.run(executable, input_data)
+	}
+
+	/// Create and run a new call stack by querying the contract `dest`.
+	///
+	/// The query is executed on behalf of `dest` itself without transferring any value.
+	/// It is up to the caller to revert any state change.
+	///
+	/// # Note
+	///
+	/// `debug_message` should only ever be set to `Some` when executing as an RPC because
+	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	pub fn run_query(
+		dest: T::AccountId,
+		gas_meter: &'a mut GasMeter<T>,
+		storage_meter: &'a mut storage::meter::Meter<T>,
+		schedule: &'a Schedule<T>,
+		input_data: Vec<u8>,
+		debug_message: Option<&'a mut Vec<u8>>,
+	) -> Result<ExecReturnValue, ExecError> {
+		let (mut stack, executable) = Self::new(
+			FrameArgs::Call {
+				dest: dest.clone(),
+				cached_info: None,
+				delegated_call: None,
+				entry_point: ExportedFunction::Query,
+			},
+			dest,
+			gas_meter,
+			storage_meter,
+			schedule,
+			Zero::zero(),
+			Vec::new(),
+			debug_message,
+			None,
+		)?;
+		stack.run(executable, input_data)
+	}
+
+	/// Create and run a new call stack by migrating the contract `dest` to the code `executable`.
+	///
+	/// The code of `dest` is replaced before its `migrate` entry point is called. The
+	/// replacement is reverted together with any other change if the migration fails.
+	///
+	/// # Note
+	///
+	/// `debug_message` should only ever be set to `Some` when executing as an RPC because
+	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	pub fn run_migrate(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		executable: E,
+		gas_meter: &'a mut GasMeter<T>,
+		storage_meter: &'a mut storage::meter::Meter<T>,
+		schedule: &'a Schedule<T>,
+		input_data: Vec<u8>,
+		debug_message: Option<&'a mut Vec<u8>>,
+	) -> Result<ExecReturnValue, ExecError> {
+		let (mut stack, executable) = Self::new(
+			FrameArgs::Call {
+				dest,
+				cached_info: None,
+				// The new code is executed in the context of `dest` like a delegate call does.
+				delegated_call: Some(DelegatedCall { executable, caller: origin.clone() }),
+				entry_point: ExportedFunction::Migrate,
+			},
+			origin,
+			gas_meter,
+			storage_meter,
+			schedule,
+			Zero::zero(),
+			Vec::new(),
+			debug_message,
+			None,
+		)?;
+		stack.run(executable, input_data)
+	}
+
+	/// Create and run a new call stack by calling the IBC `entry_point` of the contract `dest`.
+	///
+	/// The `origin` is the account relaying the IBC message.
+	///
+	/// # Note
+	///
+	/// `debug_message` should only ever be set to `Some` when executing as an RPC because
+	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	#[cfg(feature = "stargate")]
+	pub fn run_ibc(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		entry_point: ExportedFunction,
+		gas_meter: &'a mut GasMeter<T>,
+		storage_meter: &'a mut storage::meter::Meter<T>,
+		schedule: &'a Schedule<T>,
+		input_data: Vec<u8>,
+		debug_message: Option<&'a mut Vec<u8>>,
+	) -> Result<ExecReturnValue, ExecError> {
+		let (mut stack, executable) = Self::new(
+			FrameArgs::Call { dest, cached_info: None, delegated_call: None, entry_point },
+			origin,
+			gas_meter,
+			storage_meter,
+			schedule,
+			Zero::zero(),
+			Vec::new(),
 			debug_message,
+			None,
 		)?;
 		stack.run(executable, input_data)
 	}
@@ -577,8 +948,11 @@ where
 		storage_meter: &'a mut storage::meter::Meter<T>,
 		schedule: &'a Schedule<T>,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		input_data: Vec<u8>,
 		salt: &[u8],
+		admin: Option<T::AccountId>,
+		label: Vec<u8>,
 		debug_message: Option<&'a mut Vec<u8>>,
 	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
 		let (mut stack, executable) = Self::new(
@@ -587,13 +961,18 @@ where
 				nonce: Self::initial_nonce(),
 				executable,
 				salt,
+				admin,
+				label,
+				derivation: AddressDerivation::Generator,
 			},
 			origin,
 			gas_meter,
 			storage_meter,
 			schedule,
 			value,
+			assets,
 			debug_message,
+			None,
 		)?;
 		let account_id = stack.top_frame().account_id.clone();
 		stack.run(executable, input_data).map(|ret| (account_id, ret))
@@ -607,10 +986,12 @@ where
 		storage_meter: &'a mut storage::meter::Meter<T>,
 		schedule: &'a Schedule<T>,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		debug_message: Option<&'a mut Vec<u8>>,
+		tracer: Option<&'a mut CallTracer<T>>,
 	) -> Result<(Self, E), ExecError> {
 		let (first_frame, executable, nonce) =
-			Self::new_frame(args, value, gas_meter, storage_meter, 0, &schedule)?;
+			Self::new_frame(args, value, assets, gas_meter, storage_meter, 0, &schedule)?;
 		let stack = Self {
 			origin,
 			schedule,
@@ -622,6 +1003,7 @@ where
 			first_frame,
 			frames: Default::default(),
 			debug_message,
+			tracer,
 			_phantom: Default::default(),
 		};
 
@@ -635,6 +1017,7 @@ where
 	fn new_frame<S: storage::meter::State>(
 		frame_args: FrameArgs<T, E>,
 		value_transferred: BalanceOf<T>,
+		assets_transferred: AssetsOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		storage_meter: &mut storage::meter::GenericMeter<T, S>,
 		gas_limit: Weight,
@@ -642,7 +1025,7 @@ where
 	) -> Result<(Frame<T>, E, Option<u64>), ExecError> {
 		let (account_id, contract_info, executable, delegate_caller, entry_point, nonce) =
 			match frame_args {
-				FrameArgs::Call { dest, cached_info, delegated_call } => {
+				FrameArgs::Call { dest, cached_info, delegated_call, entry_point } => {
 					let contract = if let Some(contract) = cached_info {
 						contract
 					} else {
@@ -656,16 +1039,35 @@ where
 							(E::from_storage(contract.code_hash, schedule, gas_meter)?, None)
 						};
 
-					(dest, contract, executable, delegate_caller, ExportedFunction::Call, None)
+					(dest, contract, executable, delegate_caller, entry_point, None)
 				},
-				FrameArgs::Instantiate { sender, nonce, executable, salt } => {
-					let account_id =
-						<Contracts<T>>::contract_address(&sender, executable.code_hash(), &salt);
+				FrameArgs::Instantiate {
+					sender,
+					nonce,
+					executable,
+					salt,
+					admin,
+					label,
+					derivation,
+				} => {
+					ensure!(
+						label.len() as u32 <= schedule.limits.label_len,
+						Error::<T>::LabelTooLong
+					);
+					let code_hash = executable.code_hash();
+					let account_id = match derivation {
+						AddressDerivation::Generator =>
+							<Contracts<T>>::contract_address(&sender, code_hash, salt),
+						AddressDerivation::Instantiate2 =>
+							<Contracts<T>>::instantiate2_address(&sender, code_hash, salt)?,
+					};
 					let trie_id = Storage::<T>::generate_trie_id(&account_id, nonce);
 					let contract = Storage::<T>::new_contract(
 						&account_id,
 						trie_id,
 						executable.code_hash().clone(),
+						admin,
+						label,
 					)?;
 					(
 						account_id,
@@ -681,6 +1083,8 @@ where
 		let frame = Frame {
 			delegate_caller,
 			value_transferred,
+			assets_transferred,
+			cosmwasm_events: Vec::new(),
 			contract_info: CachedContract::Cached(contract_info),
 			account_id,
 			entry_point,
@@ -697,6 +1101,7 @@ where
 		&mut self,
 		frame_args: FrameArgs<T, E>,
 		value_transferred: BalanceOf<T>,
+		assets_transferred: AssetsOf<T>,
 		gas_limit: Weight,
 	) -> Result<E, ExecError> {
 		if self.frames.len() == T::CallStack::size() {
@@ -720,6 +1125,7 @@ where
 		let (frame, executable, _) = Self::new_frame(
 			frame_args,
 			value_transferred,
+			assets_transferred,
 			nested_gas,
 			nested_storage,
 			gas_limit,
@@ -734,7 +1140,26 @@ where
 	/// This can be either a call or an instantiate.
 	fn run(&mut self, executable: E, input_data: Vec<u8>) -> Result<ExecReturnValue, ExecError> {
 		let entry_point = self.top_frame().entry_point;
+		if let Some(tracer) = self.tracer.as_mut() {
+			let frame = top_frame!(self);
+			let traced_entry_point = match (entry_point, &frame.delegate_caller) {
+				(ExportedFunction::Call, Some(_)) => EntryPoint::DelegateCall,
+				(entry_point, _) => entry_point.into(),
+			};
+			tracer.enter(frame.account_id.clone(), traced_entry_point, input_data.clone());
+		}
 		let do_transaction = || {
+			// Paused contracts and codes must not execute, not even as part of a delegate call.
+			// A paused contract can still be migrated in order to fix it, but not to paused code.
+			top_frame_mut!(self).nested_gas.charge(PauseCheckToken)?;
+			ensure!(!<ContractsFrozen<T>>::get(), Error::<T>::ContractsFrozen);
+			ensure!(
+				(entry_point == ExportedFunction::Migrate ||
+					!<PausedContracts<T>>::contains_key(&top_frame!(self).account_id)) &&
+					!<PausedCodes<T>>::contains_key(executable.code_hash()),
+				Error::<T>::ContractPaused,
+			);
+
 			// We need to charge the storage deposit before the initial transfer so that
 			// it can create the account in case the initial transfer is < ed.
 			if entry_point == ExportedFunction::Constructor {
@@ -749,6 +1174,11 @@ where
 			// Every call or instantiate also optionally transferres balance.
 			self.initial_transfer()?;
 
+			// A migration switches the contract to the code it executes.
+			if entry_point == ExportedFunction::Migrate {
+				self.set_code_hash(*executable.code_hash())?;
+			}
+
 			// Call into the wasm blob.
 			let output = executable
 				.execute(self, &entry_point, input_data)
@@ -756,20 +1186,24 @@ where
 
 			// Additional work needs to be performed in case of an instantiation.
 			if !output.did_revert() && entry_point == ExportedFunction::Constructor {
-				let frame = self.top_frame();
+				let frame = self.top_frame_mut();
 
 				// It is not allowed to terminate a contract inside its constructor.
 				if matches!(frame.contract_info, CachedContract::Terminated) {
 					return Err(Error::<T>::TerminatedInConstructor.into())
 				}
 
+				// Make the contract discoverable by its label.
+				let account_id = frame.account_id.clone();
+				let label = &frame.contract_info().label;
+				if !label.is_empty() {
+					<ContractsByLabel<T>>::insert(label, &account_id, ());
+				}
+
 				// Deposit an instantiation event.
 				deposit_event::<T>(
 					vec![],
-					Event::Instantiated {
-						deployer: self.caller().clone(),
-						contract: frame.account_id.clone(),
-					},
+					Event::Instantiated { deployer: self.caller().clone(), contract: account_id },
 				);
 			}
 
@@ -799,6 +1233,14 @@ where
 			// has changed.
 			Err(error) => (false, Err(error.into())),
 		};
+		if let Some(tracer) = self.tracer.as_mut() {
+			let frame = top_frame!(self);
+			tracer.exit(
+				&output,
+				frame.nested_gas.gas_consumed(),
+				frame.nested_storage.total_deposit(),
+			);
+		}
 		self.pop_frame(success);
 		output
 	}
@@ -831,6 +1273,8 @@ where
 				return
 			}
 
+			prev.cosmwasm_events.append(&mut frame.cosmwasm_events);
+
 			// Record the storage meter changes of the nested call into the parent meter.
 			// If the dropped frame's contract wasn't terminated we update the deposit counter
 			// in its contract info. The load is necessary to to pull it from storage in case
@@ -905,12 +1349,31 @@ where
 		Ok(())
 	}
 
+	/// Transfer some `amount` of `asset` from `from` to `to`.
+	fn transfer_asset(
+		from: &T::AccountId,
+		to: &T::AccountId,
+		asset: AssetIdOf<T>,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(())
+		}
+		T::Assets::transfer(asset, from, to, amount, true)
+			.map_err(|_| Error::<T>::TransferFailed)?;
+		Ok(())
+	}
+
 	// The transfer as performed by a call or instantiate.
 	fn initial_transfer(&self) -> DispatchResult {
 		let frame = self.top_frame();
 		let value = frame.value_transferred;
 
-		Self::transfer(ExistenceRequirement::KeepAlive, self.caller(), &frame.account_id, value)
+		Self::transfer(ExistenceRequirement::KeepAlive, self.caller(), &frame.account_id, value)?;
+		for (asset, amount) in frame.assets_transferred.iter().cloned() {
+			Self::transfer_asset(self.caller(), &frame.account_id, asset, amount)?;
+		}
+		Ok(())
 	}
 
 	/// Reference to the current (top) frame.
@@ -946,38 +1409,18 @@ where
 		!self.frames().any(|f| &f.account_id == id && !f.allows_reentry)
 	}
 
-	/// Increments and returns the next nonce. Pulls it from storage if it isn't in cache.
-	fn next_nonce(&mut self) -> u64 {
-		let next = if let Some(current) = self.nonce {
-			current.wrapping_add(1)
-		} else {
-			Self::initial_nonce()
-		};
-		self.nonce = Some(next);
-		next
-	}
-
-	/// Pull the current nonce from storage.
-	fn initial_nonce() -> u64 {
-		<Nonce<T>>::get().wrapping_add(1)
-	}
-}
-
-impl<'a, T, E> Ext for Stack<'a, T, E>
-where
-	T: Config,
-	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
-	E: Executable<T>,
-{
-	type T = T;
-
-	fn call(
+	/// Call into the `entry_point` of the specified account.
+	///
+	/// See [`Ext::call`] for the meaning of `allows_reentry`.
+	fn call_entry_point(
 		&mut self,
 		gas_limit: Weight,
 		to: T::AccountId,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		input_data: Vec<u8>,
 		allows_reentry: bool,
+		entry_point: ExportedFunction,
 	) -> Result<ExecReturnValue, ExecError> {
 		// Before pushing the new frame: Protect the caller contract against reentrancy attacks.
 		// It is important to do this before calling `allows_reentry` so that a direct recursion
@@ -999,8 +1442,14 @@ where
 					_ => None,
 				});
 			let executable = self.push_frame(
-				FrameArgs::Call { dest: to, cached_info, delegated_call: None },
+				FrameArgs::Call {
+					dest: to,
+					cached_info,
+					delegated_call: None,
+					entry_point,
+				},
 				value,
+				assets,
 				gas_limit,
 			)?;
 			self.run(executable, input_data)
@@ -1015,6 +1464,68 @@ where
 		result
 	}
 
+	/// Increments and returns the next nonce. Pulls it from storage if it isn't in cache.
+	fn next_nonce(&mut self) -> u64 {
+		let next = if let Some(current) = self.nonce {
+			current.wrapping_add(1)
+		} else {
+			Self::initial_nonce()
+		};
+		self.nonce = Some(next);
+		next
+	}
+
+	/// Pull the current nonce from storage.
+	fn initial_nonce() -> u64 {
+		<Nonce<T>>::get().wrapping_add(1)
+	}
+}
+
+impl<'a, T, E> Ext for Stack<'a, T, E>
+where
+	T: Config,
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+	E: Executable<T>,
+{
+	type T = T;
+
+	fn call(
+		&mut self,
+		gas_limit: Weight,
+		to: T::AccountId,
+		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
+		input_data: Vec<u8>,
+		allows_reentry: bool,
+	) -> Result<ExecReturnValue, ExecError> {
+		self.call_entry_point(
+			gas_limit,
+			to,
+			value,
+			assets,
+			input_data,
+			allows_reentry,
+			ExportedFunction::Call,
+		)
+	}
+
+	fn query(
+		&mut self,
+		gas_limit: Weight,
+		to: T::AccountId,
+		input_data: Vec<u8>,
+	) -> Result<ExecReturnValue, ExecError> {
+		self.call_entry_point(
+			gas_limit,
+			to,
+			Zero::zero(),
+			Vec::new(),
+			input_data,
+			false,
+			ExportedFunction::Query,
+		)
+	}
+
 	fn delegate_call(
 		&mut self,
 		code_hash: CodeHash<Self::T>,
@@ -1030,8 +1541,10 @@ where
 				dest: account_id,
 				cached_info: Some(contract_info),
 				delegated_call: Some(DelegatedCall { executable, caller: self.caller().clone() }),
+				entry_point: ExportedFunction::Call,
 			},
 			value,
+			Vec::new(),
 			0,
 		)?;
 		self.run(executable, input_data)
@@ -1042,8 +1555,12 @@ where
 		gas_limit: Weight,
 		code_hash: CodeHash<T>,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		input_data: Vec<u8>,
 		salt: &[u8],
+		admin: Option<AccountIdOf<T>>,
+		label: Vec<u8>,
+		derivation: AddressDerivation,
 	) -> Result<(AccountIdOf<T>, ExecReturnValue), ExecError> {
 		let executable = E::from_storage(code_hash, &self.schedule, self.gas_meter())?;
 		let nonce = self.next_nonce();
@@ -1053,8 +1570,12 @@ where
 				nonce,
 				executable,
 				salt,
+				admin,
+				label,
+				derivation,
 			},
 			value,
+			assets,
 			gas_limit,
 		)?;
 		let account_id = self.top_frame().account_id.clone();
@@ -1076,6 +1597,9 @@ where
 			T::Currency::free_balance(&frame.account_id),
 		)?;
 		ContractInfoOf::<T>::remove(&frame.account_id);
+		if !info.label.is_empty() {
+			<ContractsByLabel<T>>::remove(&info.label, &frame.account_id);
+		}
 		E::remove_user(info.code_hash);
 		Contracts::<T>::deposit_event(Event::Terminated {
 			contract: frame.account_id.clone(),
@@ -1088,6 +1612,27 @@ where
 		Self::transfer(ExistenceRequirement::KeepAlive, &self.top_frame().account_id, to, value)
 	}
 
+	fn transfer_funds(
+		&mut self,
+		to: &T::AccountId,
+		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
+	) -> DispatchResult {
+		let from = &self.top_frame().account_id;
+		with_transaction(|| -> TransactionOutcome<DispatchResult> {
+			let result =
+				Self::transfer(ExistenceRequirement::KeepAlive, from, to, value).and_then(|_| {
+					assets.into_iter().try_for_each(|(asset, amount)| {
+						Self::transfer_asset(from, to, asset, amount)
+					})
+				});
+			match result {
+				Ok(()) => TransactionOutcome::Commit(Ok(())),
+				Err(_) => TransactionOutcome::Rollback(result),
+			}
+		})
+	}
+
 	fn get_storage(&mut self, key: &StorageKey) -> Option<Vec<u8>> {
 		Storage::<T>::read(&self.top_frame_mut().contract_info().trie_id, key)
 	}
@@ -1112,6 +1657,42 @@ where
 		)
 	}
 
+	fn get_storage_raw(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		Storage::<T>::read_raw(&self.top_frame_mut().contract_info().trie_id, key)
+	}
+
+	fn get_storage_raw_of(&mut self, address: &T::AccountId, key: &[u8]) -> Option<Vec<u8>> {
+		// A contract which is still being instantiated only has its info cached on the stack.
+		let cached_info = self.frames().find_map(|f| match &f.contract_info {
+			CachedContract::Cached(contract) if &f.account_id == address => Some(contract),
+			_ => None,
+		});
+		match cached_info {
+			Some(contract) => Storage::<T>::read_raw(&contract.trie_id, key),
+			None => Storage::<T>::read_raw(&<ContractInfoOf<T>>::get(address)?.trie_id, key),
+		}
+	}
+
+	fn next_storage_key_raw(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		Storage::<T>::next_key_raw(&self.top_frame_mut().contract_info().trie_id, key)
+	}
+
+	fn set_storage_raw(
+		&mut self,
+		key: Vec<u8>,
+		value: Option<Vec<u8>>,
+		take_old: bool,
+	) -> Result<WriteOutcome, DispatchError> {
+		let frame = self.top_frame_mut();
+		Storage::<T>::write_raw(
+			&frame.contract_info.get(&frame.account_id).trie_id,
+			&key,
+			value,
+			Some(&mut frame.nested_storage),
+			take_old,
+		)
+	}
+
 	fn address(&self) -> &T::AccountId {
 		&self.top_frame().account_id
 	}
@@ -1132,6 +1713,20 @@ where
 		<ContractInfoOf<T>>::get(&address).map(|contract| contract.code_hash)
 	}
 
+	fn code_hash_by_id(&self, code_id: CodeId) -> Option<CodeHash<Self::T>> {
+		<CodeIdToHash<T>>::get(code_id)
+	}
+
+	fn code_info(&self, code_hash: &CodeHash<Self::T>) -> Option<(CodeId, T::AccountId, [u8; 32])> {
+		let code_id = <CodeHashToId<T>>::get(code_hash)?;
+		let checksum = <CodeChecksum<T>>::get(code_hash)?;
+		<OwnerInfoOf<T>>::get(code_hash).map(|info| (code_id, info.owner().clone(), checksum))
+	}
+
+	fn contract_admin(&self, address: &T::AccountId) -> Option<T::AccountId> {
+		<ContractInfoOf<T>>::get(&address).and_then(|contract| contract.admin)
+	}
+
 	fn own_code_hash(&mut self) -> &CodeHash<Self::T> {
 		&self.top_frame_mut().contract_info().code_hash
 	}
@@ -1144,10 +1739,22 @@ where
 		T::Currency::free_balance(&self.top_frame().account_id)
 	}
 
+	fn balance_of(&self, address: &T::AccountId) -> BalanceOf<T> {
+		T::Currency::free_balance(address)
+	}
+
+	fn asset_balance_of(&self, address: &T::AccountId, asset: AssetIdOf<T>) -> BalanceOf<T> {
+		T::Assets::balance(asset, address)
+	}
+
 	fn value_transferred(&self) -> BalanceOf<T> {
 		self.top_frame().value_transferred
 	}
 
+	fn assets_transferred(&self) -> &[(AssetIdOf<T>, BalanceOf<T>)] {
+		&self.top_frame().assets_transferred
+	}
+
 	fn random(&self, subject: &[u8]) -> (SeedOf<T>, BlockNumberOf<T>) {
 		T::Randomness::random(subject)
 	}
@@ -1167,6 +1774,33 @@ where
 		);
 	}
 
+	fn deposit_cosmwasm_event(&mut self, ty: Vec<u8>, attributes: EventAttributes) {
+		let frame = self.top_frame_mut();
+		let contract = frame.account_id.clone();
+		let topics = vec![T::Hashing::hash_of(&contract), T::Hashing::hash(&ty)];
+		frame.cosmwasm_events.push((ty.clone(), attributes.clone()));
+		deposit_event::<Self::T>(topics, Event::CosmwasmEvent { contract, ty, attributes });
+	}
+
+	fn cosmwasm_events(&self) -> &[(Vec<u8>, EventAttributes)] {
+		&self.top_frame().cosmwasm_events
+	}
+
+	#[cfg(feature = "stargate")]
+	fn ibc_send_packet(
+		&mut self,
+		channel_id: &str,
+		data: Vec<u8>,
+		timeout: BlockNumberOf<T>,
+	) -> Result<u64, DispatchError> {
+		crate::ibc::send_packet::<T>(&self.top_frame().account_id, channel_id, data, timeout)
+	}
+
+	#[cfg(feature = "stargate")]
+	fn ibc_close_channel(&mut self, channel_id: &str) -> Result<(), DispatchError> {
+		crate::ibc::request_close::<T>(&self.top_frame().account_id, channel_id)
+	}
+
 	fn block_number(&self) -> T::BlockNumber {
 		self.block_number
 	}
@@ -1232,6 +1866,18 @@ where
 	}
 }
 
+/// The cost of checking whether the contract of a frame is allowed to execute.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+struct PauseCheckToken;
+
+impl<T: Config> Token<T> for PauseCheckToken {
+	fn weight(&self) -> Weight {
+		// `ContractsFrozen`, `PausedContracts` and `PausedCodes` are read.
+		T::DbWeight::get().reads(3)
+	}
+}
+
 fn deposit_event<T: Config>(topics: Vec<T::Hash>, event: Event<T>) {
 	<frame_system::Pallet<T>>::deposit_event_indexed(
 		&*topics,
@@ -1458,6 +2104,8 @@ mod tests {
 					&schedule,
 					value,
 					vec![],
+					vec![],
+					None,
 					None,
 				),
 				Ok(_)
@@ -1511,6 +2159,8 @@ mod tests {
 				&schedule,
 				55,
 				vec![],
+				vec![],
+				None,
 				None,
 			)
 			.unwrap();
@@ -1562,6 +2212,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 
@@ -1594,6 +2246,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 
@@ -1623,8 +2277,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				vec![1, 2, 3, 4],
 				None,
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1655,9 +2311,12 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				min_balance,
+				vec![],
 				vec![1, 2, 3, 4],
 				&[],
 				None,
+				vec![],
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1673,7 +2332,7 @@ mod tests {
 		let value = Default::default();
 		let recurse_ch = MockLoader::insert(Call, |ctx, _| {
 			// Try to call into yourself.
-			let r = ctx.ext.call(0, BOB, 0, vec![], true);
+			let r = ctx.ext.call(0, BOB, 0, vec![], vec![], true);
 
 			REACHED_BOTTOM.with(|reached_bottom| {
 				let mut reached_bottom = reached_bottom.borrow_mut();
@@ -1705,6 +2364,8 @@ mod tests {
 				&schedule,
 				value,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 
@@ -1728,7 +2389,7 @@ mod tests {
 				.with(|caller| *caller.borrow_mut() = Some(ctx.ext.caller().clone()));
 
 			// Call into CHARLIE contract.
-			assert_matches!(ctx.ext.call(0, CHARLIE, 0, vec![], true), Ok(_));
+			assert_matches!(ctx.ext.call(0, CHARLIE, 0, vec![], vec![], true), Ok(_));
 			exec_success()
 		});
 		let charlie_ch = MockLoader::insert(Call, |ctx, _| {
@@ -1752,6 +2413,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 
@@ -1785,6 +2448,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 			assert_matches!(result, Ok(_));
@@ -1813,8 +2478,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				vec![0],
 				None,
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1840,8 +2507,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				vec![0],
 				None,
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1859,7 +2528,7 @@ mod tests {
 			// ALICE is the origin of the call stack
 			assert!(ctx.ext.caller_is_origin());
 			// BOB calls CHARLIE
-			ctx.ext.call(0, CHARLIE, 0, vec![], true)
+			ctx.ext.call(0, CHARLIE, 0, vec![], vec![], true)
 		});
 
 		ExtBuilder::default().build().execute_with(|| {
@@ -1875,8 +2544,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				vec![0],
 				None,
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -1889,7 +2560,7 @@ mod tests {
 			assert_eq!(*ctx.ext.address(), BOB);
 
 			// Call into charlie contract.
-			assert_matches!(ctx.ext.call(0, CHARLIE, 0, vec![], true), Ok(_));
+			assert_matches!(ctx.ext.call(0, CHARLIE, 0, vec![], vec![], true), Ok(_));
 			exec_success()
 		});
 		let charlie_ch = MockLoader::insert(Call, |ctx, _| {
@@ -1911,6 +2582,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 
@@ -1938,8 +2611,11 @@ mod tests {
 					&schedule,
 					0, // <- zero value
 					vec![],
+					vec![],
 					&[],
 					None,
+					vec![],
+					None,
 				),
 				Err(_)
 			);
@@ -1971,8 +2647,11 @@ mod tests {
 					&schedule,
 					min_balance,
 					vec![],
+					vec![],
 					&[],
 					None,
+					vec![],
+					None,
 				),
 				Ok((address, ref output)) if output.data == Bytes(vec![80, 65, 83, 83]) => address
 			);
@@ -2015,8 +2694,11 @@ mod tests {
 					&schedule,
 					min_balance,
 					vec![],
+					vec![],
 					&[],
 					None,
+					vec![],
+					None,
 				),
 				Ok((address, ref output)) if output.data == Bytes(vec![70, 65, 73, 76]) => address
 			);
@@ -2043,7 +2725,11 @@ mod tests {
 						dummy_ch,
 						<Test as Config>::Currency::minimum_balance(),
 						vec![],
+						vec![],
 						&[48, 49, 50],
+						None,
+						vec![],
+						AddressDerivation::Generator,
 					)
 					.unwrap();
 
@@ -2070,6 +2756,8 @@ mod tests {
 					&schedule,
 					min_balance * 10,
 					vec![],
+					vec![],
+					None,
 					None,
 				),
 				Ok(_)
@@ -2104,7 +2792,11 @@ mod tests {
 						dummy_ch,
 						<Test as Config>::Currency::minimum_balance(),
 						vec![],
+						vec![],
 						&[],
+						None,
+						vec![],
+						AddressDerivation::Generator,
 					),
 					Err(ExecError {
 						error: DispatchError::Other("It's a trap!"),
@@ -2132,6 +2824,8 @@ mod tests {
 					&schedule,
 					0,
 					vec![],
+					vec![],
+					None,
 					None,
 				),
 				Ok(_)
@@ -2167,8 +2861,11 @@ mod tests {
 					&schedule,
 					100,
 					vec![],
+					vec![],
 					&[],
 					None,
+					vec![],
+					None,
 				),
 				Err(Error::<Test>::TerminatedInConstructor.into())
 			);
@@ -2192,13 +2889,13 @@ mod tests {
 				let info = ctx.ext.contract_info();
 				assert_eq!(info.storage_deposit, 0);
 				info.storage_deposit = 42;
-				assert_eq!(ctx.ext.call(0, CHARLIE, 0, vec![], true), exec_trapped());
+				assert_eq!(ctx.ext.call(0, CHARLIE, 0, vec![], vec![], true), exec_trapped());
 				assert_eq!(ctx.ext.contract_info().storage_deposit, 42);
 			}
 			exec_success()
 		});
 		let code_charlie = MockLoader::insert(Call, |ctx, _| {
-			assert!(ctx.ext.call(0, BOB, 0, vec![99], true).is_ok());
+			assert!(ctx.ext.call(0, BOB, 0, vec![], vec![99], true).is_ok());
 			exec_trapped()
 		});
 
@@ -2216,8 +2913,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				vec![0],
 				None,
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -2227,7 +2926,7 @@ mod tests {
 	fn recursive_call_during_constructor_fails() {
 		let code = MockLoader::insert(Constructor, |ctx, _| {
 			assert_matches!(
-				ctx.ext.call(0, ctx.ext.address().clone(), 0, vec![], true),
+				ctx.ext.call(0, ctx.ext.address().clone(), 0, vec![], vec![], true),
 				Err(ExecError{error, ..}) if error == <Error<Test>>::ContractNotFound.into()
 			);
 			exec_success()
@@ -2251,8 +2950,11 @@ mod tests {
 				&schedule,
 				min_balance,
 				vec![],
+				vec![],
 				&[],
 				None,
+				vec![],
+				None,
 			);
 			assert_matches!(result, Ok(_));
 		});
@@ -2283,7 +2985,9 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
 				Some(&mut debug_buffer),
+				None,
 			)
 			.unwrap();
 		});
@@ -2316,7 +3020,9 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
 				Some(&mut debug_buffer),
+				None,
 			);
 			assert!(result.is_err());
 		});
@@ -2329,7 +3035,7 @@ mod tests {
 		// call the contract passed as input with disabled reentry
 		let code_bob = MockLoader::insert(Call, |ctx, _| {
 			let dest = Decode::decode(&mut ctx.input_data.as_ref()).unwrap();
-			ctx.ext.call(0, dest, 0, vec![], false)
+			ctx.ext.call(0, dest, 0, vec![], vec![], false)
 		});
 
 		let code_charlie = MockLoader::insert(Call, |_, _| exec_success());
@@ -2348,8 +3054,10 @@ mod tests {
 				&mut storage_meter,
 				&schedule,
 				0,
+				vec![],
 				CHARLIE.encode(),
 				None,
+				None,
 			));
 
 			// Calling into oneself fails
@@ -2361,8 +3069,10 @@ mod tests {
 					&mut storage_meter,
 					&schedule,
 					0,
+					vec![],
 					BOB.encode(),
 					None,
+					None,
 				)
 				.map_err(|e| e.error),
 				<Error<Test>>::ReentranceDenied,
@@ -2374,7 +3084,7 @@ mod tests {
 	fn call_deny_reentry() {
 		let code_bob = MockLoader::insert(Call, |ctx, _| {
 			if ctx.input_data[0] == 0 {
-				ctx.ext.call(0, CHARLIE, 0, vec![], false)
+				ctx.ext.call(0, CHARLIE, 0, vec![], vec![], false)
 			} else {
 				exec_success()
 			}
@@ -2382,7 +3092,7 @@ mod tests {
 
 		// call BOB with input set to '1'
 		let code_charlie =
-			MockLoader::insert(Call, |ctx, _| ctx.ext.call(0, BOB, 0, vec![1], true));
+			MockLoader::insert(Call, |ctx, _| ctx.ext.call(0, BOB, 0, vec![], vec![1], true));
 
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
@@ -2399,8 +3109,87 @@ mod tests {
 					&mut storage_meter,
 					&schedule,
 					0,
+					vec![],
 					vec![0],
 					None,
+					None,
+				)
+				.map_err(|e| e.error),
+				<Error<Test>>::ReentranceDenied,
+			);
+		});
+	}
+
+	#[test]
+	fn cosmwasm_events_of_failed_calls_are_discarded() {
+		let code_charlie = MockLoader::insert(Call, |ctx, _| {
+			ctx.ext.deposit_cosmwasm_event(b"wasm".to_vec(), vec![]);
+			if ctx.input_data[0] == 0 {
+				exec_success()
+			} else {
+				exec_trapped()
+			}
+		});
+		let code_bob = MockLoader::insert(Call, |ctx, _| {
+			assert_ok!(ctx.ext.call(0, CHARLIE, 0, vec![], vec![0], true));
+			assert!(ctx.ext.call(0, CHARLIE, 0, vec![], vec![1], true).is_err());
+			assert_eq!(ctx.ext.cosmwasm_events(), &[(b"wasm".to_vec(), vec![])]);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+			place_contract(&CHARLIE, code_charlie);
+			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+
+			assert_ok!(MockStack::run_call(
+				ALICE,
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				vec![],
+				None,
+				None,
+			));
+		});
+	}
+
+	#[test]
+	fn query_deny_reentry() {
+		// BOB queries CHARLIE which calls back into BOB.
+		let code_bob = MockLoader::insert(Call, |ctx, _| {
+			if ctx.input_data[0] == 0 {
+				ctx.ext.query(0, CHARLIE, vec![])
+			} else {
+				exec_success()
+			}
+		});
+		let code_charlie =
+			MockLoader::insert(Query, |ctx, _| ctx.ext.call(0, BOB, 0, vec![], vec![1], true));
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+			place_contract(&CHARLIE, code_charlie);
+			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+
+			// BOB -> CHARLIE -> BOB fails as BOB can't be reentered during a query.
+			assert_err!(
+				MockStack::run_call(
+					ALICE,
+					BOB,
+					&mut GasMeter::<Test>::new(GAS_LIMIT),
+					&mut storage_meter,
+					&schedule,
+					0,
+					vec![],
+					vec![0],
+					None,
+					None,
 				)
 				.map_err(|e| e.error),
 				<Error<Test>>::ReentranceDenied,
@@ -2434,6 +3223,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			)
 			.unwrap();
@@ -2505,6 +3296,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			)
 			.unwrap();
@@ -2545,18 +3338,38 @@ mod tests {
 		let success_code = MockLoader::insert(Constructor, |_, _| exec_success());
 		let succ_fail_code = MockLoader::insert(Constructor, move |ctx, _| {
 			ctx.ext
-				.instantiate(0, fail_code, ctx.ext.minimum_balance() * 100, vec![], &[])
+				.instantiate(
+					0,
+					fail_code,
+					ctx.ext.minimum_balance() * 100,
+					vec![],
+					vec![],
+					&[],
+					None,
+					vec![],
+					AddressDerivation::Generator,
+				)
 				.ok();
 			exec_success()
 		});
 		let succ_succ_code = MockLoader::insert(Constructor, move |ctx, _| {
 			let (account_id, _) = ctx
 				.ext
-				.instantiate(0, success_code, ctx.ext.minimum_balance() * 100, vec![], &[])
+				.instantiate(
+					0,
+					success_code,
+					ctx.ext.minimum_balance() * 100,
+					vec![],
+					vec![],
+					&[],
+					None,
+					vec![],
+					AddressDerivation::Generator,
+				)
 				.unwrap();
 
 			// a plain call should not influence the account counter
-			ctx.ext.call(0, account_id, 0, vec![], false).unwrap();
+			ctx.ext.call(0, account_id, 0, vec![], vec![], false).unwrap();
 
 			exec_success()
 		});
@@ -2586,8 +3399,11 @@ mod tests {
 				&schedule,
 				min_balance * 100,
 				vec![],
+				vec![],
 				&[],
 				None,
+				vec![],
+				None,
 			)
 			.ok();
 			assert_eq!(<Nonce<Test>>::get(), 0);
@@ -2600,8 +3416,11 @@ mod tests {
 				&schedule,
 				min_balance * 100,
 				vec![],
+				vec![],
 				&[],
 				None,
+				vec![],
+				None,
 			));
 			assert_eq!(<Nonce<Test>>::get(), 1);
 
@@ -2613,8 +3432,11 @@ mod tests {
 				&schedule,
 				min_balance * 200,
 				vec![],
+				vec![],
 				&[],
 				None,
+				vec![],
+				None,
 			));
 			assert_eq!(<Nonce<Test>>::get(), 2);
 
@@ -2626,8 +3448,11 @@ mod tests {
 				&schedule,
 				min_balance * 200,
 				vec![],
+				vec![],
 				&[],
 				None,
+				vec![],
+				None,
 			));
 			assert_eq!(<Nonce<Test>>::get(), 4);
 		});
@@ -2688,6 +3513,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			));
 		});
@@ -2723,6 +3550,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			));
 		});
@@ -2754,6 +3583,8 @@ mod tests {
 				&schedule,
 				0,
 				vec![],
+				vec![],
+				None,
 				None,
 			);
 			assert_matches!(result, Ok(_));
@@ -0,0 +1,532 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loopback IBC between the CosmWasm contracts of this chain.
+//!
+//! Both ends of a channel are contracts deployed on this chain. This is why there are no light
+//! clients and connections: the channel handshake is performed at once by
+//! [`Pallet::ibc_open_channel`]. Packets sent by a contract are kept in storage until anyone
+//! relays them through [`Pallet::ibc_relay_packet`]. A packet times out when it is relayed at
+//! or after the block number given as timeout. Timestamp timeouts are not supported.
+//!
+//! The port of a contract is `wasm.{address}` and channels are named `channel-{id}`.
+
+use crate::{
+	exec::{AccountIdOf, BlockNumberOf, ExecError, ExportedFunction, Stack as ExecStack},
+	gas::GasMeter,
+	storage::meter::Meter as StorageMeter,
+	wasm::{
+		cosmwasm::{
+			Addr, Binary, IbcAcknowledgement, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg,
+			IbcChannelOpenMsg, IbcEndpoint, IbcOrder as CosmwasmIbcOrder, IbcPacket,
+			IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcTimeout, IbcTimeoutBlock,
+		},
+		to_vec, PrefabWasmModule,
+	},
+	AddressCodec, BalanceOf, Config, Error, Event, IbcChannels, IbcOrder, IbcPackets,
+	InternalOutput, NextIbcChannelId, Pallet, Schedule,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::DispatchError,
+	ensure,
+	storage::{with_transaction, TransactionOutcome},
+	traits::Get,
+	weights::Weight,
+};
+use frame_system::Pallet as System;
+use pallet_contracts_primitives::ExecReturnValue;
+use scale_info::TypeInfo;
+use serde::Serialize;
+use sp_core::crypto::UncheckedFrom;
+use sp_runtime::{
+	traits::{UniqueSaturatedInto, Zero},
+	RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// The identifier of one end of a channel.
+pub type ChannelId = u64;
+
+pub type ChannelEnd<T> = RawChannelEnd<AccountIdOf<T>>;
+
+pub type Packet<T> = RawPacket<BlockNumberOf<T>>;
+
+/// The state of one end of a channel.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum ChannelState {
+	/// Packets can be sent over the channel.
+	Open,
+	/// The contract requested the channel to be closed but the closure was not relayed yet.
+	Closing,
+	/// The channel is closed. Packets still in flight can only time out.
+	Closed,
+}
+
+/// One end of a channel, owned by a contract.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct RawChannelEnd<AccountId> {
+	/// The contract owning this end of the channel.
+	pub contract: AccountId,
+	/// The other end of the channel.
+	pub counterparty: ChannelId,
+	/// Whether packets must be delivered in the order they were sent.
+	pub order: IbcOrder,
+	/// The version negotiated during the handshake.
+	pub version: Vec<u8>,
+	/// The sequence number of the next packet sent over this end.
+	pub next_sequence_send: u64,
+	/// The sequence number of the next packet sent over this end which is to be relayed.
+	pub next_sequence_ack: u64,
+	/// The state of this end of the channel.
+	pub state: ChannelState,
+}
+
+/// A packet waiting to be relayed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct RawPacket<BlockNumber> {
+	/// The data of the packet.
+	pub data: Vec<u8>,
+	/// The block number from which on the packet times out.
+	pub timeout: BlockNumber,
+}
+
+/// The name of the channel `id`, as seen by contracts.
+fn channel_name(id: ChannelId) -> String {
+	format!("channel-{}", id)
+}
+
+/// The id of the channel named `name` which must belong to `contract`.
+fn owned_channel<T: Config>(
+	contract: &AccountIdOf<T>,
+	name: &str,
+) -> Result<(ChannelId, ChannelEnd<T>), DispatchError> {
+	let id = name
+		.strip_prefix("channel-")
+		.and_then(|id| id.parse().ok())
+		.ok_or(<Error<T>>::IbcChannelNotFound)?;
+	let end = <IbcChannels<T>>::get(id).ok_or(<Error<T>>::IbcChannelNotFound)?;
+	ensure!(&end.contract == contract, <Error<T>>::IbcChannelNotFound);
+	Ok((id, end))
+}
+
+/// The endpoint `id` of the channel owned by `contract`, as seen by contracts.
+fn endpoint<T: Config>(
+	contract: &AccountIdOf<T>,
+	id: ChannelId,
+) -> Result<IbcEndpoint, DispatchError> {
+	let address = T::AddressCodec::encode(contract).map_err(DispatchError::Other)?;
+	Ok(IbcEndpoint { port_id: format!("wasm.{}", address), channel_id: channel_name(id) })
+}
+
+/// The channel made of the ends `id` and `counterparty_id`, as seen by the owner of `id`.
+fn channel<T: Config>(
+	contract: &AccountIdOf<T>,
+	id: ChannelId,
+	counterparty: &AccountIdOf<T>,
+	counterparty_id: ChannelId,
+	order: IbcOrder,
+	version: String,
+) -> Result<IbcChannel, DispatchError> {
+	Ok(IbcChannel {
+		endpoint: endpoint::<T>(contract, id)?,
+		counterparty_endpoint: endpoint::<T>(counterparty, counterparty_id)?,
+		order: match order {
+			IbcOrder::Unordered => CosmwasmIbcOrder::Unordered,
+			IbcOrder::Ordered => CosmwasmIbcOrder::Ordered,
+		},
+		version,
+		connection_id: "loopback".to_string(),
+	})
+}
+
+/// Run `f` in a storage transaction which is rolled back if `f` fails.
+fn transactional<T: Config, O>(
+	gas_limit: Weight,
+	f: impl FnOnce(&mut GasMeter<T>, &Schedule<T>) -> Result<O, ExecError>,
+) -> InternalOutput<T, O>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let mut gas_meter = GasMeter::new(gas_limit);
+	let schedule = T::Schedule::get();
+	let result = with_transaction(|| {
+		let result = f(&mut gas_meter, &schedule);
+		if result.is_ok() {
+			TransactionOutcome::Commit(result)
+		} else {
+			TransactionOutcome::Rollback(result)
+		}
+	});
+	InternalOutput { result, gas_meter, storage_deposit: Default::default() }
+}
+
+/// Call the IBC `entry_point` of `contract` with `message` on behalf of the relayer `origin`.
+fn call<T: Config>(
+	origin: &AccountIdOf<T>,
+	contract: &AccountIdOf<T>,
+	entry_point: ExportedFunction,
+	message: &impl Serialize,
+	gas_meter: &mut GasMeter<T>,
+	storage_meter: &mut StorageMeter<T>,
+	schedule: &Schedule<T>,
+) -> Result<ExecReturnValue, ExecError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let result = ExecStack::<T, PrefabWasmModule<T>>::run_ibc(
+		origin.clone(),
+		contract.clone(),
+		entry_point,
+		gas_meter,
+		storage_meter,
+		schedule,
+		to_vec(message)?,
+		None,
+	)?;
+	ensure!(!result.did_revert(), <Error<T>>::ContractReverted);
+	Ok(result)
+}
+
+/// Send a packet with `data` over the channel `name` owned by `contract`.
+///
+/// Returns the sequence number of the packet.
+pub fn send_packet<T: Config>(
+	contract: &AccountIdOf<T>,
+	name: &str,
+	data: Vec<u8>,
+	timeout: BlockNumberOf<T>,
+) -> Result<u64, DispatchError> {
+	let (channel_id, mut end) = owned_channel::<T>(contract, name)?;
+	ensure!(end.state == ChannelState::Open, <Error<T>>::IbcChannelNotOpen);
+	let sequence = end.next_sequence_send;
+	end.next_sequence_send = sequence.saturating_add(1);
+	<IbcChannels<T>>::insert(channel_id, end);
+	<IbcPackets<T>>::insert(channel_id, sequence, RawPacket { data, timeout });
+	<Pallet<T>>::deposit_event(Event::IbcPacketSent { channel_id, sequence });
+	Ok(sequence)
+}
+
+/// Request the channel `name` owned by `contract` to be closed.
+pub fn request_close<T: Config>(
+	contract: &AccountIdOf<T>,
+	name: &str,
+) -> Result<(), DispatchError> {
+	let (channel_id, mut end) = owned_channel::<T>(contract, name)?;
+	ensure!(end.state == ChannelState::Open, <Error<T>>::IbcChannelNotOpen);
+	end.state = ChannelState::Closing;
+	<IbcChannels<T>>::insert(channel_id, end);
+	Ok(())
+}
+
+/// Open a channel between `contract` and `counterparty` by performing the whole handshake.
+///
+/// `contract` may change the proposed `version` in its `OpenInit` step and `counterparty` in
+/// its `OpenTry` step.
+pub fn open_channel<T: Config>(
+	origin: AccountIdOf<T>,
+	contract: AccountIdOf<T>,
+	counterparty: AccountIdOf<T>,
+	order: IbcOrder,
+	version: Vec<u8>,
+	gas_limit: Weight,
+	storage_deposit_limit: Option<BalanceOf<T>>,
+) -> InternalOutput<T, ()>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let mut storage_deposit = Default::default();
+	let mut output = transactional(gas_limit, |gas_meter, schedule| {
+		let mut storage_meter = StorageMeter::new(&origin, storage_deposit_limit, Zero::zero())?;
+		let id = <NextIbcChannelId<T>>::get();
+		let counterparty_id = id.checked_add(1).ok_or(<Error<T>>::IbcChannelNotFound)?;
+		<NextIbcChannelId<T>>::put(counterparty_id.saturating_add(1));
+		let own = |version: &[u8]| {
+			channel::<T>(
+				&contract,
+				id,
+				&counterparty,
+				counterparty_id,
+				order,
+				String::from_utf8_lossy(version).into_owned(),
+			)
+		};
+		let other = |version: &[u8]| {
+			channel::<T>(
+				&counterparty,
+				counterparty_id,
+				&contract,
+				id,
+				order,
+				String::from_utf8_lossy(version).into_owned(),
+			)
+		};
+		// A contract returns an empty version in order to accept the proposed one.
+		let negotiate = |proposed: Vec<u8>, result: ExecReturnValue| {
+			if result.data.0.is_empty() {
+				proposed
+			} else {
+				result.data.0
+			}
+		};
+
+		let message = IbcChannelOpenMsg::OpenInit { channel: own(&version)? };
+		let result = call(
+			&origin,
+			&contract,
+			ExportedFunction::IbcChannelOpen,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		let version = negotiate(version, result);
+		let message = IbcChannelOpenMsg::OpenTry {
+			channel: other(&version)?,
+			counterparty_version: String::from_utf8_lossy(&version).into_owned(),
+		};
+		let result = call(
+			&origin,
+			&counterparty,
+			ExportedFunction::IbcChannelOpen,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		let version = negotiate(version, result);
+
+		// Both ends are open once the handshake is acknowledged so that contracts can start
+		// sending packets right away.
+		let end = |contract: &AccountIdOf<T>, counterparty| RawChannelEnd {
+			contract: contract.clone(),
+			counterparty,
+			order,
+			version: version.clone(),
+			next_sequence_send: 1,
+			next_sequence_ack: 1,
+			state: ChannelState::Open,
+		};
+		<IbcChannels<T>>::insert(id, end(&contract, counterparty_id));
+		<IbcChannels<T>>::insert(counterparty_id, end(&counterparty, id));
+
+		let message = IbcChannelConnectMsg::OpenAck {
+			channel: own(&version)?,
+			counterparty_version: String::from_utf8_lossy(&version).into_owned(),
+		};
+		call(
+			&origin,
+			&contract,
+			ExportedFunction::IbcChannelConnect,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		let message = IbcChannelConnectMsg::OpenConfirm { channel: other(&version)? };
+		call(
+			&origin,
+			&counterparty,
+			ExportedFunction::IbcChannelConnect,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		storage_deposit = storage_meter.into_deposit();
+		<Pallet<T>>::deposit_event(Event::IbcChannelOpened {
+			contract: contract.clone(),
+			channel_id: id,
+			counterparty: counterparty.clone(),
+			counterparty_channel_id: counterparty_id,
+		});
+		Ok(())
+	});
+	output.storage_deposit = storage_deposit;
+	output
+}
+
+/// Relay the packet `sequence` sent over the channel `channel_id`.
+///
+/// The packet is received by the counterparty and its acknowledgement is passed back to the
+/// sender. The sender is notified of a timeout instead if the packet timed out or the
+/// counterparty end of the channel is no longer open.
+pub fn relay_packet<T: Config>(
+	origin: AccountIdOf<T>,
+	channel_id: ChannelId,
+	sequence: u64,
+	gas_limit: Weight,
+	storage_deposit_limit: Option<BalanceOf<T>>,
+) -> InternalOutput<T, ()>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let mut storage_deposit = Default::default();
+	let mut output = transactional(gas_limit, |gas_meter, schedule| {
+		let mut src = <IbcChannels<T>>::get(channel_id).ok_or(<Error<T>>::IbcChannelNotFound)?;
+		let packet =
+			<IbcPackets<T>>::take(channel_id, sequence).ok_or(<Error<T>>::IbcPacketNotFound)?;
+		if src.order == IbcOrder::Ordered {
+			ensure!(sequence == src.next_sequence_ack, <Error<T>>::IbcPacketOutOfOrder);
+			src.next_sequence_ack = sequence.saturating_add(1);
+			<IbcChannels<T>>::insert(channel_id, src.clone());
+		}
+		let dest = <IbcChannels<T>>::get(src.counterparty).ok_or(<Error<T>>::IbcChannelNotFound)?;
+		let relayer = T::AddressCodec::encode(&origin).map_err(DispatchError::Other)?;
+		let timed_out = System::<T>::block_number() >= packet.timeout;
+		let packet = IbcPacket {
+			data: Binary(packet.data),
+			src: endpoint::<T>(&src.contract, channel_id)?,
+			dest: endpoint::<T>(&dest.contract, src.counterparty)?,
+			sequence,
+			timeout: IbcTimeout {
+				block: Some(IbcTimeoutBlock {
+					revision: 0,
+					height: packet.timeout.unique_saturated_into(),
+				}),
+				timestamp: None,
+			},
+		};
+		let mut storage_meter = StorageMeter::new(&origin, storage_deposit_limit, Zero::zero())?;
+
+		if timed_out || dest.state != ChannelState::Open {
+			let message = IbcPacketTimeoutMsg { packet, relayer: Addr::unchecked(relayer) };
+			call(
+				&origin,
+				&src.contract,
+				ExportedFunction::IbcPacketTimeout,
+				&message,
+				gas_meter,
+				&mut storage_meter,
+				schedule,
+			)?;
+			<Pallet<T>>::deposit_event(Event::IbcPacketTimedOut { channel_id, sequence });
+		} else {
+			let message =
+				IbcPacketReceiveMsg { packet: packet.clone(), relayer: Addr::unchecked(&relayer) };
+			let result = call(
+				&origin,
+				&dest.contract,
+				ExportedFunction::IbcPacketReceive,
+				&message,
+				gas_meter,
+				&mut storage_meter,
+				schedule,
+			)?;
+			let message = IbcPacketAckMsg {
+				acknowledgement: IbcAcknowledgement { data: Binary(result.data.0) },
+				original_packet: packet,
+				relayer: Addr::unchecked(relayer),
+			};
+			call(
+				&origin,
+				&src.contract,
+				ExportedFunction::IbcPacketAck,
+				&message,
+				gas_meter,
+				&mut storage_meter,
+				schedule,
+			)?;
+			<Pallet<T>>::deposit_event(Event::IbcPacketAcknowledged { channel_id, sequence });
+		}
+		storage_deposit = storage_meter.into_deposit();
+		Ok(())
+	});
+	output.storage_deposit = storage_deposit;
+	output
+}
+
+/// Close the channel `channel_id` whose owner requested it to be closed.
+///
+/// Both ends of the channel are closed.
+pub fn close_channel<T: Config>(
+	origin: AccountIdOf<T>,
+	channel_id: ChannelId,
+	gas_limit: Weight,
+	storage_deposit_limit: Option<BalanceOf<T>>,
+) -> InternalOutput<T, ()>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	let mut storage_deposit = Default::default();
+	let mut output = transactional(gas_limit, |gas_meter, schedule| {
+		let mut end = <IbcChannels<T>>::get(channel_id).ok_or(<Error<T>>::IbcChannelNotFound)?;
+		ensure!(end.state == ChannelState::Closing, <Error<T>>::IbcChannelNotClosing);
+		let counterparty_id = end.counterparty;
+		let mut counterparty =
+			<IbcChannels<T>>::get(counterparty_id).ok_or(<Error<T>>::IbcChannelNotFound)?;
+		let mut storage_meter = StorageMeter::new(&origin, storage_deposit_limit, Zero::zero())?;
+		let version = String::from_utf8_lossy(&end.version).into_owned();
+
+		end.state = ChannelState::Closed;
+		counterparty.state = ChannelState::Closed;
+		<IbcChannels<T>>::insert(channel_id, end.clone());
+		<IbcChannels<T>>::insert(counterparty_id, counterparty.clone());
+
+		let message = IbcChannelCloseMsg::CloseInit {
+			channel: channel::<T>(
+				&end.contract,
+				channel_id,
+				&counterparty.contract,
+				counterparty_id,
+				end.order,
+				version.clone(),
+			)?,
+		};
+		call(
+			&origin,
+			&end.contract,
+			ExportedFunction::IbcChannelClose,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		let message = IbcChannelCloseMsg::CloseConfirm {
+			channel: channel::<T>(
+				&counterparty.contract,
+				counterparty_id,
+				&end.contract,
+				channel_id,
+				end.order,
+				version,
+			)?,
+		};
+		call(
+			&origin,
+			&counterparty.contract,
+			ExportedFunction::IbcChannelClose,
+			&message,
+			gas_meter,
+			&mut storage_meter,
+			schedule,
+		)?;
+		storage_deposit = storage_meter.into_deposit();
+		<Pallet<T>>::deposit_event(Event::IbcChannelClosed { contract: end.contract, channel_id });
+		<Pallet<T>>::deposit_event(Event::IbcChannelClosed {
+			contract: counterparty.contract,
+			channel_id: counterparty_id,
+		});
+		Ok(())
+	});
+	output.storage_deposit = storage_deposit;
+	output
+}
@@ -81,7 +81,7 @@
 //! WebAssembly based smart contracts in the Rust programming language. This is a work in progress.
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg_attr(feature = "runtime-benchmarks", recursion_limit = "512")]
+#![cfg_attr(feature = "runtime-benchmarks", recursion_limit = "1024")]
 
 extern crate alloc;
 
@@ -89,6 +89,8 @@ extern crate alloc;
 mod gas;
 mod benchmarking;
 mod exec;
+#[cfg(feature = "stargate")]
+mod ibc;
 mod schedule;
 mod storage;
 mod wasm;
@@ -106,37 +108,68 @@ pub use crate::{
 	schedule::{HostFnWeights, InstructionWeights, Limits, Schedule},
 };
 use crate::{
-	exec::{AccountIdOf, ExecError, Executable, Stack as ExecStack},
+	exec::{AccountIdOf, AssetsOf, CallTracer, ExecError, Executable, Stack as ExecStack},
 	gas::GasMeter,
 	storage::{meter::Meter as StorageMeter, ContractInfo, DeletedContract, Storage},
-	wasm::{OwnerInfo, PrefabWasmModule},
+	wasm::{
+		cosmwasm::{instantiate2_address, CanonicalAddr, Instantiate2AddressError},
+		OwnerInfo, PrefabWasmModule,
+	},
 	weights::WeightInfo,
 };
-use codec::{Encode, HasCompact};
+#[cfg(feature = "stargate")]
+use crate::ibc::{ChannelEnd, ChannelId, Packet};
+use alloc::string::{String, ToString};
+use bech32::{FromBase32, ToBase32};
+use codec::{Decode, DecodeAll, Encode, HasCompact};
 use frame_support::{
-	dispatch::Dispatchable,
+	dispatch::{DispatchError, DispatchResult, Dispatchable},
 	ensure,
-	traits::{Contains, Currency, Get, Randomness, ReservableCurrency, StorageVersion, Time},
+	storage::{with_transaction, TransactionOutcome},
+	traits::{
+		fungibles, Contains, Currency, Get, Randomness, ReservableCurrency, StorageVersion, Time,
+	},
 	weights::{GetDispatchInfo, Pays, PostDispatchInfo, Weight},
 };
-use frame_system::Pallet as System;
+use frame_system::{EventRecord, Pallet as System, Phase};
 use pallet_contracts_primitives::{
-	Code, CodeUploadResult, CodeUploadReturnValue, ContractAccessError, ContractExecResult,
-	ContractInstantiateResult, ExecReturnValue, GetStorageResult, InstantiateReturnValue,
-	StorageDeposit,
+	Code, CodeUploadResult, CodeUploadReturnValue, ContractAccessError, ContractEstimateResult,
+	ContractEvent, ContractExecResult, ContractInstantiateResult, ContractTraceResult,
+	ExecReturnValue, GetStorageResult, InstantiateReturnValue, StorageDeposit,
 };
 use scale_info::TypeInfo;
 use sp_core::{crypto::UncheckedFrom, Bytes};
-use sp_runtime::traits::{Convert, Hash, Saturating, StaticLookup};
-use sp_std::{fmt::Debug, prelude::*};
+use sp_runtime::{
+	traits::{Convert, Hash, Saturating, StaticLookup, TrailingZeroInput, Zero},
+	RuntimeDebug,
+};
+use sp_std::{
+	fmt::{Debug, Display},
+	marker::PhantomData,
+	prelude::*,
+	str::FromStr,
+};
 
 type CodeHash<T> = <T as frame_system::Config>::Hash;
+/// The sequential identifier CosmWasm uses to refer to uploaded code.
+pub type CodeId = u64;
 type TrieId = Vec<u8>;
+
+/// Whether the packets of an IBC channel must be delivered in the order they were sent.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum IbcOrder {
+	/// Packets are delivered in any order.
+	Unordered,
+	/// Packets are delivered in the order they were sent.
+	Ordered,
+}
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type AssetIdOf<T> =
+	<<T as Config>::Assets as fungibles::Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
 
 /// The current storage version.
-const STORAGE_VERSION: StorageVersion = StorageVersion::new(7);
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(10);
 
 /// Used as a sentinel value when reading and writing contract memory.
 ///
@@ -195,6 +228,167 @@ where
 	}
 }
 
+/// Converts account ids from and to the human readable addresses used by CosmWasm contracts.
+///
+/// The canonical form of an address, as seen by contracts, is the SCALE encoding of the
+/// account id. See [`Ss58AddressCodec`] and [`Bech32AddressCodec`] for the provided
+/// implementations.
+pub trait AddressCodec<T: frame_system::Config> {
+	/// Decode the account id from its human readable `address`.
+	fn decode(address: &str) -> Result<T::AccountId, &'static str>;
+
+	/// Encode `account` into its human readable address.
+	fn encode(account: &T::AccountId) -> Result<String, &'static str>;
+}
+
+/// Length of the checksum appended to SS58 addresses.
+const SS58_CHECKSUM_LEN: usize = 2;
+
+/// Hash of an SS58 address body from which the checksum is taken.
+fn ss58hash(data: &[u8]) -> [u8; 64] {
+	sp_core_hashing::blake2_512(&[&b"SS58PRE"[..], data].concat())
+}
+
+/// SS58 address codec.
+///
+/// Addresses are encoded with the [`frame_system::Config::SS58Prefix`] of the chain and
+/// addresses using any other prefix are rejected.
+pub struct Ss58AddressCodec;
+
+impl<T: frame_system::Config> AddressCodec<T> for Ss58AddressCodec {
+	fn decode(address: &str) -> Result<T::AccountId, &'static str> {
+		let data = bs58::decode(address).into_vec().map_err(|_| "Invalid base58 encoding")?;
+		let (prefix_len, ident) = match data[..] {
+			[first @ 0..=63, ..] => (1, first as u16),
+			[first @ 64..=127, second, ..] => {
+				let lower = (first << 2) | (second >> 6);
+				let upper = second & 0b0011_1111;
+				(2, (lower as u16) | ((upper as u16) << 8))
+			},
+			_ => return Err("Invalid SS58 prefix"),
+		};
+		ensure!(ident == T::SS58Prefix::get(), "Invalid SS58 prefix");
+		ensure!(data.len() >= prefix_len + SS58_CHECKSUM_LEN, "Invalid address length");
+		let (body, checksum) = data.split_at(data.len() - SS58_CHECKSUM_LEN);
+		ensure!(ss58hash(body)[..SS58_CHECKSUM_LEN] == *checksum, "Invalid SS58 checksum");
+		T::AccountId::decode_all(&mut &body[prefix_len..]).map_err(|_| "Invalid address length")
+	}
+
+	fn encode(account: &T::AccountId) -> Result<String, &'static str> {
+		let ident = T::SS58Prefix::get() & 0b0011_1111_1111_1111;
+		let mut data = match ident {
+			0..=63 => vec![ident as u8],
+			_ => {
+				let first = ((ident & 0b0000_0000_1111_1100) as u8) >> 2;
+				let second = ((ident >> 8) as u8) | ((ident & 0b0000_0000_0000_0011) as u8) << 6;
+				vec![first | 0b0100_0000, second]
+			},
+		};
+		data.extend(account.encode());
+		let checksum = ss58hash(&data);
+		data.extend(&checksum[..SS58_CHECKSUM_LEN]);
+		Ok(bs58::encode(data).into_string())
+	}
+}
+
+/// Bech32 address codec, as used by Cosmos SDK chains.
+///
+/// Addresses are encoded with `Hrp` as human readable part and addresses using any other
+/// human readable part are rejected.
+pub struct Bech32AddressCodec<Hrp>(PhantomData<Hrp>);
+
+impl<T, Hrp> AddressCodec<T> for Bech32AddressCodec<Hrp>
+where
+	T: frame_system::Config,
+	Hrp: Get<&'static str>,
+{
+	fn decode(address: &str) -> Result<T::AccountId, &'static str> {
+		let (hrp, data, variant) =
+			bech32::decode(address).map_err(|_| "Invalid bech32 encoding")?;
+		ensure!(hrp == Hrp::get(), "Invalid bech32 prefix");
+		ensure!(variant == bech32::Variant::Bech32, "Invalid bech32 variant");
+		let data = Vec::<u8>::from_base32(&data).map_err(|_| "Invalid bech32 encoding")?;
+		T::AccountId::decode_all(&mut &data[..]).map_err(|_| "Invalid address length")
+	}
+
+	fn encode(account: &T::AccountId) -> Result<String, &'static str> {
+		bech32::encode(Hrp::get(), account.encode().to_base32(), bech32::Variant::Bech32)
+			.map_err(|_| "Invalid bech32 prefix")
+	}
+}
+
+/// Maps the denoms of the `Coin`s exchanged with CosmWasm contracts to assets.
+///
+/// The [`Config::NativeDenom`] always refers to the [`Config::Currency`] and is never passed to
+/// the resolver. See [`PrefixedDenomResolver`] for the provided implementation.
+pub trait DenomResolver<AssetId> {
+	/// Returns the asset referred to by `denom` or `None` if there is no such asset.
+	fn resolve(denom: &str) -> Option<AssetId>;
+
+	/// Returns the denom which refers to `asset`.
+	fn denom(asset: &AssetId) -> String;
+}
+
+/// Resolves no denom at all: contracts can only deal with the native currency.
+impl<AssetId> DenomResolver<AssetId> for () {
+	fn resolve(_denom: &str) -> Option<AssetId> {
+		None
+	}
+
+	fn denom(_asset: &AssetId) -> String {
+		String::new()
+	}
+}
+
+/// Denom resolver which refers to the asset with id `id` by the denom `{Prefix}{id}`.
+///
+/// Only the canonical form of the id is accepted, e.g. `asset/1` but not `asset/01`.
+pub struct PrefixedDenomResolver<Prefix>(PhantomData<Prefix>);
+
+impl<AssetId, Prefix> DenomResolver<AssetId> for PrefixedDenomResolver<Prefix>
+where
+	AssetId: FromStr + Display,
+	Prefix: Get<&'static str>,
+{
+	fn resolve(denom: &str) -> Option<AssetId> {
+		let id = denom.strip_prefix(Prefix::get())?;
+		let asset = id.parse::<AssetId>().ok()?;
+		(asset.to_string() == id).then(|| asset)
+	}
+
+	fn denom(asset: &AssetId) -> String {
+		[Prefix::get(), &asset.to_string()].concat()
+	}
+}
+
+/// Converts between the gas units of the CosmWasm SDK and [`Weight`].
+///
+/// Gas limits set by CosmWasm contracts, like the one of a `SubMsg`, are expressed in gas units
+/// of the SDK. See [`FixedGasRatio`] for the provided implementation.
+pub trait GasConverter {
+	/// Returns the weight which amounts to `gas` units of gas.
+	fn gas_to_weight(gas: u64) -> Weight;
+
+	/// Returns the units of gas which amount to `weight`.
+	fn weight_to_gas(weight: Weight) -> u64;
+}
+
+/// Gas converter where each unit of gas amounts to `WeightPerGas`.
+///
+/// wasmd charges 140_000_000 gas of its VM per unit of SDK gas with one millisecond of
+/// execution costing 10^12 gas of its VM. This amounts to 140_000 weight per unit of gas.
+pub struct FixedGasRatio<WeightPerGas>(PhantomData<WeightPerGas>);
+
+impl<WeightPerGas: Get<Weight>> GasConverter for FixedGasRatio<WeightPerGas> {
+	fn gas_to_weight(gas: u64) -> Weight {
+		gas.saturating_mul(WeightPerGas::get())
+	}
+
+	fn weight_to_gas(weight: Weight) -> u64 {
+		weight / WeightPerGas::get().max(1)
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -309,6 +503,40 @@ pub mod pallet {
 
 		/// The address generator used to generate the addresses of contracts.
 		type AddressGenerator: AddressGenerator<Self>;
+
+		/// The codec used to convert between account ids and the human readable addresses
+		/// of CosmWasm contracts.
+		type AddressCodec: AddressCodec<Self>;
+
+		/// The chain id exposed to CosmWasm contracts in `env.block.chain_id`.
+		#[pallet::constant]
+		type ChainId: Get<&'static str>;
+
+		/// The denomination of the native currency in the `Coin`s exchanged with CosmWasm
+		/// contracts.
+		#[pallet::constant]
+		type NativeDenom: Get<&'static str>;
+
+		/// The capabilities which CosmWasm contracts can require by exporting
+		/// `requires_<capability>`, e.g. `iterator` or `stargate`.
+		///
+		/// Contracts requiring any other capability are rejected when their code is uploaded.
+		/// `stargate` is only honored if the pallet is built with the `stargate` feature.
+		#[pallet::constant]
+		type Capabilities: Get<&'static [&'static str]>;
+
+		/// The assets which CosmWasm contracts can hold and transfer besides the native
+		/// currency.
+		type Assets: fungibles::Transfer<Self::AccountId, Balance = BalanceOf<Self>>;
+
+		/// Maps the denoms other than [`Self::NativeDenom`] to [`Self::Assets`].
+		type DenomResolver: DenomResolver<AssetIdOf<Self>>;
+
+		/// Converts the gas units of the CosmWasm SDK into weight and back.
+		type GasConverter: GasConverter;
+
+		/// The origin which can pause contracts and codes or freeze all contract calls.
+		type ContractsPauseOrigin: EnsureOrigin<Self::Origin>;
 	}
 
 	#[pallet::pallet]
@@ -372,10 +600,12 @@ pub mod pallet {
 				origin,
 				dest,
 				value,
+				Vec::new(),
 				gas_limit,
 				storage_deposit_limit.map(Into::into),
 				data,
 				None,
+				None,
 			);
 			if let Ok(retval) = &output.result {
 				if retval.did_revert() {
@@ -385,6 +615,49 @@ pub mod pallet {
 			output.gas_meter.into_dispatch_result(output.result, T::WeightInfo::call())
 		}
 
+		/// Makes a call to an account, transferring some balance and assets.
+		///
+		/// Same as [`Self::call`] but the `assets` are transferred from the `origin` to `dest`
+		/// in addition to the `value`. CosmWasm contracts receive both as the `funds` of
+		/// their `MessageInfo`. The number of `assets` is limited by the current schedule.
+		#[pallet::weight(
+			T::WeightInfo::call_with_assets(assets.len() as u32).saturating_add(*gas_limit)
+		)]
+		pub fn call_with_assets(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[pallet::compact] value: BalanceOf<T>,
+			assets: AssetsOf<T>,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+			data: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			ensure!(
+				assets.len() as u32 <= T::Schedule::get().limits.transferred_assets,
+				<Error<T>>::TooManyAssets,
+			);
+			let dest = T::Lookup::lookup(dest)?;
+			let weight = T::WeightInfo::call_with_assets(assets.len() as u32);
+			let mut output = Self::internal_call(
+				origin,
+				dest,
+				value,
+				assets,
+				gas_limit,
+				storage_deposit_limit.map(Into::into),
+				data,
+				None,
+				None,
+			);
+			if let Ok(retval) = &output.result {
+				if retval.did_revert() {
+					output.result = Err(<Error<T>>::ContractReverted.into());
+				}
+			}
+			output.gas_meter.into_dispatch_result(output.result, weight)
+		}
+
 		/// Instantiates a new contract from the supplied `code` optionally transferring
 		/// some balance.
 		///
@@ -401,6 +674,9 @@ pub mod pallet {
 		/// * `code`: The contract code to deploy in raw bytes.
 		/// * `data`: The input data to pass to the contract constructor.
 		/// * `salt`: Used for the address derivation. See [`Pallet::contract_address`].
+		/// * `admin`: The account which is allowed to migrate the contract, if any.
+		/// * `label`: A human readable label of the contract. The contract can be looked up by a
+		///   non empty label. See [`Pallet::contracts_by_label`].
 		///
 		/// Instantiation is executed as follows:
 		///
@@ -423,18 +699,24 @@ pub mod pallet {
 			code: Vec<u8>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
+			admin: Option<<T::Lookup as StaticLookup>::Source>,
+			label: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
 			let origin = ensure_signed(origin)?;
+			let admin = admin.map(T::Lookup::lookup).transpose()?;
 			let code_len = code.len() as u32;
 			let salt_len = salt.len() as u32;
 			let mut output = Self::internal_instantiate(
 				origin,
 				value,
+				Vec::new(),
 				gas_limit,
 				storage_deposit_limit.map(Into::into),
 				Code::Upload(Bytes(code)),
 				data,
 				salt,
+				admin,
+				label,
 				None,
 			);
 			if let Ok(retval) = &output.result {
@@ -464,17 +746,23 @@ pub mod pallet {
 			code_hash: CodeHash<T>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
+			admin: Option<<T::Lookup as StaticLookup>::Source>,
+			label: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
 			let origin = ensure_signed(origin)?;
+			let admin = admin.map(T::Lookup::lookup).transpose()?;
 			let salt_len = salt.len() as u32;
 			let mut output = Self::internal_instantiate(
 				origin,
 				value,
+				Vec::new(),
 				gas_limit,
 				storage_deposit_limit.map(Into::into),
 				Code::Existing(code_hash),
 				data,
 				salt,
+				admin,
+				label,
 				None,
 			);
 			if let Ok(retval) = &output.result {
@@ -488,6 +776,54 @@ pub mod pallet {
 			)
 		}
 
+		/// Instantiates a contract from a previously deployed wasm binary identified by its
+		/// code id.
+		///
+		/// This function is identical to [`Self::instantiate`] but takes the [`CodeId`] which
+		/// was assigned to the code on upload instead of its `code_hash`.
+		#[pallet::weight(
+			T::WeightInfo::instantiate(salt.len() as u32)
+				.saturating_add(T::DbWeight::get().reads(1))
+				.saturating_add(*gas_limit)
+		)]
+		pub fn instantiate_from_code_id(
+			origin: OriginFor<T>,
+			#[pallet::compact] value: BalanceOf<T>,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+			#[pallet::compact] code_id: CodeId,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+			admin: Option<<T::Lookup as StaticLookup>::Source>,
+			label: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			let admin = admin.map(T::Lookup::lookup).transpose()?;
+			let salt_len = salt.len() as u32;
+			let mut output = Self::internal_instantiate(
+				origin,
+				value,
+				Vec::new(),
+				gas_limit,
+				storage_deposit_limit.map(Into::into),
+				Code::Id(code_id),
+				data,
+				salt,
+				admin,
+				label,
+				None,
+			);
+			if let Ok(retval) = &output.result {
+				if retval.1.did_revert() {
+					output.result = Err(<Error<T>>::ContractReverted.into());
+				}
+			}
+			output.gas_meter.into_dispatch_result(
+				output.result.map(|(_address, output)| output),
+				T::WeightInfo::instantiate(salt_len).saturating_add(T::DbWeight::get().reads(1)),
+			)
+		}
+
 		/// Upload new `code` without instantiating a contract from it.
 		///
 		/// If the code does not already exist a deposit is reserved from the caller
@@ -528,6 +864,295 @@ pub mod pallet {
 			// we waive the fee because removing unused code is beneficial
 			Ok(Pays::No.into())
 		}
+
+		/// Migrate a contract to another code.
+		///
+		/// # Parameters
+		///
+		/// * `dest`: Address of the contract to migrate.
+		/// * `gas_limit`: The gas limit enforced when executing the migration.
+		/// * `storage_deposit_limit`: The maximum amount of balance that can be charged from the
+		///   caller to pay for the storage consumed.
+		/// * `code_id`: The id of the code the contract is migrated to.
+		/// * `data`: The input data to pass to the `migrate` function of the new code.
+		///
+		/// Only the admin of the contract can migrate it. The code of the contract is replaced
+		/// and the `migrate` function of the new code is executed in the context of the contract.
+		/// The code is not replaced if the migration fails.
+		#[pallet::weight(T::WeightInfo::migrate().saturating_add(*gas_limit))]
+		pub fn migrate(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+			#[pallet::compact] code_id: CodeId,
+			data: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let mut output = Self::internal_migrate(
+				origin,
+				dest,
+				gas_limit,
+				storage_deposit_limit.map(Into::into),
+				code_id,
+				data,
+				None,
+			);
+			if let Ok(retval) = &output.result {
+				if retval.did_revert() {
+					output.result = Err(<Error<T>>::ContractReverted.into());
+				}
+			}
+			output.gas_meter.into_dispatch_result(output.result, T::WeightInfo::migrate())
+		}
+
+		/// Set the admin of a contract to `admin`.
+		///
+		/// Only the current admin of the contract can do so.
+		#[pallet::weight(T::WeightInfo::update_admin())]
+		pub fn update_admin(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+			admin: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let admin = T::Lookup::lookup(admin)?;
+			Self::set_admin(&origin, dest, Some(admin))
+		}
+
+		/// Remove the admin of a contract, which makes it impossible to migrate it.
+		///
+		/// Only the current admin of the contract can do so.
+		#[pallet::weight(T::WeightInfo::clear_admin())]
+		pub fn clear_admin(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			Self::set_admin(&origin, dest, None)
+		}
+
+		/// Open an IBC channel between the CosmWasm contracts `contract` and `counterparty`.
+		///
+		/// # Parameters
+		///
+		/// * `contract`: The contract initiating the channel.
+		/// * `counterparty`: The contract at the other end of the channel.
+		/// * `order`: Whether packets must be delivered in the order they were sent.
+		/// * `version`: The version proposed to the contracts.
+		/// * `gas_limit`: The gas limit enforced when executing the handshake.
+		/// * `storage_deposit_limit`: The maximum amount of balance that can be charged from the
+		///   caller to pay for the storage consumed.
+		///
+		/// The whole channel handshake is performed by calling `ibc_channel_open` and then
+		/// `ibc_channel_connect` on both contracts. No channel is opened if any of them fails.
+		/// Only available if the pallet is built with the `stargate` feature.
+		#[pallet::weight(T::WeightInfo::ibc_open_channel().saturating_add(*gas_limit))]
+		pub fn ibc_open_channel(
+			origin: OriginFor<T>,
+			contract: <T::Lookup as StaticLookup>::Source,
+			counterparty: <T::Lookup as StaticLookup>::Source,
+			order: IbcOrder,
+			version: Vec<u8>,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			let contract = T::Lookup::lookup(contract)?;
+			let counterparty = T::Lookup::lookup(counterparty)?;
+			#[cfg(feature = "stargate")]
+			{
+				let output = ibc::open_channel::<T>(
+					origin,
+					contract,
+					counterparty,
+					order,
+					version,
+					gas_limit,
+					storage_deposit_limit.map(Into::into),
+				);
+				output
+					.gas_meter
+					.into_dispatch_result(output.result, T::WeightInfo::ibc_open_channel())
+			}
+			#[cfg(not(feature = "stargate"))]
+			{
+				let _ = (origin, contract, counterparty, order, version);
+				let _ = (gas_limit, storage_deposit_limit);
+				Err(<Error<T>>::IbcDisabled.into())
+			}
+		}
+
+		/// Relay the IBC packet `sequence` sent over the channel `channel_id`.
+		///
+		/// The packet is passed to `ibc_packet_receive` of the receiving contract and the
+		/// acknowledgement it returns to `ibc_packet_ack` of the sending contract. If the packet
+		/// timed out or the receiving end of the channel is closed `ibc_packet_timeout` of the
+		/// sending contract is called instead. Packets of ordered channels must be relayed in
+		/// order. Only available if the pallet is built with the `stargate` feature.
+		#[pallet::weight(T::WeightInfo::ibc_relay_packet().saturating_add(*gas_limit))]
+		pub fn ibc_relay_packet(
+			origin: OriginFor<T>,
+			#[pallet::compact] channel_id: u64,
+			#[pallet::compact] sequence: u64,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			#[cfg(feature = "stargate")]
+			{
+				let output = ibc::relay_packet::<T>(
+					origin,
+					channel_id,
+					sequence,
+					gas_limit,
+					storage_deposit_limit.map(Into::into),
+				);
+				output
+					.gas_meter
+					.into_dispatch_result(output.result, T::WeightInfo::ibc_relay_packet())
+			}
+			#[cfg(not(feature = "stargate"))]
+			{
+				let _ = (origin, channel_id, sequence, gas_limit, storage_deposit_limit);
+				Err(<Error<T>>::IbcDisabled.into())
+			}
+		}
+
+		/// Close the IBC channel `channel_id` whose contract requested it to be closed.
+		///
+		/// `ibc_channel_close` is called on the contracts at both ends of the channel. Packets
+		/// which were not relayed yet can only time out afterwards. Only available if the
+		/// pallet is built with the `stargate` feature.
+		#[pallet::weight(T::WeightInfo::ibc_close_channel().saturating_add(*gas_limit))]
+		pub fn ibc_close_channel(
+			origin: OriginFor<T>,
+			#[pallet::compact] channel_id: u64,
+			#[pallet::compact] gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			#[cfg(feature = "stargate")]
+			{
+				let output = ibc::close_channel::<T>(
+					origin,
+					channel_id,
+					gas_limit,
+					storage_deposit_limit.map(Into::into),
+				);
+				output
+					.gas_meter
+					.into_dispatch_result(output.result, T::WeightInfo::ibc_close_channel())
+			}
+			#[cfg(not(feature = "stargate"))]
+			{
+				let _ = (origin, channel_id, gas_limit, storage_deposit_limit);
+				Err(<Error<T>>::IbcDisabled.into())
+			}
+		}
+
+		/// Pause the contract at `dest`.
+		///
+		/// A paused contract can not be called until it is unpaused. This includes calls from
+		/// other contracts and queries. Its admin can still migrate it to code which isn't
+		/// paused, e.g. in order to fix it.
+		#[pallet::weight(T::WeightInfo::pause_contract())]
+		pub fn pause_contract(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			ensure!(<ContractInfoOf<T>>::contains_key(&dest), <Error<T>>::ContractNotFound);
+			<PausedContracts<T>>::insert(&dest, ());
+			Self::deposit_event(Event::ContractPaused { contract: dest });
+			Ok(())
+		}
+
+		/// Unpause the contract at `dest`.
+		#[pallet::weight(T::WeightInfo::unpause_contract())]
+		pub fn unpause_contract(
+			origin: OriginFor<T>,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			if <PausedContracts<T>>::take(&dest).is_some() {
+				Self::deposit_event(Event::ContractUnpaused { contract: dest });
+			}
+			Ok(())
+		}
+
+		/// Pause all contracts whose code hash is `code_hash`.
+		///
+		/// No contract can be instantiated from, migrated to or execute the code until it is
+		/// unpaused, which includes delegate calls into it.
+		#[pallet::weight(T::WeightInfo::pause_code())]
+		pub fn pause_code(origin: OriginFor<T>, code_hash: CodeHash<T>) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			ensure!(<OwnerInfoOf<T>>::contains_key(code_hash), <Error<T>>::CodeNotFound);
+			<PausedCodes<T>>::insert(code_hash, ());
+			Self::deposit_event(Event::CodePaused { code_hash });
+			Ok(())
+		}
+
+		/// Unpause the contracts whose code hash is `code_hash`.
+		#[pallet::weight(T::WeightInfo::unpause_code())]
+		pub fn unpause_code(origin: OriginFor<T>, code_hash: CodeHash<T>) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			if <PausedCodes<T>>::take(code_hash).is_some() {
+				Self::deposit_event(Event::CodeUnpaused { code_hash });
+			}
+			Ok(())
+		}
+
+		/// Reject all contract calls and instantiations until [`Self::unfreeze_contracts`]
+		/// is called.
+		///
+		/// Uploading and removing code is still possible while contracts are frozen.
+		#[pallet::weight(T::WeightInfo::freeze_contracts())]
+		pub fn freeze_contracts(origin: OriginFor<T>) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			if !<ContractsFrozen<T>>::get() {
+				<ContractsFrozen<T>>::put(true);
+				Self::deposit_event(Event::ContractsFrozen);
+			}
+			Ok(())
+		}
+
+		/// Allow contract calls again after they were frozen by [`Self::freeze_contracts`].
+		#[pallet::weight(T::WeightInfo::unfreeze_contracts())]
+		pub fn unfreeze_contracts(origin: OriginFor<T>) -> DispatchResult {
+			T::ContractsPauseOrigin::ensure_origin(origin)?;
+			if <ContractsFrozen<T>>::take() {
+				Self::deposit_event(Event::ContractsUnfrozen);
+			}
+			Ok(())
+		}
+
+		/// Register the metadata of the code stored under `code_hash`, e.g. its ink! ABI.
+		///
+		/// Only the owner of the code can set its metadata. A deposit is reserved for the
+		/// storage it occupies and refunded once the metadata is replaced, cleared by passing an
+		/// empty `metadata`, or removed together with the code.
+		#[pallet::weight(T::WeightInfo::set_code_metadata(metadata.len() as u32))]
+		pub fn set_code_metadata(
+			origin: OriginFor<T>,
+			code_hash: CodeHash<T>,
+			metadata: Vec<u8>,
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			ensure!(
+				metadata.len() as u32 <= T::Schedule::get().limits.metadata_len,
+				<Error<T>>::CodeMetadataTooLong,
+			);
+			<PrefabWasmModule<T>>::set_metadata(&origin, code_hash, metadata)?;
+			Self::deposit_event(Event::CodeMetadataUpdated { code_hash });
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
@@ -573,6 +1198,73 @@ pub mod pallet {
 			/// Previous code hash of the contract.
 			old_code_hash: T::Hash,
 		},
+
+		/// The admin of a contract was changed.
+		ContractAdminUpdated {
+			/// The contract whose admin was changed.
+			contract: T::AccountId,
+			/// The new admin of the contract, if any.
+			new_admin: Option<T::AccountId>,
+		},
+
+		/// A CosmWasm contract emitted an event.
+		///
+		/// As in wasmd the attributes of a response form a `wasm` event while custom events
+		/// have their type prefixed with `wasm-`. The topics of the event are the hashes of
+		/// `contract` and of `ty` so that indexers can filter by contract and event type.
+		CosmwasmEvent {
+			/// The contract that emitted the event.
+			contract: T::AccountId,
+			/// The type of the event.
+			ty: Vec<u8>,
+			/// The key-value attributes of the event, starting with `_contract_address`.
+			attributes: Vec<(Vec<u8>, Vec<u8>)>,
+		},
+
+		/// An IBC channel was opened between two contracts.
+		IbcChannelOpened {
+			/// The contract which initiated the channel.
+			contract: T::AccountId,
+			/// The end of the channel owned by `contract`.
+			channel_id: u64,
+			/// The contract at the other end of the channel.
+			counterparty: T::AccountId,
+			/// The end of the channel owned by `counterparty`.
+			counterparty_channel_id: u64,
+		},
+
+		/// An end of an IBC channel was closed.
+		IbcChannelClosed { contract: T::AccountId, channel_id: u64 },
+
+		/// A contract sent an IBC packet which awaits to be relayed.
+		IbcPacketSent { channel_id: u64, sequence: u64 },
+
+		/// An IBC packet was received and its acknowledgement was passed back to the sender.
+		IbcPacketAcknowledged { channel_id: u64, sequence: u64 },
+
+		/// An IBC packet timed out before it could be received.
+		IbcPacketTimedOut { channel_id: u64, sequence: u64 },
+
+		/// A contract was paused.
+		ContractPaused { contract: T::AccountId },
+
+		/// A contract was unpaused.
+		ContractUnpaused { contract: T::AccountId },
+
+		/// The contracts using a code were paused.
+		CodePaused { code_hash: T::Hash },
+
+		/// The contracts using a code were unpaused.
+		CodeUnpaused { code_hash: T::Hash },
+
+		/// All contract calls were frozen.
+		ContractsFrozen,
+
+		/// Contract calls were unfrozen.
+		ContractsUnfrozen,
+
+		/// The metadata of a code was set or cleared.
+		CodeMetadataUpdated { code_hash: T::Hash },
 	}
 
 	#[pallet::error]
@@ -652,6 +1344,49 @@ pub mod pallet {
 		/// A more detailed error can be found on the node console if debug messages are enabled
 		/// or in the debug buffer which is returned to RPC clients.
 		CodeRejected,
+		/// The CosmWasm contract targets an interface version which is not supported.
+		UnsupportedInterfaceVersion,
+		/// The contract imports a function which is not provided to contracts of its type.
+		UnsupportedImport,
+		/// The CosmWasm contract requires a capability which is not enabled on this chain.
+		UnsupportedCapability,
+		/// The label of a contract exceeds the limit specified in the current schedule.
+		LabelTooLong,
+		/// The salt of a predictable CosmWasm address must be between 1 and 64 bytes long.
+		InvalidSalt,
+		/// The CosmWasm contract returned an error. The error message can be found in the debug
+		/// buffer which is returned to RPC clients.
+		CosmwasmContractError,
+		/// Only CosmWasm contracts can be queried.
+		ContractNotQueryable,
+		/// A contract tried to change state while being queried.
+		StateChangeDenied,
+		/// The origin is not the admin of the contract.
+		NotContractAdmin,
+		/// Only CosmWasm contracts can be migrated.
+		ContractNotMigratable,
+		/// The pallet was built without support for IBC.
+		IbcDisabled,
+		/// Only CosmWasm contracts can take part in IBC.
+		ContractNotIbcEnabled,
+		/// No IBC channel exists with the given id or it is owned by another contract.
+		IbcChannelNotFound,
+		/// Packets can only be sent over open IBC channels.
+		IbcChannelNotOpen,
+		/// Only IBC channels whose closure was requested can be closed.
+		IbcChannelNotClosing,
+		/// No IBC packet awaits to be relayed with the given sequence number.
+		IbcPacketNotFound,
+		/// The packets of an ordered IBC channel must be relayed in the order they were sent.
+		IbcPacketOutOfOrder,
+		/// The called contract or the code it executes was paused.
+		ContractPaused,
+		/// All contract calls are frozen.
+		ContractsFrozen,
+		/// The metadata of a code exceeds the limit specified in the current schedule.
+		CodeMetadataTooLong,
+		/// More assets are transferred than the limit specified in the current schedule allows.
+		TooManyAssets,
 	}
 
 	/// A mapping from an original code hash to the original code, untouched by instrumentation.
@@ -667,6 +1402,16 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type OwnerInfoOf<T: Config> = StorageMap<_, Identity, CodeHash<T>, OwnerInfo<T>>;
 
+	/// The metadata registered by the owner of a code, e.g. its ink! ABI.
+	#[pallet::storage]
+	pub(crate) type CodeMetadata<T: Config> = StorageMap<_, Identity, CodeHash<T>, Vec<u8>>;
+
+	/// A mapping from an original code hash to the sha256 hash of the original code.
+	///
+	/// CosmWasm identifies codes by this checksum, e.g. to derive `instantiate2` addresses.
+	#[pallet::storage]
+	pub(crate) type CodeChecksum<T: Config> = StorageMap<_, Identity, CodeHash<T>, [u8; 32]>;
+
 	/// This is a **monotonic** counter incremented on contract instantiation.
 	///
 	/// This is used in order to generate unique trie ids for contracts.
@@ -692,6 +1437,20 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type Nonce<T: Config> = StorageValue<_, u64, ValueQuery>;
 
+	/// The code id which is assigned to the next uploaded code.
+	#[pallet::storage]
+	pub(crate) type NextCodeId<T: Config> = StorageValue<_, CodeId, ValueQuery>;
+
+	/// A mapping from a code id to the hash of the code it was assigned to.
+	///
+	/// CosmWasm contracts refer to code by these ids instead of code hashes.
+	#[pallet::storage]
+	pub(crate) type CodeIdToHash<T: Config> = StorageMap<_, Twox64Concat, CodeId, CodeHash<T>>;
+
+	/// A mapping from the hash of a code to the code id it was assigned.
+	#[pallet::storage]
+	pub(crate) type CodeHashToId<T: Config> = StorageMap<_, Identity, CodeHash<T>, CodeId>;
+
 	/// The code associated with a given account.
 	///
 	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
@@ -699,12 +1458,47 @@ pub mod pallet {
 	pub(crate) type ContractInfoOf<T: Config> =
 		StorageMap<_, Twox64Concat, T::AccountId, ContractInfo<T>>;
 
+	/// The contracts which were given a non empty label on instantiation, by label.
+	///
+	/// Labels are not unique: Multiple contracts can share the same label.
+	#[pallet::storage]
+	pub(crate) type ContractsByLabel<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Twox64Concat, T::AccountId, ()>;
+
+	/// The contracts which can not be called until they are unpaused.
+	#[pallet::storage]
+	pub(crate) type PausedContracts<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, ()>;
+
+	/// The codes which can not be executed until they are unpaused.
+	#[pallet::storage]
+	pub(crate) type PausedCodes<T: Config> = StorageMap<_, Identity, CodeHash<T>, ()>;
+
+	/// Whether all contract calls are rejected.
+	#[pallet::storage]
+	pub(crate) type ContractsFrozen<T: Config> = StorageValue<_, bool, ValueQuery>;
+
 	/// Evicted contracts that await child trie deletion.
 	///
 	/// Child trie deletion is a heavy operation depending on the amount of storage items
 	/// stored in said trie. Therefore this operation is performed lazily in `on_initialize`.
 	#[pallet::storage]
 	pub(crate) type DeletionQueue<T: Config> = StorageValue<_, Vec<DeletedContract>, ValueQuery>;
+
+	/// The id which is assigned to the next opened IBC channel end.
+	#[cfg(feature = "stargate")]
+	#[pallet::storage]
+	pub(crate) type NextIbcChannelId<T: Config> = StorageValue<_, ChannelId, ValueQuery>;
+
+	/// The ends of the IBC channels opened between contracts.
+	#[cfg(feature = "stargate")]
+	#[pallet::storage]
+	pub(crate) type IbcChannels<T: Config> = StorageMap<_, Twox64Concat, ChannelId, ChannelEnd<T>>;
+
+	/// The IBC packets awaiting to be relayed, by channel end and sequence number.
+	#[cfg(feature = "stargate")]
+	#[pallet::storage]
+	pub(crate) type IbcPackets<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, ChannelId, Twox64Concat, u64, Packet<T>>;
 }
 
 /// Return type of the private [`Pallet::internal_call`] function.
@@ -753,10 +1547,12 @@ where
 			origin,
 			dest,
 			value,
+			Vec::new(),
 			gas_limit,
 			storage_deposit_limit,
 			data,
 			debug_message.as_mut(),
+			None,
 		);
 		ContractExecResult {
 			result: output.result.map_err(|r| r.error),
@@ -767,6 +1563,177 @@ where
 		}
 	}
 
+	/// Perform a call to a specified contract and record the frames it executes.
+	///
+	/// This is the same as [`Self::bare_call`] with `debug` set to `true` but additionally
+	/// returns the tree of frames executed by the call. Each
+	/// [`CallTrace`](pallet_contracts_primitives::CallTrace) contains the contract, entry point,
+	/// input, output, consumed gas and storage deposit of a frame.
+	///
+	/// # Note
+	///
+	/// This function should only ever be called when executing as an RPC because it copies
+	/// the input and output of every frame.
+	pub fn bare_trace_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Weight,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		data: Vec<u8>,
+	) -> ContractTraceResult<T::AccountId, BalanceOf<T>> {
+		let mut debug_message = Vec::new();
+		let mut tracer = CallTracer::default();
+		let output = Self::internal_call(
+			origin,
+			dest,
+			value,
+			Vec::new(),
+			gas_limit,
+			storage_deposit_limit,
+			data,
+			Some(&mut debug_message),
+			Some(&mut tracer),
+		);
+		ContractTraceResult {
+			call: ContractExecResult {
+				result: output.result.map_err(|r| r.error),
+				gas_consumed: output.gas_meter.gas_consumed(),
+				gas_required: output.gas_meter.gas_required(),
+				storage_deposit: output.storage_deposit,
+				debug_message,
+			},
+			trace: tracer.into_trace(),
+		}
+	}
+
+	/// Estimate the gas limit and storage deposit limit required by a call to a contract.
+	///
+	/// The call is executed with `gas_limit` and then repeatedly with smaller gas limits,
+	/// bisecting towards the smallest gas limit with which it still succeeds. The gas required
+	/// by the first execution is tried first because it is the answer in most cases. Every
+	/// execution is rolled back so that they all start from the same state.
+	///
+	/// A call succeeds if it neither fails nor reverts. No estimation is performed if the
+	/// call does not succeed with `gas_limit`.
+	///
+	/// # Note
+	///
+	/// This function should only ever be called when executing as an RPC because it executes
+	/// the call many times and with `debug` set to `true`.
+	pub fn bare_estimate_call(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Weight,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		data: Vec<u8>,
+	) -> ContractEstimateResult<BalanceOf<T>> {
+		let execute = |gas_limit| {
+			with_transaction(|| -> TransactionOutcome<Result<_, DispatchError>> {
+				TransactionOutcome::Rollback(Ok(Self::bare_call(
+					origin.clone(),
+					dest.clone(),
+					value,
+					gas_limit,
+					storage_deposit_limit,
+					data.clone(),
+					true,
+				)))
+			})
+			.unwrap_or_else(|error| ContractExecResult {
+				result: Err(error),
+				gas_consumed: Zero::zero(),
+				gas_required: Zero::zero(),
+				storage_deposit: Default::default(),
+				debug_message: Vec::new(),
+			})
+		};
+		let succeeded = |call: &ContractExecResult<BalanceOf<T>>| {
+			matches!(&call.result, Ok(retval) if !retval.did_revert())
+		};
+
+		let mut call = execute(gas_limit);
+		let mut storage_deposit = call.storage_deposit.clone();
+		if !succeeded(&call) {
+			return ContractEstimateResult { gas_required: call.gas_required, storage_deposit, call }
+		}
+
+		// The call succeeds with `hi` and `call` is its result. It is assumed to fail with `lo`.
+		let (mut lo, mut hi) = (0, gas_limit);
+		// A call can succeed with less gas than it required if it was refunded some.
+		let mut hints = [call.gas_required, call.gas_required.saturating_sub(1)].into_iter();
+		loop {
+			let limit = match hints.next() {
+				Some(limit) if lo < limit && limit < hi => limit,
+				_ if hi - lo > 1 => lo + (hi - lo) / 2,
+				_ => break,
+			};
+			let attempt = execute(limit);
+			// The storage deposit can differ between executions because a call which runs out
+			// of gas does not reach the refunds of its later frames.
+			if let StorageDeposit::Charge(amount) =
+				attempt.storage_deposit.saturating_sub(&storage_deposit)
+			{
+				if !amount.is_zero() {
+					storage_deposit = attempt.storage_deposit.clone();
+				}
+			}
+			if succeeded(&attempt) {
+				hi = limit;
+				call = attempt;
+			} else {
+				lo = limit;
+			}
+		}
+
+		ContractEstimateResult { gas_required: hi, storage_deposit, call }
+	}
+
+	/// Query a CosmWasm contract.
+	///
+	/// The `query` entry point of the contract at `dest` is called with `data` as message.
+	/// The contract is not allowed to change any state and all changes are reverted anyways.
+	///
+	/// # Note
+	///
+	/// `debug` should only ever be set to `true` when executing as an RPC because
+	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
+	/// If set to `true` it returns additional human readable debugging information.
+	///
+	/// It returns the query result and the amount of used weight.
+	pub fn bare_query(
+		dest: T::AccountId,
+		gas_limit: Weight,
+		data: Vec<u8>,
+		debug: bool,
+	) -> ContractExecResult<BalanceOf<T>> {
+		let mut debug_message = if debug { Some(Vec::new()) } else { None };
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let result = with_transaction(|| -> TransactionOutcome<Result<_, DispatchError>> {
+			let try_query = || {
+				let mut storage_meter = StorageMeter::new(&dest, Some(Zero::zero()), Zero::zero())?;
+				let schedule = T::Schedule::get();
+				ExecStack::<T, PrefabWasmModule<T>>::run_query(
+					dest,
+					&mut gas_meter,
+					&mut storage_meter,
+					&schedule,
+					data,
+					debug_message.as_mut(),
+				)
+			};
+			TransactionOutcome::Rollback(Ok(try_query()))
+		});
+		ContractExecResult {
+			result: result.map_err(Into::into).and_then(|r| r).map_err(|r| r.error),
+			gas_consumed: gas_meter.gas_consumed(),
+			gas_required: gas_meter.gas_required(),
+			storage_deposit: Default::default(),
+			debug_message: debug_message.unwrap_or_default(),
+		}
+	}
+
 	/// Instantiate a new contract.
 	///
 	/// This function is similar to [`Self::instantiate`], but doesn't perform any address lookups
@@ -793,11 +1760,15 @@ where
 		let output = Self::internal_instantiate(
 			origin,
 			value,
+			Vec::new(),
 			gas_limit,
 			storage_deposit_limit,
 			code,
 			data,
 			salt,
+			// The admin and the label have no influence on the instantiation itself.
+			None,
+			Vec::new(),
 			debug_message.as_mut(),
 		);
 		ContractInstantiateResult {
@@ -822,8 +1793,8 @@ where
 		storage_deposit_limit: Option<BalanceOf<T>>,
 	) -> CodeUploadResult<CodeHash<T>, BalanceOf<T>> {
 		let schedule = T::Schedule::get();
-		let module = PrefabWasmModule::from_code(code, &schedule, origin)
-			.map_err(|_| <Error<T>>::CodeRejected)?;
+		let module =
+			PrefabWasmModule::from_code(code, &schedule, origin).map_err(|(err, _)| err)?;
 		let deposit = module.open_deposit();
 		if let Some(storage_deposit_limit) = storage_deposit_limit {
 			ensure!(storage_deposit_limit >= deposit, <Error<T>>::StorageDepositLimitExhausted);
@@ -842,6 +1813,59 @@ where
 		Ok(maybe_value)
 	}
 
+	/// The code hash of the contract at `address`.
+	pub fn code_hash(address: &T::AccountId) -> Option<CodeHash<T>> {
+		ContractInfoOf::<T>::get(address).map(|contract_info| contract_info.code_hash)
+	}
+
+	/// The metadata registered for the code stored under `code_hash`.
+	pub fn code_metadata(code_hash: &CodeHash<T>) -> Option<Vec<u8>> {
+		<CodeMetadata<T>>::get(code_hash)
+	}
+
+	/// The sha256 checksum of the code stored under `code_hash`.
+	pub fn code_checksum(code_hash: &CodeHash<T>) -> Option<[u8; 32]> {
+		<CodeChecksum<T>>::get(code_hash)
+	}
+
+	/// The contracts which were given `label` on instantiation.
+	///
+	/// Contracts without a label can't be looked up.
+	pub fn contracts_by_label(label: &[u8]) -> Vec<T::AccountId> {
+		<ContractsByLabel<T>>::iter_key_prefix(label).collect()
+	}
+
+	/// The events which were emitted by contracts through `seal_deposit_event` in the
+	/// current block.
+	///
+	/// # Note
+	///
+	/// This function should only ever be called when executing as an RPC because it reads
+	/// all events of the block.
+	pub fn contract_events() -> Vec<ContractEvent<T::AccountId, T::Hash>>
+	where
+		<T as Config>::Event: TryInto<Event<T>>,
+	{
+		<System<T>>::read_events_no_consensus()
+			.into_iter()
+			.filter_map(|record| {
+				let EventRecord { phase, event, topics } = *record;
+				match <T as Config>::Event::from(event).try_into() {
+					Ok(Event::ContractEmitted { contract, data }) => Some(ContractEvent {
+						extrinsic_index: match phase {
+							Phase::ApplyExtrinsic(index) => Some(index),
+							_ => None,
+						},
+						contract,
+						data: data.into(),
+						topics,
+					}),
+					_ => None,
+				}
+			})
+			.collect()
+	}
+
 	/// Determine the address of a contract.
 	///
 	/// This is the address generation function used by contract instantiation. See
@@ -854,6 +1878,28 @@ where
 		T::AddressGenerator::generate_address(deploying_address, code_hash, salt)
 	}
 
+	/// Determine the address of a contract instantiated through `WasmMsg::Instantiate2`.
+	///
+	/// This matches the `instantiate2_address` function of CosmWasm, using the sha256 checksum
+	/// of the code and the canonical form of `deploying_address` as creator. The address is
+	/// padded with zeroes or truncated to fit the account id.
+	pub fn instantiate2_address(
+		deploying_address: &T::AccountId,
+		code_hash: &CodeHash<T>,
+		salt: &[u8],
+	) -> Result<T::AccountId, DispatchError> {
+		let checksum = <CodeChecksum<T>>::get(code_hash).ok_or(<Error<T>>::CodeNotFound)?;
+		let creator = CanonicalAddr::from(deploying_address.encode());
+		let address =
+			instantiate2_address(&checksum, &creator, salt).map_err(|error| match error {
+				Instantiate2AddressError::InvalidSaltLength => Error::<T>::InvalidSalt,
+				Instantiate2AddressError::InvalidChecksumLength =>
+					unreachable!("The checksum is a sha256 hash; qed"),
+			})?;
+		Ok(T::AccountId::decode(&mut TrailingZeroInput::new(&address))
+			.expect("infinite length input; no invalid inputs for type; qed"))
+	}
+
 	/// Store code for benchmarks which does not check nor instrument the code.
 	#[cfg(feature = "runtime-benchmarks")]
 	fn store_code_raw(
@@ -881,10 +1927,12 @@ where
 		origin: T::AccountId,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		gas_limit: Weight,
 		storage_deposit_limit: Option<BalanceOf<T>>,
 		data: Vec<u8>,
 		debug_message: Option<&mut Vec<u8>>,
+		tracer: Option<&mut CallTracer<T>>,
 	) -> InternalCallOutput<T> {
 		let mut gas_meter = GasMeter::new(gas_limit);
 		let mut storage_meter = match StorageMeter::new(&origin, storage_deposit_limit, value) {
@@ -904,23 +1952,82 @@ where
 			&mut storage_meter,
 			&schedule,
 			value,
+			assets,
 			data,
 			debug_message,
+			tracer,
 		);
 		InternalCallOutput { result, gas_meter, storage_deposit: storage_meter.into_deposit() }
 	}
 
+	/// Internal function that does the actual migration.
+	///
+	/// Called by dispatchables and public functions.
+	fn internal_migrate(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		gas_limit: Weight,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		code_id: CodeId,
+		data: Vec<u8>,
+		debug_message: Option<&mut Vec<u8>>,
+	) -> InternalCallOutput<T> {
+		let mut storage_deposit = Default::default();
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let try_exec = || {
+			let contract = <ContractInfoOf<T>>::get(&dest).ok_or(<Error<T>>::ContractNotFound)?;
+			ensure!(contract.admin.as_ref() == Some(&origin), <Error<T>>::NotContractAdmin);
+			let code_hash = <CodeIdToHash<T>>::get(code_id).ok_or(<Error<T>>::CodeNotFound)?;
+			let schedule = T::Schedule::get();
+			let executable = PrefabWasmModule::from_storage(code_hash, &schedule, &mut gas_meter)?;
+			let mut storage_meter =
+				StorageMeter::new(&origin, storage_deposit_limit, Zero::zero())?;
+			let result = ExecStack::<T, PrefabWasmModule<T>>::run_migrate(
+				origin,
+				dest,
+				executable,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				data,
+				debug_message,
+			);
+			storage_deposit = storage_meter.into_deposit();
+			result
+		};
+		InternalCallOutput { result: try_exec(), gas_meter, storage_deposit }
+	}
+
+	/// Replace the admin of the contract `dest` on behalf of its current admin `origin`.
+	fn set_admin(
+		origin: &T::AccountId,
+		dest: T::AccountId,
+		new_admin: Option<T::AccountId>,
+	) -> DispatchResult {
+		<ContractInfoOf<T>>::try_mutate(&dest, |contract| {
+			let contract = contract.as_mut().ok_or(<Error<T>>::ContractNotFound)?;
+			ensure!(contract.admin.as_ref() == Some(origin), <Error<T>>::NotContractAdmin);
+			contract.admin = new_admin.clone();
+			Ok::<_, DispatchError>(())
+		})?;
+		Self::deposit_event(Event::ContractAdminUpdated { contract: dest, new_admin });
+		Ok(())
+	}
+
 	/// Internal function that does the actual instantiation.
 	///
 	/// Called by dispatchables and public functions.
 	fn internal_instantiate(
 		origin: T::AccountId,
 		value: BalanceOf<T>,
+		assets: AssetsOf<T>,
 		gas_limit: Weight,
 		storage_deposit_limit: Option<BalanceOf<T>>,
 		code: Code<CodeHash<T>>,
 		data: Vec<u8>,
 		salt: Vec<u8>,
+		admin: Option<T::AccountId>,
+		label: Vec<u8>,
 		mut debug_message: Option<&mut Vec<u8>>,
 	) -> InternalInstantiateOutput<T> {
 		let mut storage_deposit = Default::default();
@@ -934,9 +2041,9 @@ where
 						<Error<T>>::CodeTooLarge
 					);
 					let executable = PrefabWasmModule::from_code(binary, &schedule, origin.clone())
-						.map_err(|msg| {
+						.map_err(|(err, msg)| {
 							debug_message.as_mut().map(|buffer| buffer.extend(msg.as_bytes()));
-							<Error<T>>::CodeRejected
+							err
 						})?;
 					ensure!(
 						executable.code_len() <= schedule.limits.code_len,
@@ -952,6 +2059,13 @@ where
 					Default::default(),
 					PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter)?,
 				),
+				Code::Id(code_id) => {
+					let hash = <CodeIdToHash<T>>::get(code_id).ok_or(<Error<T>>::CodeNotFound)?;
+					(
+						Default::default(),
+						PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter)?,
+					)
+				},
 			};
 			let mut storage_meter = StorageMeter::new(
 				&origin,
@@ -965,8 +2079,11 @@ where
 				&mut storage_meter,
 				&schedule,
 				value,
+				assets,
 				data,
 				&salt,
+				admin,
+				label,
 				debug_message,
 			);
 			storage_deposit = storage_meter
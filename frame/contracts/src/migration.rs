@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{BalanceOf, CodeHash, Config, Pallet, TrieId, Weight};
+use crate::{BalanceOf, CodeHash, CodeId, Config, Pallet, TrieId, Weight};
 use codec::{Decode, Encode};
 use frame_support::{
 	codec, generate_storage_alias,
@@ -53,6 +53,21 @@ pub fn migrate<T: Config>() -> Weight {
 		StorageVersion::new(7).put::<Pallet<T>>();
 	}
 
+	if version < 8 {
+		weight = weight.saturating_add(v8::migrate::<T>());
+		StorageVersion::new(8).put::<Pallet<T>>();
+	}
+
+	if version < 9 {
+		weight = weight.saturating_add(v9::migrate::<T>());
+		StorageVersion::new(9).put::<Pallet<T>>();
+	}
+
+	if version < 10 {
+		weight = weight.saturating_add(v10::migrate::<T>());
+		StorageVersion::new(10).put::<Pallet<T>>();
+	}
+
 	weight
 }
 
@@ -273,3 +288,152 @@ mod v7 {
 		T::DbWeight::get().reads_writes(1, 2)
 	}
 }
+
+/// V8: Contracts have an optional admin and every code is assigned a `CodeId`.
+mod v8 {
+	use super::*;
+
+	use v6::OwnerInfo;
+
+	#[derive(Encode, Decode)]
+	struct OldContractInfo<T: Config> {
+		trie_id: TrieId,
+		code_hash: CodeHash<T>,
+		storage_deposit: BalanceOf<T>,
+	}
+
+	#[derive(Encode, Decode)]
+	struct ContractInfo<T: Config> {
+		trie_id: TrieId,
+		code_hash: CodeHash<T>,
+		storage_deposit: BalanceOf<T>,
+		admin: Option<T::AccountId>,
+	}
+
+	generate_storage_alias!(
+		Contracts,
+		ContractInfoOf<T: Config> => Map<(Twox64Concat, T::AccountId), ContractInfo<T>>
+	);
+
+	generate_storage_alias!(
+		Contracts,
+		OwnerInfoOf<T: Config> => Map<(Identity, CodeHash<T>), OwnerInfo<T>>
+	);
+
+	generate_storage_alias!(
+		Contracts,
+		NextCodeId => Value<CodeId, ValueQuery>
+	);
+
+	generate_storage_alias!(
+		Contracts,
+		CodeIdToHash<T: Config> => Map<(Twox64Concat, CodeId), CodeHash<T>>
+	);
+
+	generate_storage_alias!(
+		Contracts,
+		CodeHashToId<T: Config> => Map<(Identity, CodeHash<T>), CodeId>
+	);
+
+	pub fn migrate<T: Config>() -> Weight {
+		let mut weight: Weight = 0;
+
+		<ContractInfoOf<T>>::translate(|_key, old: OldContractInfo<T>| {
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			Some(ContractInfo::<T> {
+				trie_id: old.trie_id,
+				code_hash: old.code_hash,
+				storage_deposit: old.storage_deposit,
+				admin: None,
+			})
+		});
+
+		// Codes which were uploaded before ids were assigned on upload don't have one yet.
+		let mut next_code_id = NextCodeId::get();
+		for code_hash in <OwnerInfoOf<T>>::iter_keys() {
+			weight = weight.saturating_add(T::DbWeight::get().reads(2));
+			if <CodeHashToId<T>>::contains_key(&code_hash) {
+				continue
+			}
+			<CodeIdToHash<T>>::insert(next_code_id, code_hash);
+			<CodeHashToId<T>>::insert(code_hash, next_code_id);
+			next_code_id = next_code_id.wrapping_add(1);
+			weight = weight.saturating_add(T::DbWeight::get().writes(2));
+		}
+		NextCodeId::put(next_code_id);
+
+		weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+	}
+}
+
+/// V9: Contracts have a label.
+mod v9 {
+	use super::*;
+
+	#[derive(Encode, Decode)]
+	struct OldContractInfo<T: Config> {
+		trie_id: TrieId,
+		code_hash: CodeHash<T>,
+		storage_deposit: BalanceOf<T>,
+		admin: Option<T::AccountId>,
+	}
+
+	#[derive(Encode, Decode)]
+	struct ContractInfo<T: Config> {
+		trie_id: TrieId,
+		code_hash: CodeHash<T>,
+		storage_deposit: BalanceOf<T>,
+		admin: Option<T::AccountId>,
+		label: Vec<u8>,
+	}
+
+	generate_storage_alias!(
+		Contracts,
+		ContractInfoOf<T: Config> => Map<(Twox64Concat, T::AccountId), ContractInfo<T>>
+	);
+
+	pub fn migrate<T: Config>() -> Weight {
+		let mut weight: Weight = 0;
+
+		// Contracts instantiated before labels were introduced get an empty label which is
+		// not indexed in `ContractsByLabel`.
+		<ContractInfoOf<T>>::translate(|_key, old: OldContractInfo<T>| {
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			Some(ContractInfo::<T> {
+				trie_id: old.trie_id,
+				code_hash: old.code_hash,
+				storage_deposit: old.storage_deposit,
+				admin: old.admin,
+				label: Vec::new(),
+			})
+		});
+
+		weight
+	}
+}
+
+/// V10: The sha256 checksum of each code is stored in `CodeChecksum`.
+mod v10 {
+	use super::*;
+
+	generate_storage_alias!(
+		Contracts,
+		PristineCode<T: Config> => Map<(Identity, CodeHash<T>), Vec<u8>>
+	);
+
+	generate_storage_alias!(
+		Contracts,
+		CodeChecksum<T: Config> => Map<(Identity, CodeHash<T>), [u8; 32]>
+	);
+
+	pub fn migrate<T: Config>() -> Weight {
+		let mut weight: Weight = 0;
+
+		for (code_hash, code) in <PristineCode<T>>::iter() {
+			weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			<CodeChecksum<T>>::insert(code_hash, sp_io::hashing::sha2_256(&code));
+		}
+
+		weight
+	}
+}
@@ -152,6 +152,15 @@ pub struct Limits {
 	/// version of the code. Therefore `instantiate_with_code` can fail even when supplying
 	/// a wasm binary below this maximum size.
 	pub code_len: u32,
+
+	/// The maximum length of a contract label in bytes.
+	pub label_len: u32,
+
+	/// The maximum length of the metadata registered for a code in bytes.
+	pub metadata_len: u32,
+
+	/// The maximum number of assets which can be transferred by a single call.
+	pub transferred_assets: u32,
 }
 
 impl Limits {
@@ -421,6 +430,63 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_ecdsa_to_eth_address`.
 	pub ecdsa_to_eth_address: Weight,
 
+	/// Weight of calling `db_read`.
+	pub db_read: Weight,
+
+	/// Weight per byte of a value read by `db_read`.
+	pub db_read_per_byte: Weight,
+
+	/// Weight of calling `db_write`.
+	pub db_write: Weight,
+
+	/// Weight per byte of the value written by `db_write`.
+	pub db_write_per_new_byte: Weight,
+
+	/// Weight per byte of the value overwritten by `db_write`.
+	pub db_write_per_old_byte: Weight,
+
+	/// Weight of calling `db_remove`.
+	pub db_remove: Weight,
+
+	/// Weight per byte of the value removed by `db_remove`.
+	pub db_remove_per_byte: Weight,
+
+	/// Weight of calling `db_scan`.
+	pub db_scan: Weight,
+
+	/// Weight of visiting a key through `db_next` or a descending `db_scan`.
+	pub db_next: Weight,
+
+	/// Weight per byte of a key and value visited through `db_next`.
+	pub db_next_per_byte: Weight,
+
+	/// Weight of calling `addr_validate`.
+	pub addr_validate: Weight,
+
+	/// Weight of calling `addr_canonicalize`.
+	pub addr_canonicalize: Weight,
+
+	/// Weight of calling `addr_humanize`.
+	pub addr_humanize: Weight,
+
+	/// Weight of calling `secp256k1_verify`.
+	pub secp256k1_verify: Weight,
+
+	/// Weight of calling `secp256k1_recover_pubkey`.
+	pub secp256k1_recover_pubkey: Weight,
+
+	/// Weight of calling `ed25519_verify`.
+	pub ed25519_verify: Weight,
+
+	/// Weight per message byte of calling `ed25519_verify`.
+	pub ed25519_verify_per_byte: Weight,
+
+	/// Weight of calling `ed25519_batch_verify`, excluding the verification of each signature.
+	pub ed25519_batch_verify: Weight,
+
+	/// Weight of calling `query_chain`.
+	pub query_chain: Weight,
+
 	/// The type parameter is used in the default implementation.
 	#[codec(skip)]
 	pub _phantom: PhantomData<T>,
@@ -523,6 +589,9 @@ impl Default for Limits {
 			call_depth: 32,
 			payload_len: 16 * 1024,
 			code_len: 128 * 1024,
+			label_len: 128,
+			metadata_len: 256 * 1024,
+			transferred_assets: 16,
 		}
 	}
 }
@@ -657,6 +726,25 @@ impl<T: Config> Default for HostFnWeights<T> {
 			hash_blake2_128_per_byte: cost_byte_batched!(seal_hash_blake2_128_per_kb),
 			ecdsa_recover: cost_batched!(seal_ecdsa_recover),
 			ecdsa_to_eth_address: cost_batched!(seal_ecdsa_to_eth_address),
+			db_read: cost_batched!(db_read),
+			db_read_per_byte: cost_byte_batched!(db_read_per_kb),
+			db_write: cost_batched!(db_write),
+			db_write_per_new_byte: cost_byte_batched!(db_write_per_new_kb),
+			db_write_per_old_byte: cost_byte_batched!(db_write_per_old_kb),
+			db_remove: cost_batched!(db_remove),
+			db_remove_per_byte: cost_byte_batched!(db_remove_per_kb),
+			db_scan: cost_batched!(db_scan),
+			db_next: cost_batched!(db_next),
+			db_next_per_byte: cost_byte_batched!(db_next_per_kb),
+			addr_validate: cost_batched!(addr_validate),
+			addr_canonicalize: cost_batched!(addr_canonicalize),
+			addr_humanize: cost_batched!(addr_humanize),
+			secp256k1_verify: cost_batched!(secp256k1_verify),
+			secp256k1_recover_pubkey: cost_batched!(secp256k1_recover_pubkey),
+			ed25519_verify: cost_batched!(ed25519_verify),
+			ed25519_verify_per_byte: cost_byte_batched!(ed25519_verify_per_kb),
+			ed25519_batch_verify: cost_batched!(ed25519_batch_verify),
+			query_chain: cost_batched!(query_chain),
 			_phantom: PhantomData,
 		}
 	}
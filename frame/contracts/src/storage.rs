@@ -40,21 +40,25 @@ use sp_runtime::{
 };
 use sp_std::{marker::PhantomData, prelude::*};
 
-pub type ContractInfo<T> = RawContractInfo<CodeHash<T>, BalanceOf<T>>;
+pub type ContractInfo<T> = RawContractInfo<CodeHash<T>, BalanceOf<T>, AccountIdOf<T>>;
 
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
-pub struct RawContractInfo<CodeHash, Balance> {
+pub struct RawContractInfo<CodeHash, Balance, AccountId> {
 	/// Unique ID for the subtree encoded as a bytes vector.
 	pub trie_id: TrieId,
 	/// The code associated with a given account.
 	pub code_hash: CodeHash,
 	/// The amount of balance that is currently deposited to pay for consumed storage.
 	pub storage_deposit: Balance,
+	/// The account which is allowed to migrate the contract to another code.
+	pub admin: Option<AccountId>,
+	/// A human readable label given to the contract on instantiation.
+	pub label: Vec<u8>,
 }
 
-impl<CodeHash, Balance> RawContractInfo<CodeHash, Balance> {
+impl<CodeHash, Balance, AccountId> RawContractInfo<CodeHash, Balance, AccountId> {
 	/// Associated child trie unique id is built from the hash part of the trie id.
 	#[cfg(test)]
 	pub fn child_trie_info(&self) -> ChildInfo {
@@ -126,7 +130,22 @@ where
 	/// The read is performed from the `trie_id` only. The `address` is not necessary. If the
 	/// contract doesn't store under the given `key` `None` is returned.
 	pub fn read(trie_id: &TrieId, key: &StorageKey) -> Option<Vec<u8>> {
-		child::get_raw(&child_trie_info(trie_id), &blake2_256(key))
+		Self::read_raw(trie_id, &blake2_256(key))
+	}
+
+	/// Reads a storage kv pair of a contract without hashing the `key`.
+	///
+	/// This is used by CosmWasm contracts which address their storage by arbitrary byte keys
+	/// and rely on them being stored in order.
+	pub fn read_raw(trie_id: &TrieId, key: &[u8]) -> Option<Vec<u8>> {
+		child::get_raw(&child_trie_info(trie_id), key)
+	}
+
+	/// Returns the unhashed key following `key` in a contract's kv storage.
+	///
+	/// Keys are ordered lexicographically. Returns `None` if there is no key after `key`.
+	pub fn next_key_raw(trie_id: &TrieId, key: &[u8]) -> Option<Vec<u8>> {
+		sp_io::default_child_storage::next_key(child_trie_info(trie_id).storage_key(), key)
 	}
 
 	/// Returns `Some(len)` (in bytes) if a storage item exists at `key`.
@@ -151,13 +170,25 @@ where
 		storage_meter: Option<&mut meter::NestedMeter<T>>,
 		take: bool,
 	) -> Result<WriteOutcome, DispatchError> {
-		let hashed_key = blake2_256(key);
+		Self::write_raw(trie_id, &blake2_256(key), new_value, storage_meter, take)
+	}
+
+	/// Update a storage entry into a contract's kv storage without hashing the `key`.
+	///
+	/// See [`Self::write`] and [`Self::read_raw`].
+	pub fn write_raw(
+		trie_id: &TrieId,
+		key: &[u8],
+		new_value: Option<Vec<u8>>,
+		storage_meter: Option<&mut meter::NestedMeter<T>>,
+		take: bool,
+	) -> Result<WriteOutcome, DispatchError> {
 		let child_trie_info = &child_trie_info(trie_id);
 		let (old_len, old_value) = if take {
-			let val = child::get_raw(&child_trie_info, &hashed_key);
+			let val = child::get_raw(&child_trie_info, key);
 			(val.as_ref().map(|v| v.len() as u32), val)
 		} else {
-			(child::len(&child_trie_info, &hashed_key), None)
+			(child::len(&child_trie_info, key), None)
 		};
 
 		if let Some(storage_meter) = storage_meter {
@@ -183,8 +214,8 @@ where
 		}
 
 		match &new_value {
-			Some(new_value) => child::put_raw(&child_trie_info, &hashed_key, new_value),
-			None => child::kill(&child_trie_info, &hashed_key),
+			Some(new_value) => child::put_raw(&child_trie_info, key, new_value),
+			None => child::kill(&child_trie_info, key),
 		}
 
 		Ok(match (old_len, old_value) {
@@ -194,21 +225,28 @@ where
 		})
 	}
 
-	/// Creates a new contract descriptor in the storage with the given code hash at the given
-	/// address.
+	/// Creates a new contract descriptor in the storage with the given code hash and admin at
+	/// the given address.
 	///
 	/// Returns `Err` if there is already a contract at the given address.
 	pub fn new_contract(
 		account: &AccountIdOf<T>,
 		trie_id: TrieId,
 		ch: CodeHash<T>,
+		admin: Option<AccountIdOf<T>>,
+		label: Vec<u8>,
 	) -> Result<ContractInfo<T>, DispatchError> {
 		if <ContractInfoOf<T>>::contains_key(account) {
 			return Err(Error::<T>::DuplicateContract.into())
 		}
 
-		let contract =
-			ContractInfo::<T> { code_hash: ch, trie_id, storage_deposit: <BalanceOf<T>>::zero() };
+		let contract = ContractInfo::<T> {
+			code_hash: ch,
+			trie_id,
+			storage_deposit: <BalanceOf<T>>::zero(),
+			admin,
+			label,
+		};
 
 		Ok(contract)
 	}
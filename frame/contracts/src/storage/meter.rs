@@ -234,6 +234,11 @@ where
 		}
 	}
 
+	/// The amount of balance that was used in this meter and all of its already absorbed children.
+	pub fn total_deposit(&self) -> DepositOf<T> {
+		self.total_deposit.clone()
+	}
+
 	/// The amount of balance that is still available from the original `limit`.
 	fn available(&self) -> BalanceOf<T> {
 		self.total_deposit.available(&self.limit)
@@ -524,6 +529,8 @@ mod tests {
 			trie_id: <Storage<Test>>::generate_trie_id(&ALICE, 42),
 			code_hash: <Test as frame_system::Config>::Hashing::hash(b"42"),
 			storage_deposit: deposit,
+			admin: None,
+			label: vec![],
 		}
 	}
 
@@ -22,16 +22,25 @@ use crate::{
 	},
 	exec::Frame,
 	storage::Storage,
-	wasm::{PrefabWasmModule, ReturnCode as RuntimeReturnCode},
+	wasm::{
+		cosmwasm::{
+			instantiate2_address, BalanceResponse, BankMsg, BankQuery, Binary, CanonicalAddr,
+			CodeInfoResponse, Coin, ContractInfoResponse, QueryRequest, Reply, SubMsg,
+			SubMsgResponse, SubMsgResult, WasmMsg, WasmQuery,
+		},
+		from_slice, to_vec, PrefabWasmModule, ReturnCode as RuntimeReturnCode,
+	},
 	weights::WeightInfo,
-	BalanceOf, Code, CodeStorage, Config, ContractInfoOf, DefaultAddressGenerator, Error, Pallet,
-	Schedule,
+	AddressCodec, BalanceOf, Bech32AddressCodec, Code, CodeHashToId, CodeStorage, Config,
+	ContractInfoOf, DefaultAddressGenerator, DenomResolver, Error, FixedGasRatio, GasConverter,
+	Pallet, PrefixedDenomResolver, Schedule, Ss58AddressCodec,
 };
 use assert_matches::assert_matches;
+use bech32::ToBase32;
 use codec::Encode;
 use frame_support::{
 	assert_err, assert_err_ignore_postinfo, assert_noop, assert_ok,
-	dispatch::DispatchErrorWithPostInfo,
+	dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo},
 	parameter_types,
 	storage::child,
 	traits::{
@@ -40,14 +49,16 @@ use frame_support::{
 	weights::{constants::WEIGHT_PER_SECOND, DispatchClass, PostDispatchInfo, Weight},
 };
 use frame_system::{self as system, EventRecord, Phase};
+use pallet_contracts_primitives::{ContractEvent, EntryPoint, StorageDeposit};
 use pretty_assertions::assert_eq;
+use serde::de::DeserializeOwned;
 use sp_core::Bytes;
-use sp_io::hashing::blake2_256;
+use sp_io::hashing::{blake2_256, sha2_256};
 use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
 	testing::{Header, H256},
 	traits::{BlakeTwo256, Convert, Hash, IdentityLookup},
-	AccountId32,
+	AccountId32, DispatchError,
 };
 use std::{cell::RefCell, sync::Arc};
 
@@ -64,6 +75,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Randomness: pallet_randomness_collective_flip::{Pallet, Storage},
 		Utility: pallet_utility::{Pallet, Call, Storage, Event},
@@ -84,7 +96,8 @@ pub mod test_utils {
 		});
 		let trie_id = Storage::<Test>::generate_trie_id(address, nonce);
 		set_balance(address, <Test as Config>::Currency::minimum_balance() * 10);
-		let contract = Storage::<Test>::new_contract(&address, trie_id, code_hash).unwrap();
+		let contract =
+			Storage::<Test>::new_contract(&address, trie_id, code_hash, None, vec![]).unwrap();
 		<ContractInfoOf<Test>>::insert(address, contract);
 	}
 	pub fn set_balance(who: &AccountIdOf<Test>, amount: u64) {
@@ -221,6 +234,23 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId32>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
 impl pallet_timestamp::Config for Test {
 	type Moment = u64;
 	type OnTimestampSet = ();
@@ -247,6 +277,10 @@ parameter_types! {
 	pub const TransactionByteFee: u64 = 0;
 	pub static DepositPerByte: BalanceOf<Test> = 1;
 	pub const DepositPerItem: BalanceOf<Test> = 2;
+	pub const ChainId: &'static str = "substrate-testnet";
+	pub const NativeDenom: &'static str = "unit";
+	pub const Capabilities: &'static [&'static str] = &["iterator", "stargate"];
+	pub const AssetDenomPrefix: &'static str = "asset/";
 }
 
 impl Convert<Weight, BalanceOf<Self>> for Test {
@@ -291,6 +325,14 @@ impl Config for Test {
 	type DepositPerByte = DepositPerByte;
 	type DepositPerItem = DepositPerItem;
 	type AddressGenerator = DefaultAddressGenerator;
+	type AddressCodec = Ss58AddressCodec;
+	type ChainId = ChainId;
+	type NativeDenom = NativeDenom;
+	type Capabilities = Capabilities;
+	type Assets = Assets;
+	type DenomResolver = PrefixedDenomResolver<AssetDenomPrefix>;
+	type GasConverter = FixedGasRatio<ConstU64<140_000>>;
+	type ContractsPauseOrigin = frame_system::EnsureRoot<AccountId32>;
 }
 
 pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
@@ -346,6 +388,17 @@ where
 	Ok((wasm_binary, code_hash))
 }
 
+/// Load the prebuilt contract `fixtures/{fixture_name}.wasm`.
+fn load_module<T>(fixture_name: &str) -> std::io::Result<(Vec<u8>, <T::Hashing as Hash>::Output)>
+where
+	T: frame_system::Config,
+{
+	let fixture_path = ["fixtures/", fixture_name, ".wasm"].concat();
+	let wasm_binary = std::fs::read(fixture_path)?;
+	let code_hash = T::Hashing::hash(&wasm_binary);
+	Ok((wasm_binary, code_hash))
+}
+
 fn initialize_block(number: u64) {
 	System::reset_events();
 	System::initialize(&number, &[0u8; 32].into(), &Default::default());
@@ -398,6 +451,8 @@ fn instantiate_and_call_and_deposit_event() {
 			code_hash,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		assert!(ContractInfoOf::<Test>::contains_key(&addr));
@@ -480,6 +535,8 @@ fn deposit_event_max_value_limit() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -523,6 +580,8 @@ fn run_out_of_gas() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -562,6 +621,8 @@ fn instantiate_unique_trie_id() {
 			code_hash,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let trie_id = ContractInfoOf::<Test>::get(&addr).unwrap().trie_id;
 
@@ -575,6 +636,8 @@ fn instantiate_unique_trie_id() {
 				code_hash,
 				vec![],
 				vec![],
+				None,
+				vec![],
 			),
 			<Error<Test>>::DuplicateContract,
 		);
@@ -598,6 +661,8 @@ fn instantiate_unique_trie_id() {
 			code_hash,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 
 		// Trie ids shouldn't match or we might have a collision
@@ -620,6 +685,8 @@ fn storage_max_value_limit() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		ContractInfoOf::<Test>::get(&addr).unwrap();
@@ -669,6 +736,8 @@ fn deploy_and_call_other_contract() {
 			caller_wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		assert_ok!(Contracts::instantiate_with_code(
 			Origin::signed(ALICE),
@@ -678,6 +747,8 @@ fn deploy_and_call_other_contract() {
 			callee_wasm,
 			0u32.to_le_bytes().encode(),
 			vec![42],
+			None,
+			vec![],
 		));
 
 		// Drop previous events
@@ -778,6 +849,8 @@ fn delegate_call() {
 			caller_wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		// Only upload 'callee' code
 		assert_ok!(Contracts::upload_code(
@@ -812,6 +885,8 @@ fn cannot_self_destruct_through_draning() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -853,6 +928,8 @@ fn cannot_self_destruct_through_storage_refund_after_price_change() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -906,6 +983,8 @@ fn cannot_self_destruct_by_refund_after_slash() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -984,6 +1063,8 @@ fn cannot_self_destruct_while_live() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -1018,6 +1099,8 @@ fn self_destruct_works() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -1102,7 +1185,9 @@ fn destroy_contract_and_transfer_funds() {
 			None,
 			callee_wasm,
 			vec![],
-			vec![42]
+			vec![42],
+			None,
+			vec![],
 		));
 
 		// This deploys the BOB contract, which in turn deploys the CHARLIE contract during
@@ -1115,6 +1200,8 @@ fn destroy_contract_and_transfer_funds() {
 			caller_wasm,
 			callee_code_hash.as_ref().to_vec(),
 			vec![],
+			None,
+			vec![],
 		));
 		let addr_bob = Contracts::contract_address(&ALICE, &caller_code_hash, &[]);
 		let addr_charlie = Contracts::contract_address(&addr_bob, &callee_code_hash, &[0x47, 0x11]);
@@ -1153,6 +1240,8 @@ fn cannot_self_destruct_in_constructor() {
 				wasm,
 				vec![],
 				vec![],
+				None,
+				vec![],
 			),
 			Error::<Test>::TerminatedInConstructor,
 		);
@@ -1175,6 +1264,8 @@ fn crypto_hashes() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		// Perform the call.
@@ -1224,6 +1315,8 @@ fn transfer_return_code() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -1263,6 +1356,8 @@ fn call_return_code() {
 			caller_code,
 			vec![0],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr_bob = Contracts::contract_address(&ALICE, &caller_hash, &[]);
 		Balances::make_free_balance_be(&addr_bob, min_balance);
@@ -1289,6 +1384,8 @@ fn call_return_code() {
 			callee_code,
 			vec![0],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr_django = Contracts::contract_address(&CHARLIE, &callee_hash, &[]);
 		Balances::make_free_balance_be(&addr_django, min_balance);
@@ -1390,6 +1487,8 @@ fn instantiate_return_code() {
 			callee_code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		assert_ok!(Contracts::instantiate_with_code(
@@ -1400,6 +1499,8 @@ fn instantiate_return_code() {
 			caller_code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &caller_hash, &[]);
 
@@ -1490,6 +1591,8 @@ fn disabled_chain_extension_wont_deploy() {
 				code,
 				vec![],
 				vec![],
+				None,
+				vec![],
 			),
 			<Error<Test>>::CodeRejected,
 		);
@@ -1510,6 +1613,8 @@ fn disabled_chain_extension_errors_on_call() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
 		TestExtension::disable();
@@ -1534,6 +1639,8 @@ fn chain_extension_works() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
 
@@ -1585,6 +1692,8 @@ fn lazy_removal_works() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
@@ -1635,6 +1744,8 @@ fn lazy_batch_removal_works() {
 				code.clone(),
 				vec![],
 				vec![i],
+				None,
+				vec![],
 			),);
 
 			let addr = Contracts::contract_address(&ALICE, &hash, &[i]);
@@ -1697,6 +1808,8 @@ fn lazy_removal_partial_remove_works() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
@@ -1776,6 +1889,8 @@ fn lazy_removal_does_no_run_on_full_block() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
@@ -1860,6 +1975,8 @@ fn lazy_removal_does_not_use_all_weight() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
@@ -1934,6 +2051,8 @@ fn deletion_queue_full() {
 			code,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 
 		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
@@ -1968,6 +2087,8 @@ fn refcounter() {
 			wasm.clone(),
 			vec![],
 			vec![0],
+			None,
+			vec![],
 		));
 		assert_ok!(Contracts::instantiate_with_code(
 			Origin::signed(ALICE),
@@ -1977,6 +2098,8 @@ fn refcounter() {
 			wasm.clone(),
 			vec![],
 			vec![1],
+			None,
+			vec![],
 		));
 		assert_refcount!(code_hash, 2);
 
@@ -1989,6 +2112,8 @@ fn refcounter() {
 			code_hash,
 			vec![],
 			vec![2],
+			None,
+			vec![],
 		));
 		assert_refcount!(code_hash, 3);
 
@@ -2035,6 +2160,8 @@ fn reinstrument_does_charge() {
 			wasm,
 			zero.clone(),
 			vec![],
+			None,
+			vec![],
 		));
 
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
@@ -2084,6 +2211,8 @@ fn debug_message_works() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		let result = Contracts::bare_call(ALICE, addr, 0, GAS_LIMIT, None, vec![], true);
@@ -2107,6 +2236,8 @@ fn debug_message_logging_disabled() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		// disable logging by passing `false`
@@ -2132,6 +2263,8 @@ fn debug_message_invalid_utf8() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		),);
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 		let result = Contracts::bare_call(ALICE, addr, 0, GAS_LIMIT, None, vec![], true);
@@ -2156,6 +2289,8 @@ fn gas_estimation_nested_call_fixed_limit() {
 			caller_code,
 			vec![],
 			vec![0],
+			None,
+			vec![],
 		),);
 		let addr_caller = Contracts::contract_address(&ALICE, &caller_hash, &[0]);
 
@@ -2167,6 +2302,8 @@ fn gas_estimation_nested_call_fixed_limit() {
 			callee_code,
 			vec![],
 			vec![1],
+			None,
+			vec![],
 		),);
 		let addr_callee = Contracts::contract_address(&ALICE, &callee_hash, &[1]);
 
@@ -2225,6 +2362,8 @@ fn gas_estimation_call_runtime() {
 			caller_code,
 			vec![],
 			vec![0],
+			None,
+			vec![],
 		),);
 		let addr_caller = Contracts::contract_address(&ALICE, &caller_hash, &[0]);
 
@@ -2236,6 +2375,8 @@ fn gas_estimation_call_runtime() {
 			callee_code,
 			vec![],
 			vec![1],
+			None,
+			vec![],
 		),);
 		let addr_callee = Contracts::contract_address(&ALICE, &callee_hash, &[1]);
 
@@ -2294,6 +2435,8 @@ fn ecdsa_recover() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -2517,6 +2660,8 @@ fn remove_code_in_use() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 
 		// Drop previous events
@@ -2569,6 +2714,8 @@ fn instantiate_with_zero_balance_works() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -2660,6 +2807,8 @@ fn instantiate_with_below_existential_deposit_works() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -2756,6 +2905,8 @@ fn storage_deposit_works() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -2878,6 +3029,8 @@ fn call_after_killed_account_needs_funding() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
@@ -2994,6 +3147,8 @@ fn contract_reverted() {
 				code_hash,
 				input.clone(),
 				vec![],
+				None,
+				vec![],
 			),
 			<Error<Test>>::ContractReverted,
 		);
@@ -3008,6 +3163,8 @@ fn contract_reverted() {
 				wasm,
 				input.clone(),
 				vec![],
+				None,
+				vec![],
 			),
 			<Error<Test>>::ContractReverted,
 		);
@@ -3063,14 +3220,14 @@ fn contract_reverted() {
 }
 
 #[test]
-fn code_rejected_error_works() {
+fn unsupported_import_error_works() {
 	let (wasm, _) = compile_module::<Test>("invalid_import").unwrap();
 	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
 		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 
 		assert_noop!(
 			Contracts::upload_code(Origin::signed(ALICE), wasm.clone(), None),
-			<Error<Test>>::CodeRejected,
+			<Error<Test>>::UnsupportedImport,
 		);
 
 		let result = Contracts::bare_instantiate(
@@ -3083,7 +3240,7 @@ fn code_rejected_error_works() {
 			vec![],
 			true,
 		);
-		assert_err!(result.result, <Error<Test>>::CodeRejected);
+		assert_err!(result.result, <Error<Test>>::UnsupportedImport);
 		assert_eq!(
 			std::str::from_utf8(&result.debug_message).unwrap(),
 			"module imports a non-existent function"
@@ -3091,6 +3248,19 @@ fn code_rejected_error_works() {
 	});
 }
 
+#[test]
+fn cosmwasm_unsupported_capability_is_rejected() {
+	let (wasm, _) = compile_module::<Test>("cosmwasm_requires_staking").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_noop!(
+			Contracts::upload_code(Origin::signed(ALICE), wasm, None),
+			<Error<Test>>::UnsupportedCapability,
+		);
+	});
+}
+
 #[test]
 #[cfg(feature = "unstable-interface")]
 fn set_code_hash() {
@@ -3111,6 +3281,8 @@ fn set_code_hash() {
 			wasm,
 			vec![],
 			vec![],
+			None,
+			vec![],
 		));
 		// upload new code
 		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), new_wasm.clone(), None));
@@ -3151,3 +3323,2126 @@ fn set_code_hash() {
 		);
 	});
 }
+
+#[test]
+fn migrate_works() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_dummy").unwrap();
+	let (new_wasm, new_code_hash) = compile_module::<Test>("cosmwasm_migrate").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			Some(ALICE),
+			vec![],
+		));
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), new_wasm, None));
+		let code_id = <CodeHashToId<Test>>::get(&code_hash).unwrap();
+		let new_code_id = <CodeHashToId<Test>>::get(&new_code_hash).unwrap();
+
+		// Only the admin can migrate the contract.
+		assert_err_ignore_postinfo!(
+			Contracts::migrate(
+				Origin::signed(BOB),
+				contract_addr.clone(),
+				GAS_LIMIT,
+				None,
+				new_code_id,
+				b"{}".to_vec(),
+			),
+			<Error<Test>>::NotContractAdmin,
+		);
+
+		initialize_block(2);
+		assert_ok!(Contracts::migrate(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			GAS_LIMIT,
+			None,
+			new_code_id,
+			b"{}".to_vec(),
+		));
+
+		let contract = <ContractInfoOf<Test>>::get(&contract_addr).unwrap();
+		assert_eq!(contract.code_hash, new_code_hash);
+		assert_eq!(
+			Storage::<Test>::read_raw(&contract.trie_id, b"migrated"),
+			Some(b"yes".to_vec())
+		);
+		assert_refcount!(&code_hash, 0);
+		assert_refcount!(&new_code_hash, 1);
+		let code_updated = Event::Contracts(crate::Event::ContractCodeUpdated {
+			contract: contract_addr.clone(),
+			new_code_hash,
+			old_code_hash: code_hash,
+		});
+		assert!(System::events().iter().any(|record| record.event == code_updated));
+
+		// The old code has no `migrate` entry point: the code isn't replaced.
+		assert!(Contracts::migrate(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			GAS_LIMIT,
+			None,
+			code_id,
+			b"{}".to_vec(),
+		)
+		.is_err());
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().code_hash, new_code_hash);
+		assert_refcount!(&code_hash, 0);
+		assert_refcount!(&new_code_hash, 1);
+	});
+}
+
+#[test]
+fn ink_contracts_cannot_be_migrated() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+			Some(ALICE),
+			vec![],
+		));
+		let code_id = <CodeHashToId<Test>>::get(&code_hash).unwrap();
+
+		assert_err_ignore_postinfo!(
+			Contracts::migrate(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				GAS_LIMIT,
+				None,
+				code_id,
+				vec![],
+			),
+			<Error<Test>>::ContractNotMigratable,
+		);
+		assert_refcount!(&code_hash, 1);
+	});
+}
+
+#[test]
+fn instantiate_from_code_id_works() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		let code_id = <CodeHashToId<Test>>::get(code_hash).unwrap();
+
+		assert_ok!(Contracts::instantiate_from_code_id(
+			Origin::signed(ALICE),
+			0,
+			GAS_LIMIT,
+			None,
+			code_id,
+			vec![],
+			vec![],
+			None,
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		assert_eq!(<ContractInfoOf<Test>>::get(&addr).unwrap().code_hash, code_hash);
+
+		// RPC clients can refer to the code by its id as well.
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Id(code_id),
+			vec![],
+			vec![1],
+			false,
+		);
+		assert_eq!(
+			result.result.unwrap().account_id,
+			Contracts::contract_address(&ALICE, &code_hash, &[1]),
+		);
+
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate_from_code_id(
+				Origin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				None,
+				code_id + 1,
+				vec![],
+				vec![],
+				None,
+				vec![],
+			),
+			<Error<Test>>::CodeNotFound,
+		);
+	});
+}
+
+#[test]
+fn contract_labels_work() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
+	ExtBuilder::default().existential_deposit(1_000).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let label = b"my-contract".to_vec();
+
+		// Labels are not unique.
+		for salt in [vec![0], vec![1]] {
+			assert_ok!(Contracts::instantiate_with_code(
+				Origin::signed(ALICE),
+				100_000,
+				GAS_LIMIT,
+				None,
+				wasm.clone(),
+				vec![],
+				salt,
+				None,
+				label.clone(),
+			));
+		}
+		let first = Contracts::contract_address(&ALICE, &code_hash, &[0]);
+		let second = Contracts::contract_address(&ALICE, &code_hash, &[1]);
+		assert_eq!(<ContractInfoOf<Test>>::get(&first).unwrap().label, label);
+
+		// Contracts without a label are not indexed.
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			code_hash,
+			vec![],
+			vec![2],
+			None,
+			vec![],
+		));
+		assert!(Contracts::contracts_by_label(&[]).is_empty());
+
+		let mut contracts = Contracts::contracts_by_label(&label);
+		contracts.sort();
+		let mut expected = vec![first.clone(), second.clone()];
+		expected.sort();
+		assert_eq!(contracts, expected);
+
+		// Calling the contract without input data triggers its termination.
+		assert_ok!(Contracts::call(Origin::signed(ALICE), first, 0, GAS_LIMIT, None, vec![]));
+		assert_eq!(Contracts::contracts_by_label(&label), vec![second]);
+
+		let label_len = <Test as Config>::Schedule::get().limits.label_len as usize;
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				100_000,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				vec![],
+				vec![3],
+				None,
+				vec![b'a'; label_len + 1],
+			),
+			<Error<Test>>::LabelTooLong,
+		);
+	});
+}
+
+#[test]
+fn update_and_clear_admin_works() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+			Some(ALICE),
+			vec![],
+		));
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().admin, Some(ALICE));
+
+		// Only the admin can replace the admin.
+		assert_noop!(
+			Contracts::update_admin(Origin::signed(BOB), contract_addr.clone(), BOB),
+			<Error<Test>>::NotContractAdmin,
+		);
+
+		initialize_block(2);
+		assert_ok!(Contracts::update_admin(Origin::signed(ALICE), contract_addr.clone(), BOB));
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().admin, Some(BOB));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::ContractAdminUpdated {
+				contract: contract_addr.clone(),
+				new_admin: Some(BOB),
+			}),
+		);
+
+		assert_noop!(
+			Contracts::clear_admin(Origin::signed(ALICE), contract_addr.clone()),
+			<Error<Test>>::NotContractAdmin,
+		);
+		assert_ok!(Contracts::clear_admin(Origin::signed(BOB), contract_addr.clone()));
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().admin, None);
+
+		// Without an admin nobody can change the contract anymore.
+		assert_noop!(
+			Contracts::update_admin(Origin::signed(BOB), contract_addr.clone(), BOB),
+			<Error<Test>>::NotContractAdmin,
+		);
+		assert_noop!(
+			Contracts::update_admin(Origin::signed(ALICE), BOB, ALICE),
+			<Error<Test>>::ContractNotFound,
+		);
+	});
+}
+
+#[test]
+fn pause_contracts_works() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+			None,
+			vec![],
+		));
+		let call = || {
+			Contracts::call(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				vec![],
+			)
+		};
+
+		// Only the pause origin can pause contracts.
+		assert_noop!(
+			Contracts::pause_contract(Origin::signed(ALICE), contract_addr.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Contracts::pause_contract(Origin::root(), BOB),
+			<Error<Test>>::ContractNotFound,
+		);
+
+		initialize_block(2);
+		assert_ok!(Contracts::pause_contract(Origin::root(), contract_addr.clone()));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::ContractPaused { contract: contract_addr.clone() }),
+		);
+		assert_err_ignore_postinfo!(call(), <Error<Test>>::ContractPaused);
+		assert_ok!(Contracts::unpause_contract(Origin::root(), contract_addr.clone()));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::ContractUnpaused { contract: contract_addr.clone() }),
+		);
+		assert_ok!(call());
+
+		// Pausing a code pauses all of its contracts as well as new instantiations.
+		assert_noop!(
+			Contracts::pause_code(Origin::root(), H256::zero()),
+			<Error<Test>>::CodeNotFound,
+		);
+		assert_ok!(Contracts::pause_code(Origin::root(), code_hash));
+		assert_err_ignore_postinfo!(call(), <Error<Test>>::ContractPaused);
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				300_000,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				vec![],
+				vec![1],
+				None,
+				vec![],
+			),
+			<Error<Test>>::ContractPaused,
+		);
+		assert_ok!(Contracts::unpause_code(Origin::root(), code_hash));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::CodeUnpaused { code_hash }),
+		);
+		assert_ok!(call());
+
+		// Freezing rejects all contract calls.
+		assert_ok!(Contracts::freeze_contracts(Origin::root()));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::ContractsFrozen),
+		);
+		assert_err_ignore_postinfo!(call(), <Error<Test>>::ContractsFrozen);
+		assert_ok!(Contracts::unfreeze_contracts(Origin::root()));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::ContractsUnfrozen),
+		);
+		assert_ok!(call());
+	});
+}
+
+#[test]
+fn paused_contract_can_be_migrated() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_dummy").unwrap();
+	let (new_wasm, new_code_hash) = compile_module::<Test>("cosmwasm_migrate").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			Some(ALICE),
+			vec![],
+		));
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), new_wasm, None));
+		let new_code_id = <CodeHashToId<Test>>::get(new_code_hash).unwrap();
+		let migrate = || {
+			Contracts::migrate(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				GAS_LIMIT,
+				None,
+				new_code_id,
+				b"{}".to_vec(),
+			)
+		};
+		assert_ok!(Contracts::pause_contract(Origin::root(), contract_addr.clone()));
+
+		// The contract can't be migrated to paused code.
+		assert_ok!(Contracts::pause_code(Origin::root(), new_code_hash));
+		assert_err_ignore_postinfo!(migrate(), <Error<Test>>::ContractPaused);
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().code_hash, code_hash);
+
+		assert_ok!(Contracts::unpause_code(Origin::root(), new_code_hash));
+		assert_ok!(migrate());
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().code_hash, new_code_hash);
+
+		// The contract stays paused after the migration.
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				b"{}".to_vec(),
+			),
+			<Error<Test>>::ContractPaused,
+		);
+	});
+}
+
+#[test]
+fn set_code_metadata_works() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		let code_deposit = Balances::reserved_balance(&ALICE);
+		let deposit = |len: u64| len * DepositPerByte::get() + DepositPerItem::get();
+
+		// Only the owner of an existing code can set its metadata.
+		assert_noop!(
+			Contracts::set_code_metadata(Origin::signed(BOB), code_hash, vec![1; 100]),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Contracts::set_code_metadata(Origin::signed(ALICE), H256::zero(), vec![1; 100]),
+			<Error<Test>>::CodeNotFound,
+		);
+		let metadata_len = <Test as Config>::Schedule::get().limits.metadata_len as usize;
+		assert_noop!(
+			Contracts::set_code_metadata(
+				Origin::signed(ALICE),
+				code_hash,
+				vec![1; metadata_len + 1]
+			),
+			<Error<Test>>::CodeMetadataTooLong,
+		);
+
+		initialize_block(2);
+		assert_ok!(Contracts::set_code_metadata(Origin::signed(ALICE), code_hash, vec![1; 100]));
+		assert_eq!(Contracts::code_metadata(&code_hash), Some(vec![1; 100]));
+		assert_eq!(Balances::reserved_balance(&ALICE), code_deposit + deposit(100));
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::Contracts(crate::Event::CodeMetadataUpdated { code_hash }),
+		);
+
+		// Replacing the metadata refunds the deposit of the bytes which were removed.
+		assert_ok!(Contracts::set_code_metadata(Origin::signed(ALICE), code_hash, vec![2; 40]));
+		assert_eq!(Contracts::code_metadata(&code_hash), Some(vec![2; 40]));
+		assert_eq!(Balances::reserved_balance(&ALICE), code_deposit + deposit(40));
+
+		// Empty metadata clears it.
+		assert_ok!(Contracts::set_code_metadata(Origin::signed(ALICE), code_hash, vec![]));
+		assert_eq!(Contracts::code_metadata(&code_hash), None);
+		assert_eq!(Balances::reserved_balance(&ALICE), code_deposit);
+
+		// The metadata is removed and its deposit refunded together with the code.
+		assert_ok!(Contracts::set_code_metadata(Origin::signed(ALICE), code_hash, vec![3; 10]));
+		assert_ok!(Contracts::remove_code(Origin::signed(ALICE), code_hash));
+		assert_eq!(Contracts::code_metadata(&code_hash), None);
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+	});
+}
+
+#[test]
+fn call_with_assets_works() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_dummy").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Assets::force_create(Origin::root(), 1, ALICE, true, 1));
+		assert_ok!(Assets::mint(Origin::signed(ALICE), 1, ALICE, 100));
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+
+		assert_ok!(Contracts::call_with_assets(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			50,
+			vec![(1, 30)],
+			GAS_LIMIT,
+			None,
+			b"{}".to_vec(),
+		));
+		assert_eq!(Assets::balance(1, &ALICE), 70);
+		assert_eq!(Assets::balance(1, &contract_addr), 30);
+		assert_eq!(Balances::free_balance(&contract_addr), 300_050);
+
+		// Nothing is transferred when one of the assets can't be transferred.
+		assert_err_ignore_postinfo!(
+			Contracts::call_with_assets(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				0,
+				vec![(1, 10), (2, 10)],
+				GAS_LIMIT,
+				None,
+				b"{}".to_vec(),
+			),
+			<Error<Test>>::TransferFailed,
+		);
+		assert_eq!(Assets::balance(1, &ALICE), 70);
+		assert_eq!(Assets::balance(1, &contract_addr), 30);
+
+		// The number of assets transferred by a single call is limited.
+		let limit = <Test as Config>::Schedule::get().limits.transferred_assets as usize;
+		assert_err_ignore_postinfo!(
+			Contracts::call_with_assets(
+				Origin::signed(ALICE),
+				contract_addr.clone(),
+				0,
+				vec![(1, 1); limit + 1],
+				GAS_LIMIT,
+				None,
+				b"{}".to_vec(),
+			),
+			<Error<Test>>::TooManyAssets,
+		);
+		assert_eq!(Assets::balance(1, &ALICE), 70);
+	});
+}
+
+#[test]
+fn cosmwasm_events_are_deposited() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_dummy").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			300_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+
+		// The empty response still emits a `wasm` event holding the contract address.
+		let address = <Ss58AddressCodec as AddressCodec<Test>>::encode(&contract_addr).unwrap();
+		let record = System::events()
+			.into_iter()
+			.find(|record| {
+				matches!(record.event, Event::Contracts(crate::Event::CosmwasmEvent { .. }))
+			})
+			.unwrap();
+		assert_eq!(
+			record.event,
+			Event::Contracts(crate::Event::CosmwasmEvent {
+				contract: contract_addr.clone(),
+				ty: b"wasm".to_vec(),
+				attributes: vec![(b"_contract_address".to_vec(), address.into_bytes())],
+			}),
+		);
+		assert_eq!(
+			record.topics,
+			vec![BlakeTwo256::hash_of(&contract_addr), BlakeTwo256::hash(b"wasm")],
+		);
+	});
+}
+
+#[test]
+fn fixed_gas_ratio_works() {
+	type Converter = FixedGasRatio<ConstU64<140_000>>;
+
+	assert_eq!(Converter::gas_to_weight(3), 420_000);
+	assert_eq!(Converter::weight_to_gas(420_000), 3);
+	// Partial units of gas are rounded down.
+	assert_eq!(Converter::weight_to_gas(419_999), 2);
+	assert_eq!(Converter::gas_to_weight(u64::MAX), Weight::MAX);
+}
+
+#[test]
+fn prefixed_denom_resolver_works() {
+	type Resolver = PrefixedDenomResolver<AssetDenomPrefix>;
+
+	assert_eq!(<Resolver as DenomResolver<u32>>::resolve("asset/42"), Some(42));
+	assert_eq!(<Resolver as DenomResolver<u32>>::denom(&42), "asset/42");
+
+	// Only the canonical form of an id within the prefix is accepted.
+	assert_eq!(<Resolver as DenomResolver<u32>>::resolve("asset/042"), None);
+	assert_eq!(<Resolver as DenomResolver<u32>>::resolve("asset/-1"), None);
+	assert_eq!(<Resolver as DenomResolver<u32>>::resolve("asset/"), None);
+	assert_eq!(<Resolver as DenomResolver<u32>>::resolve("token/42"), None);
+}
+
+#[test]
+fn ss58_address_codec_works() {
+	use sp_core::crypto::{Ss58AddressFormat, Ss58Codec};
+	type Codec = Ss58AddressCodec;
+
+	let address = ALICE.to_ss58check_with_version(Ss58AddressFormat::custom(0));
+	assert_eq!(<Codec as AddressCodec<Test>>::encode(&ALICE), Ok(address.clone()));
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Ok(ALICE));
+
+	// Addresses of other networks are rejected.
+	let address = ALICE.to_ss58check_with_version(Ss58AddressFormat::custom(2));
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Err("Invalid SS58 prefix"));
+	let address = ALICE.to_ss58check_with_version(Ss58AddressFormat::custom(1000));
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Err("Invalid SS58 prefix"));
+
+	// The checksum is verified.
+	let mut data = bs58::decode(ALICE.to_ss58check_with_version(Ss58AddressFormat::custom(0)))
+		.into_vec()
+		.unwrap();
+	*data.last_mut().unwrap() ^= 1;
+	assert_eq!(
+		<Codec as AddressCodec<Test>>::decode(&bs58::encode(data).into_string()),
+		Err("Invalid SS58 checksum")
+	);
+
+	assert_eq!(<Codec as AddressCodec<Test>>::decode("0OIl"), Err("Invalid base58 encoding"));
+}
+
+#[test]
+fn bech32_address_codec_works() {
+	parameter_types! {
+		pub const Hrp: &'static str = "wasm";
+	}
+	type Codec = Bech32AddressCodec<Hrp>;
+
+	let address = <Codec as AddressCodec<Test>>::encode(&ALICE).unwrap();
+	assert!(address.starts_with("wasm1"));
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Ok(ALICE));
+
+	// Addresses with another human readable part are rejected.
+	let address = bech32::encode("cosmos", ALICE.to_base32(), bech32::Variant::Bech32).unwrap();
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Err("Invalid bech32 prefix"));
+
+	// Only account ids of the right length are accepted.
+	let address = bech32::encode("wasm", [1u8; 20].to_base32(), bech32::Variant::Bech32).unwrap();
+	assert_eq!(<Codec as AddressCodec<Test>>::decode(&address), Err("Invalid address length"));
+}
+
+/// The address of `account` as seen by CosmWasm contracts.
+fn cosmwasm_address(account: &AccountId32) -> String {
+	<<Test as Config>::AddressCodec as AddressCodec<Test>>::encode(account).unwrap()
+}
+
+/// Execute the reflect contract `contract` in order to dispatch `messages`.
+fn reflect(contract: &AccountId32, messages: Vec<SubMsg>) -> DispatchResultWithPostInfo {
+	Contracts::call(
+		Origin::signed(ALICE),
+		contract.clone(),
+		0,
+		GAS_LIMIT,
+		None,
+		to_vec(&messages).unwrap(),
+	)
+}
+
+/// Make the reflect contract `contract` answer `request` and decode its answer.
+fn reflect_query<R: DeserializeOwned>(contract: &AccountId32, request: QueryRequest) -> R {
+	let output = Contracts::bare_query(
+		contract.clone(),
+		GAS_LIMIT,
+		to_vec(&request).unwrap(),
+		false,
+	)
+	.result
+	.unwrap();
+	from_slice(&output.data.0).unwrap()
+}
+
+/// The value stored under `key` by the reflect contract `contract`, read through a query.
+fn reflect_raw(contract: &AccountId32, key: &[u8]) -> Vec<u8> {
+	let request: QueryRequest = QueryRequest::Wasm(WasmQuery::Raw {
+		contract_addr: cosmwasm_address(contract),
+		key: Binary(key.to_vec()),
+	});
+	Contracts::bare_query(contract.clone(), GAS_LIMIT, to_vec(&request).unwrap(), false)
+		.result
+		.unwrap()
+		.data
+		.0
+}
+
+#[test]
+fn cosmwasm_reflect_execute_and_query_work() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm.clone(),
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+
+		initialize_block(2);
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::new(BankMsg::Send {
+				to_address: cosmwasm_address(&BOB),
+				amount: vec![Coin { denom: "unit".into(), amount: 1_000 }],
+			})],
+		));
+		assert_eq!(Balances::free_balance(&BOB), 1_001_000);
+		let reflected = Event::Contracts(crate::Event::CosmwasmEvent {
+			contract: contract_addr.clone(),
+			ty: b"wasm".to_vec(),
+			attributes: vec![
+				(b"_contract_address".to_vec(), cosmwasm_address(&contract_addr).into_bytes()),
+				(b"action".to_vec(), b"reflect".to_vec()),
+			],
+		});
+		assert!(System::events().iter().any(|record| record.event == reflected));
+
+		// Queries of the bank and of contracts are answered.
+		let balance: BalanceResponse = reflect_query(
+			&contract_addr,
+			QueryRequest::Bank(BankQuery::Balance {
+				address: cosmwasm_address(&BOB),
+				denom: "unit".into(),
+			}),
+		);
+		assert_eq!(balance.amount, Coin { denom: "unit".into(), amount: 1_001_000 });
+		let info: ContractInfoResponse = reflect_query(
+			&contract_addr,
+			QueryRequest::Wasm(WasmQuery::ContractInfo {
+				contract_addr: cosmwasm_address(&contract_addr),
+			}),
+		);
+		assert_eq!(info.code_id, <CodeHashToId<Test>>::get(code_hash).unwrap());
+		assert_eq!(info.admin, None);
+		let info: CodeInfoResponse = reflect_query(
+			&contract_addr,
+			QueryRequest::Wasm(WasmQuery::CodeInfo { code_id: info.code_id }),
+		);
+		assert_eq!(info.creator, cosmwasm_address(&ALICE));
+		assert_eq!(info.checksum.0, sha2_256(&wasm));
+		assert_eq!(reflect_raw(&contract_addr, b"reply"), Vec::<u8>::new());
+
+		// A message which fails reverts the messages dispatched before it.
+		assert!(reflect(
+			&contract_addr,
+			vec![
+				SubMsg::new(BankMsg::Send {
+					to_address: cosmwasm_address(&BOB),
+					amount: vec![Coin { denom: "unit".into(), amount: 1_000 }],
+				}),
+				SubMsg::new(BankMsg::Send {
+					to_address: "foo".into(),
+					amount: vec![Coin { denom: "unit".into(), amount: 1_000 }],
+				}),
+			],
+		)
+		.is_err());
+		assert_eq!(Balances::free_balance(&BOB), 1_001_000);
+	});
+}
+
+#[test]
+fn cosmwasm_reflect_sub_messages_work() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[0]);
+	let callee_addr = Contracts::contract_address(&ALICE, &code_hash, &[1]);
+	let child_addr = Contracts::contract_address(&contract_addr, &code_hash, b"child");
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		let code_id = <CodeHashToId<Test>>::get(code_hash).unwrap();
+		for salt in [0, 1] {
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				10_000,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				b"{}".to_vec(),
+				vec![salt],
+				None,
+				vec![],
+			));
+		}
+
+		// The events of a successful sub-message are passed to `reply`.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_on_success(
+				WasmMsg::Execute {
+					contract_addr: cosmwasm_address(&callee_addr),
+					msg: Binary(b"[]".to_vec()),
+					funds: vec![Coin { denom: "unit".into(), amount: 500 }],
+				},
+				1,
+			)],
+		));
+		assert_eq!(Balances::free_balance(&callee_addr), 10_500);
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 1);
+		let response = match reply.result {
+			SubMsgResult::Ok(response) => response,
+			SubMsgResult::Err(error) => panic!("sub-message failed: {}", error),
+		};
+		assert_eq!(response.events.len(), 1);
+		assert_eq!(response.events[0].ty, "wasm");
+		assert_eq!(response.events[0].attributes[1], ("action", "reflect"));
+
+		// The error of a failed sub-message is passed to `reply`.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_on_error(
+				BankMsg::Send {
+					to_address: "foo".into(),
+					amount: vec![Coin { denom: "unit".into(), amount: 1_000 }],
+				},
+				2,
+			)],
+		));
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 2);
+		assert!(matches!(reply.result, SubMsgResult::Err(_)));
+
+		// Contracts instantiated by a sub-message report their address.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_always(
+				WasmMsg::Instantiate {
+					admin: Some(cosmwasm_address(&ALICE)),
+					code_id,
+					msg: Binary(b"{}".to_vec()),
+					funds: vec![],
+					label: "child".into(),
+				},
+				3,
+			)],
+		));
+		let contract = <ContractInfoOf<Test>>::get(&child_addr).unwrap();
+		assert_eq!(contract.code_hash, code_hash);
+		assert_eq!(contract.admin, Some(ALICE));
+		assert_eq!(contract.label, b"child".to_vec());
+		assert_eq!(Contracts::contracts_by_label(b"child"), vec![child_addr.clone()]);
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 3);
+		let data = match reply.result {
+			SubMsgResult::Ok(SubMsgResponse { data: Some(data), .. }) => data.0,
+			result => panic!("unexpected result: {:?}", result),
+		};
+		// The address is the first field of the `MsgInstantiateContractResponse`.
+		let address = cosmwasm_address(&child_addr).into_bytes();
+		assert_eq!(data[2..], address[..]);
+	});
+}
+
+#[test]
+fn cosmwasm_failed_bank_send_is_reverted() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+
+		// The second coin can't be transferred because the asset doesn't exist.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_on_error(
+				BankMsg::Send {
+					to_address: cosmwasm_address(&BOB),
+					amount: vec![
+						Coin { denom: "unit".into(), amount: 1_000 },
+						Coin { denom: "asset/1".into(), amount: 10 },
+					],
+				},
+				1,
+			)],
+		));
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 1);
+		assert!(matches!(reply.result, SubMsgResult::Err(_)));
+		assert_eq!(Balances::free_balance(&BOB), 1_000_000);
+		assert_eq!(Balances::free_balance(&contract_addr), 10_000);
+	});
+}
+
+#[test]
+fn cosmwasm_failed_multi_denom_send_is_reverted() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Assets::force_create(Origin::root(), 1, ALICE, true, 1));
+		assert_ok!(Assets::force_create(Origin::root(), 2, ALICE, true, 1));
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+		assert_ok!(Assets::mint(Origin::signed(ALICE), 1, contract_addr.clone(), 50));
+		assert_ok!(Assets::mint(Origin::signed(ALICE), 2, contract_addr.clone(), 5));
+
+		// The first asset is delivered before the second one fails for lack of balance.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_on_error(
+				BankMsg::Send {
+					to_address: cosmwasm_address(&BOB),
+					amount: vec![
+						Coin { denom: "asset/1".into(), amount: 10 },
+						Coin { denom: "asset/2".into(), amount: 10 },
+					],
+				},
+				1,
+			)],
+		));
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		assert!(matches!(reply.result, SubMsgResult::Err(_)));
+		assert_eq!(Assets::balance(1, &BOB), 0);
+		assert_eq!(Assets::balance(1, &contract_addr), 50);
+		assert_eq!(Assets::balance(2, &contract_addr), 5);
+
+		// Every asset is delivered when all of them can be transferred.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::new(BankMsg::Send {
+				to_address: cosmwasm_address(&BOB),
+				amount: vec![
+					Coin { denom: "asset/1".into(), amount: 10 },
+					Coin { denom: "asset/2".into(), amount: 4 },
+				],
+			})],
+		));
+		assert_eq!(Assets::balance(1, &BOB), 10);
+		assert_eq!(Assets::balance(2, &BOB), 4);
+	});
+}
+
+#[test]
+fn trace_call_records_nested_frames() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[0]);
+	let callee_addr = Contracts::contract_address(&ALICE, &code_hash, &[1]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		for salt in [0, 1] {
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				10_000,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				b"{}".to_vec(),
+				vec![salt],
+				None,
+				vec![],
+			));
+		}
+
+		// The first message makes the callee fail, the second one succeeds.
+		let failing: Vec<SubMsg> = vec![SubMsg::new(BankMsg::Send {
+			to_address: "foo".into(),
+			amount: vec![Coin { denom: "unit".into(), amount: 1 }],
+		})];
+		let failing = to_vec(&failing).unwrap();
+		let messages: Vec<SubMsg> = vec![
+			SubMsg::reply_on_error(
+				WasmMsg::Execute {
+					contract_addr: cosmwasm_address(&callee_addr),
+					msg: Binary(failing.clone()),
+					funds: vec![],
+				},
+				1,
+			),
+			SubMsg::new(WasmMsg::Execute {
+				contract_addr: cosmwasm_address(&callee_addr),
+				msg: Binary(b"[]".to_vec()),
+				funds: vec![],
+			}),
+		];
+		let input = to_vec(&messages).unwrap();
+		let result = Contracts::bare_trace_call(
+			ALICE,
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			input.clone(),
+		);
+		assert_ok!(&result.call.result);
+		let trace = result.trace.unwrap();
+		assert_eq!(trace.contract, contract_addr);
+		assert_eq!(trace.entry_point, EntryPoint::Call);
+		assert_eq!(trace.input, Bytes(input));
+		assert_ok!(&trace.result);
+		assert!(trace.gas_consumed <= result.call.gas_consumed);
+		assert_eq!(trace.calls.len(), 2);
+
+		let failed = &trace.calls[0];
+		assert_eq!(failed.contract, callee_addr);
+		assert_eq!(failed.entry_point, EntryPoint::Call);
+		assert_eq!(failed.input, Bytes(failing));
+		assert!(failed.result.is_err());
+		assert!(failed.calls.is_empty());
+
+		let succeeded = &trace.calls[1];
+		assert_eq!(succeeded.contract, callee_addr);
+		assert_eq!(succeeded.input, Bytes(b"[]".to_vec()));
+		assert_ok!(&succeeded.result);
+		assert!(failed.gas_consumed + succeeded.gas_consumed <= trace.gas_consumed);
+
+		// Nothing is traced when the call fails before executing any code.
+		let result = Contracts::bare_trace_call(ALICE, BOB, 0, GAS_LIMIT, None, vec![]);
+		assert_err!(result.call.result, Error::<Test>::ContractNotFound);
+		assert_eq!(result.trace, None);
+	});
+}
+
+#[test]
+fn estimate_call_finds_minimal_limits() {
+	let (wasm, code_hash) = compile_module::<Test>("store").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+			None,
+			vec![],
+		));
+
+		let deposit = <ContractInfoOf<Test>>::get(&contract_addr).unwrap().storage_deposit;
+		let expected_deposit = 100 * DepositPerByte::get() + DepositPerItem::get();
+		let input = 100u32.encode();
+		let estimate = Contracts::bare_estimate_call(
+			ALICE,
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			input.clone(),
+		);
+		assert_ok!(&estimate.call.result);
+		assert!(estimate.gas_required <= estimate.call.gas_required);
+		assert_eq!(estimate.storage_deposit, StorageDeposit::Charge(expected_deposit));
+
+		// No state was changed by the estimation.
+		assert_eq!(<ContractInfoOf<Test>>::get(&contract_addr).unwrap().storage_deposit, deposit);
+
+		// The call succeeds with exactly the estimated limits and fails with any less.
+		let call = |gas_limit, storage_deposit_limit| {
+			Contracts::bare_call(
+				ALICE,
+				contract_addr.clone(),
+				0,
+				gas_limit,
+				Some(storage_deposit_limit),
+				input.clone(),
+				false,
+			)
+			.result
+		};
+		assert_err!(call(estimate.gas_required - 1, expected_deposit), Error::<Test>::OutOfGas);
+		assert_err!(
+			call(estimate.gas_required, expected_deposit - 1),
+			Error::<Test>::StorageDepositLimitExhausted,
+		);
+		assert_ok!(call(estimate.gas_required, expected_deposit));
+
+		// No estimation is performed for failing calls.
+		let estimate = Contracts::bare_estimate_call(ALICE, BOB, 0, GAS_LIMIT, None, vec![]);
+		assert_err!(estimate.call.result, Error::<Test>::ContractNotFound);
+		assert_eq!(estimate.gas_required, estimate.call.gas_required);
+	});
+}
+
+#[test]
+fn contract_events_are_decoded() {
+	let (wasm, code_hash) = compile_module::<Test>("event_size").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			30_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+			None,
+			vec![],
+		));
+		assert_eq!(Contracts::contract_events(), vec![]);
+
+		// The contract emits the length of its input as event data.
+		initialize_block(2);
+		System::note_finished_initialize();
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			4u32.encode(),
+		));
+		System::note_applied_extrinsic(&Ok(().into()), Default::default());
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			2u32.encode(),
+		));
+		assert_eq!(
+			Contracts::contract_events(),
+			vec![
+				ContractEvent {
+					extrinsic_index: Some(0),
+					contract: contract_addr.clone(),
+					data: Bytes(vec![4, 0, 0, 0]),
+					topics: vec![],
+				},
+				ContractEvent {
+					extrinsic_index: Some(1),
+					contract: contract_addr,
+					data: Bytes(vec![4, 0]),
+					topics: vec![],
+				},
+			],
+		);
+	});
+}
+
+#[test]
+fn cosmwasm_reflect_instantiate2_works() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm.clone(),
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+
+		// The address is derived from the sha256 checksum of the code, as in CosmWasm.
+		let child_addr =
+			Contracts::instantiate2_address(&contract_addr, &code_hash, b"salt").unwrap();
+		let expected = instantiate2_address(
+			&sha2_256(&wasm),
+			&CanonicalAddr::from(contract_addr.encode()),
+			b"salt",
+		)
+		.unwrap();
+		assert_eq!(child_addr.encode(), expected.0 .0);
+
+		let code_id = <CodeHashToId<Test>>::get(code_hash).unwrap();
+		let instantiate2 = |salt: &[u8]| WasmMsg::Instantiate2 {
+			admin: None,
+			code_id,
+			label: "child".into(),
+			msg: Binary(b"{}".to_vec()),
+			funds: vec![],
+			salt: Binary(salt.to_vec()),
+		};
+
+		// The address of the child is known before its instantiation.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::reply_on_success(instantiate2(b"salt"), 1)],
+		));
+		let contract = <ContractInfoOf<Test>>::get(&child_addr).unwrap();
+		assert_eq!(contract.code_hash, code_hash);
+		let reply: Reply = from_slice(&reflect_raw(&contract_addr, b"reply")).unwrap();
+		let data = match reply.result {
+			SubMsgResult::Ok(SubMsgResponse { data: Some(data), .. }) => data.0,
+			result => panic!("unexpected result: {:?}", result),
+		};
+		let address = cosmwasm_address(&child_addr).into_bytes();
+		assert_eq!(data[2..], address[..]);
+
+		// The same salt can't be used twice for the same code.
+		assert_err_ignore_postinfo!(
+			reflect(&contract_addr, vec![SubMsg::new(instantiate2(b"salt"))]),
+			Error::<Test>::DuplicateContract,
+		);
+
+		// The salt must not be empty.
+		assert_err_ignore_postinfo!(
+			reflect(&contract_addr, vec![SubMsg::new(instantiate2(b""))]),
+			Error::<Test>::InvalidSalt,
+		);
+	});
+}
+
+#[test]
+fn cosmwasm_reflect_migrate_works() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_dummy").unwrap();
+	let (reflect_wasm, reflect_code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			Some(ALICE),
+			vec![],
+		));
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), reflect_wasm, None));
+		let code_id = <CodeHashToId<Test>>::get(reflect_code_hash).unwrap();
+
+		assert_ok!(Contracts::migrate(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			GAS_LIMIT,
+			None,
+			code_id,
+			br#"{"version":2}"#.to_vec(),
+		));
+		assert_eq!(reflect_raw(&contract_addr, b"migrated"), br#"{"version":2}"#.to_vec());
+
+		// The contract is executed with its new code.
+		assert_ok!(reflect(
+			&contract_addr,
+			vec![SubMsg::new(BankMsg::Send {
+				to_address: cosmwasm_address(&BOB),
+				amount: vec![Coin { denom: "unit".into(), amount: 1_000 }],
+			})],
+		));
+		assert_eq!(Balances::free_balance(&BOB), 1_000);
+	});
+}
+
+/// Upload the prebuilt contract `fixture_name` and instantiate it with the JSON message `msg`
+/// on behalf of ALICE, who becomes its admin.
+fn instantiate_prebuilt(fixture_name: &str, msg: String) -> AccountId32 {
+	let (wasm, code_hash) = load_module::<Test>(fixture_name).unwrap();
+	assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+	assert_ok!(Contracts::instantiate(
+		Origin::signed(ALICE),
+		0,
+		GAS_LIMIT,
+		None,
+		code_hash,
+		msg.into_bytes(),
+		vec![],
+		Some(ALICE),
+		fixture_name.as_bytes().to_vec(),
+	));
+	Contracts::contract_address(&ALICE, &code_hash, &[])
+}
+
+/// Execute the CosmWasm contract `contract` with the JSON message `msg` on behalf of `origin`.
+fn cosmwasm_execute(
+	origin: &AccountId32,
+	contract: &AccountId32,
+	msg: String,
+) -> DispatchResultWithPostInfo {
+	Contracts::call(
+		Origin::signed(origin.clone()),
+		contract.clone(),
+		0,
+		GAS_LIMIT,
+		None,
+		msg.into_bytes(),
+	)
+}
+
+/// Query the CosmWasm contract `contract` with the JSON message `msg`.
+fn cosmwasm_query(contract: &AccountId32, msg: String) -> serde_json::Value {
+	let output = Contracts::bare_query(contract.clone(), GAS_LIMIT, msg.into_bytes(), false)
+		.result
+		.unwrap();
+	from_slice(&output.data.0).unwrap()
+}
+
+/// The cw20 balance of `account` held by the token contract `token`.
+fn cw20_balance(token: &AccountId32, account: &AccountId32) -> serde_json::Value {
+	let msg = format!(r#"{{"balance":{{"address":"{}"}}}}"#, cosmwasm_address(account));
+	cosmwasm_query(token, msg)["balance"].clone()
+}
+
+/// The instantiation message of a cw20 token with an initial balance of 1000 for `holder`
+/// which can be minted by ALICE.
+fn cw20_instantiate_msg(holder: &AccountId32) -> String {
+	format!(
+		concat!(
+			r#"{{"name":"Token","symbol":"TKN","decimals":6,"#,
+			r#""initial_balances":[{{"address":"{}","amount":"1000"}}],"#,
+			r#""mint":{{"minter":"{}","cap":null}},"marketing":null}}"#,
+		),
+		cosmwasm_address(holder),
+		cosmwasm_address(&ALICE),
+	)
+}
+
+#[test]
+fn cw20_base_works() {
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let token = instantiate_prebuilt("cw20_base_0_14_0", cw20_instantiate_msg(&ALICE));
+		let info = cosmwasm_query(&token, r#"{"token_info":{}}"#.into());
+		assert_eq!(
+			info,
+			serde_json::json!({
+				"name": "Token",
+				"symbol": "TKN",
+				"decimals": 6,
+				"total_supply": "1000",
+			})
+		);
+		assert_eq!(cw20_balance(&token, &ALICE), "1000");
+
+		let transfer = |amount: u32| {
+			format!(
+				r#"{{"transfer":{{"recipient":"{}","amount":"{}"}}}}"#,
+				cosmwasm_address(&BOB),
+				amount,
+			)
+		};
+		assert_ok!(cosmwasm_execute(&ALICE, &token, transfer(100)));
+		assert_eq!(cw20_balance(&token, &ALICE), "900");
+		assert_eq!(cw20_balance(&token, &BOB), "100");
+
+		// Failed executions are reverted.
+		assert_err_ignore_postinfo!(
+			cosmwasm_execute(&ALICE, &token, transfer(1_000)),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		assert_eq!(cw20_balance(&token, &ALICE), "900");
+
+		// Only the minter can mint.
+		let mint = format!(
+			r#"{{"mint":{{"recipient":"{}","amount":"50"}}}}"#,
+			cosmwasm_address(&CHARLIE),
+		);
+		assert_err_ignore_postinfo!(
+			cosmwasm_execute(&BOB, &token, mint.clone()),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		assert_ok!(cosmwasm_execute(&ALICE, &token, mint));
+		assert_ok!(cosmwasm_execute(&ALICE, &token, r#"{"burn":{"amount":"10"}}"#.into()));
+		let info = cosmwasm_query(&token, r#"{"token_info":{}}"#.into());
+		assert_eq!(info["total_supply"], "1040");
+
+		// Allowances can be spent by the spender.
+		let allow = format!(
+			r#"{{"increase_allowance":{{"spender":"{}","amount":"50","expires":null}}}}"#,
+			cosmwasm_address(&BOB),
+		);
+		assert_ok!(cosmwasm_execute(&ALICE, &token, allow));
+		let transfer_from = format!(
+			r#"{{"transfer_from":{{"owner":"{}","recipient":"{}","amount":"20"}}}}"#,
+			cosmwasm_address(&ALICE),
+			cosmwasm_address(&CHARLIE),
+		);
+		assert_ok!(cosmwasm_execute(&BOB, &token, transfer_from));
+		assert_eq!(cw20_balance(&token, &CHARLIE), "70");
+		let allowance = format!(
+			r#"{{"allowance":{{"owner":"{}","spender":"{}"}}}}"#,
+			cosmwasm_address(&ALICE),
+			cosmwasm_address(&BOB),
+		);
+		assert_eq!(cosmwasm_query(&token, allowance)["allowance"], "30");
+
+		// Accounts are iterated in order.
+		let mut holders =
+			vec![cosmwasm_address(&ALICE), cosmwasm_address(&BOB), cosmwasm_address(&CHARLIE)];
+		holders.sort();
+		let accounts = cosmwasm_query(&token, r#"{"all_accounts":{}}"#.into());
+		assert_eq!(accounts, serde_json::json!({ "accounts": holders }));
+	});
+}
+
+#[test]
+fn cw20_base_sub_messages_work() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+	let reflect_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+		let token = instantiate_prebuilt("cw20_base_0_14_0", cw20_instantiate_msg(&reflect_addr));
+		let transfer = |amount: u32| WasmMsg::Execute {
+			contract_addr: cosmwasm_address(&token),
+			msg: Binary(
+				format!(
+					r#"{{"transfer":{{"recipient":"{}","amount":"{}"}}}}"#,
+					cosmwasm_address(&BOB),
+					amount,
+				)
+				.into_bytes(),
+			),
+			funds: vec![],
+		};
+
+		// The token contract is executed on behalf of the contract dispatching the message.
+		assert_ok!(reflect(&reflect_addr, vec![SubMsg::reply_on_success(transfer(100), 1)]));
+		assert_eq!(cw20_balance(&token, &reflect_addr), "900");
+		assert_eq!(cw20_balance(&token, &BOB), "100");
+		let reply: Reply = from_slice(&reflect_raw(&reflect_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 1);
+		let response = match reply.result {
+			SubMsgResult::Ok(response) => response,
+			SubMsgResult::Err(error) => panic!("sub-message failed: {}", error),
+		};
+		let transfer_event = response.events.iter().find(|event| event.ty == "wasm").unwrap();
+		assert!(transfer_event.attributes.iter().any(|attr| *attr == ("action", "transfer")));
+
+		// A failed transfer is reported to `reply` and reverted.
+		assert_ok!(reflect(&reflect_addr, vec![SubMsg::reply_on_error(transfer(1_000), 2)]));
+		let reply: Reply = from_slice(&reflect_raw(&reflect_addr, b"reply")).unwrap();
+		assert_eq!(reply.id, 2);
+		assert!(matches!(reply.result, SubMsgResult::Err(_)));
+		assert_eq!(cw20_balance(&token, &reflect_addr), "900");
+
+		// A failed message without reply fails the whole execution.
+		assert_err_ignore_postinfo!(
+			reflect(&reflect_addr, vec![SubMsg::new(transfer(100)), SubMsg::new(transfer(1_000))]),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		assert_eq!(cw20_balance(&token, &BOB), "100");
+
+		// Sending tokens to a contract calls it with a `receive` message. The reflect contract
+		// doesn't understand it, which reverts the whole send.
+		let send = WasmMsg::Execute {
+			contract_addr: cosmwasm_address(&token),
+			msg: Binary(
+				format!(
+					r#"{{"send":{{"contract":"{}","amount":"100","msg":""}}}}"#,
+					cosmwasm_address(&reflect_addr),
+				)
+				.into_bytes(),
+			),
+			funds: vec![],
+		};
+		assert!(reflect(&reflect_addr, vec![SubMsg::new(send)]).is_err());
+		assert_eq!(cw20_balance(&token, &reflect_addr), "900");
+	});
+}
+
+#[test]
+fn cw20_base_migrate_works() {
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		// Both codes need to be paid for.
+		let _ = Balances::deposit_creating(&ALICE, 10_000_000);
+		let token = instantiate_prebuilt("cw20_base_0_13_4", cw20_instantiate_msg(&ALICE));
+		let allow = format!(
+			r#"{{"increase_allowance":{{"spender":"{}","amount":"50","expires":null}}}}"#,
+			cosmwasm_address(&BOB),
+		);
+		assert_ok!(cosmwasm_execute(&ALICE, &token, allow));
+		let (wasm, code_hash) = load_module::<Test>("cw20_base_0_14_0").unwrap();
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		let code_id = <CodeHashToId<Test>>::get(code_hash).unwrap();
+
+		// Only the admin can migrate the contract.
+		assert_err_ignore_postinfo!(
+			Contracts::migrate(
+				Origin::signed(BOB),
+				token.clone(),
+				GAS_LIMIT,
+				None,
+				code_id,
+				b"{}".to_vec(),
+			),
+			<Error<Test>>::NotContractAdmin,
+		);
+		assert_ok!(Contracts::migrate(
+			Origin::signed(ALICE),
+			token.clone(),
+			GAS_LIMIT,
+			None,
+			code_id,
+			b"{}".to_vec(),
+		));
+		assert_eq!(<ContractInfoOf<Test>>::get(&token).unwrap().code_hash, code_hash);
+
+		// The migration indexed the allowances by spender, which the new code can query.
+		let spender = cosmwasm_address(&BOB);
+		let allowances = format!(r#"{{"all_spender_allowances":{{"spender":"{}"}}}}"#, spender);
+		assert_eq!(
+			cosmwasm_query(&token, allowances),
+			serde_json::json!({
+				"allowances": [{
+					"owner": cosmwasm_address(&ALICE),
+					"allowance": "50",
+					"expires": { "never": {} },
+				}],
+			})
+		);
+		assert_eq!(cw20_balance(&token, &ALICE), "1000");
+	});
+}
+
+#[test]
+fn cw721_base_works() {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_reflect").unwrap();
+	let reflect_addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			10_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			b"{}".to_vec(),
+			vec![],
+			None,
+			vec![],
+		));
+		let msg = format!(
+			r#"{{"name":"Collection","symbol":"NFT","minter":"{}"}}"#,
+			cosmwasm_address(&ALICE),
+		);
+		let nft = instantiate_prebuilt("cw721_base_0_16_0", msg);
+		let mint = |token_id: &str, owner: &AccountId32| {
+			format!(
+				concat!(
+					r#"{{"mint":{{"token_id":"{}","owner":"{}","#,
+					r#""token_uri":"ipfs://{}","extension":null}}}}"#,
+				),
+				token_id,
+				cosmwasm_address(owner),
+				token_id,
+			)
+		};
+		let owner_of = |token_id: &str| {
+			let msg = format!(r#"{{"owner_of":{{"token_id":"{}"}}}}"#, token_id);
+			cosmwasm_query(&nft, msg)["owner"].clone()
+		};
+
+		// Only the minter can mint.
+		assert_err_ignore_postinfo!(
+			cosmwasm_execute(&BOB, &nft, mint("1", &BOB)),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		assert_ok!(cosmwasm_execute(&ALICE, &nft, mint("1", &ALICE)));
+		assert_ok!(cosmwasm_execute(&ALICE, &nft, mint("2", &reflect_addr)));
+		// Token ids are unique.
+		assert_err_ignore_postinfo!(
+			cosmwasm_execute(&ALICE, &nft, mint("1", &BOB)),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		assert_eq!(cosmwasm_query(&nft, r#"{"num_tokens":{}}"#.into())["count"], 2);
+		assert_eq!(owner_of("1"), cosmwasm_address(&ALICE));
+		let info = cosmwasm_query(&nft, r#"{"nft_info":{"token_id":"1"}}"#.into());
+		assert_eq!(info["token_uri"], "ipfs://1");
+
+		// Tokens can only be transferred by their owner or an approved spender.
+		let transfer = |token_id: &str, recipient: &AccountId32| {
+			format!(
+				r#"{{"transfer_nft":{{"recipient":"{}","token_id":"{}"}}}}"#,
+				cosmwasm_address(recipient),
+				token_id,
+			)
+		};
+		assert_err_ignore_postinfo!(
+			cosmwasm_execute(&BOB, &nft, transfer("1", &BOB)),
+			<Error<Test>>::CosmwasmContractError,
+		);
+		let approve = format!(
+			r#"{{"approve":{{"spender":"{}","token_id":"1","expires":null}}}}"#,
+			cosmwasm_address(&BOB),
+		);
+		assert_ok!(cosmwasm_execute(&ALICE, &nft, approve));
+		assert_ok!(cosmwasm_execute(&BOB, &nft, transfer("1", &CHARLIE)));
+		assert_eq!(owner_of("1"), cosmwasm_address(&CHARLIE));
+
+		// Tokens owned by a contract are transferred by its sub-messages.
+		let transfer_msg = WasmMsg::Execute {
+			contract_addr: cosmwasm_address(&nft),
+			msg: Binary(transfer("2", &BOB).into_bytes()),
+			funds: vec![],
+		};
+		assert_ok!(reflect(&reflect_addr, vec![SubMsg::reply_always(transfer_msg.clone(), 1)]));
+		assert_eq!(owner_of("2"), cosmwasm_address(&BOB));
+		let reply: Reply = from_slice(&reflect_raw(&reflect_addr, b"reply")).unwrap();
+		assert!(matches!(reply.result, SubMsgResult::Ok(_)));
+		// The contract no longer owns the token.
+		assert_ok!(reflect(&reflect_addr, vec![SubMsg::reply_always(transfer_msg, 2)]));
+		let reply: Reply = from_slice(&reflect_raw(&reflect_addr, b"reply")).unwrap();
+		assert!(matches!(reply.result, SubMsgResult::Err(_)));
+		assert_eq!(owner_of("2"), cosmwasm_address(&BOB));
+
+		let tokens = format!(r#"{{"tokens":{{"owner":"{}"}}}}"#, cosmwasm_address(&BOB));
+		assert_eq!(cosmwasm_query(&nft, tokens), serde_json::json!({ "tokens": ["2"] }));
+	});
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_channel_lifecycle_works() {
+	use crate::{ibc::ChannelState, IbcChannels, IbcOrder, IbcPackets};
+
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_ibc").unwrap();
+
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[0]);
+	let counterparty_addr = Contracts::contract_address(&ALICE, &code_hash, &[1]);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+		let read = |contract: &AccountId32, key: &[u8]| {
+			let contract = <ContractInfoOf<Test>>::get(contract).unwrap();
+			Storage::<Test>::read_raw(&contract.trie_id, key)
+		};
+
+		assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+		for salt in [0, 1] {
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				b"{}".to_vec(),
+				vec![salt],
+				None,
+				vec![],
+			));
+		}
+
+		// Only contracts can take part in a channel.
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_open_channel(
+				Origin::signed(BOB),
+				contract_addr.clone(),
+				BOB,
+				IbcOrder::Unordered,
+				b"ping-1".to_vec(),
+				GAS_LIMIT,
+				None,
+			),
+			<Error<Test>>::ContractNotFound,
+		);
+		assert_ok!(Contracts::ibc_open_channel(
+			Origin::signed(BOB),
+			contract_addr.clone(),
+			counterparty_addr.clone(),
+			IbcOrder::Unordered,
+			b"ping-1".to_vec(),
+			GAS_LIMIT,
+			None,
+		));
+		let end = <IbcChannels<Test>>::get(0).unwrap();
+		assert_eq!(end.contract, contract_addr);
+		assert_eq!(end.counterparty, 1);
+		assert_eq!(end.version, b"ping-1".to_vec());
+		assert_eq!(end.state, ChannelState::Open);
+		assert_eq!(<IbcChannels<Test>>::get(1).unwrap().contract, counterparty_addr);
+		let connected = read(&counterparty_addr, b"connected").unwrap();
+		assert!(String::from_utf8(connected).unwrap().contains("open_confirm"));
+
+		// A packet is received by the counterparty and its acknowledgement passed back.
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"{}".to_vec(),
+		));
+		assert!(<IbcPackets<Test>>::contains_key(0, 1));
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 2, GAS_LIMIT, None),
+			<Error<Test>>::IbcPacketNotFound,
+		);
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 1, GAS_LIMIT, None));
+		assert!(!<IbcPackets<Test>>::contains_key(0, 1));
+		let received = String::from_utf8(read(&counterparty_addr, b"received").unwrap()).unwrap();
+		// "ping" and "pong" in base64.
+		assert!(received.contains("cGluZw=="));
+		let acked = String::from_utf8(read(&contract_addr, b"acked").unwrap()).unwrap();
+		assert!(acked.contains("cG9uZw=="));
+		let acknowledged = Event::Contracts(crate::Event::IbcPacketAcknowledged {
+			channel_id: 0,
+			sequence: 1,
+		});
+		assert!(System::events().iter().any(|record| record.event == acknowledged));
+
+		// A packet relayed once its timeout is reached times out.
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"{}".to_vec(),
+		));
+		initialize_block(10);
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 2, GAS_LIMIT, None));
+		assert!(read(&contract_addr, b"timed_out").is_some());
+		let timed_out =
+			Event::Contracts(crate::Event::IbcPacketTimedOut { channel_id: 0, sequence: 2 });
+		assert!(System::events().iter().any(|record| record.event == timed_out));
+
+		// A channel can only be closed once its contract requested it.
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None),
+			<Error<Test>>::IbcChannelNotClosing,
+		);
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"\"close\"".to_vec(),
+		));
+		assert_ok!(Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().state, ChannelState::Closed);
+		assert_eq!(<IbcChannels<Test>>::get(1).unwrap().state, ChannelState::Closed);
+		assert!(read(&contract_addr, b"closed").is_some());
+		assert!(read(&counterparty_addr, b"closed").is_some());
+
+		// Packets can no longer be sent over a closed channel.
+		assert!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"{}".to_vec(),
+		)
+		.is_err());
+	});
+}
+
+/// Instantiate two `cosmwasm_ibc` contracts and open a channel between them.
+///
+/// Returns the contract owning `channel-0` and the counterparty owning `channel-1`.
+#[cfg(feature = "stargate")]
+fn open_ibc_channel(order: crate::IbcOrder) -> (AccountId32, AccountId32) {
+	let (wasm, code_hash) = compile_module::<Test>("cosmwasm_ibc").unwrap();
+	let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+	let _ = Balances::deposit_creating(&BOB, 1_000_000);
+	assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None));
+	for salt in [0, 1] {
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			0,
+			GAS_LIMIT,
+			None,
+			code_hash,
+			b"{}".to_vec(),
+			vec![salt],
+			None,
+			vec![],
+		));
+	}
+	let contract_addr = Contracts::contract_address(&ALICE, &code_hash, &[0]);
+	let counterparty_addr = Contracts::contract_address(&ALICE, &code_hash, &[1]);
+	assert_ok!(Contracts::ibc_open_channel(
+		Origin::signed(BOB),
+		contract_addr.clone(),
+		counterparty_addr.clone(),
+		order,
+		b"ping-1".to_vec(),
+		GAS_LIMIT,
+		None,
+	));
+	(contract_addr, counterparty_addr)
+}
+
+/// Read the value stored under `key` by `contract`.
+#[cfg(feature = "stargate")]
+fn read_ibc_key(contract: &AccountId32, key: &[u8]) -> Option<Vec<u8>> {
+	let contract = <ContractInfoOf<Test>>::get(contract).unwrap();
+	Storage::<Test>::read_raw(&contract.trie_id, key)
+}
+
+/// Make `contract` send "ping" over `channel-0`.
+#[cfg(feature = "stargate")]
+fn send_ibc_ping(contract: &AccountId32) {
+	assert_ok!(Contracts::call(
+		Origin::signed(ALICE),
+		contract.clone(),
+		0,
+		GAS_LIMIT,
+		None,
+		b"{}".to_vec(),
+	));
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_relay_packet_works() {
+	use crate::{IbcOrder, IbcPackets};
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let (contract_addr, counterparty_addr) = open_ibc_channel(IbcOrder::Unordered);
+		send_ibc_ping(&contract_addr);
+		send_ibc_ping(&contract_addr);
+		assert!(<IbcPackets<Test>>::contains_key(0, 1));
+		assert!(<IbcPackets<Test>>::contains_key(0, 2));
+		let sent = Event::Contracts(crate::Event::IbcPacketSent { channel_id: 0, sequence: 2 });
+		assert!(System::events().iter().any(|record| record.event == sent));
+
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(BOB), 2, 1, GAS_LIMIT, None),
+			<Error<Test>>::IbcChannelNotFound,
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 3, GAS_LIMIT, None),
+			<Error<Test>>::IbcPacketNotFound,
+		);
+
+		// Packets of an unordered channel can be relayed in any order.
+		for sequence in [2, 1] {
+			assert_ok!(Contracts::ibc_relay_packet(
+				Origin::signed(BOB),
+				0,
+				sequence,
+				GAS_LIMIT,
+				None,
+			));
+			assert!(!<IbcPackets<Test>>::contains_key(0, sequence));
+			let acknowledged =
+				Event::Contracts(crate::Event::IbcPacketAcknowledged { channel_id: 0, sequence });
+			assert!(System::events().iter().any(|record| record.event == acknowledged));
+		}
+		let received = read_ibc_key(&counterparty_addr, b"received").unwrap();
+		// "ping" and "pong" in base64.
+		assert!(String::from_utf8(received).unwrap().contains("cGluZw=="));
+		let acked = read_ibc_key(&contract_addr, b"acked").unwrap();
+		assert!(String::from_utf8(acked).unwrap().contains("cG9uZw=="));
+		assert!(read_ibc_key(&contract_addr, b"timed_out").is_none());
+
+		// A packet is only relayed once.
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 1, GAS_LIMIT, None),
+			<Error<Test>>::IbcPacketNotFound,
+		);
+	});
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_ordered_channel_relays_in_order() {
+	use crate::{IbcChannels, IbcOrder, IbcPackets};
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let (contract_addr, _) = open_ibc_channel(IbcOrder::Ordered);
+		send_ibc_ping(&contract_addr);
+		send_ibc_ping(&contract_addr);
+
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 2, GAS_LIMIT, None),
+			<Error<Test>>::IbcPacketOutOfOrder,
+		);
+		// The packet is kept so that it can be relayed later on.
+		assert!(<IbcPackets<Test>>::contains_key(0, 2));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().next_sequence_ack, 1);
+
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 1, GAS_LIMIT, None));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().next_sequence_ack, 2);
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 2, GAS_LIMIT, None));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().next_sequence_ack, 3);
+		assert!(!<IbcPackets<Test>>::contains_key(0, 2));
+	});
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_packet_timeout_works() {
+	use crate::{IbcOrder, IbcPackets};
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let (contract_addr, counterparty_addr) = open_ibc_channel(IbcOrder::Unordered);
+		send_ibc_ping(&contract_addr);
+
+		// The packet times out at the block number given as its timeout.
+		initialize_block(10);
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 1, GAS_LIMIT, None));
+		assert!(!<IbcPackets<Test>>::contains_key(0, 1));
+		let timed_out = read_ibc_key(&contract_addr, b"timed_out").unwrap();
+		assert!(String::from_utf8(timed_out).unwrap().contains("cGluZw=="));
+		assert!(read_ibc_key(&counterparty_addr, b"received").is_none());
+		assert!(read_ibc_key(&contract_addr, b"acked").is_none());
+		let timed_out =
+			Event::Contracts(crate::Event::IbcPacketTimedOut { channel_id: 0, sequence: 1 });
+		assert!(System::events().iter().any(|record| record.event == timed_out));
+	});
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_packet_to_closed_channel_times_out() {
+	use crate::IbcOrder;
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let (contract_addr, counterparty_addr) = open_ibc_channel(IbcOrder::Unordered);
+		send_ibc_ping(&contract_addr);
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"\"close\"".to_vec(),
+		));
+		assert_ok!(Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None));
+
+		// The packet was in flight when the channel was closed and can only time out.
+		assert_ok!(Contracts::ibc_relay_packet(Origin::signed(BOB), 0, 1, GAS_LIMIT, None));
+		assert!(read_ibc_key(&contract_addr, b"timed_out").is_some());
+		assert!(read_ibc_key(&counterparty_addr, b"received").is_none());
+		let timed_out =
+			Event::Contracts(crate::Event::IbcPacketTimedOut { channel_id: 0, sequence: 1 });
+		assert!(System::events().iter().any(|record| record.event == timed_out));
+	});
+}
+
+#[test]
+#[cfg(feature = "stargate")]
+fn ibc_close_channel_works() {
+	use crate::{ibc::ChannelState, IbcChannels, IbcOrder};
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let (contract_addr, counterparty_addr) = open_ibc_channel(IbcOrder::Unordered);
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_close_channel(Origin::signed(BOB), 2, GAS_LIMIT, None),
+			<Error<Test>>::IbcChannelNotFound,
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None),
+			<Error<Test>>::IbcChannelNotClosing,
+		);
+
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			contract_addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			b"\"close\"".to_vec(),
+		));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().state, ChannelState::Closing);
+		assert_eq!(<IbcChannels<Test>>::get(1).unwrap().state, ChannelState::Open);
+		assert_ok!(Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None));
+		assert_eq!(<IbcChannels<Test>>::get(0).unwrap().state, ChannelState::Closed);
+		assert_eq!(<IbcChannels<Test>>::get(1).unwrap().state, ChannelState::Closed);
+		let closed = read_ibc_key(&contract_addr, b"closed").unwrap();
+		assert!(String::from_utf8(closed).unwrap().contains("close_init"));
+		let closed = read_ibc_key(&counterparty_addr, b"closed").unwrap();
+		assert!(String::from_utf8(closed).unwrap().contains("close_confirm"));
+		for (contract, channel_id) in [(contract_addr.clone(), 0), (counterparty_addr, 1)] {
+			let closed = Event::Contracts(crate::Event::IbcChannelClosed { contract, channel_id });
+			assert!(System::events().iter().any(|record| record.event == closed));
+		}
+
+		// A channel is only closed once.
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_close_channel(Origin::signed(BOB), 0, GAS_LIMIT, None),
+			<Error<Test>>::IbcChannelNotClosing,
+		);
+	});
+}
+
+#[test]
+#[cfg(not(feature = "stargate"))]
+fn ibc_requires_stargate() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_open_channel(
+				Origin::signed(ALICE),
+				ALICE,
+				BOB,
+				crate::IbcOrder::Unordered,
+				b"ping-1".to_vec(),
+				GAS_LIMIT,
+				None,
+			),
+			<Error<Test>>::IbcDisabled,
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_relay_packet(Origin::signed(ALICE), 0, 1, GAS_LIMIT, None),
+			<Error<Test>>::IbcDisabled,
+		);
+		assert_err_ignore_postinfo!(
+			Contracts::ibc_close_channel(Origin::signed(ALICE), 0, GAS_LIMIT, None),
+			<Error<Test>>::IbcDisabled,
+		);
+	});
+}
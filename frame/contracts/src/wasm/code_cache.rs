@@ -30,18 +30,20 @@
 
 use crate::{
 	gas::{GasMeter, Token},
+	storage::meter::Diff,
 	wasm::{prepare, PrefabWasmModule},
 	weights::WeightInfo,
-	CodeHash, CodeStorage, Config, Error, Event, OwnerInfoOf, Pallet, PristineCode, Schedule,
-	Weight,
+	CodeChecksum, CodeHash, CodeHashToId, CodeIdToHash, CodeMetadata, CodeStorage, Config, Error,
+	Event, NextCodeId, OwnerInfoOf, Pallet, PristineCode, Schedule, Weight,
 };
 use frame_support::{
 	dispatch::{DispatchError, DispatchResult},
 	ensure,
 	traits::ReservableCurrency,
 };
+use pallet_contracts_primitives::StorageDeposit as Deposit;
 use sp_core::crypto::UncheckedFrom;
-use sp_runtime::traits::BadOrigin;
+use sp_runtime::traits::{BadOrigin, Saturating};
 
 /// Put the instrumented module in storage.
 ///
@@ -93,8 +95,16 @@ where
 			T::Currency::reserve(&owner_info.owner, owner_info.deposit)
 				.map_err(|_| <Error<T>>::StorageDepositNotEnoughFunds)?;
 			owner_info.refcount = if instantiated { 1 } else { 0 };
+			<CodeChecksum<T>>::insert(code_hash, sp_io::hashing::sha2_256(&orig_code));
 			<PristineCode<T>>::insert(&code_hash, orig_code);
 			<OwnerInfoOf<T>>::insert(&code_hash, owner_info);
+			let code_id = <NextCodeId<T>>::mutate(|next| {
+				let code_id = *next;
+				*next = next.wrapping_add(1);
+				code_id
+			});
+			<CodeIdToHash<T>>::insert(code_id, code_hash);
+			<CodeHashToId<T>>::insert(code_hash, code_id);
 			*existing = Some(module);
 			<Pallet<T>>::deposit_event(Event::CodeStored { code_hash });
 			Ok(())
@@ -142,6 +152,11 @@ pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> D
 			*existing = None;
 			<PristineCode<T>>::remove(&code_hash);
 			<CodeStorage<T>>::remove(&code_hash);
+			<CodeMetadata<T>>::remove(code_hash);
+			<CodeChecksum<T>>::remove(code_hash);
+			if let Some(code_id) = <CodeHashToId<T>>::take(&code_hash) {
+				<CodeIdToHash<T>>::remove(code_id);
+			}
 			<Pallet<T>>::deposit_event(Event::CodeRemoved { code_hash });
 			Ok(())
 		} else {
@@ -150,6 +165,47 @@ pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> D
 	})
 }
 
+/// Set or clear the metadata of a code.
+///
+/// The deposit for storing the metadata is added to the deposit of the code so that it is
+/// refunded when the code is removed.
+pub fn set_metadata<T: Config>(
+	origin: &T::AccountId,
+	code_hash: CodeHash<T>,
+	metadata: Vec<u8>,
+) -> DispatchResult {
+	<OwnerInfoOf<T>>::try_mutate(code_hash, |existing| -> DispatchResult {
+		let owner_info = existing.as_mut().ok_or(<Error<T>>::CodeNotFound)?;
+		ensure!(&owner_info.owner == origin, BadOrigin);
+		let old_len = <CodeMetadata<T>>::decode_len(code_hash);
+		let diff = Diff {
+			bytes_added: metadata.len() as u32,
+			bytes_removed: old_len.unwrap_or(0) as u32,
+			items_added: (!metadata.is_empty()).into(),
+			items_removed: old_len.is_some().into(),
+			..Default::default()
+		};
+		match diff.to_deposit::<T>() {
+			Deposit::Charge(amount) => {
+				T::Currency::reserve(&owner_info.owner, amount)
+					.map_err(|_| <Error<T>>::StorageDepositNotEnoughFunds)?;
+				owner_info.deposit = owner_info.deposit.saturating_add(amount);
+			},
+			Deposit::Refund(amount) => {
+				let amount = amount.min(owner_info.deposit);
+				T::Currency::unreserve(&owner_info.owner, amount);
+				owner_info.deposit = owner_info.deposit.saturating_sub(amount);
+			},
+		}
+		if metadata.is_empty() {
+			<CodeMetadata<T>>::remove(code_hash);
+		} else {
+			<CodeMetadata<T>>::insert(code_hash, metadata);
+		}
+		Ok(())
+	})
+}
+
 /// Load code with the given code hash.
 ///
 /// If the module was instrumented with a lower version of schedule than
@@ -301,9 +301,23 @@ pub enum CosmosMsg<T = Empty> {
     // by default we use RawMsg, but a contract can override that
     // to call into more app-specific code (whatever they define)
     Custom(T),
+    #[cfg(feature = "stargate")]
+    Ibc(IbcMsg),
     Wasm(WasmMsg),
 }
 
+impl<T> From<BankMsg> for CosmosMsg<T> {
+    fn from(msg: BankMsg) -> Self {
+        CosmosMsg::Bank(msg)
+    }
+}
+
+impl<T> From<WasmMsg> for CosmosMsg<T> {
+    fn from(msg: WasmMsg) -> Self {
+        CosmosMsg::Wasm(msg)
+    }
+}
+
 /// The message types of the bank module.
 ///
 /// See https://github.com/cosmos/cosmos-sdk/blob/v0.40.0/proto/cosmos/bank/v1beta1/tx.proto
@@ -355,6 +369,23 @@ pub enum WasmMsg {
         /// A human-readbale label for the contract
         label: String,
     },
+    /// Instantiates a new contracts from previously uploaded Wasm code
+    /// using a predictable address derivation algorithm implemented in
+    /// [`instantiate2_address`].
+    ///
+    /// This is translated to a [MsgInstantiateContract2](https://github.com/CosmWasm/wasmd/blob/v0.29.2/proto/cosmwasm/wasm/v1/tx.proto#L73-L96).
+    /// `sender` is automatically filled with the current contract's address.
+    /// `fix_msg` is automatically set to false.
+    Instantiate2 {
+        admin: Option<String>,
+        code_id: u64,
+        /// A human-readbale label for the contract
+        label: String,
+        /// msg is the JSON-encoded InstantiateMsg struct (as raw Binary)
+        msg: Binary,
+        funds: Vec<Coin>,
+        salt: Binary,
+    },
     /// Migrates a given contracts to use new wasm code. Passes a MigrateMsg to allow us to
     /// customize behavior.
     ///
@@ -380,6 +411,128 @@ pub enum WasmMsg {
     ClearAdmin { contract_addr: String },
 }
 
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryRequest<C = Empty> {
+    Bank(BankQuery),
+    Custom(C),
+    Wasm(WasmQuery),
+}
+
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BankQuery {
+    /// This calls into the native bank module for one denomination
+    /// Return value is BalanceResponse
+    Balance { address: String, denom: String },
+    /// This calls into the native bank module for all denominations.
+    /// Note that this may be much more expensive than Balance and should be avoided if possible.
+    /// Return value is AllBalanceResponse.
+    AllBalances { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub struct BalanceResponse {
+    /// Always returns a Coin with the requested denom.
+    /// This may be of 0 amount if no such funds.
+    pub amount: Coin,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub struct AllBalanceResponse {
+    /// Returns all non-zero coins held by this account.
+    pub amount: Vec<Coin>,
+}
+
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmQuery {
+    /// this queries the public API of another contract at a known address (with known ABI)
+    /// Return value is whatever the contract returns (caller should know), wrapped in a
+    /// ContractResult that is JSON encoded.
+    Smart {
+        contract_addr: String,
+        /// msg is the json-encoded QueryMsg struct
+        msg: Binary,
+    },
+    /// this queries the raw kv-store of the contract.
+    /// returns the raw, unparsed data stored at that key, which may be an empty vector if not present
+    Raw {
+        contract_addr: String,
+        /// Key is the raw key used in the contracts Storage
+        key: Binary,
+    },
+    /// returns a ContractInfoResponse with metadata on the contract from the runtime
+    ContractInfo { contract_addr: String },
+    /// Returns a [`CodeInfoResponse`] with metadata of the code
+    CodeInfo { code_id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ContractInfoResponse {
+    pub code_id: u64,
+    /// address that instantiated this contract
+    pub creator: String,
+    /// admin who can run migrations (if any)
+    pub admin: Option<String>,
+    /// if set, the contract is pinned to the cache, and thus uses less gas when called
+    pub pinned: bool,
+    /// set if this contract has bound an IBC port
+    pub ibc_port: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CodeInfoResponse {
+    pub code_id: u64,
+    /// The address that initially stored the code
+    pub creator: String,
+    /// The hash of the Wasm blob
+    pub checksum: HexBinary,
+}
+
+/// This is the outer result type returned by a querier to the contract.
+///
+/// We use a custom type here instead of Rust's Result because we want to be able to
+/// define the serialization, which is a public interface. Every language that compiles
+/// to Wasm and runs in the ComsWasm VM needs to create the same JSON representation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemResult<S> {
+    Ok(S),
+    #[serde(rename = "error")]
+    Err(SystemError),
+}
+
+/// SystemError is used for errors inside the VM and is API friendly (i.e. serializable).
+///
+/// This is used on return values for Querier as a nested result: Result<StdResult<T>, SystemError>
+/// The first wrap (SystemError) will trigger if the contract address doesn't exist,
+/// the QueryRequest is malformed, etc. The second wrap will be an error message from
+/// the contract itself.
+///
+/// Such errors are only created by the VM. The error type is defined in the standard library, to ensure
+/// the contract understands the error format without creating a dependency on cosmwasm-vm.
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemError {
+    InvalidRequest { error: String, request: Binary },
+    InvalidResponse { error: String, response: Binary },
+    NoSuchContract { addr: String },
+    NoSuchCode { code_id: u64 },
+    Unknown {},
+    UnsupportedRequest { kind: String },
+}
+
 /// A full [*Cosmos SDK* event].
 ///
 /// This version uses string attributes (similar to [*Cosmos SDK* StringEvent]),
@@ -505,9 +658,26 @@ impl<S> From<ContractResult<S>> for Result<S, String> {
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 pub struct Coin {
     pub denom: String,
+    #[serde(with = "uint128")]
     pub amount: u128,
 }
 
+/// (De)serialize an amount the way `cosmwasm_std::Uint128` does, i.e. as a decimal string,
+/// because JSON numbers can't represent 128 bits integers.
+mod uint128 {
+    use super::*;
+    use alloc::string::ToString;
+
+    pub fn serialize<S: ser::Serializer>(amount: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        let amount = String::deserialize(deserializer)?;
+        amount.parse().map_err(de::Error::custom)
+    }
+}
+
 impl core::fmt::Display for Coin {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         // We use the formatting without a space between amount and denom,
@@ -717,6 +887,93 @@ impl<'de> de::Visitor<'de> for Base64Visitor {
     }
 }
 
+/// This is a wrapper around Vec<u8> to add hex de/serialization
+/// with serde. It also adds some helper methods to help encode inline.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HexBinary(pub Vec<u8>);
+
+impl HexBinary {
+    pub fn from_hex(input: &str) -> Result<Self, DispatchError> {
+        let binary = hex::decode(input).map_err(|_| DispatchError::Other("invalid hex"))?;
+        Ok(Self(binary))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+}
+
+impl core::fmt::Display for HexBinary {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl core::fmt::Debug for HexBinary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Use an output inspired by tuples (https://doc.rust-lang.org/std/fmt/struct.Formatter.html#method.debug_tuple)
+        // but with a custom implementation to avoid the need for an intemediate hex string.
+        write!(f, "HexBinary(")?;
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")?;
+        Ok(())
+    }
+}
+
+impl<const LENGTH: usize> From<[u8; LENGTH]> for HexBinary {
+    fn from(source: [u8; LENGTH]) -> Self {
+        Self(source.into())
+    }
+}
+
+impl From<Vec<u8>> for HexBinary {
+    fn from(vec: Vec<u8>) -> Self {
+        Self(vec)
+    }
+}
+
+/// Serializes as a hex string
+impl Serialize for HexBinary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+/// Deserializes as a hex string
+impl<'de> Deserialize<'de> for HexBinary {
+    fn deserialize<D>(deserializer: D) -> Result<HexBinary, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+struct HexVisitor;
+
+impl<'de> de::Visitor<'de> for HexVisitor {
+    type Value = HexBinary;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("valid hex encoded string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match HexBinary::from_hex(v) {
+            Ok(data) => Ok(data),
+            Err(_) => Err(E::custom("")),
+        }
+    }
+}
+
 /// A human readable address.
 ///
 /// In Cosmos, this is typically bech32 encoded. But for multi-chain smart contracts no
@@ -883,6 +1140,63 @@ impl core::fmt::Display for CanonicalAddr {
     }
 }
 
+/// Errors that can happen during the creation of a predictable contract address.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Instantiate2AddressError {
+    /// Checksum must be 32 bytes
+    InvalidChecksumLength,
+    /// Salt must be between 1 and 64 bytes
+    InvalidSaltLength,
+}
+
+/// Creates a contract address using the predictable address format introduced with
+/// wasmd 0.29. When using instantiate2, this is a way to precompute the address.
+/// When using instantiate, the contract address will use a different algorithm and
+/// cannot be pre-computed as it contains inputs from the chain's state at the time of
+/// message execution.
+///
+/// The predicable address format of instantiate2 is stable. But bear in mind this is
+/// a powerful tool that requires multiple software components to work together smoothly.
+/// It should be used carefully and tested thoroughly to avoid the loss of funds.
+pub fn instantiate2_address(
+    checksum: &[u8],
+    creator: &CanonicalAddr,
+    salt: &[u8],
+) -> Result<CanonicalAddr, Instantiate2AddressError> {
+    if checksum.len() != 32 {
+        return Err(Instantiate2AddressError::InvalidChecksumLength);
+    }
+
+    if salt.is_empty() || salt.len() > 64 {
+        return Err(Instantiate2AddressError::InvalidSaltLength);
+    };
+
+    // Non-empty msg values are discouraged and not supported by `WasmMsg::Instantiate2`.
+    let msg: &[u8] = b"";
+
+    let mut key = Vec::<u8>::new();
+    key.extend_from_slice(b"wasm\0");
+    key.extend_from_slice(&(checksum.len() as u64).to_be_bytes());
+    key.extend_from_slice(checksum);
+    key.extend_from_slice(&(creator.len() as u64).to_be_bytes());
+    key.extend_from_slice(creator);
+    key.extend_from_slice(&(salt.len() as u64).to_be_bytes());
+    key.extend_from_slice(salt);
+    key.extend_from_slice(&(msg.len() as u64).to_be_bytes());
+    key.extend_from_slice(msg);
+    Ok(hash("module", &key).into())
+}
+
+/// The "Basic Address" Hash from
+/// https://github.com/cosmos/cosmos-sdk/blob/v0.45.8/docs/architecture/adr-028-public-key-addresses.md
+fn hash(ty: &str, key: &[u8]) -> Vec<u8> {
+    let inner = sp_io::hashing::sha2_256(ty.as_bytes());
+    let mut preimage = Vec::with_capacity(inner.len() + key.len());
+    preimage.extend_from_slice(&inner);
+    preimage.extend_from_slice(key);
+    sp_io::hashing::sha2_256(&preimage).to_vec()
+}
+
 #[derive(
     Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord,
 )]
@@ -992,3 +1306,268 @@ pub struct MessageInfo {
 pub struct ContractInfo {
     pub address: Addr,
 }
+
+/// These are messages in the IBC lifecycle. Only usable by IBC-enabled contracts
+/// (contracts that directly speak the IBC protocol via 6 entry points)
+#[cfg(feature = "stargate")]
+#[non_exhaustive]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcMsg {
+    /// Sends bank tokens owned by the contract to the given address on another chain.
+    /// The channel must already be established between the ibctransfer module on this chain
+    /// and a matching module on the remote chain.
+    /// We cannot select the port_id, this is whatever the local chain has bound the ibctransfer
+    /// module to.
+    Transfer {
+        /// exisiting channel to send the tokens over
+        channel_id: String,
+        /// address on the remote chain to receive these tokens
+        to_address: String,
+        /// packet data only supports one coin
+        /// https://github.com/cosmos/cosmos-sdk/blob/v0.40.0/proto/ibc/applications/transfer/v1/transfer.proto#L11-L20
+        amount: Coin,
+        /// when packet times out, measured on remote chain
+        timeout: IbcTimeout,
+    },
+    /// Sends an IBC packet with given data over the existing channel.
+    /// Data should be encoded in a format defined by the channel version,
+    /// and the module on the other side should know how to parse this.
+    SendPacket {
+        channel_id: String,
+        data: Binary,
+        /// when packet times out, measured on remote chain
+        timeout: IbcTimeout,
+    },
+    /// This will close an existing channel that is owned by this contract.
+    /// Port is auto-assigned to the contract's IBC port
+    CloseChannel { channel_id: String },
+}
+
+#[cfg(feature = "stargate")]
+impl<T> From<IbcMsg> for CosmosMsg<T> {
+    fn from(msg: IbcMsg) -> Self {
+        CosmosMsg::Ibc(msg)
+    }
+}
+
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IbcEndpoint {
+    pub port_id: String,
+    pub channel_id: String,
+}
+
+/// In IBC each package must set at least one type of timeout:
+/// the timestamp or the block height. Using this rather complex enum instead of
+/// two timeout fields we ensure that at least one timeout is set.
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IbcTimeout {
+    pub block: Option<IbcTimeoutBlock>,
+    pub timestamp: Option<Timestamp>,
+}
+
+/// IbcChannel defines all information on a channel.
+/// This is generally used in the hand-shake process, but can be queried directly.
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcChannel {
+    pub endpoint: IbcEndpoint,
+    pub counterparty_endpoint: IbcEndpoint,
+    pub order: IbcOrder,
+    /// Note: in ibcv3 this may be "", in the IbcOpenChannel handshake messages
+    pub version: String,
+    /// The connection upon which this channel was created. If this is a multi-hop
+    /// channel, we only expose the first hop.
+    pub connection_id: String,
+}
+
+/// IbcOrder defines if a channel is ORDERED or UNORDERED
+/// Values come from https://github.com/cosmos/cosmos-sdk/blob/v0.40.0/proto/ibc/core/channel/v1/channel.proto#L69-L80
+/// Naming comes from the protobuf files and go translations.
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum IbcOrder {
+    #[serde(rename = "ORDER_UNORDERED")]
+    Unordered,
+    #[serde(rename = "ORDER_ORDERED")]
+    Ordered,
+}
+
+/// IBCTimeoutHeight Height is a monotonically increasing data type
+/// that can be compared against another Height for the purposes of updating and
+/// freezing clients.
+/// Ordering is (revision_number, timeout_height)
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IbcTimeoutBlock {
+    /// the version that the client is currently on
+    /// (eg. after reseting the chain this could increment 1 as height drops to 0)
+    pub revision: u64,
+    /// block height after which the packet times out.
+    /// the height within the given revision
+    pub height: u64,
+}
+
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcPacket {
+    /// The raw data sent from the other side in the packet
+    pub data: Binary,
+    /// identifies the channel and port on the sending chain.
+    pub src: IbcEndpoint,
+    /// identifies the channel and port on the receiving chain.
+    pub dest: IbcEndpoint,
+    /// The sequence number of the packet on the given channel
+    pub sequence: u64,
+    pub timeout: IbcTimeout,
+}
+
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcAcknowledgement {
+    pub data: Binary,
+    // we may add more info here in the future (meta-data from the acknowledgement)
+    // there have been proposals to extend this type in core ibc for future versions
+}
+
+/// The message that is passed into `ibc_channel_open`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcChannelOpenMsg {
+    /// The ChanOpenInit step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    OpenInit { channel: IbcChannel },
+    /// The ChanOpenTry step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    OpenTry {
+        channel: IbcChannel,
+        counterparty_version: String,
+    },
+}
+
+/// This serializes either as "null" or a JSON object.
+#[cfg(feature = "stargate")]
+pub type IbcChannelOpenResponse = Option<Ibc3ChannelOpenResponse>;
+
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Ibc3ChannelOpenResponse {
+    /// We can set the channel version to a different one than we were called with
+    pub version: String,
+}
+
+/// The message that is passed into `ibc_channel_connect`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcChannelConnectMsg {
+    /// The ChanOpenAck step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    OpenAck {
+        channel: IbcChannel,
+        counterparty_version: String,
+    },
+    /// The ChanOpenConfirm step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    OpenConfirm { channel: IbcChannel },
+}
+
+/// The message that is passed into `ibc_channel_close`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcChannelCloseMsg {
+    /// The ChanCloseInit step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    CloseInit { channel: IbcChannel },
+    /// The ChanCloseConfirm step from https://github.com/cosmos/ibc/tree/master/spec/core/ics-004-channel-and-packet-semantics#channel-lifecycle-management
+    CloseConfirm { channel: IbcChannel },
+}
+
+/// The message that is passed into `ibc_packet_receive`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcPacketReceiveMsg {
+    pub packet: IbcPacket,
+    pub relayer: Addr,
+}
+
+/// The message that is passed into `ibc_packet_ack`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcPacketAckMsg {
+    pub acknowledgement: IbcAcknowledgement,
+    pub original_packet: IbcPacket,
+    pub relayer: Addr,
+}
+
+/// The message that is passed into `ibc_packet_timeout`
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcPacketTimeoutMsg {
+    pub packet: IbcPacket,
+    pub relayer: Addr,
+}
+
+/// This is the return value for the majority of the ibc handlers.
+/// That are able to dispatch messages / events on their own,
+/// but have no meaningful return value to the calling code.
+///
+/// Callbacks that have return values (like receive_packet)
+/// or that cannot redispatch messages (like the handshake callbacks)
+/// will use other Response types
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcBasicResponse<T = Empty> {
+    /// Optional list of messages to pass. These will be executed in order.
+    /// If the ReplyOn member is set, they will invoke this contract's `reply` entry point
+    /// after execution. Otherwise, they act like "fire and forget".
+    /// Use `SubMsg::new` to create messages with the older "fire and forget" semantics.
+    pub messages: Vec<SubMsg<T>>,
+    /// The attributes that will be emitted as part of a `wasm` event.
+    pub attributes: Vec<Attribute>,
+    /// Extra, custom events separate from the main `wasm` one. These will have
+    /// `wasm-` prepended to the type.
+    pub events: Vec<Event>,
+}
+
+#[cfg(feature = "stargate")]
+impl<T> From<IbcBasicResponse<T>> for Response<T> {
+    fn from(response: IbcBasicResponse<T>) -> Self {
+        Response {
+            messages: response.messages,
+            attributes: response.attributes,
+            events: response.events,
+            data: None,
+        }
+    }
+}
+
+/// This is the return value of `ibc_packet_receive`.
+///
+/// The acknowledgement bytes may contain an encoded error message to be returned to
+/// the calling chain. (Returning ContractResult::Err will abort processing of this packet
+/// and not inform the calling chain).
+#[cfg(feature = "stargate")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IbcReceiveResponse<T = Empty> {
+    /// The bytes we return to the contract that sent the packet.
+    /// This may represent a success or error of exection
+    pub acknowledgement: Binary,
+    /// Optional list of messages to pass. These will be executed in order.
+    /// If the ReplyOn member is set, they will invoke this contract's `reply` entry point
+    /// after execution. Otherwise, they act like "fire and forget".
+    /// Use `call` or `msg.into()` to create messages with the older "fire and forget" semantics.
+    pub messages: Vec<SubMsg<T>>,
+    /// The attributes that will be emitted as part of a "wasm" event.
+    pub attributes: Vec<Attribute>,
+    /// Extra, custom events separate from the main `wasm` one. These will have
+    /// `wasm-` prepended to the type.
+    pub events: Vec<Event>,
+}
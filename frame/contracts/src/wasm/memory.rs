@@ -56,6 +56,49 @@ pub fn write_region(memory: &Memory, ptr: u32, data: &[u8]) -> MemoryResult<()>
 	Ok(())
 }
 
+/// Encodes multiple sections of data into one vector.
+///
+/// Each section is suffixed by a section length encoded as big endian uint32.
+/// Using suffixes instead of prefixes allows reading sections in reverse order,
+/// such that the first element does not need to be re-allocated if the contract's
+/// data structure supports truncation (such as a Rust vector).
+///
+/// This is the same as `cosmwasm_vm::sections::encode_sections`.
+pub fn encode_sections(sections: &[Vec<u8>]) -> Vec<u8> {
+	let mut out_data =
+		Vec::with_capacity(sections.iter().map(|section| section.len() + 4).sum());
+	for section in sections {
+		out_data.extend_from_slice(section);
+		out_data.extend_from_slice(&(section.len() as u32).to_be_bytes());
+	}
+	out_data
+}
+
+/// Decodes data encoded by [`encode_sections`] into its sections.
+///
+/// Fails if `data` does not consist of well formed sections.
+pub fn decode_sections(data: &[u8]) -> MemoryResult<Vec<&[u8]>> {
+	let mut sections = Vec::new();
+	let mut remaining = data;
+	while !remaining.is_empty() {
+		let (rest, len) = remaining
+			.len()
+			.checked_sub(4)
+			.map(|split| remaining.split_at(split))
+			.ok_or(DispatchError::Other("invalid sections"))?;
+		let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+		let (rest, section) = rest
+			.len()
+			.checked_sub(len)
+			.map(|split| rest.split_at(split))
+			.ok_or(DispatchError::Other("invalid sections"))?;
+		sections.push(section);
+		remaining = rest;
+	}
+	sections.reverse();
+	Ok(sections)
+}
+
 /// Reads in a Region at ptr in wasm memory and returns a copy of it
 fn get_region(memory: &Memory, ptr: u32) -> MemoryResult<Region> {
 	memory
@@ -88,6 +131,26 @@ fn set_region(memory: &Memory, ptr: u32, data: Region) -> MemoryResult<()> {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn encode_sections_works() {
+		assert_eq!(encode_sections(&[]), Vec::<u8>::new());
+		assert_eq!(encode_sections(&[vec![], vec![]]), vec![0; 8]);
+		assert_eq!(
+			encode_sections(&[b"ab".to_vec(), b"c".to_vec()]),
+			vec![b'a', b'b', 0, 0, 0, 2, b'c', 0, 0, 0, 1]
+		);
+	}
+
+	#[test]
+	fn decode_sections_works() {
+		assert_eq!(decode_sections(&[]), Ok(vec![]));
+		assert_eq!(decode_sections(&[0; 8]), Ok(vec![&[][..], &[][..]]));
+		let sections = [b"ab".to_vec(), vec![], b"c".to_vec()];
+		assert_eq!(decode_sections(&encode_sections(&sections)), Ok(vec![&b"ab"[..], &[], b"c"]));
+		assert!(decode_sections(&[0; 3]).is_err());
+		assert!(decode_sections(&[b'a', 0, 0, 0, 2]).is_err());
+	}
+
 	#[test]
 	fn validate_region_passes_for_valid_region() {
 		// empty
@@ -21,36 +21,34 @@
 #[macro_use]
 mod env_def;
 mod code_cache;
-mod cosmwasm;
+pub(crate) mod cosmwasm;
 mod memory;
 mod prepare;
 mod runtime;
 
 #[cfg(feature = "runtime-benchmarks")]
 pub use crate::wasm::code_cache::reinstrument;
-pub use crate::wasm::runtime::{CallFlags, ReturnCode, Runtime, RuntimeCosts};
+#[cfg(test)]
+pub use crate::wasm::runtime::ReturnCode;
+pub use crate::wasm::runtime::{CallFlags, Runtime, RuntimeCosts};
 use crate::{
 	exec::{ExecError, ExecResult, Executable, ExportedFunction, Ext},
 	gas::GasMeter,
-	wasm::{
-		cosmwasm::*,
-		env_def::FunctionImplProvider,
-		memory::{read_region, write_region},
-	},
-	AccountIdOf, BalanceOf, CodeHash, CodeStorage, Config, Schedule,
+	wasm::{cosmwasm::*, env_def::FunctionImplProvider},
+	AccountIdOf, AddressCodec, BalanceOf, CodeHash, CodeStorage, Config, DenomResolver, Error,
+	Schedule,
 };
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::dispatch::{DispatchError, DispatchResult};
-use sp_core::crypto::UncheckedFrom;
-use sp_sandbox::{
-	default_executor::Memory, ReturnValue, SandboxEnvironmentBuilder, SandboxInstance,
-	SandboxMemory, Value,
-};
+use frame_support::traits::Get;
+use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
+use sp_core::{crypto::UncheckedFrom, Bytes};
+use sp_runtime::traits::{UniqueSaturatedInto, Zero};
+use sp_sandbox::{SandboxEnvironmentBuilder, SandboxInstance, SandboxMemory, Value};
 use sp_std::fmt::Debug;
 use sp_std::prelude::*;
 #[cfg(test)]
 pub use tests::MockExt;
-use wasmi::ExternVal;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
 pub enum ModuleType {
@@ -118,6 +116,46 @@ pub struct OwnerInfo<T: Config> {
 	refcount: u64,
 }
 
+impl<T: Config> OwnerInfo<T> {
+	/// The account that has deployed the code.
+	pub fn owner(&self) -> &AccountIdOf<T> {
+		&self.owner
+	}
+}
+
+/// Build the CosmWasm `Env` and `MessageInfo` of the contract currently executed by `ext`.
+fn env_and_info<E: Ext>(ext: &E) -> Result<(Env, MessageInfo), DispatchError> {
+	let encode_address = |account| {
+		<<E::T as Config>::AddressCodec as AddressCodec<E::T>>::encode(account)
+			.map(Addr::unchecked)
+			.map_err(DispatchError::Other)
+	};
+	let height: u64 = ext.block_number().unique_saturated_into();
+	// `Now` is in milliseconds whereas CosmWasm expects nanoseconds.
+	let time: u64 = (*ext.now()).unique_saturated_into();
+	let env = Env {
+		block: BlockInfo {
+			height,
+			time: Timestamp(time.saturating_mul(1_000_000).to_string()),
+			chain_id: <E::T as Config>::ChainId::get().into(),
+		},
+		transaction: None,
+		contract: ContractInfo { address: encode_address(ext.address())? },
+	};
+	let value = ext.value_transferred();
+	let native = (!value.is_zero()).then(|| Coin {
+		denom: <E::T as Config>::NativeDenom::get().into(),
+		amount: value.unique_saturated_into(),
+	});
+	let assets = ext.assets_transferred().iter().map(|(asset, amount)| Coin {
+		denom: <E::T as Config>::DenomResolver::denom(asset),
+		amount: (*amount).unique_saturated_into(),
+	});
+	let funds = native.into_iter().chain(assets).collect();
+	let info = MessageInfo { sender: encode_address(ext.caller())?, funds };
+	Ok((env, info))
+}
+
 impl ExportedFunction {
 	/// The wasm export name for the function.
 	fn identifier(&self, module_type: ModuleType) -> &str {
@@ -127,6 +165,21 @@ impl ExportedFunction {
 
 			(Self::Constructor, ModuleType::Cosmwasm) => "instantiate",
 			(Self::Call, ModuleType::Cosmwasm) => "execute",
+
+			(Self::Query, _) => "query",
+			(Self::Migrate, _) => "migrate",
+			#[cfg(feature = "stargate")]
+			(Self::IbcChannelOpen, _) => "ibc_channel_open",
+			#[cfg(feature = "stargate")]
+			(Self::IbcChannelConnect, _) => "ibc_channel_connect",
+			#[cfg(feature = "stargate")]
+			(Self::IbcChannelClose, _) => "ibc_channel_close",
+			#[cfg(feature = "stargate")]
+			(Self::IbcPacketReceive, _) => "ibc_packet_receive",
+			#[cfg(feature = "stargate")]
+			(Self::IbcPacketAck, _) => "ibc_packet_ack",
+			#[cfg(feature = "stargate")]
+			(Self::IbcPacketTimeout, _) => "ibc_packet_timeout",
 		}
 	}
 }
@@ -143,7 +196,7 @@ where
 		original_code: Vec<u8>,
 		schedule: &Schedule<T>,
 		owner: AccountIdOf<T>,
-	) -> Result<Self, &'static str> {
+	) -> Result<Self, (DispatchError, &'static str)> {
 		prepare::prepare_contract(original_code, schedule, owner)
 	}
 
@@ -161,6 +214,17 @@ where
 		code_cache::try_remove::<T>(origin, code_hash)
 	}
 
+	/// Set the metadata of the code and adjust the deposit of its owner.
+	///
+	/// An empty `metadata` removes the metadata of the code.
+	pub fn set_metadata(
+		origin: &T::AccountId,
+		code_hash: CodeHash<T>,
+		metadata: Vec<u8>,
+	) -> DispatchResult {
+		code_cache::set_metadata::<T>(origin, code_hash, metadata)
+	}
+
 	/// Returns whether there is a deposit to be payed for this module.
 	///
 	/// Returns `0` if the module is already in storage and hence no deposit will
@@ -252,6 +316,17 @@ where
 		input_data: Vec<u8>,
 	) -> ExecResult {
 		let module_type = self.module_type;
+		match (module_type, function) {
+			(ModuleType::Ink, ExportedFunction::Query) =>
+				return Err(Error::<T>::ContractNotQueryable.into()),
+			(ModuleType::Ink, ExportedFunction::Migrate) =>
+				return Err(Error::<T>::ContractNotMigratable.into()),
+			#[cfg(feature = "stargate")]
+			(ModuleType::Ink, ExportedFunction::Constructor | ExportedFunction::Call) => (),
+			#[cfg(feature = "stargate")]
+			(ModuleType::Ink, _) => return Err(Error::<T>::ContractNotIbcEnabled.into()),
+			_ => (),
+		}
 		let initial = self.initial;
 		let maximum = self.maximum;
 		// We store before executing so that the code hash is available in the constructor.
@@ -283,84 +358,68 @@ where
 
 		// Instantiate the instance from the instrumented module code and invoke the contract
 		// entrypoint.
-		let mut runtime = Runtime::new(module_type, ext, input_data, memory);
+		let read_only = *function == ExportedFunction::Query;
+		let mut runtime = Runtime::new(module_type, ext, input_data, memory, read_only);
 		let mut instance =
 			sp_sandbox::default_executor::Instance::new(&code, &imports, &mut runtime)
 				.map_err(|_| DispatchError::Other(""))?;
-		let result = {
-			let function_name = function.identifier(module_type);
-			log::debug!(target: "runtime::contracts", "Executing function {}", function_name);
-			match module_type {
-				ModuleType::Ink => instance.invoke(function_name, &[], &mut runtime),
-				ModuleType::Cosmwasm => {
-					match instance.get_export("memory").expect("impossible") {
-						ExternVal::Memory(memory) => {
-							log::debug!(target: "runtime::contracts", "Set internal memory");
-							runtime.memory = Memory { memref: memory };
-						},
-						_ => panic!("impossible"),
-					}
-					let mut builtin = |f, x| -> Result<u32, DispatchError> {
-						match instance.invoke(f, &[Value::I32(x as i32)], &mut runtime) {
-							Ok(ReturnValue::Value(Value::I32(v))) => Ok(v as u32),
-							_ => Err(DispatchError::Other("allocate failed")),
-						}
-					};
-					let env = Env {
-						block: BlockInfo {
-							height: 0,
-							time: Timestamp(
-								to_string(&0u64)
-									.map_err(|_| DispatchError::Other("marshall failed"))?,
-							),
-							chain_id: Default::default(),
-						},
-						transaction: Some(TransactionInfo { index: 0 }),
-						contract: ContractInfo { address: Addr::unchecked("321") },
-					};
-					let info = MessageInfo { sender: Addr::unchecked("123"), funds: vec![] };
-					log::debug!(target: "runtime::contracts", "Marshalling");
-					log::debug!(target: "runtime::contracts", "Env: {}", to_string(&env).map_err(|_| DispatchError::Other("marshall failed"))?);
-					log::debug!(target: "runtime::contracts", "Info: {}", to_string(&info).map_err(|_| DispatchError::Other("marshall failed"))?);
-					log::debug!(target: "runtime::contracts", "InstantiateMsg: {}", to_string(&Empty {}).map_err(|_| DispatchError::Other("marshall failed"))?);
-					let args = vec![
-						to_vec(&env).map_err(|_| DispatchError::Other("marshall failed"))?,
-						to_vec(&info).map_err(|_| DispatchError::Other("marshall failed"))?,
-						to_vec(&Empty {}).map_err(|_| DispatchError::Other("marshall failed"))?,
-					];
-					let mut arg_region_ptrs = Vec::<Value>::with_capacity(args.len());
-					for (arg, region_ptr) in args
-						.iter()
-						.map(|arg| {
-							log::debug!(target: "runtime::contracts", "Allocate");
-							Result::<_, DispatchError>::Ok((arg, builtin("allocate", arg.len())?))
+		let function_name = function.identifier(module_type);
+		log::debug!(target: "runtime::contracts", "Executing function {}", function_name);
+		match module_type {
+			ModuleType::Ink => {
+				let result = instance.invoke(function_name, &[], &mut runtime);
+				log::debug!(target: "runtime::contracts", "Call completed {:?}", result);
+				runtime.to_execution_result(result)
+			},
+			ModuleType::Cosmwasm => {
+				// CosmWasm contracts define their own memory instead of importing ours.
+				runtime.memory = instance
+					.get_memory("memory")
+					.ok_or(Error::<T>::CodeRejected)?;
+				log::debug!(target: "runtime::contracts", "Set internal memory");
+				runtime.instance = Some(instance.clone());
+				let (env, info) = env_and_info(runtime.ext())?;
+				// The message is the JSON payload of the extrinsic, passed through verbatim.
+				let message = runtime.take_input_data().unwrap_or_default();
+				log::debug!(target: "runtime::contracts", "Env: {}", to_string(&env)?);
+				// Only instantiations and calls receive a `MessageInfo`.
+				let args = match function {
+					ExportedFunction::Constructor | ExportedFunction::Call => {
+						log::debug!(target: "runtime::contracts", "Info: {}", to_string(&info)?);
+						vec![to_vec(&env)?, to_vec(&info)?, message]
+					},
+					_ => vec![to_vec(&env)?, message],
+				};
+				let mut arg_region_ptrs = Vec::<Value>::with_capacity(args.len());
+				for arg in args.iter() {
+					let region_ptr = runtime.write_to_contract(arg)?;
+					arg_region_ptrs.push(Value::I32(region_ptr as i32));
+				}
+				let result = instance.invoke(function_name, &arg_region_ptrs, &mut runtime);
+				log::debug!(target: "runtime::contracts", "Call completed {:?}", result);
+				let response_limit = match function {
+					ExportedFunction::Constructor => deserialization_limits::RESULT_INSTANTIATE,
+					ExportedFunction::Call => deserialization_limits::RESULT_EXECUTE,
+					ExportedFunction::Migrate => deserialization_limits::RESULT_MIGRATE,
+					ExportedFunction::Query => {
+						let data: Binary = runtime
+							.cosmwasm_result(result, deserialization_limits::RESULT_QUERY)?;
+						return Ok(ExecReturnValue {
+							flags: ReturnFlags::empty(),
+							data: Bytes(data.0),
 						})
-						.collect::<Result<Vec<_>, _>>()?
-					{
-						write_region(&runtime.memory, region_ptr, &arg)?;
-						arg_region_ptrs.push(Value::I32(region_ptr as i32));
-					}
-					log::debug!(target: "runtime::contracts", "Instantiate");
-					let result = instance.invoke(function_name, &arg_region_ptrs, &mut runtime);
-					match result {
-						Ok(ReturnValue::Value(Value::I32(response_ptr))) => {
-							log::debug!(target: "runtime::contracts", "Instantiate done {:?}", result);
-							let region = read_region(
-								&runtime.memory,
-								response_ptr as u32,
-								deserialization_limits::RESULT_INSTANTIATE,
-							)?;
-							let response = from_slice::<ContractResult<Response<Empty>>>(&region)?;
-							log::debug!(target: "runtime::contracts", "Reponse: {:?}", response);
-						},
-						_ => {},
-					}
-					result
-				},
-			}
-		};
-		log::debug!(target: "runtime::contracts", "Call completed {:?}", result);
-		runtime.to_execution_result(result)
+					},
+					#[cfg(feature = "stargate")]
+					_ => return runtime.ibc_result(function, result),
+				};
+				let response = runtime.cosmwasm_result(result, response_limit)?;
+				let data = runtime.dispatch_messages(response)?;
+				Ok(ExecReturnValue {
+					flags: ReturnFlags::empty(),
+					data: Bytes(data.map(|data| data.0).unwrap_or_default()),
+				})
+			},
+		}
 	}
 
 	fn code_hash(&self) -> &CodeHash<T> {
@@ -377,12 +436,13 @@ mod tests {
 	use super::*;
 	use crate::{
 		exec::{
-			AccountIdOf, BlockNumberOf, ErrorOrigin, ExecError, Executable, Ext, SeedOf, StorageKey,
+			AccountIdOf, AddressDerivation, BlockNumberOf, ErrorOrigin, EventAttributes, ExecError,
+			Executable, Ext, SeedOf, StorageKey,
 		},
 		gas::GasMeter,
 		storage::WriteOutcome,
-		tests::{Call, Test, ALICE, BOB},
-		BalanceOf, CodeHash, Error, Pallet as Contracts,
+		tests::{Call, Test, ALICE, BOB, CHARLIE},
+		AddressCodec, BalanceOf, CodeHash, CodeId, Error, Pallet as Contracts, Ss58AddressCodec,
 	};
 	use assert_matches::assert_matches;
 	use frame_support::{assert_ok, dispatch::DispatchResultWithPostInfo, weights::Weight};
@@ -394,7 +454,11 @@ mod tests {
 	use std::{
 		borrow::BorrowMut,
 		cell::RefCell,
-		collections::hash_map::{Entry, HashMap},
+		collections::{
+			btree_map::{self, BTreeMap},
+			hash_map::{Entry, HashMap},
+		},
+		ops::Bound,
 	};
 
 	#[derive(Debug, PartialEq, Eq)]
@@ -404,6 +468,10 @@ mod tests {
 		data: Vec<u8>,
 		gas_left: u64,
 		salt: Vec<u8>,
+		admin: Option<AccountIdOf<Test>>,
+		label: Vec<u8>,
+		assets: Vec<(u32, u64)>,
+		derivation: AddressDerivation,
 	}
 
 	#[derive(Debug, PartialEq, Eq)]
@@ -423,6 +491,7 @@ mod tests {
 		value: u64,
 		data: Vec<u8>,
 		allows_reentry: bool,
+		assets: Vec<(u32, u64)>,
 	}
 
 	#[derive(Debug, PartialEq, Eq)]
@@ -433,13 +502,24 @@ mod tests {
 
 	pub struct MockExt {
 		storage: HashMap<StorageKey, Vec<u8>>,
+		raw_storage: BTreeMap<Vec<u8>, Vec<u8>>,
 		instantiates: Vec<InstantiateEntry>,
 		terminations: Vec<TerminationEntry>,
 		calls: Vec<CallEntry>,
+		queries: Vec<(AccountIdOf<Test>, Vec<u8>)>,
 		code_calls: Vec<CallCodeEntry>,
 		transfers: Vec<TransferEntry>,
+		// (to, asset, amount)
+		asset_transfers: Vec<(AccountIdOf<Test>, u32, u64)>,
+		assets_transferred: Vec<(u32, u64)>,
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
+		cosmwasm_events: Vec<(Vec<u8>, EventAttributes)>,
+		// (channel_id, data, timeout)
+		#[cfg(feature = "stargate")]
+		ibc_packets: Vec<(String, Vec<u8>, u64)>,
+		#[cfg(feature = "stargate")]
+		ibc_closed_channels: Vec<String>,
 		runtime_calls: RefCell<Vec<Call>>,
 		schedule: Schedule<Test>,
 		gas_meter: GasMeter<Test>,
@@ -458,12 +538,21 @@ mod tests {
 			Self {
 				code_hashes: Default::default(),
 				storage: Default::default(),
+				raw_storage: Default::default(),
 				instantiates: Default::default(),
 				terminations: Default::default(),
 				calls: Default::default(),
+				queries: Default::default(),
 				code_calls: Default::default(),
 				transfers: Default::default(),
+				asset_transfers: Default::default(),
+				assets_transferred: Default::default(),
 				events: Default::default(),
+				cosmwasm_events: Default::default(),
+				#[cfg(feature = "stargate")]
+				ibc_packets: Default::default(),
+				#[cfg(feature = "stargate")]
+				ibc_closed_channels: Default::default(),
 				runtime_calls: Default::default(),
 				schedule: Default::default(),
 				gas_meter: GasMeter::new(10_000_000_000),
@@ -481,10 +570,22 @@ mod tests {
 			_gas_limit: Weight,
 			to: AccountIdOf<Self::T>,
 			value: u64,
+			assets: Vec<(u32, u64)>,
 			data: Vec<u8>,
 			allows_reentry: bool,
 		) -> Result<ExecReturnValue, ExecError> {
-			self.calls.push(CallEntry { to, value, data, allows_reentry });
+			self.calls.push(CallEntry { to, value, data, allows_reentry, assets });
+			// Pretend that the callee emitted an event.
+			self.cosmwasm_events.push((b"wasm-called".to_vec(), vec![]));
+			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: call_return_data() })
+		}
+		fn query(
+			&mut self,
+			_gas_limit: Weight,
+			to: AccountIdOf<Self::T>,
+			data: Vec<u8>,
+		) -> Result<ExecReturnValue, ExecError> {
+			self.queries.push((to, data));
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: call_return_data() })
 		}
 		fn delegate_call(
@@ -500,8 +601,12 @@ mod tests {
 			gas_limit: Weight,
 			code_hash: CodeHash<Test>,
 			value: u64,
+			assets: Vec<(u32, u64)>,
 			data: Vec<u8>,
 			salt: &[u8],
+			admin: Option<AccountIdOf<Self::T>>,
+			label: Vec<u8>,
+			derivation: AddressDerivation,
 		) -> Result<(AccountIdOf<Self::T>, ExecReturnValue), ExecError> {
 			self.instantiates.push(InstantiateEntry {
 				code_hash: code_hash.clone(),
@@ -509,6 +614,10 @@ mod tests {
 				data: data.to_vec(),
 				gas_left: gas_limit,
 				salt: salt.to_vec(),
+				admin,
+				label,
+				assets,
+				derivation,
 			});
 			Ok((
 				Contracts::<Test>::contract_address(&ALICE, &code_hash, salt),
@@ -523,6 +632,17 @@ mod tests {
 			self.transfers.push(TransferEntry { to: to.clone(), value });
 			Ok(())
 		}
+		fn transfer_funds(
+			&mut self,
+			to: &AccountIdOf<Self::T>,
+			value: u64,
+			assets: Vec<(u32, u64)>,
+		) -> Result<(), DispatchError> {
+			self.transfers.push(TransferEntry { to: to.clone(), value });
+			self.asset_transfers
+				.extend(assets.into_iter().map(|(asset, amount)| (to.clone(), asset, amount)));
+			Ok(())
+		}
 		fn terminate(&mut self, beneficiary: &AccountIdOf<Self::T>) -> Result<(), DispatchError> {
 			self.terminations.push(TerminationEntry { beneficiary: beneficiary.clone() });
 			Ok(())
@@ -552,6 +672,41 @@ mod tests {
 			}
 			Ok(result)
 		}
+		fn get_storage_raw(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+			self.raw_storage.get(key).cloned()
+		}
+		fn get_storage_raw_of(
+			&mut self,
+			_address: &AccountIdOf<Self::T>,
+			key: &[u8],
+		) -> Option<Vec<u8>> {
+			self.raw_storage.get(key).cloned()
+		}
+		fn next_storage_key_raw(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+			self.raw_storage
+				.range::<[u8], _>((Bound::Excluded(key), Bound::Unbounded))
+				.next()
+				.map(|(key, _)| key.clone())
+		}
+		fn set_storage_raw(
+			&mut self,
+			key: Vec<u8>,
+			value: Option<Vec<u8>>,
+			take_old: bool,
+		) -> Result<WriteOutcome, DispatchError> {
+			let entry = self.raw_storage.entry(key.clone());
+			let result = match (entry, take_old) {
+				(btree_map::Entry::Vacant(_), _) => WriteOutcome::New,
+				(btree_map::Entry::Occupied(entry), false) => {
+					WriteOutcome::Overwritten(entry.remove().len() as u32)
+				},
+				(btree_map::Entry::Occupied(entry), true) => WriteOutcome::Taken(entry.remove()),
+			};
+			if let Some(value) = value {
+				self.raw_storage.insert(key, value);
+			}
+			Ok(result)
+		}
 		fn caller(&self) -> &AccountIdOf<Self::T> {
 			&ALICE
 		}
@@ -561,6 +716,18 @@ mod tests {
 		fn code_hash(&self, _address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>> {
 			Some(H256::from_slice(&[0x11; 32]))
 		}
+		fn code_hash_by_id(&self, code_id: CodeId) -> Option<CodeHash<Self::T>> {
+			Some(H256::from_low_u64_be(code_id))
+		}
+		fn code_info(
+			&self,
+			_code_hash: &CodeHash<Self::T>,
+		) -> Option<(CodeId, AccountIdOf<Test>, [u8; 32])> {
+			Some((42, ALICE, [0x11; 32]))
+		}
+		fn contract_admin(&self, _address: &AccountIdOf<Self::T>) -> Option<AccountIdOf<Self::T>> {
+			Some(CHARLIE)
+		}
 		fn own_code_hash(&mut self) -> &CodeHash<Self::T> {
 			const HASH: H256 = H256::repeat_byte(0x10);
 			&HASH
@@ -574,9 +741,18 @@ mod tests {
 		fn balance(&self) -> u64 {
 			228
 		}
+		fn balance_of(&self, _address: &AccountIdOf<Self::T>) -> u64 {
+			100
+		}
+		fn asset_balance_of(&self, _address: &AccountIdOf<Self::T>, asset: u32) -> u64 {
+			asset.into()
+		}
 		fn value_transferred(&self) -> u64 {
 			1337
 		}
+		fn assets_transferred(&self) -> &[(u32, u64)] {
+			&self.assets_transferred
+		}
 		fn now(&self) -> &u64 {
 			&1111
 		}
@@ -589,6 +765,27 @@ mod tests {
 		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
 			self.events.push((topics, data))
 		}
+		fn deposit_cosmwasm_event(&mut self, ty: Vec<u8>, attributes: EventAttributes) {
+			self.cosmwasm_events.push((ty, attributes))
+		}
+		fn cosmwasm_events(&self) -> &[(Vec<u8>, EventAttributes)] {
+			&self.cosmwasm_events
+		}
+		#[cfg(feature = "stargate")]
+		fn ibc_send_packet(
+			&mut self,
+			channel_id: &str,
+			data: Vec<u8>,
+			timeout: u64,
+		) -> Result<u64, DispatchError> {
+			self.ibc_packets.push((channel_id.to_string(), data, timeout));
+			Ok(self.ibc_packets.len() as u64)
+		}
+		#[cfg(feature = "stargate")]
+		fn ibc_close_channel(&mut self, channel_id: &str) -> Result<(), DispatchError> {
+			self.ibc_closed_channels.push(channel_id.to_string());
+			Ok(())
+		}
 		fn block_number(&self) -> u64 {
 			121
 		}
@@ -731,7 +928,13 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 6, data: vec![1, 2, 3, 4], allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+				assets: vec![],
+			}]
 		);
 	}
 
@@ -832,7 +1035,13 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: false }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: false,
+				assets: vec![],
+			}]
 		);
 	}
 
@@ -887,7 +1096,13 @@ mod tests {
 		assert_eq!(result.data.0, input);
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: true,
+				assets: vec![],
+			}]
 		);
 	}
 
@@ -934,7 +1149,13 @@ mod tests {
 		assert_eq!(result.data, call_return_data());
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: false }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: false,
+				assets: vec![],
+			}]
 		);
 	}
 
@@ -1068,10 +1289,16 @@ mod tests {
 				data,
 				gas_left: _,
 				salt,
+				admin: None,
+				label,
+				assets,
+				derivation: AddressDerivation::Generator,
 			}] if
 				code_hash == &[0x11; 32].into() &&
 				data == &vec![1, 2, 3, 4] &&
-				salt == &vec![0x42, 0x43, 0x44, 0x45]
+				salt == &vec![0x42, 0x43, 0x44, 0x45] &&
+				label.is_empty() &&
+				assets.is_empty()
 		);
 	}
 
@@ -1159,7 +1386,13 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 6, data: vec![1, 2, 3, 4], allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+				assets: vec![],
+			}]
 		);
 	}
 
@@ -2705,4 +2938,1100 @@ mod tests {
 
 		assert_eq!(mock_ext.code_hashes.pop().unwrap(), H256::from_slice(&[17u8; 32]));
 	}
+
+	/// Builds a minimal CosmWasm contract importing `imports` whose `execute` entrypoint runs
+	/// `body` before returning an empty successful response.
+	///
+	/// `allocate` is a bump allocator starting at 0x1000 which never frees memory.
+	fn cosmwasm_code(imports: &str, body: &str) -> String {
+		cosmwasm_code_with_query(imports, body, "")
+	}
+
+	/// Same as [`cosmwasm_code`] but the `query` entrypoint runs `query_body` first.
+	fn cosmwasm_code_with_query(imports: &str, body: &str, query_body: &str) -> String {
+		format!(
+			r#"
+(module
+	{}
+	(memory (export "memory") 1)
+	(global $heap (mut i32) (i32.const 0x1000))
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+	(func (export "allocate") (param $len i32) (result i32)
+		(local $ptr i32)
+		(set_local $ptr (get_global $heap))
+		(i32.store (get_local $ptr) (i32.add (get_local $ptr) (i32.const 12)))
+		(i32.store (i32.add (get_local $ptr) (i32.const 4)) (get_local $len))
+		(i32.store (i32.add (get_local $ptr) (i32.const 8)) (i32.const 0))
+		(set_global $heap
+			(i32.add (get_local $ptr) (i32.add (get_local $len) (i32.const 12)))
+		)
+		(get_local $ptr)
+	)
+	(func (export "deallocate") (param i32))
+	(func (export "interface_version_8"))
+	(func (export "instantiate") (param i32 i32 i32) (result i32)
+		(i32.const 0x1F0)
+	)
+	(func (export "execute") (param i32 i32 i32) (result i32)
+		{}
+		(i32.const 0x1F0)
+	)
+	(func (export "query") (param i32 i32) (result i32)
+		{}
+		(i32.const 0x1F0)
+	)
+
+	;; Region of the response.
+	(data (i32.const 0x1F0) "\00\02\00\00\3e\00\00\00\3e\00\00\00")
+	(data (i32.const 0x200) "{{\"ok\":{{\"messages\":[],\"attributes\":[],\"events\":[],\"data\":null}}}}")
+)
+"#,
+			imports, body, query_body
+		)
+	}
+
+	#[test]
+	fn cosmwasm_db_write_and_read() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "db_read" (func $db_read (param i32) (result i32)))
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+
+	;; Region of the key "foo".
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	;; Region of the value "bar".
+	(data (i32.const 0x20) "\04\01\00\00\03\00\00\00\03\00\00\00")
+	;; Region of the missing key "baz".
+	(data (i32.const 0x30) "\08\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x100) "foo\00bar\00baz")
+"#,
+			r#"
+		(local $value_ptr i32)
+		(call $db_write (i32.const 0x10) (i32.const 0x20))
+		(set_local $value_ptr (call $db_read (i32.const 0x10)))
+		(call $assert (i32.ne (get_local $value_ptr) (i32.const 0)))
+		;; length of the value
+		(call $assert
+			(i32.eq (i32.load (i32.add (get_local $value_ptr) (i32.const 8))) (i32.const 3))
+		)
+		;; first byte of the value
+		(call $assert
+			(i32.eq (i32.load8_u (i32.load (get_local $value_ptr))) (i32.const 0x62))
+		)
+		;; missing key
+		(call $assert (i32.eqz (call $db_read (i32.const 0x30))))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(mock_ext.raw_storage.get(&b"foo"[..]), Some(&b"bar".to_vec()));
+	}
+
+	#[test]
+	fn cosmwasm_db_remove() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "db_read" (func $db_read (param i32) (result i32)))
+	(import "env" "db_remove" (func $db_remove (param i32)))
+
+	;; Region of the key "foo".
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x100) "foo")
+"#,
+			r#"
+		(call $assert (i32.ne (call $db_read (i32.const 0x10)) (i32.const 0)))
+		(call $db_remove (i32.const 0x10))
+		(call $assert (i32.eqz (call $db_read (i32.const 0x10))))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		mock_ext.raw_storage.insert(b"foo".to_vec(), b"bar".to_vec());
+		mock_ext.raw_storage.insert(b"fop".to_vec(), b"baz".to_vec());
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(mock_ext.raw_storage.get(&b"foo"[..]), None);
+		assert_eq!(mock_ext.raw_storage.get(&b"fop"[..]), Some(&b"baz".to_vec()));
+	}
+	const CODE_COSMWASM_DB_SCAN_IMPORTS: &str = r#"
+	(import "env" "db_scan" (func $db_scan (param i32 i32 i32) (result i32)))
+	(import "env" "db_next" (func $db_next (param i32) (result i32)))
+
+	;; Returns the first byte of the key of an item returned by `db_next` or 0 if the
+	;; iterator is exhausted.
+	(func $key_byte (param $item i32) (result i32)
+		(if (result i32) (i32.eq (i32.load (i32.add (get_local $item) (i32.const 8))) (i32.const 8))
+			(then (i32.const 0))
+			(else (i32.load8_u (i32.load (get_local $item))))
+		)
+	)
+
+	;; Region of the key "b".
+	(data (i32.const 0x10) "\00\01\00\00\01\00\00\00\01\00\00\00")
+	;; Region of the key "d".
+	(data (i32.const 0x20) "\01\01\00\00\01\00\00\00\01\00\00\00")
+	(data (i32.const 0x100) "bd")
+"#;
+
+	fn mock_ext_with_raw_storage() -> MockExt {
+		let mut mock_ext = MockExt::default();
+		for (key, value) in [(b"a", b"1"), (b"b", b"2"), (b"c", b"3"), (b"d", b"4")] {
+			mock_ext.raw_storage.insert(key.to_vec(), value.to_vec());
+		}
+		mock_ext
+	}
+
+	#[test]
+	fn cosmwasm_db_scan_ascending() {
+		let code = cosmwasm_code(
+			CODE_COSMWASM_DB_SCAN_IMPORTS,
+			r#"
+		(local $iterator i32)
+		(local $item i32)
+		(set_local $iterator (call $db_scan (i32.const 0x10) (i32.const 0x20) (i32.const 1)))
+		(set_local $item (call $db_next (get_local $iterator)))
+		(call $assert (i32.eq (call $key_byte (get_local $item)) (i32.const 0x62)))
+		;; the value follows the key and its length
+		(call $assert
+			(i32.eq
+				(i32.load8_u (i32.add (i32.load (get_local $item)) (i32.const 5)))
+				(i32.const 0x32)
+			)
+		)
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x63)))
+		(call $assert (i32.eqz (call $key_byte (call $db_next (get_local $iterator)))))
+		(call $assert (i32.eqz (call $key_byte (call $db_next (get_local $iterator)))))
+"#,
+		);
+		execute(&code, vec![], mock_ext_with_raw_storage()).unwrap();
+	}
+
+	#[test]
+	fn cosmwasm_db_scan_descending() {
+		let code = cosmwasm_code(
+			CODE_COSMWASM_DB_SCAN_IMPORTS,
+			r#"
+		(local $iterator i32)
+		(set_local $iterator (call $db_scan (i32.const 0) (i32.const 0) (i32.const 2)))
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x64)))
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x63)))
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x62)))
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x61)))
+		(call $assert (i32.eqz (call $key_byte (call $db_next (get_local $iterator)))))
+
+		;; a second iterator does not interfere with the first one
+		(set_local $iterator (call $db_scan (i32.const 0x10) (i32.const 0) (i32.const 2)))
+		(call $assert (i32.eq (get_local $iterator) (i32.const 1)))
+		(call $assert (i32.eq (call $key_byte (call $db_next (get_local $iterator))) (i32.const 0x64)))
+		(call $assert (i32.eqz (call $key_byte (call $db_next (i32.const 0)))))
+"#,
+		);
+		execute(&code, vec![], mock_ext_with_raw_storage()).unwrap();
+	}
+
+	#[test]
+	fn cosmwasm_db_scan_rejects_invalid_order() {
+		let code = cosmwasm_code(
+			CODE_COSMWASM_DB_SCAN_IMPORTS,
+			r#"
+		(drop (call $db_scan (i32.const 0) (i32.const 0) (i32.const 3)))
+"#,
+		);
+		assert_eq!(
+			execute(&code, vec![], mock_ext_with_raw_storage()),
+			Err(ExecError {
+				error: DispatchError::Other("invalid order"),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+	}
+	#[test]
+	fn cosmwasm_addr_conversions() {
+		let address = <Ss58AddressCodec as AddressCodec<Test>>::encode(&ALICE).unwrap();
+		let imports = format!(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+	(import "env" "addr_validate" (func $addr_validate (param i32) (result i32)))
+	(import "env" "addr_canonicalize" (func $addr_canonicalize (param i32 i32) (result i32)))
+	(import "env" "addr_humanize" (func $addr_humanize (param i32 i32) (result i32)))
+
+	;; Region of the address of ALICE.
+	(data (i32.const 0x10) "\00\01\00\00\{len:02x}\00\00\00\{len:02x}\00\00\00")
+	;; Region receiving the canonical address.
+	(data (i32.const 0x20) "\00\03\00\00\40\00\00\00\00\00\00\00")
+	;; Region receiving the human readable address.
+	(data (i32.const 0x30) "\00\04\00\00\40\00\00\00\00\00\00\00")
+	;; Region of the invalid address "foo".
+	(data (i32.const 0x40) "\80\01\00\00\03\00\00\00\03\00\00\00")
+	;; Regions of the keys "human", "error" and "error2".
+	(data (i32.const 0x50) "\90\01\00\00\05\00\00\00\05\00\00\00")
+	(data (i32.const 0x60) "\a0\01\00\00\05\00\00\00\05\00\00\00")
+	(data (i32.const 0x70) "\b0\01\00\00\06\00\00\00\06\00\00\00")
+	(data (i32.const 0x100) "{address}")
+	(data (i32.const 0x180) "foo")
+	(data (i32.const 0x190) "human")
+	(data (i32.const 0x1a0) "error")
+	(data (i32.const 0x1b0) "error2")
+"#,
+			address = address,
+			len = address.len(),
+		);
+		let code = cosmwasm_code(
+			&imports,
+			r#"
+		(call $assert (i32.eqz (call $addr_validate (i32.const 0x10))))
+		(call $assert (i32.eqz (call $addr_canonicalize (i32.const 0x10) (i32.const 0x20))))
+		(call $assert (i32.eq (i32.load (i32.const 0x28)) (i32.const 32)))
+		(call $assert (i32.eq (i32.load8_u (i32.const 0x300)) (i32.const 1)))
+		(call $assert (i32.eqz (call $addr_humanize (i32.const 0x20) (i32.const 0x30))))
+		(call $db_write (i32.const 0x50) (i32.const 0x30))
+		(call $db_write (i32.const 0x60) (call $addr_validate (i32.const 0x40)))
+		(call $db_write (i32.const 0x70) (call $addr_humanize (i32.const 0x40) (i32.const 0x30)))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(mock_ext.raw_storage.get(&b"human"[..]), Some(&address.into_bytes()));
+		assert_eq!(
+			mock_ext.raw_storage.get(&b"error"[..]),
+			Some(
+				&<Ss58AddressCodec as AddressCodec<Test>>::decode("foo")
+					.unwrap_err()
+					.as_bytes()
+					.to_vec()
+			)
+		);
+		assert_eq!(
+			mock_ext.raw_storage.get(&b"error2"[..]),
+			Some(&b"Invalid canonical address length".to_vec())
+		);
+	}
+
+	#[test]
+	fn cosmwasm_env_and_info() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+
+	;; Regions of the keys "env" and "info".
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x20) "\04\01\00\00\04\00\00\00\04\00\00\00")
+	(data (i32.const 0x100) "env")
+	(data (i32.const 0x104) "info")
+"#,
+			r#"
+		(call $db_write (i32.const 0x10) (get_local 0))
+		(call $db_write (i32.const 0x20) (get_local 1))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		mock_ext.assets_transferred = vec![(7, 8)];
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		let encode = |account| <Ss58AddressCodec as AddressCodec<Test>>::encode(account).unwrap();
+		let env: Env = serde_json::from_slice(&mock_ext.raw_storage[&b"env"[..]]).unwrap();
+		assert_eq!(
+			env,
+			Env {
+				block: BlockInfo {
+					height: 121,
+					time: Timestamp("1111000000".into()),
+					chain_id: "substrate-testnet".into(),
+				},
+				transaction: None,
+				contract: ContractInfo { address: Addr::unchecked(encode(&BOB)) },
+			}
+		);
+		assert_eq!(
+			std::str::from_utf8(&mock_ext.raw_storage[&b"info"[..]]).unwrap(),
+			format!(
+				r#"{{"sender":"{}","funds":[{}]}}"#,
+				encode(&ALICE),
+				r#"{"denom":"unit","amount":"1337"},{"denom":"asset/7","amount":"8"}"#
+			),
+		);
+	}
+
+	#[test]
+	fn cosmwasm_message_is_input_data() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+
+	;; Region of the key "msg".
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x100) "msg")
+"#,
+			r#"
+		(call $db_write (i32.const 0x10) (get_local 2))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		execute(&code, br#"{"transfer":{"amount":"10"}}"#.to_vec(), &mut mock_ext).unwrap();
+		assert_eq!(
+			mock_ext.raw_storage.get(&b"msg"[..]),
+			Some(&br#"{"transfer":{"amount":"10"}}"#.to_vec())
+		);
+	}
+
+	#[test]
+	fn cosmwasm_response_data_is_returned() {
+		let response = br#"{"ok":{"messages":[],"attributes":[],"events":[],"data":"aGVsbG8="}}"#;
+		let code = cosmwasm_code(
+			&format!(
+				r#"
+	;; Region of the response.
+	(data (i32.const 0x10) "{}")
+	(data (i32.const 0x400) "{}")
+"#,
+				wat_region(0x400, response.len()),
+				wat_escape(response),
+			),
+			r#"
+		(return (i32.const 0x10))
+"#,
+		);
+		let output = execute(&code, vec![], MockExt::default()).unwrap();
+		assert_eq!(
+			output,
+			ExecReturnValue { flags: ReturnFlags::empty(), data: Bytes(b"hello".to_vec()) }
+		);
+	}
+
+	#[test]
+	fn cosmwasm_debug() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "debug" (func $debug (param i32)))
+
+	;; Region of the message "Hello".
+	(data (i32.const 0x10) "\00\01\00\00\05\00\00\00\05\00\00\00")
+	(data (i32.const 0x100) "Hello")
+"#,
+			r#"
+		(call $debug (i32.const 0x10))
+		(call $debug (i32.const 0x10))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(&code, vec![], &mut mock_ext));
+		assert_eq!(std::str::from_utf8(&mock_ext.debug_buffer).unwrap(), "Hello\nHello\n");
+	}
+
+	#[test]
+	fn cosmwasm_debug_invalid_utf8_is_replaced() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "debug" (func $debug (param i32)))
+
+	;; Region of the message "Hello" followed by an invalid UTF-8 byte.
+	(data (i32.const 0x10) "\00\01\00\00\06\00\00\00\06\00\00\00")
+	(data (i32.const 0x100) "Hello\ff")
+"#,
+			r#"
+		(call $debug (i32.const 0x10))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(&code, vec![], &mut mock_ext));
+		assert_eq!(std::str::from_utf8(&mock_ext.debug_buffer).unwrap(), "Hello\u{FFFD}\n");
+	}
+
+	#[test]
+	fn cosmwasm_abort() {
+		let code = cosmwasm_code(
+			r#"
+	(import "env" "abort" (func $abort (param i32)))
+
+	;; Region of the message "panicked".
+	(data (i32.const 0x10) "\00\01\00\00\08\00\00\00\08\00\00\00")
+	(data (i32.const 0x100) "panicked")
+"#,
+			r#"
+		(call $abort (i32.const 0x10))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		let result = execute(&code, vec![], &mut mock_ext);
+		assert_eq!(result.unwrap_err().error, Error::<Test>::ContractTrapped.into());
+		assert_eq!(std::str::from_utf8(&mock_ext.debug_buffer).unwrap(), "panicked\n");
+	}
+
+	#[test]
+	fn cosmwasm_contract_error_is_exec_error() {
+		let code = cosmwasm_code(
+			r#"
+	;; Region of the response.
+	(data (i32.const 0x10) "\00\01\00\00\10\00\00\00\10\00\00\00")
+	(data (i32.const 0x100) "{\"error\":\"boom\"}")
+"#,
+			r#"
+		(return (i32.const 0x10))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		assert_eq!(
+			execute(&code, vec![], &mut mock_ext),
+			Err(ExecError {
+				error: Error::<Test>::CosmwasmContractError.into(),
+				origin: ErrorOrigin::Callee,
+			})
+		);
+		assert_eq!(mock_ext.debug_buffer, b"boom".to_vec());
+	}
+
+	#[test]
+	fn cosmwasm_invalid_response_is_rejected() {
+		let code = cosmwasm_code(
+			r#"
+	;; Region of the response.
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x100) "foo")
+"#,
+			r#"
+		(return (i32.const 0x10))
+"#,
+		);
+		assert_eq!(
+			execute(&code, vec![], MockExt::default()),
+			Err(DispatchError::Other("couldn't deserialize").into())
+		);
+	}
+
+	fn query<E: BorrowMut<MockExt>>(wat: &str, input_data: Vec<u8>, mut ext: E) -> ExecResult {
+		let wasm = wat::parse_str(wat).unwrap();
+		let schedule = crate::Schedule::default();
+		let executable =
+			PrefabWasmModule::<<MockExt as Ext>::T>::from_code(wasm, &schedule, ALICE).unwrap();
+		executable.execute(ext.borrow_mut(), &ExportedFunction::Query, input_data)
+	}
+
+	#[test]
+	fn cosmwasm_query() {
+		let code = cosmwasm_code_with_query(
+			r#"
+	;; Region of the query result.
+	(data (i32.const 0x10) "\00\01\00\00\0d\00\00\00\0d\00\00\00")
+	(data (i32.const 0x100) "{\"ok\":\"aGk=\"}")
+"#,
+			"",
+			r#"
+		;; the message is passed as second argument
+		(call $assert (i32.eq (i32.load (i32.add (get_local 1) (i32.const 8))) (i32.const 2)))
+		(return (i32.const 0x10))
+"#,
+		);
+		let output = query(&code, b"{}".to_vec(), MockExt::default()).unwrap();
+		assert_eq!(
+			output,
+			ExecReturnValue { flags: ReturnFlags::empty(), data: Bytes(b"hi".to_vec()) }
+		);
+	}
+
+	#[test]
+	fn cosmwasm_query_cannot_change_state() {
+		let code = cosmwasm_code_with_query(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+
+	;; Region of the key "foo".
+	(data (i32.const 0x10) "\00\01\00\00\03\00\00\00\03\00\00\00")
+	(data (i32.const 0x100) "foo")
+"#,
+			"",
+			r#"
+		(call $db_write (i32.const 0x10) (i32.const 0x10))
+"#,
+		);
+		let mut mock_ext = MockExt::default();
+		assert_eq!(
+			query(&code, b"{}".to_vec(), &mut mock_ext),
+			Err(Error::<Test>::StateChangeDenied.into())
+		);
+		assert!(mock_ext.raw_storage.is_empty());
+	}
+
+	#[test]
+	fn ink_contracts_cannot_be_queried() {
+		let wasm = wat::parse_str(CODE_TRANSFER).unwrap();
+		let schedule = crate::Schedule::default();
+		let executable =
+			PrefabWasmModule::<<MockExt as Ext>::T>::from_code(wasm, &schedule, ALICE).unwrap();
+		assert_eq!(
+			executable.execute(&mut MockExt::default(), &ExportedFunction::Query, vec![]),
+			Err(Error::<Test>::ContractNotQueryable.into())
+		);
+	}
+
+	#[test]
+	fn cosmwasm_query_chain() {
+		let encode = |account| <Ss58AddressCodec as AddressCodec<Test>>::encode(account).unwrap();
+		let bob = encode(&BOB);
+		let requests = [
+			format!(r#"{{"bank":{{"balance":{{"address":"{}","denom":"unit"}}}}}}"#, bob),
+			format!(r#"{{"bank":{{"balance":{{"address":"{}","denom":"foo"}}}}}}"#, bob),
+			format!(r#"{{"bank":{{"balance":{{"address":"{}","denom":"asset/9"}}}}}}"#, bob),
+			format!(r#"{{"bank":{{"all_balances":{{"address":"{}"}}}}}}"#, bob),
+			format!(r#"{{"wasm":{{"smart":{{"contract_addr":"{}","msg":"e30="}}}}}}"#, bob),
+			format!(r#"{{"wasm":{{"raw":{{"contract_addr":"{}","key":"Zm9v"}}}}}}"#, bob),
+			format!(r#"{{"wasm":{{"contract_info":{{"contract_addr":"{}"}}}}}}"#, bob),
+			r#"{"wasm":{"code_info":{"code_id":42}}}"#.into(),
+			r#"{"wasm":{"raw":{"contract_addr":"nope","key":"Zm9v"}}}"#.into(),
+			r#"{"staking":{}}"#.into(),
+		];
+		// Each request is answered in storage under its index.
+		let mut imports = String::from(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+	(import "env" "query_chain" (func $query_chain (param i32) (result i32)))
+"#,
+		);
+		let mut body = String::new();
+		let mut offset = 0x400;
+		for (i, request) in requests.iter().enumerate() {
+			let (key_region, request_region) = (0x10 + 0x18 * i, 0x1C + 0x18 * i);
+			imports.push_str(&format!(
+				"\t(data (i32.const {}) \"{}\")\n\t(data (i32.const {}) \"{}\")\n",
+				key_region,
+				wat_region(offset, 1),
+				request_region,
+				wat_region(offset + 1, request.len()),
+			));
+			imports.push_str(&format!(
+				"\t(data (i32.const {}) \"{}{}\")\n",
+				offset,
+				i,
+				wat_escape(request.as_bytes())
+			));
+			body.push_str(&format!(
+				"\t\t(call $db_write (i32.const {}) (call $query_chain (i32.const {})))\n",
+				key_region, request_region
+			));
+			offset += 1 + request.len() as u32;
+		}
+		let code = cosmwasm_code(&imports, &body);
+		let mut mock_ext = MockExt::default();
+		mock_ext.raw_storage.insert(b"foo".to_vec(), b"bar".to_vec());
+		execute(&code, vec![], &mut mock_ext).unwrap();
+
+		let answer = |i: usize| -> serde_json::Value {
+			serde_json::from_slice(&mock_ext.raw_storage[i.to_string().as_bytes()]).unwrap()
+		};
+		let data = |i: usize| -> Vec<u8> {
+			base64::decode(answer(i)["ok"]["ok"].as_str().unwrap()).unwrap()
+		};
+		assert_eq!(data(0), br#"{"amount":{"denom":"unit","amount":"100"}}"#.to_vec());
+		assert_eq!(data(1), br#"{"amount":{"denom":"foo","amount":"0"}}"#.to_vec());
+		assert_eq!(data(2), br#"{"amount":{"denom":"asset/9","amount":"9"}}"#.to_vec());
+		assert_eq!(data(3), br#"{"amount":[{"denom":"unit","amount":"100"}]}"#.to_vec());
+		assert_eq!(data(4), call_return_data().0);
+		assert_eq!(mock_ext.queries, vec![(BOB, b"{}".to_vec())]);
+		assert_eq!(data(5), b"bar".to_vec());
+		assert_eq!(
+			String::from_utf8(data(6)).unwrap(),
+			format!(
+				r#"{{"code_id":42,"creator":"{}","admin":"{}","pinned":false,"ibc_port":null}}"#,
+				encode(&ALICE),
+				encode(&CHARLIE)
+			)
+		);
+		assert_eq!(
+			String::from_utf8(data(7)).unwrap(),
+			format!(
+				r#"{{"code_id":42,"creator":"{}","checksum":"{}"}}"#,
+				encode(&ALICE),
+				"11".repeat(32)
+			)
+		);
+		assert_eq!(answer(8), serde_json::json!({"error":{"no_such_contract":{"addr":"nope"}}}));
+		assert_eq!(answer(9)["error"]["invalid_request"]["error"], "couldn't deserialize");
+	}
+
+	/// A CosmWasm contract whose `execute` returns `messages` and whose `reply` stores the
+	/// `Reply` it is called with under the key "reply".
+	fn cosmwasm_code_with_messages(messages: Vec<SubMsg<Empty>>) -> String {
+		cosmwasm_code_with_response(Response::<Empty> {
+			messages,
+			attributes: vec![],
+			events: vec![],
+			data: Some(Binary(b"executed".to_vec())),
+		})
+	}
+
+	/// A CosmWasm contract whose `execute` returns `response` and whose `reply` stores the
+	/// `Reply` it is called with under the key "reply".
+	fn cosmwasm_code_with_response(response: Response<Empty>) -> String {
+		let response = to_vec(&ContractResult::Ok(response)).unwrap();
+		let reply_response =
+			br#"{"ok":{"messages":[],"attributes":[],"events":[],"data":"cmVwbGllZA=="}}"#;
+		format!(
+			r#"
+(module
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+	(memory (export "memory") 1)
+	(global $heap (mut i32) (i32.const 0x1000))
+	(func (export "allocate") (param $len i32) (result i32)
+		(local $ptr i32)
+		(set_local $ptr (get_global $heap))
+		(i32.store (get_local $ptr) (i32.add (get_local $ptr) (i32.const 12)))
+		(i32.store (i32.add (get_local $ptr) (i32.const 4)) (get_local $len))
+		(i32.store (i32.add (get_local $ptr) (i32.const 8)) (i32.const 0))
+		(set_global $heap
+			(i32.add (get_local $ptr) (i32.add (get_local $len) (i32.const 12)))
+		)
+		(get_local $ptr)
+	)
+	(func (export "deallocate") (param i32))
+	(func (export "interface_version_8"))
+	(func (export "instantiate") (param i32 i32 i32) (result i32)
+		(i32.const 0x10)
+	)
+	(func (export "execute") (param i32 i32 i32) (result i32)
+		(i32.const 0x10)
+	)
+	(func (export "query") (param i32 i32) (result i32)
+		(unreachable)
+	)
+	(func (export "reply") (param i32 i32) (result i32)
+		(call $db_write (i32.const 0x30) (get_local 1))
+		(i32.const 0x20)
+	)
+
+	;; Regions of the responses of `execute` and `reply`.
+	(data (i32.const 0x10) "{}")
+	(data (i32.const 0x20) "{}")
+	;; Region of the key "reply".
+	(data (i32.const 0x30) "\00\01\00\00\05\00\00\00\05\00\00\00")
+	(data (i32.const 0x100) "reply")
+	(data (i32.const 0x400) "{}")
+	(data (i32.const 0x800) "{}")
+)
+"#,
+			wat_region(0x400, response.len()),
+			wat_region(0x800, reply_response.len()),
+			wat_escape(&response),
+			wat_escape(reply_response),
+		)
+	}
+
+	#[test]
+	fn cosmwasm_messages_are_dispatched() {
+		let encode = |account| <Ss58AddressCodec as AddressCodec<Test>>::encode(account).unwrap();
+		let code = cosmwasm_code_with_messages(vec![
+			SubMsg::new(BankMsg::Send {
+				to_address: encode(&BOB),
+				amount: vec![
+					Coin { denom: "unit".into(), amount: 5 },
+					Coin { denom: "asset/1".into(), amount: 2 },
+				],
+			}),
+			SubMsg::new(WasmMsg::Execute {
+				contract_addr: encode(&BOB),
+				msg: Binary(b"{}".to_vec()),
+				funds: vec![
+					Coin { denom: "asset/2".into(), amount: 3 },
+					Coin { denom: "unit".into(), amount: 7 },
+				],
+			}),
+			SubMsg::new(WasmMsg::Instantiate {
+				admin: Some(encode(&CHARLIE)),
+				code_id: 3,
+				msg: Binary(b"{}".to_vec()),
+				funds: vec![Coin { denom: "asset/3".into(), amount: 4 }],
+				label: "child".into(),
+			}),
+		]);
+		let mut mock_ext = MockExt::default();
+		let output = execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(output.data, Bytes(b"executed".to_vec()));
+		assert_eq!(&mock_ext.transfers, &[TransferEntry { to: BOB, value: 5 }]);
+		assert_eq!(&mock_ext.asset_transfers, &[(BOB, 1, 2)]);
+		assert_eq!(
+			&mock_ext.calls,
+			&[CallEntry {
+				to: BOB,
+				value: 7,
+				data: b"{}".to_vec(),
+				allows_reentry: true,
+				assets: vec![(2, 3)],
+			}]
+		);
+		assert_eq!(
+			&mock_ext.instantiates,
+			&[InstantiateEntry {
+				code_hash: H256::from_low_u64_be(3),
+				value: 0,
+				data: b"{}".to_vec(),
+				gas_left: 0,
+				salt: b"child".to_vec(),
+				admin: Some(CHARLIE),
+				label: b"child".to_vec(),
+				assets: vec![(3, 4)],
+				derivation: AddressDerivation::Generator,
+			}]
+		);
+		assert_eq!(mock_ext.raw_storage.get(&b"reply"[..]), None);
+	}
+
+	#[test]
+	fn cosmwasm_message_gas_limit_is_converted() {
+		let code = cosmwasm_code_with_messages(vec![SubMsg::new(WasmMsg::Instantiate {
+			admin: None,
+			code_id: 3,
+			msg: Binary(b"{}".to_vec()),
+			funds: vec![],
+			label: "child".into(),
+		})
+		.with_gas_limit(10)]);
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(&code, vec![], &mut mock_ext));
+		assert_eq!(mock_ext.instantiates[0].gas_left, 10 * 140_000);
+	}
+
+	#[test]
+	fn cosmwasm_instantiate2_message_works() {
+		let code = cosmwasm_code_with_messages(vec![SubMsg::new(WasmMsg::Instantiate2 {
+			admin: None,
+			code_id: 3,
+			label: "child".into(),
+			msg: Binary(b"{}".to_vec()),
+			funds: vec![],
+			salt: Binary(b"salt".to_vec()),
+		})]);
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(&code, vec![], &mut mock_ext));
+		assert_eq!(
+			&mock_ext.instantiates,
+			&[InstantiateEntry {
+				code_hash: H256::from_low_u64_be(3),
+				value: 0,
+				data: b"{}".to_vec(),
+				gas_left: 0,
+				salt: b"salt".to_vec(),
+				admin: None,
+				label: b"child".to_vec(),
+				assets: vec![],
+				derivation: AddressDerivation::Instantiate2,
+			}]
+		);
+	}
+
+	#[test]
+	fn instantiate2_address_matches_cosmwasm() {
+		// Test vector of `instantiate2_address` in cosmwasm-std.
+		let checksum =
+			hex!("13a1fc994cc6d1c81b746ee0c0ff6f90043875e0bf1d9be6b7d779fc978dc2a5");
+		let creator = CanonicalAddr::from(
+			&hex!("9999999999aaaaaaaaaabbbbbbbbbbcccccccccc")[..],
+		);
+		assert_eq!(
+			instantiate2_address(&checksum, &creator, b"a"),
+			Ok(CanonicalAddr::from(
+				&hex!(
+					"5e865d3e45ad3e961f77fd77d46543417ced44d924dc3e079b5415ff6775f847"
+				)[..]
+			)),
+		);
+		assert_eq!(
+			instantiate2_address(&checksum, &creator, b""),
+			Err(Instantiate2AddressError::InvalidSaltLength),
+		);
+		assert_eq!(
+			instantiate2_address(&checksum[1..], &creator, b"a"),
+			Err(Instantiate2AddressError::InvalidChecksumLength),
+		);
+	}
+
+	#[test]
+	fn cosmwasm_failed_message_fails_execution() {
+		let code = cosmwasm_code_with_messages(vec![SubMsg::reply_on_success(
+			BankMsg::Send {
+				to_address: <Ss58AddressCodec as AddressCodec<Test>>::encode(&BOB).unwrap(),
+				amount: vec![Coin { denom: "foo".into(), amount: 5 }],
+			},
+			1,
+		)]);
+		assert_eq!(
+			execute(&code, vec![], MockExt::default()),
+			Err(DispatchError::Other("unsupported denom").into())
+		);
+	}
+
+	#[test]
+	fn cosmwasm_reply_on_success() {
+		let code = cosmwasm_code_with_messages(vec![SubMsg::reply_on_success(
+			WasmMsg::Execute {
+				contract_addr: <Ss58AddressCodec as AddressCodec<Test>>::encode(&BOB).unwrap(),
+				msg: Binary(b"{}".to_vec()),
+				funds: vec![],
+			},
+			42,
+		)]);
+		let mut mock_ext = MockExt::default();
+		let output = execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(output.data, Bytes(b"replied".to_vec()));
+		// The data of the call is wrapped in a `MsgExecuteContractResponse`.
+		let data = [&[0x0a, 0x04][..], &call_return_data().0].concat();
+		assert_eq!(
+			from_slice::<Reply>(&mock_ext.raw_storage[&b"reply"[..]]).unwrap(),
+			Reply {
+				id: 42,
+				result: SubMsgResult::Ok(SubMsgResponse {
+					events: vec![Event { ty: "wasm-called".into(), attributes: vec![] }],
+					data: Some(Binary(data)),
+				}),
+			}
+		);
+	}
+
+	#[test]
+	fn cosmwasm_reply_on_error() {
+		let code = cosmwasm_code_with_messages(vec![SubMsg::reply_on_error(
+			BankMsg::Send {
+				to_address: "foo".into(),
+				amount: vec![Coin { denom: "unit".into(), amount: 5 }],
+			},
+			7,
+		)]);
+		let mut mock_ext = MockExt::default();
+		let output = execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(output.data, Bytes(b"replied".to_vec()));
+		assert!(mock_ext.transfers.is_empty());
+		let error = <Ss58AddressCodec as AddressCodec<Test>>::decode("foo").unwrap_err();
+		assert_eq!(
+			from_slice::<Reply>(&mock_ext.raw_storage[&b"reply"[..]]).unwrap(),
+			Reply { id: 7, result: SubMsgResult::Err(error.into()) }
+		);
+	}
+
+	#[test]
+	fn cosmwasm_events_are_emitted() {
+		let code = cosmwasm_code_with_response(Response::<Empty> {
+			messages: vec![],
+			attributes: vec![Attribute { key: "action".into(), value: "foo".into() }],
+			events: vec![Event {
+				ty: "transfer".into(),
+				attributes: vec![Attribute { key: "amount".into(), value: "42".into() }],
+			}],
+			data: None,
+		});
+		let mut mock_ext = MockExt::default();
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		let address = <Ss58AddressCodec as AddressCodec<Test>>::encode(&BOB).unwrap().into_bytes();
+		let contract_address = (b"_contract_address".to_vec(), address);
+		assert_eq!(
+			mock_ext.cosmwasm_events,
+			vec![
+				(
+					b"wasm".to_vec(),
+					vec![contract_address.clone(), (b"action".to_vec(), b"foo".to_vec())],
+				),
+				(
+					b"wasm-transfer".to_vec(),
+					vec![contract_address, (b"amount".to_vec(), b"42".to_vec())],
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn cosmwasm_reserved_attribute_keys_are_rejected() {
+		let code = cosmwasm_code_with_response(Response::<Empty> {
+			messages: vec![],
+			attributes: vec![Attribute { key: "_contract_address".into(), value: "foo".into() }],
+			events: vec![],
+			data: None,
+		});
+		assert_eq!(
+			execute(&code, vec![], MockExt::default()),
+			Err(DispatchError::Other("reserved attribute key").into())
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "stargate")]
+	fn cosmwasm_ibc_messages() {
+		use super::cosmwasm::{IbcMsg, IbcTimeout, IbcTimeoutBlock, Timestamp};
+
+		let timeout = |block, timestamp| IbcTimeout { block, timestamp };
+		let code = cosmwasm_code_with_messages(vec![
+			SubMsg::new(IbcMsg::SendPacket {
+				channel_id: "channel-1".into(),
+				data: Binary(b"ping".to_vec()),
+				timeout: timeout(Some(IbcTimeoutBlock { revision: 0, height: 200 }), None),
+			}),
+			SubMsg::new(IbcMsg::CloseChannel { channel_id: "channel-1".into() }),
+		]);
+		let mut mock_ext = MockExt::default();
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		assert_eq!(mock_ext.ibc_packets, vec![("channel-1".to_string(), b"ping".to_vec(), 200)]);
+		assert_eq!(mock_ext.ibc_closed_channels, vec!["channel-1".to_string()]);
+
+		let code = cosmwasm_code_with_messages(vec![SubMsg::new(IbcMsg::SendPacket {
+			channel_id: "channel-1".into(),
+			data: Binary(b"ping".to_vec()),
+			timeout: timeout(None, Some(Timestamp("1000000000".into()))),
+		})]);
+		assert_eq!(
+			execute(&code, vec![], MockExt::default()),
+			Err(DispatchError::Other("only block timeouts are supported").into())
+		);
+	}
+
+	/// Escape `bytes` to be used in a WAT data segment.
+	fn wat_escape(bytes: &[u8]) -> String {
+		bytes.iter().map(|byte| format!("\\{:02x}", byte)).collect()
+	}
+
+	/// Escape the region of `len` bytes starting at `offset` to be used in a WAT data segment.
+	fn wat_region(offset: u32, len: usize) -> String {
+		let len = len as u32;
+		wat_escape(&[offset.to_le_bytes(), len.to_le_bytes(), len.to_le_bytes()].concat())
+	}
+
+	#[test]
+	fn cosmwasm_crypto() {
+		use sp_core::{ecdsa, ed25519, Pair};
+
+		let message = b"hello";
+		let ed25519_pair = ed25519::Pair::from_seed(&[1; 32]);
+		let ed25519_other_pair = ed25519::Pair::from_seed(&[3; 32]);
+		let ed25519_signature = ed25519_pair.sign(message);
+		let ed25519_other_signature = ed25519_other_pair.sign(message);
+		let message_hash = sp_io::hashing::keccak_256(message);
+		let ecdsa_pair = ecdsa::Pair::from_seed(&[2; 32]);
+		let ecdsa_signature = ecdsa_pair.sign_prehashed(&message_hash);
+		let uncompressed_public_key =
+			sp_io::crypto::secp256k1_ecdsa_recover(&ecdsa_signature.0, &message_hash)
+				.ok()
+				.unwrap();
+		let signatures = memory::encode_sections(&[
+			ed25519_signature.0.to_vec(),
+			ed25519_other_signature.0.to_vec(),
+		]);
+		let public_keys = memory::encode_sections(&[
+			ed25519_pair.public().0.to_vec(),
+			ed25519_other_pair.public().0.to_vec(),
+		]);
+		let messages = memory::encode_sections(&[message.to_vec()]);
+		let imports = format!(
+			r#"
+	(import "env" "db_write" (func $db_write (param i32 i32)))
+	(import "env" "secp256k1_verify" (func $secp256k1_verify (param i32 i32 i32) (result i32)))
+	(import "env" "secp256k1_recover_pubkey"
+		(func $secp256k1_recover_pubkey (param i32 i32 i32) (result i64)))
+	(import "env" "ed25519_verify" (func $ed25519_verify (param i32 i32 i32) (result i32)))
+	(import "env" "ed25519_batch_verify"
+		(func $ed25519_batch_verify (param i32 i32 i32) (result i32)))
+
+	;; Regions of the message, its Ed25519 signature and public key.
+	(data (i32.const 0x10) "{}")
+	(data (i32.const 0x20) "{}")
+	(data (i32.const 0x30) "{}")
+	;; Regions of the message hash, its ECDSA signature and compressed public key.
+	(data (i32.const 0x40) "{}")
+	(data (i32.const 0x50) "{}")
+	(data (i32.const 0x60) "{}")
+	;; Regions of the batch messages, signatures and public keys.
+	(data (i32.const 0x70) "{}")
+	(data (i32.const 0x80) "{}")
+	(data (i32.const 0x90) "{}")
+	;; Region of the key "pubkey".
+	(data (i32.const 0xa0) "{}")
+	(data (i32.const 0x400) "{}")
+	(data (i32.const 0x410) "{}")
+	(data (i32.const 0x450) "{}")
+	(data (i32.const 0x470) "{}")
+	(data (i32.const 0x490) "{}")
+	(data (i32.const 0x4d0) "{}")
+	(data (i32.const 0x500) "{}")
+	(data (i32.const 0x600) "{}")
+	(data (i32.const 0x700) "{}")
+	(data (i32.const 0x800) "pubkey")
+"#,
+			wat_region(0x400, message.len()),
+			wat_region(0x410, 64),
+			wat_region(0x450, 32),
+			wat_region(0x470, 32),
+			wat_region(0x490, 64),
+			wat_region(0x4d0, 33),
+			wat_region(0x500, messages.len()),
+			wat_region(0x600, signatures.len()),
+			wat_region(0x700, public_keys.len()),
+			wat_region(0x800, 6),
+			wat_escape(message),
+			wat_escape(&ed25519_signature.0),
+			wat_escape(&ed25519_pair.public().0),
+			wat_escape(&message_hash),
+			wat_escape(&ecdsa_signature.0[..64]),
+			wat_escape(&ecdsa_pair.public().0),
+			wat_escape(&messages),
+			wat_escape(&signatures),
+			wat_escape(&public_keys),
+		);
+		let code = cosmwasm_code(
+			&imports,
+			&format!(
+				r#"
+		(call $assert (i32.eqz
+			(call $ed25519_verify (i32.const 0x10) (i32.const 0x20) (i32.const 0x30))
+		))
+		(call $assert (i32.eq
+			(call $ed25519_verify (i32.const 0x40) (i32.const 0x20) (i32.const 0x30))
+			(i32.const 1)
+		))
+		(call $assert (i32.eq
+			(call $ed25519_verify (i32.const 0x10) (i32.const 0x10) (i32.const 0x30))
+			(i32.const 4)
+		))
+		(call $assert (i32.eqz
+			(call $secp256k1_verify (i32.const 0x40) (i32.const 0x50) (i32.const 0x60))
+		))
+		(call $assert (i32.eq
+			(call $secp256k1_verify (i32.const 0x10) (i32.const 0x50) (i32.const 0x60))
+			(i32.const 3)
+		))
+		(call $assert (i32.eq
+			(call $secp256k1_verify (i32.const 0x40) (i32.const 0x50) (i32.const 0x10))
+			(i32.const 5)
+		))
+		(call $assert (i64.eq
+			(call $secp256k1_recover_pubkey (i32.const 0x40) (i32.const 0x50) (i32.const 2))
+			(i64.const 0x600000000)
+		))
+		(call $db_write
+			(i32.const 0xa0)
+			(i32.wrap/i64
+				(call $secp256k1_recover_pubkey (i32.const 0x40) (i32.const 0x50) (i32.const {}))
+			)
+		)
+		(call $assert (i32.eqz
+			(call $ed25519_batch_verify (i32.const 0x70) (i32.const 0x80) (i32.const 0x90))
+		))
+		(call $assert (i32.eq
+			(call $ed25519_batch_verify (i32.const 0x80) (i32.const 0x80) (i32.const 0x90))
+			(i32.const 1)
+		))
+		(call $assert (i32.eq
+			(call $ed25519_batch_verify (i32.const 0x70) (i32.const 0x80) (i32.const 0x70))
+			(i32.const 7)
+		))
+"#,
+				ecdsa_signature.0[64]
+			),
+		);
+		// `ed25519_batch_verify` charges for the largest possible batch before reading it.
+		let mut mock_ext = MockExt { gas_meter: GasMeter::new(Weight::MAX), ..Default::default() };
+		execute(&code, vec![], &mut mock_ext).unwrap();
+		// The unused part of the charge is refunded once the real sizes are known.
+		assert!(mock_ext.gas_meter.gas_consumed() < 10_000_000_000);
+		assert_eq!(
+			mock_ext.raw_storage.get(&b"pubkey"[..]),
+			Some(&[&[0x04][..], &uncompressed_public_key[..]].concat())
+		);
+	}
 }
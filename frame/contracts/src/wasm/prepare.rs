@@ -23,9 +23,10 @@ use crate::{
 	chain_extension::ChainExtension,
 	storage::meter::Diff,
 	wasm::{env_def::ImportSatisfyCheck, ModuleType, OwnerInfo, PrefabWasmModule},
-	AccountIdOf, Config, Schedule,
+	AccountIdOf, Config, Error, Schedule,
 };
 use codec::{Encode, MaxEncodedLen};
+use frame_support::{dispatch::DispatchError, traits::Get};
 use sp_runtime::traits::Hash;
 use sp_std::prelude::*;
 use wasm_instrument::parity_wasm::elements::{
@@ -36,6 +37,20 @@ use wasm_instrument::parity_wasm::elements::{
 /// compiler toolchains might not support specifying other modules than "env" for memory imports.
 pub const IMPORT_MODULE_MEMORY: &str = "env";
 
+/// The module from which CosmWasm contracts import their host functions.
+pub const IMPORT_MODULE_COSMWASM: &str = "env";
+
+/// The CosmWasm interface version implemented by this pallet.
+const COSMWASM_INTERFACE_VERSION: &str = "8";
+
+/// CosmWasm contracts mark the interface version they are compiled against by exporting
+/// `interface_version_<version>`.
+const COSMWASM_INTERFACE_VERSION_PREFIX: &str = "interface_version_";
+
+/// CosmWasm contracts declare every capability they need from the chain by exporting
+/// `requires_<capability>`.
+const COSMWASM_CAPABILITY_PREFIX: &str = "requires_";
+
 struct ContractModule<'a, T: Config> {
 	/// A deserialized module. The module is valid (this is Guaranteed by `new` method).
 	module: elements::Module,
@@ -232,7 +247,10 @@ impl<'a, T: Config> ContractModule<'a, T> {
 			.count();
 
 		let (module_type, required_exports): (ModuleType, Vec<(&str, fn(&[ValueType]) -> bool)>) =
-			if export_entries.iter().any(|export| export.field() == "interface_version_8") {
+			if export_entries.iter().any(|export| {
+				export.field().strip_prefix(COSMWASM_INTERFACE_VERSION_PREFIX) ==
+					Some(COSMWASM_INTERFACE_VERSION)
+			}) {
 				(
 					ModuleType::Cosmwasm,
 					vec![
@@ -292,17 +310,59 @@ impl<'a, T: Config> ContractModule<'a, T> {
 		Ok(module_type)
 	}
 
+	/// Check the markers exported by CosmWasm contracts.
+	///
+	/// A module exporting any `interface_version_*` is a CosmWasm contract. It is rejected if
+	/// it targets another interface version than the one we implement or if it exports a
+	/// `requires_*` capability which isn't listed in [`Config::Capabilities`]. Other modules
+	/// are left untouched.
+	fn scan_cosmwasm_markers(&self) -> Result<(), (DispatchError, &'static str)> {
+		let export_entries = self.module.export_section().map(|is| is.entries()).unwrap_or(&[]);
+
+		let mut versions = export_entries
+			.iter()
+			.filter_map(|export| export.field().strip_prefix(COSMWASM_INTERFACE_VERSION_PREFIX))
+			.peekable();
+		if versions.peek().is_none() {
+			return Ok(())
+		}
+		if versions.any(|version| version != COSMWASM_INTERFACE_VERSION) {
+			return Err((
+				Error::<T>::UnsupportedInterfaceVersion.into(),
+				"unsupported CosmWasm interface version",
+			))
+		}
+
+		for capability in export_entries
+			.iter()
+			.filter_map(|export| export.field().strip_prefix(COSMWASM_CAPABILITY_PREFIX))
+		{
+			if !is_capability_supported::<T>(capability) {
+				log::debug!(target: "runtime::contracts", "Unsupported capability: {}", capability);
+				return Err((
+					Error::<T>::UnsupportedCapability.into(),
+					"module requires an unsupported capability",
+				))
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Scan an import section if any.
 	///
 	/// This accomplishes two tasks:
 	///
 	/// - checks any imported function against defined host functions set, incl. their signatures.
+	///   CosmWasm contracts may only import functions from [`IMPORT_MODULE_COSMWASM`] whereas
+	///   ink! contracts may not import from it at all.
 	/// - if there is a memory import, returns it's descriptor
 	/// `import_fn_banlist`: list of function names that are disallowed to be imported
 	fn scan_imports<C: ImportSatisfyCheck>(
 		&self,
+		module_type: ModuleType,
 		import_fn_banlist: &[&[u8]],
-	) -> Result<Option<&MemoryType>, &'static str> {
+	) -> Result<Option<&MemoryType>, (DispatchError, &'static str)> {
 		let module = &self.module;
 
 		let types = module.type_section().map(|ts| ts.types()).unwrap_or(&[]);
@@ -312,18 +372,20 @@ impl<'a, T: Config> ContractModule<'a, T> {
 
 		for import in import_entries {
 			let type_idx = match import.external() {
-				&External::Table(_) => return Err("Cannot import tables"),
-				&External::Global(_) => return Err("Cannot import globals"),
+				&External::Table(_) => return Err(rejected::<T>("Cannot import tables")),
+				&External::Global(_) => return Err(rejected::<T>("Cannot import globals")),
 				&External::Function(ref type_idx) => type_idx,
 				&External::Memory(ref memory_type) => {
 					if import.module() != IMPORT_MODULE_MEMORY {
-						return Err("Invalid module for imported memory");
+						return Err(rejected::<T>("Invalid module for imported memory"));
 					}
 					if import.field() != "memory" {
-						return Err("Memory import must have the field name 'memory'");
+						return Err(rejected::<T>(
+							"Memory import must have the field name 'memory'",
+						));
 					}
 					if imported_mem_type.is_some() {
-						return Err("Multiple memory imports defined");
+						return Err(rejected::<T>("Multiple memory imports defined"));
 					}
 					imported_mem_type = Some(memory_type);
 					continue;
@@ -332,19 +394,40 @@ impl<'a, T: Config> ContractModule<'a, T> {
 
 			let Type::Function(ref func_ty) = types
 				.get(*type_idx as usize)
-				.ok_or_else(|| "validation: import entry points to a non-existent type")?;
+				.ok_or_else(|| {
+					rejected::<T>("validation: import entry points to a non-existent type")
+				})?;
 
 			if !T::ChainExtension::enabled()
 				&& import.field().as_bytes() == b"seal_call_chain_extension"
 			{
-				return Err("module uses chain extensions but chain extensions are disabled");
+				return Err(rejected::<T>(
+					"module uses chain extensions but chain extensions are disabled",
+				));
+			}
+
+			let is_cosmwasm_import = import.module() == IMPORT_MODULE_COSMWASM;
+			if is_cosmwasm_import != (module_type == ModuleType::Cosmwasm) {
+				log::debug!(
+					target: "runtime::contracts",
+					"Function from a foreign module: {}::{}",
+					import.module(),
+					import.field(),
+				);
+				return Err((
+					Error::<T>::UnsupportedImport.into(),
+					"module imports a function from a module of another contract type",
+				))
 			}
 
 			if import_fn_banlist.iter().any(|f| import.field().as_bytes() == *f)
 				|| !C::can_satisfy(import.module().as_bytes(), import.field().as_bytes(), func_ty)
 			{
 				log::debug!(target: "runtime::contracts", "Non existent function: {}", import.field());
-				return Err("module imports a non-existent function");
+				return Err((
+					Error::<T>::UnsupportedImport.into(),
+					"module imports a non-existent function",
+				))
 			}
 		}
 		Ok(imported_mem_type)
@@ -355,6 +438,19 @@ impl<'a, T: Config> ContractModule<'a, T> {
 	}
 }
 
+/// Attributes a generic validation failure to [`Error::CodeRejected`].
+fn rejected<T: Config>(msg: &'static str) -> (DispatchError, &'static str) {
+	(Error::<T>::CodeRejected.into(), msg)
+}
+
+/// Whether a CosmWasm contract requiring `capability` can run on this chain.
+///
+/// `stargate` additionally depends on the `stargate` feature which enables the IBC support.
+fn is_capability_supported<T: Config>(capability: &str) -> bool {
+	T::Capabilities::get().contains(&capability) &&
+		(capability != "stargate" || cfg!(feature = "stargate"))
+}
+
 fn get_memory_limits<T: Config>(
 	module: Option<&MemoryType>,
 	schedule: &Schedule<T>,
@@ -389,31 +485,45 @@ fn get_memory_limits<T: Config>(
 fn check_and_instrument<C: ImportSatisfyCheck, T: Config>(
 	original_code: &[u8],
 	schedule: &Schedule<T>,
-) -> Result<(ModuleType, Vec<u8>, (u32, u32)), &'static str> {
+) -> Result<(ModuleType, Vec<u8>, (u32, u32)), (DispatchError, &'static str)> {
 	let result = (|| {
-		let contract_module = ContractModule::new(&original_code, schedule)?;
-		let module_type = contract_module.scan_exports()?;
-		contract_module.ensure_no_internal_memory()?;
-		contract_module.ensure_table_size_limit(schedule.limits.table_size)?;
-		contract_module.ensure_global_variable_limit(schedule.limits.globals)?;
-		contract_module.ensure_no_floating_types()?;
-		contract_module.ensure_parameter_limit(schedule.limits.parameters)?;
-		contract_module.ensure_br_table_size_limit(schedule.limits.br_table_size)?;
+		let contract_module =
+			ContractModule::new(&original_code, schedule).map_err(rejected::<T>)?;
+		contract_module.scan_cosmwasm_markers()?;
+		let module_type = contract_module.scan_exports().map_err(rejected::<T>)?;
+		contract_module.ensure_no_internal_memory().map_err(rejected::<T>)?;
+		contract_module
+			.ensure_table_size_limit(schedule.limits.table_size)
+			.map_err(rejected::<T>)?;
+		contract_module
+			.ensure_global_variable_limit(schedule.limits.globals)
+			.map_err(rejected::<T>)?;
+		contract_module.ensure_no_floating_types().map_err(rejected::<T>)?;
+		contract_module
+			.ensure_parameter_limit(schedule.limits.parameters)
+			.map_err(rejected::<T>)?;
+		contract_module
+			.ensure_br_table_size_limit(schedule.limits.br_table_size)
+			.map_err(rejected::<T>)?;
 
 		// We disallow importing `gas` function here since it is treated as implementation detail.
 		let disallowed_imports = [b"gas".as_ref()];
-		let memory_limits =
-			get_memory_limits(contract_module.scan_imports::<C>(&disallowed_imports)?, schedule)?;
+		let memory_limits = get_memory_limits(
+			contract_module.scan_imports::<C>(module_type, &disallowed_imports)?,
+			schedule,
+		)
+		.map_err(rejected::<T>)?;
 
 		let code = contract_module
-			.inject_gas_metering()?
-			.inject_stack_height_metering()?
-			.into_wasm_code()?;
+			.inject_gas_metering()
+			.and_then(ContractModule::inject_stack_height_metering)
+			.and_then(ContractModule::into_wasm_code)
+			.map_err(rejected::<T>)?;
 
 		Ok((module_type, code, memory_limits))
 	})();
 
-	if let Err(msg) = &result {
+	if let Err((_, msg)) = &result {
 		log::debug!(target: "runtime::contracts", "CodeRejected: {}", msg);
 	}
 
@@ -424,7 +534,7 @@ fn do_preparation<C: ImportSatisfyCheck, T: Config>(
 	original_code: Vec<u8>,
 	schedule: &Schedule<T>,
 	owner: AccountIdOf<T>,
-) -> Result<PrefabWasmModule<T>, &'static str> {
+) -> Result<PrefabWasmModule<T>, (DispatchError, &'static str)> {
 	let (module_type, code, (initial, maximum)) =
 		check_and_instrument::<C, T>(original_code.as_ref(), schedule)?;
 	let original_code_len = original_code.len();
@@ -464,13 +574,18 @@ fn do_preparation<C: ImportSatisfyCheck, T: Config>(
 /// - the module doesn't define an internal memory instance,
 /// - imported memory (if any) doesn't reserve more memory than permitted by the `schedule`,
 /// - all imported functions from the external environment matches defined by `env` module,
+/// - CosmWasm contracts target the supported interface version and only require configured
+///   capabilities,
 ///
 /// The preprocessing includes injecting code for gas metering and metering the height of stack.
+///
+/// On failure the error to be returned to the caller is accompanied by a message describing
+/// the reason of the rejection.
 pub fn prepare_contract<T: Config>(
 	original_code: Vec<u8>,
 	schedule: &Schedule<T>,
 	owner: AccountIdOf<T>,
-) -> Result<PrefabWasmModule<T>, &'static str> {
+) -> Result<PrefabWasmModule<T>, (DispatchError, &'static str)> {
 	do_preparation::<super::runtime::Env, T>(original_code, schedule, owner)
 }
 
@@ -483,7 +598,9 @@ pub fn reinstrument_contract<T: Config>(
 	original_code: Vec<u8>,
 	schedule: &Schedule<T>,
 ) -> Result<Vec<u8>, &'static str> {
-	Ok(check_and_instrument::<super::runtime::Env, T>(&original_code, schedule)?.1)
+	check_and_instrument::<super::runtime::Env, T>(&original_code, schedule)
+		.map(|(_, code, _)| code)
+		.map_err(|(_, msg)| msg)
 }
 
 /// Alternate (possibly unsafe) preparation functions used only for benchmarking.
@@ -509,8 +626,13 @@ pub mod benchmarking {
 		owner: AccountIdOf<T>,
 	) -> Result<PrefabWasmModule<T>, &'static str> {
 		let contract_module = ContractModule::new(&original_code, schedule)?;
-		let memory_limits = get_memory_limits(contract_module.scan_imports::<()>(&[])?, schedule)?;
+		let module_type = contract_module.scan_exports()?;
+		let memory_limits = get_memory_limits(
+			contract_module.scan_imports::<()>(module_type, &[]).map_err(|(_, msg)| msg)?,
+			schedule,
+		)?;
 		Ok(PrefabWasmModule {
+			module_type,
 			instruction_weights_version: schedule.instruction_weights.version,
 			initial: memory_limits.0,
 			maximum: memory_limits.1,
@@ -560,13 +682,31 @@ mod tests {
 
 			// new version of nop with other data type for argumebt
 			[seal1] nop(_ctx, _unused: i32) => { unreachable!(); },
+
+			[env] db_read(_ctx, _key: u32) -> u32 => { unreachable!(); },
 		);
 	}
 
 	macro_rules! prepare_test {
+		($name:ident, $wat:expr, Err($error:path)) => {
+			#[test]
+			fn $name() {
+				let r = prepare_test!(@prepare $wat);
+				assert_matches::assert_matches!(
+					r.map_err(|(err, _)| err),
+					Err(err) if err == $error.into()
+				);
+			}
+		};
 		($name:ident, $wat:expr, $($expected:tt)*) => {
 			#[test]
 			fn $name() {
+				let r = prepare_test!(@prepare $wat);
+				assert_matches::assert_matches!(r.map_err(|(_, msg)| msg), $($expected)*);
+			}
+		};
+		(@prepare $wat:expr) => {
+			{
 				let wasm = wat::parse_str($wat).unwrap();
 				let schedule = Schedule {
 					limits: Limits {
@@ -579,8 +719,7 @@ mod tests {
 					},
 					.. Default::default()
 				};
-				let r = do_preparation::<env::Test, Test>(wasm, &schedule, ALICE);
-				assert_matches::assert_matches!(r, $($expected)*);
+				do_preparation::<env::Test, Test>(wasm, &schedule, ALICE)
 			}
 		};
 	}
@@ -1111,4 +1250,141 @@ mod tests {
 			Err("use of floating point type in function types is forbidden")
 		);
 	}
+
+	mod cosmwasm {
+		use super::*;
+
+		/// A CosmWasm module exporting the mandatory entry points besides `$items`.
+		macro_rules! cosmwasm_module {
+			($items:expr) => {
+				&format!(
+					r#"
+					(module
+						{}
+						(func (export "instantiate") (param i32 i32 i32))
+						(func (export "query") (param i32 i32))
+						(func (export "allocate") (param i32))
+						(func (export "deallocate") (param i32))
+					)
+					"#,
+					$items
+				)
+			};
+		}
+
+		prepare_test!(
+			it_works,
+			cosmwasm_module!(r#"(func (export "interface_version_8"))"#),
+			Ok(_)
+		);
+
+		prepare_test!(
+			unsupported_interface_version,
+			cosmwasm_module!(r#"(func (export "interface_version_7"))"#),
+			Err(Error::<Test>::UnsupportedInterfaceVersion)
+		);
+
+		prepare_test!(
+			ambiguous_interface_version,
+			cosmwasm_module!(
+				r#"
+				(func (export "interface_version_8"))
+				(func (export "interface_version_9"))
+				"#
+			),
+			Err(Error::<Test>::UnsupportedInterfaceVersion)
+		);
+
+		prepare_test!(
+			can_import_env_function,
+			cosmwasm_module!(
+				r#"
+				(import "env" "db_read" (func (param i32) (result i32)))
+				(func (export "interface_version_8"))
+				"#
+			),
+			Ok(_)
+		);
+
+		prepare_test!(
+			can_not_import_unknown_env_function,
+			cosmwasm_module!(
+				r#"
+				(import "env" "db_scan" (func (param i32 i32 i32) (result i32)))
+				(func (export "interface_version_8"))
+				"#
+			),
+			Err(Error::<Test>::UnsupportedImport)
+		);
+
+		prepare_test!(
+			can_not_import_seal_function,
+			cosmwasm_module!(
+				r#"
+				(import "seal0" "nop" (func (param i64)))
+				(func (export "interface_version_8"))
+				"#
+			),
+			Err(Error::<Test>::UnsupportedImport)
+		);
+
+		prepare_test!(
+			ink_can_not_import_env_function,
+			r#"
+			(module
+				(import "env" "db_read" (func (param i32) (result i32)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err(Error::<Test>::UnsupportedImport)
+		);
+
+		prepare_test!(
+			configured_capability_works,
+			cosmwasm_module!(
+				r#"
+				(func (export "interface_version_8"))
+				(func (export "requires_iterator"))
+				"#
+			),
+			Ok(_)
+		);
+
+		prepare_test!(
+			unknown_capability,
+			cosmwasm_module!(
+				r#"
+				(func (export "interface_version_8"))
+				(func (export "requires_staking"))
+				"#
+			),
+			Err(Error::<Test>::UnsupportedCapability)
+		);
+
+		#[cfg(feature = "stargate")]
+		prepare_test!(
+			stargate_capability_works,
+			cosmwasm_module!(
+				r#"
+				(func (export "interface_version_8"))
+				(func (export "requires_stargate"))
+				"#
+			),
+			Ok(_)
+		);
+
+		#[cfg(not(feature = "stargate"))]
+		prepare_test!(
+			stargate_capability_requires_feature,
+			cosmwasm_module!(
+				r#"
+				(func (export "interface_version_8"))
+				(func (export "requires_stargate"))
+				"#
+			),
+			Err(Error::<Test>::UnsupportedCapability)
+		);
+	}
 }
@@ -19,21 +19,49 @@
 
 use super::ModuleType;
 use crate::{
-	exec::{ExecError, ExecResult, Ext, StorageKey, TopicOf},
+	exec::{
+		AddressDerivation, AssetsOf, ErrorOrigin, EventAttributes, ExecError, ExecResult, Ext,
+		StorageKey, TopicOf,
+	},
 	gas::{ChargedAmount, Token},
 	schedule::HostFnWeights,
-	wasm::env_def::ConvertibleToWasm,
-	BalanceOf, CodeHash, Config, Error, SENTINEL,
+	wasm::{
+		cosmwasm::{
+			deserialization_limits, AllBalanceResponse, Attribute, BalanceResponse, BankMsg,
+			BankQuery, Binary, CodeInfoResponse, Coin, ContractInfoResponse, ContractResult,
+			CosmosMsg, Empty, Event as CosmwasmEvent, QueryRequest, Reply, ReplyOn, Response,
+			SubMsg, SubMsgResponse, SubMsgResult, SystemError, SystemResult, WasmMsg, WasmQuery,
+		},
+		env_def::ConvertibleToWasm,
+		from_slice,
+		memory::{decode_sections, encode_sections, read_region, write_region},
+		to_vec,
+	},
+	AccountIdOf, AddressCodec, BalanceOf, CodeHash, CodeId, Config, DenomResolver, Error,
+	GasConverter, SENTINEL,
+};
+#[cfg(feature = "stargate")]
+use crate::{
+	exec::ExportedFunction,
+	wasm::cosmwasm::{IbcBasicResponse, IbcChannelOpenResponse, IbcMsg, IbcReceiveResponse},
+};
+use alloc::{
+	format,
+	string::{String, ToString},
 };
 use bitflags::bitflags;
 use codec::{Decode, DecodeAll, Encode, MaxEncodedLen};
-use frame_support::{dispatch::DispatchError, ensure, weights::Weight};
+use frame_support::{dispatch::DispatchError, ensure, traits::Get, weights::Weight};
 use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
 use sp_core::{crypto::UncheckedFrom, Bytes};
 use sp_io::hashing::{blake2_128, blake2_256, keccak_256, sha2_256};
-use sp_runtime::traits::{Bounded, Zero};
-use sp_sandbox::SandboxMemory;
-use sp_std::prelude::*;
+use sp_runtime::{
+	traits::{Bounded, CheckedAdd, UniqueSaturatedInto, Zero},
+	ArithmeticError,
+};
+use sp_sandbox::{SandboxInstance, SandboxMemory};
+use serde::de::DeserializeOwned;
+use sp_std::{ops::Bound, prelude::*};
 use wasm_instrument::parity_wasm::elements::ValueType;
 
 /// Every error that can be returned to a contract when it calls any of the host functions.
@@ -231,6 +259,33 @@ pub enum RuntimeCosts {
 	/// Weight of calling `ecdsa_to_eth_address`
 	#[cfg(feature = "unstable-interface")]
 	EcdsaToEthAddress,
+	/// Weight of calling `db_read` with the specified size in storage.
+	DbRead(u32),
+	/// Weight of calling `db_write` for the given storage item sizes.
+	DbWrite { old_bytes: u32, new_bytes: u32 },
+	/// Weight of calling `db_remove` per removed byte.
+	DbRemove(u32),
+	/// Weight of calling `db_scan`.
+	DbScan,
+	/// Weight of visiting a storage item of the given size with `db_next`.
+	DbNext(u32),
+	/// Weight of calling `addr_validate`.
+	AddrValidate,
+	/// Weight of calling `addr_canonicalize`.
+	AddrCanonicalize,
+	/// Weight of calling `addr_humanize`.
+	AddrHumanize,
+	/// Weight of calling `secp256k1_verify`.
+	Secp256k1Verify,
+	/// Weight of calling `secp256k1_recover_pubkey`.
+	Secp256k1RecoverPubkey,
+	/// Weight of calling `ed25519_verify` for the given message size.
+	Ed25519Verify(u32),
+	/// Weight of calling `ed25519_batch_verify` for the given number of signatures and their
+	/// total message size.
+	Ed25519BatchVerify { count: u32, len: u32 },
+	/// Weight of calling `query_chain`, excluding the execution of a smart query.
+	QueryChain,
 }
 
 impl RuntimeCosts {
@@ -246,7 +301,8 @@ impl RuntimeCosts {
 			CopyToContract(len) => s.input_per_byte.saturating_mul(len.into()),
 			Caller => s.caller,
 			IsContract => s.is_contract,
-			#[cfg(feature = "unstable-interface")]Hash => s.code_hash,
+			#[cfg(feature = "unstable-interface")]
+			CodeHash => s.code_hash,
 			#[cfg(feature = "unstable-interface")]
 			OwnCodeHash => s.own_code_hash,
 			CallerIsOrigin => s.caller_is_origin,
@@ -317,6 +373,28 @@ impl RuntimeCosts {
 			SetCodeHash => s.set_code_hash,
 			#[cfg(feature = "unstable-interface")]
 			EcdsaToEthAddress => s.ecdsa_to_eth_address,
+			DbRead(len) => s.db_read.saturating_add(s.db_read_per_byte.saturating_mul(len.into())),
+			DbWrite { new_bytes, old_bytes } => s
+				.db_write
+				.saturating_add(s.db_write_per_new_byte.saturating_mul(new_bytes.into()))
+				.saturating_add(s.db_write_per_old_byte.saturating_mul(old_bytes.into())),
+			DbRemove(len) =>
+				s.db_remove.saturating_add(s.db_remove_per_byte.saturating_mul(len.into())),
+			DbScan => s.db_scan,
+			DbNext(len) => s.db_next.saturating_add(s.db_next_per_byte.saturating_mul(len.into())),
+			AddrValidate => s.addr_validate,
+			AddrCanonicalize => s.addr_canonicalize,
+			AddrHumanize => s.addr_humanize,
+			Secp256k1Verify => s.secp256k1_verify,
+			Secp256k1RecoverPubkey => s.secp256k1_recover_pubkey,
+			Ed25519Verify(len) => s
+				.ed25519_verify
+				.saturating_add(s.ed25519_verify_per_byte.saturating_mul(len.into())),
+			Ed25519BatchVerify { count, len } => s
+				.ed25519_batch_verify
+				.saturating_add(s.ed25519_verify.saturating_mul(count.into()))
+				.saturating_add(s.ed25519_verify_per_byte.saturating_mul(len.into())),
+			QueryChain => s.query_chain,
 		};
 		RuntimeToken {
 			#[cfg(test)]
@@ -425,12 +503,57 @@ fn already_charged(_: u32) -> Option<RuntimeCosts> {
 	None
 }
 
+/// The ordering of keys requested from `db_scan`.
+///
+/// Same values as `cosmwasm_std::Order`.
+const ORDER_ASCENDING: i32 = 1;
+const ORDER_DESCENDING: i32 = 2;
+
+/// Length of the preimage hashed by `instantiate2_address` without the salt.
+///
+/// Consists of the hashed address type, the module name, the code hash, a 32 byte account id
+/// and the length prefixes of the checksum, the creator, the salt and the message.
+const INSTANTIATE2_KEY_LEN: u32 = 32 + 5 + 32 + 32 + 4 * 8;
+
+/// An iterator over the storage of a CosmWasm contract created by `db_scan`.
+enum StorageIterator {
+	/// Keys are looked up on demand from `cursor` until right before `end`.
+	Ascending { cursor: Bound<Vec<u8>>, end: Option<Vec<u8>> },
+	/// The keys which are left to visit, the next one being the last.
+	Descending(Vec<Vec<u8>>),
+}
+
+/// Append the protobuf encoding of the bytes `field` with the given `tag` to `out`.
+///
+/// Empty fields are skipped, as protobuf does for default values.
+fn encode_proto_bytes(tag: u8, field: &[u8], out: &mut Vec<u8>) {
+	if field.is_empty() {
+		return
+	}
+	// Length delimited wire type.
+	out.push(tag << 3 | 2);
+	let mut len = field.len();
+	while len >= 0x80 {
+		out.push(len as u8 | 0x80);
+		len >>= 7;
+	}
+	out.push(len as u8);
+	out.extend_from_slice(field);
+}
+
 /// Can only be used for one call.
 pub struct Runtime<'a, E: Ext + 'a> {
 	module_type: ModuleType,
 	ext: &'a mut E,
 	input_data: Option<Vec<u8>>,
 	pub memory: sp_sandbox::default_executor::Memory,
+	/// The instance executing the contract. Only set for CosmWasm contracts which manage
+	/// their own memory and must be called back into through `allocate`.
+	pub instance: Option<sp_sandbox::default_executor::Instance<Runtime<'a, E>>>,
+	/// The iterators created by `db_scan`, identified by their index.
+	iterators: Vec<StorageIterator>,
+	/// Whether the contract is executed in a context where it can't change any state.
+	read_only: bool,
 	trap_reason: Option<TrapReason>,
 }
 
@@ -445,8 +568,18 @@ where
 		ext: &'a mut E,
 		input_data: Vec<u8>,
 		memory: sp_sandbox::default_executor::Memory,
+		read_only: bool,
 	) -> Self {
-		Runtime { module_type, ext, input_data: Some(input_data), memory, trap_reason: None }
+		Runtime {
+			module_type,
+			ext,
+			input_data: Some(input_data),
+			memory,
+			instance: None,
+			iterators: Vec::new(),
+			read_only,
+			trap_reason: None,
+		}
 	}
 
 	/// Converts the sandbox result and the runtime state into the execution outcome.
@@ -455,11 +588,11 @@ where
 	/// bases the outcome on the value if this variable. Only if `trap_reason` is `None`
 	/// the result of the sandbox is evaluated.
 	pub fn to_execution_result(
-		self,
+		&mut self,
 		sandbox_result: Result<sp_sandbox::ReturnValue, sp_sandbox::Error>,
 	) -> ExecResult {
 		// If a trap reason is set we base our decision solely on that.
-		if let Some(trap_reason) = self.trap_reason {
+		if let Some(trap_reason) = self.trap_reason.take() {
 			return match trap_reason {
 				// The trap was the result of the execution `return` host function.
 				TrapReason::Return(ReturnData { flags, data }) => {
@@ -492,6 +625,493 @@ where
 		}
 	}
 
+	/// Read the result returned by a CosmWasm entry point.
+	///
+	/// Traps are turned into errors like [`Self::to_execution_result`] does. Otherwise the
+	/// entry point returned a region holding its `ContractResult`, which is read with at most
+	/// `response_limit` bytes. An error returned by the contract is recorded in the debug
+	/// buffer as it can't be carried by a [`DispatchError`].
+	pub fn cosmwasm_result<R: DeserializeOwned>(
+		&mut self,
+		sandbox_result: Result<sp_sandbox::ReturnValue, sp_sandbox::Error>,
+		response_limit: usize,
+	) -> Result<R, ExecError> {
+		let response_ptr = match sandbox_result {
+			Ok(sp_sandbox::ReturnValue::Value(sp_sandbox::Value::I32(response_ptr)))
+				if self.trap_reason.is_none() =>
+				response_ptr as u32,
+			// CosmWasm entry points can't return without a response.
+			sandbox_result => {
+				self.to_execution_result(sandbox_result)?;
+				return Err(Error::<E::T>::ContractTrapped.into())
+			},
+		};
+		let response = read_region(&self.memory, response_ptr, response_limit)?;
+		match from_slice::<ContractResult<R>>(&response)? {
+			ContractResult::Ok(response) => Ok(response),
+			ContractResult::Err(message) => {
+				self.ext.append_debug_buffer(&message);
+				Err(ExecError {
+					error: Error::<E::T>::CosmwasmContractError.into(),
+					origin: ErrorOrigin::Callee,
+				})
+			},
+		}
+	}
+
+	/// Handle the `sandbox_result` of the IBC entry point `function` of a CosmWasm contract.
+	///
+	/// The messages of the response are dispatched. The version chosen by `ibc_channel_open`
+	/// and the acknowledgement of `ibc_packet_receive` are returned as output data.
+	#[cfg(feature = "stargate")]
+	pub fn ibc_result(
+		&mut self,
+		function: &ExportedFunction,
+		sandbox_result: Result<sp_sandbox::ReturnValue, sp_sandbox::Error>,
+	) -> ExecResult {
+		let response_limit = match function {
+			ExportedFunction::IbcChannelOpen => {
+				let response: IbcChannelOpenResponse = self.cosmwasm_result(
+					sandbox_result,
+					deserialization_limits::RESULT_IBC_CHANNEL_OPEN,
+				)?;
+				return Ok(ExecReturnValue {
+					flags: ReturnFlags::empty(),
+					data: Bytes(response.map(|r| r.version.into_bytes()).unwrap_or_default()),
+				})
+			},
+			ExportedFunction::IbcPacketReceive => {
+				let response: IbcReceiveResponse = self.cosmwasm_result(
+					sandbox_result,
+					deserialization_limits::RESULT_IBC_PACKET_RECEIVE,
+				)?;
+				self.dispatch_messages(Response {
+					messages: response.messages,
+					attributes: response.attributes,
+					events: response.events,
+					data: None,
+				})?;
+				return Ok(ExecReturnValue {
+					flags: ReturnFlags::empty(),
+					data: Bytes(response.acknowledgement.0),
+				})
+			},
+			ExportedFunction::IbcChannelConnect =>
+				deserialization_limits::RESULT_IBC_CHANNEL_CONNECT,
+			ExportedFunction::IbcChannelClose => deserialization_limits::RESULT_IBC_CHANNEL_CLOSE,
+			ExportedFunction::IbcPacketAck => deserialization_limits::RESULT_IBC_PACKET_ACK,
+			ExportedFunction::IbcPacketTimeout => deserialization_limits::RESULT_IBC_PACKET_TIMEOUT,
+			_ => return Err(DispatchError::Other("not an IBC entry point").into()),
+		};
+		let response: IbcBasicResponse = self.cosmwasm_result(sandbox_result, response_limit)?;
+		self.dispatch_messages(response.into())?;
+		Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Bytes(Vec::new()) })
+	}
+
+	/// Emit the events of a CosmWasm `response` and dispatch its messages in order.
+	///
+	/// The changes of each message are reverted when it fails: contract calls run in their
+	/// own frame and bank transfers are applied all or nothing. The `reply` entry point of the
+	/// contract is called when the outcome of a message matches its `reply_on`. Otherwise a
+	/// failed message fails the whole execution.
+	///
+	/// Returns the data of the response, which is overwritten by the data of the replies.
+	pub fn dispatch_messages(
+		&mut self,
+		response: Response<Empty>,
+	) -> Result<Option<Binary>, ExecError> {
+		self.emit_events(response.attributes, response.events)?;
+		let mut data = response.data;
+		for SubMsg { id, msg, gas_limit, reply_on } in response.messages {
+			// No gas limit means that all the remaining gas can be used.
+			let gas_limit = gas_limit.map_or(0, <E::T as Config>::GasConverter::gas_to_weight);
+			let result = match (self.dispatch_message(msg, gas_limit), reply_on) {
+				(Ok(response), ReplyOn::Always | ReplyOn::Success) => SubMsgResult::Ok(response),
+				(Err(error), ReplyOn::Always | ReplyOn::Error) =>
+					SubMsgResult::Err(<&'static str>::from(error.error).into()),
+				(Ok(_), ReplyOn::Error | ReplyOn::Never) => continue,
+				(Err(error), ReplyOn::Success | ReplyOn::Never) => return Err(error),
+			};
+			let response = self.reply(Reply { id, result })?;
+			if let Some(reply_data) = self.dispatch_messages(response)? {
+				data = Some(reply_data);
+			}
+		}
+		Ok(data)
+	}
+
+	/// Emit the `attributes` of a response as a `wasm` event followed by its custom `events`.
+	///
+	/// As in wasmd, the type of custom events is prefixed with `wasm-` and every event starts
+	/// with the `_contract_address` attribute. Keys starting with `_` are reserved.
+	fn emit_events(
+		&mut self,
+		attributes: Vec<Attribute>,
+		events: Vec<CosmwasmEvent>,
+	) -> Result<(), ExecError> {
+		let address = <E::T as Config>::AddressCodec::encode(self.ext.address())
+			.map_err(DispatchError::Other)?;
+		let events =
+			events.into_iter().map(|event| (format!("wasm-{}", event.ty), event.attributes));
+		for (ty, attributes) in Some(("wasm".to_string(), attributes)).into_iter().chain(events) {
+			if attributes.iter().any(|attribute| attribute.key.starts_with('_')) {
+				return Err(DispatchError::Other("reserved attribute key").into())
+			}
+			let attributes: EventAttributes = Some(("_contract_address".into(), address.clone()))
+				.into_iter()
+				.chain(attributes.into_iter().map(|Attribute { key, value }| (key, value)))
+				.map(|(key, value)| (key.into_bytes(), value.into_bytes()))
+				.collect();
+			let len = attributes.iter().fold(ty.len(), |len, (key, value)| {
+				len.saturating_add(key.len()).saturating_add(value.len())
+			});
+			self.charge_gas(RuntimeCosts::DepositEvent { num_topic: 2, len: len as u32 })?;
+			self.ext.deposit_cosmwasm_event(ty.into_bytes(), attributes);
+		}
+		Ok(())
+	}
+
+	/// Dispatch a single CosmWasm message on behalf of the executing contract.
+	///
+	/// The response holds the events emitted while the message was executed.
+	fn dispatch_message(
+		&mut self,
+		msg: CosmosMsg<Empty>,
+		gas_limit: Weight,
+	) -> Result<SubMsgResponse, ExecError> {
+		let emitted = self.ext.cosmwasm_events().len();
+		let data = match msg {
+			CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+				self.charge_gas(RuntimeCosts::Transfer)?;
+				let to = Self::decode_address(&to_address)?;
+				let (value, assets) = Self::resolve_funds(&amount)?;
+				// Charge for all the transfers before applying any of them.
+				for _ in &assets {
+					self.charge_gas(RuntimeCosts::Transfer)?;
+				}
+				self.ext.transfer_funds(&to, value, assets)?;
+				None
+			},
+			CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, funds }) => {
+				self.charge_gas(RuntimeCosts::CallBase)?;
+				let to = Self::decode_address(&contract_addr)?;
+				let (value, assets) = Self::resolve_funds(&funds)?;
+				if !value.is_zero() || !assets.is_empty() {
+					self.charge_gas(RuntimeCosts::CallSurchargeTransfer)?;
+				}
+				let output = self.ext.call(gas_limit, to, value, assets, msg.0, true)?;
+				if output.did_revert() {
+					return Err(Error::<E::T>::ContractReverted.into())
+				}
+				// Same encoding as the `MsgExecuteContractResponse` of wasmd.
+				let mut data = Vec::new();
+				encode_proto_bytes(1, &output.data.0, &mut data);
+				Some(Binary(data))
+			},
+			CosmosMsg::Wasm(WasmMsg::Instantiate { admin, code_id, msg, funds, label }) => {
+				// Contracts with the same code and label instantiated by the same contract
+				// share their address.
+				let salt = label.clone().into_bytes();
+				Some(self.instantiate_from_message(
+					gas_limit,
+					code_id,
+					admin,
+					msg,
+					funds,
+					label,
+					salt,
+					AddressDerivation::Generator,
+				)?)
+			},
+			CosmosMsg::Wasm(WasmMsg::Instantiate2 { admin, code_id, label, msg, funds, salt }) => {
+				// The address is derived from the code, the sender and the salt only.
+				self.charge_gas(RuntimeCosts::HashSha256(
+					INSTANTIATE2_KEY_LEN.saturating_add(salt.0.len() as u32),
+				))?;
+				Some(self.instantiate_from_message(
+					gas_limit,
+					code_id,
+					admin,
+					msg,
+					funds,
+					label,
+					salt.0,
+					AddressDerivation::Instantiate2,
+				)?)
+			},
+			#[cfg(feature = "stargate")]
+			CosmosMsg::Ibc(IbcMsg::SendPacket { channel_id, data, timeout }) => {
+				// Sending a packet amounts to storing it until it is relayed.
+				let new_bytes = data.0.len() as u32;
+				self.charge_gas(RuntimeCosts::DbWrite { new_bytes, old_bytes: 0 })?;
+				let timeout = timeout
+					.block
+					.ok_or(DispatchError::Other("only block timeouts are supported"))?;
+				self.ext.ibc_send_packet(
+					&channel_id,
+					data.0,
+					timeout.height.unique_saturated_into(),
+				)?;
+				None
+			},
+			#[cfg(feature = "stargate")]
+			CosmosMsg::Ibc(IbcMsg::CloseChannel { channel_id }) => {
+				self.charge_gas(RuntimeCosts::DbWrite { new_bytes: 0, old_bytes: 0 })?;
+				self.ext.ibc_close_channel(&channel_id)?;
+				None
+			},
+			_ => return Err(DispatchError::Other("unsupported message").into()),
+		};
+		let events = self.ext.cosmwasm_events()[emitted..]
+			.iter()
+			.map(|(ty, attributes)| CosmwasmEvent {
+				ty: String::from_utf8_lossy(ty).into_owned(),
+				attributes: attributes
+					.iter()
+					.map(|(key, value)| Attribute {
+						key: String::from_utf8_lossy(key).into_owned(),
+						value: String::from_utf8_lossy(value).into_owned(),
+					})
+					.collect(),
+			})
+			.collect();
+		Ok(SubMsgResponse { events, data })
+	}
+
+	/// Call the `reply` entry point of the executing contract.
+	fn reply(&mut self, reply: Reply) -> Result<Response<Empty>, ExecError> {
+		let mut instance = self.instance.clone().ok_or(Error::<E::T>::ContractTrapped)?;
+		let (env, _) = super::env_and_info(self.ext)?;
+		let mut args = Vec::with_capacity(2);
+		for arg in [to_vec(&env)?, to_vec(&reply)?] {
+			args.push(sp_sandbox::Value::I32(self.write_to_contract(&arg)? as i32));
+		}
+		let result = instance.invoke("reply", &args, self);
+		self.cosmwasm_result(result, deserialization_limits::RESULT_REPLY)
+	}
+
+	/// Instantiate a contract on behalf of a `WasmMsg::Instantiate` or `WasmMsg::Instantiate2`.
+	///
+	/// Returns the data of the response, encoded like `MsgInstantiateContractResponse`.
+	fn instantiate_from_message(
+		&mut self,
+		gas_limit: Weight,
+		code_id: u64,
+		admin: Option<String>,
+		msg: Binary,
+		funds: Vec<Coin>,
+		label: String,
+		salt: Vec<u8>,
+		derivation: AddressDerivation,
+	) -> Result<Binary, ExecError> {
+		self.charge_gas(RuntimeCosts::InstantiateBase {
+			input_data_len: msg.0.len() as u32,
+			salt_len: salt.len() as u32,
+		})?;
+		let code_hash = self.ext.code_hash_by_id(code_id).ok_or(Error::<E::T>::CodeNotFound)?;
+		let admin = admin.map(|admin| Self::decode_address(&admin)).transpose()?;
+		let (value, assets) = Self::resolve_funds(&funds)?;
+		if !value.is_zero() || !assets.is_empty() {
+			self.charge_gas(RuntimeCosts::InstantiateSurchargeTransfer)?;
+		}
+		let (address, output) = self.ext.instantiate(
+			gas_limit,
+			code_hash,
+			value,
+			assets,
+			msg.0,
+			&salt,
+			admin,
+			label.into_bytes(),
+			derivation,
+		)?;
+		if output.did_revert() {
+			return Err(Error::<E::T>::ContractReverted.into())
+		}
+		let address =
+			<E::T as Config>::AddressCodec::encode(&address).map_err(DispatchError::Other)?;
+		// Same encoding as the `MsgInstantiateContractResponse` of wasmd.
+		let mut data = Vec::new();
+		encode_proto_bytes(1, address.as_bytes(), &mut data);
+		encode_proto_bytes(2, &output.data.0, &mut data);
+		Ok(Binary(data))
+	}
+
+	/// Decode the account id of a human readable address.
+	fn decode_address(address: &str) -> Result<AccountIdOf<E::T>, DispatchError> {
+		<E::T as Config>::AddressCodec::decode(address).map_err(DispatchError::Other)
+	}
+
+	/// Split `coins` into a value of the native currency and the amounts of other assets.
+	///
+	/// Fails if a denom can't be resolved by the [`Config::DenomResolver`].
+	fn resolve_funds(
+		coins: &[Coin],
+	) -> Result<(BalanceOf<E::T>, AssetsOf<E::T>), DispatchError> {
+		let denom = <E::T as Config>::NativeDenom::get();
+		let mut value = BalanceOf::<E::T>::zero();
+		let mut assets = AssetsOf::<E::T>::new();
+		for coin in coins {
+			let amount: BalanceOf<E::T> =
+				coin.amount.try_into().map_err(|_| ArithmeticError::Overflow)?;
+			if coin.denom == denom {
+				value = value.checked_add(&amount).ok_or(ArithmeticError::Overflow)?;
+			} else {
+				let asset = <E::T as Config>::DenomResolver::resolve(&coin.denom)
+					.ok_or(DispatchError::Other("unsupported denom"))?;
+				assets.push((asset, amount));
+			}
+		}
+		Ok((value, assets))
+	}
+
+	/// Returns the balance of `account` in `denom`.
+	///
+	/// Denoms which can't be resolved have no balance at all.
+	fn balance_in(&self, account: &AccountIdOf<E::T>, denom: &str) -> u128 {
+		let balance = if denom == <E::T as Config>::NativeDenom::get() {
+			self.ext.balance_of(account)
+		} else if let Some(asset) = <E::T as Config>::DenomResolver::resolve(denom) {
+			self.ext.asset_balance_of(account, asset)
+		} else {
+			Zero::zero()
+		};
+		balance.unique_saturated_into()
+	}
+
+	/// Answer the `QueryRequest` held by the region at `request_ptr`.
+	///
+	/// Returns the pointer to a region holding the `SystemResult` of the query.
+	fn query_chain(&mut self, request_ptr: u32) -> Result<u32, TrapReason> {
+		self.charge_gas(RuntimeCosts::QueryChain)?;
+		let request =
+			read_region(&self.memory, request_ptr, MAX_LENGTH_QUERY_CHAIN_REQUEST as usize)?;
+		let result = match from_slice::<QueryRequest<Empty>>(&request) {
+			Ok(QueryRequest::Bank(query)) => SystemResult::Ok(self.query_bank(query)),
+			Ok(QueryRequest::Wasm(query)) => self.query_wasm(query)?,
+			Ok(_) => SystemResult::Err(SystemError::UnsupportedRequest { kind: "custom".into() }),
+			Err(error) => SystemResult::Err(SystemError::InvalidRequest {
+				error: <&'static str>::from(error).into(),
+				request: Binary(request),
+			}),
+		};
+		Ok(self.write_to_contract(&to_vec(&result)?)?)
+	}
+
+	/// Answer a query to the bank.
+	///
+	/// The assets held by an account can't be enumerated: all balances only contain the
+	/// native currency.
+	fn query_bank(&mut self, query: BankQuery) -> ContractResult<Binary> {
+		let response = match query {
+			BankQuery::Balance { address, denom } =>
+				Self::decode_address(&address).and_then(|account| {
+					let amount = self.balance_in(&account, &denom);
+					to_vec(&BalanceResponse { amount: Coin { denom, amount } })
+				}),
+			BankQuery::AllBalances { address } =>
+				Self::decode_address(&address).and_then(|account| {
+					let denom = <E::T as Config>::NativeDenom::get();
+					let amount = match self.balance_in(&account, denom) {
+						0 => vec![],
+						amount => vec![Coin { denom: denom.into(), amount }],
+					};
+					to_vec(&AllBalanceResponse { amount })
+				}),
+		};
+		match response {
+			Ok(response) => ContractResult::Ok(Binary(response)),
+			Err(error) => ContractResult::Err(<&'static str>::from(error).into()),
+		}
+	}
+
+	/// Answer a query about a contract.
+	///
+	/// Smart queries are executed in a nested frame which can't change any state.
+	fn query_wasm(
+		&mut self,
+		query: WasmQuery,
+	) -> Result<SystemResult<ContractResult<Binary>>, DispatchError> {
+		let contract_addr = match &query {
+			WasmQuery::Smart { contract_addr, .. } |
+			WasmQuery::Raw { contract_addr, .. } |
+			WasmQuery::ContractInfo { contract_addr } => contract_addr.clone(),
+			WasmQuery::CodeInfo { code_id } => return self.query_code_info(*code_id),
+		};
+		let contract = match Self::decode_address(&contract_addr) {
+			Ok(contract) if self.ext.is_contract(&contract) => contract,
+			_ => return Ok(SystemResult::Err(SystemError::NoSuchContract { addr: contract_addr })),
+		};
+		let response = match query {
+			WasmQuery::Smart { msg, .. } => {
+				self.charge_gas(RuntimeCosts::CallBase)?;
+				self.ext.query(0, contract, msg.0).map(|output| output.data.0).map_err(|e| e.error)
+			},
+			WasmQuery::Raw { key, .. } => {
+				let charged =
+					self.charge_gas(RuntimeCosts::GetStorage(self.ext.max_value_size()))?;
+				let value = self.ext.get_storage_raw_of(&contract, &key.0).unwrap_or_default();
+				self.adjust_gas(charged, RuntimeCosts::GetStorage(value.len() as u32));
+				Ok(value)
+			},
+			WasmQuery::ContractInfo { .. } => {
+				let code_hash = self.ext.code_hash(&contract).ok_or(Error::<E::T>::CodeNotFound)?;
+				let (code_id, owner, _) =
+					self.ext.code_info(&code_hash).ok_or(Error::<E::T>::CodeNotFound)?;
+				// The instantiator of a contract isn't tracked: report the owner of its code.
+				let creator = <E::T as Config>::AddressCodec::encode(&owner)
+					.map_err(DispatchError::Other)?;
+				let admin = self
+					.ext
+					.contract_admin(&contract)
+					.map(|admin| <E::T as Config>::AddressCodec::encode(&admin))
+					.transpose()
+					.map_err(DispatchError::Other)?;
+				to_vec(&ContractInfoResponse {
+					code_id,
+					creator,
+					admin,
+					pinned: false,
+					ibc_port: None,
+				})
+			},
+			WasmQuery::CodeInfo { .. } => unreachable!("Code queries are answered above; qed"),
+		};
+		Ok(SystemResult::Ok(match response {
+			Ok(response) => ContractResult::Ok(Binary(response)),
+			Err(error) => ContractResult::Err(<&'static str>::from(error).into()),
+		}))
+	}
+
+	/// Answer a query about the code with the id `code_id`.
+	fn query_code_info(
+		&mut self,
+		code_id: CodeId,
+	) -> Result<SystemResult<ContractResult<Binary>>, DispatchError> {
+		let code_info = self
+			.ext
+			.code_hash_by_id(code_id)
+			.and_then(|code_hash| self.ext.code_info(&code_hash));
+		let (code_id, owner, checksum) = match code_info {
+			Some(code_info) => code_info,
+			None => return Ok(SystemResult::Err(SystemError::NoSuchCode { code_id })),
+		};
+		let creator =
+			<E::T as Config>::AddressCodec::encode(&owner).map_err(DispatchError::Other)?;
+		let response = to_vec(&CodeInfoResponse { code_id, creator, checksum: checksum.into() });
+		Ok(SystemResult::Ok(match response {
+			Ok(response) => ContractResult::Ok(Binary(response)),
+			Err(error) => ContractResult::Err(<&'static str>::from(error).into()),
+		}))
+	}
+
+	/// Take the input data passed to the contract.
+	///
+	/// Returns `None` if it was already taken.
+	pub fn take_input_data(&mut self) -> Option<Vec<u8>> {
+		self.input_data.take()
+	}
+
 	/// Get a mutable reference to the inner `Ext`.
 	///
 	/// This is mainly for the chain extension to have access to the environment the
@@ -640,20 +1260,158 @@ where
 		Ok(())
 	}
 
-	fn write_to_contract(&mut self, input: &[u8]) -> Result<u32, DispatchError> {
-		// self.write_sandbox_memory(ptr, buf)
-		// let out_size = input.len() as u32;
-		// self.call(
-		// 	CallFlags::ALLOW_REENTRY,
-		// 	CallType::Call { callee_ptr: , value_ptr: (), gas: () }, input_data_ptr, input_data_len, output_ptr, output_len_ptr)
-		// let result = env.call_function1("allocate", &[out_size.into()])?;
-		// let target_ptr = ref_to_u32(&result)?;
-		// if target_ptr == 0 {
-		// 	return Err(CommunicationError::zero_address().into());
-		// }
-		// write_region(&env.memory(), target_ptr, input)?;
-		// Ok(target_ptr)
-		Ok(0)
+	/// Reserve `len` bytes of the contract memory by calling its `allocate` export.
+	///
+	/// Returns the pointer to the region describing the allocated memory.
+	pub fn allocate(&mut self, len: u32) -> Result<u32, DispatchError> {
+		let mut instance =
+			self.instance.clone().ok_or(DispatchError::Other("no instance to allocate"))?;
+		match instance.invoke("allocate", &[sp_sandbox::Value::I32(len as i32)], self) {
+			Ok(sp_sandbox::ReturnValue::Value(sp_sandbox::Value::I32(ptr))) if ptr != 0 =>
+				Ok(ptr as u32),
+			_ => Err(DispatchError::Other("allocate failed")),
+		}
+	}
+
+	/// Allocate a region within the contract memory and copy `input` into it.
+	///
+	/// Returns the pointer to the region holding the data.
+	pub fn write_to_contract(&mut self, input: &[u8]) -> Result<u32, DispatchError> {
+		let region_ptr = self.allocate(input.len() as u32)?;
+		write_region(&self.memory, region_ptr, input)?;
+		Ok(region_ptr)
+	}
+
+	/// Read the region at `ptr` or return `None` if `ptr` is null.
+	fn read_optional_region(
+		&self,
+		ptr: u32,
+		max_length: u32,
+	) -> Result<Option<Vec<u8>>, DispatchError> {
+		if ptr == 0 {
+			Ok(None)
+		} else {
+			read_region(&self.memory, ptr, max_length as usize).map(Some)
+		}
+	}
+
+	/// Returns `key` if it exists in the contract storage or else the key following it.
+	fn storage_key_from(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		if self.ext.get_storage_raw(key).is_some() {
+			Some(key.to_vec())
+		} else {
+			self.ext.next_storage_key_raw(key)
+		}
+	}
+
+	/// Create an iterator over the contract storage for the keys within `start` (inclusive)
+	/// and `end` (exclusive).
+	///
+	/// Returns the identifier of the iterator.
+	fn db_scan(&mut self, start_ptr: u32, end_ptr: u32, order: i32) -> Result<u32, TrapReason> {
+		self.charge_gas(RuntimeCosts::DbScan)?;
+		let start = self.read_optional_region(start_ptr, MAX_LENGTH_DB_KEY)?;
+		let end = self.read_optional_region(end_ptr, MAX_LENGTH_DB_KEY)?;
+		let iterator = match order {
+			ORDER_ASCENDING => StorageIterator::Ascending {
+				cursor: start.map_or(Bound::Unbounded, Bound::Included),
+				end,
+			},
+			ORDER_DESCENDING => {
+				// The child trie can only be walked forward: collect the keys upfront.
+				let mut keys = Vec::new();
+				let mut next = self.storage_key_from(&start.unwrap_or_default());
+				while let Some(key) = next {
+					if end.as_ref().map_or(false, |end| &key >= end) {
+						break
+					}
+					self.charge_gas(RuntimeCosts::DbNext(key.len() as u32))?;
+					next = self.ext.next_storage_key_raw(&key);
+					keys.push(key);
+				}
+				StorageIterator::Descending(keys)
+			},
+			_ => Err(DispatchError::Other("invalid order"))?,
+		};
+		self.iterators.push(iterator);
+		Ok((self.iterators.len() - 1) as u32)
+	}
+
+	/// Advance the iterator identified by `iterator_id`.
+	///
+	/// Returns the key and value of the visited item encoded as sections or two empty
+	/// sections if the iterator is exhausted.
+	fn db_next(&mut self, iterator_id: u32) -> Result<Vec<u8>, TrapReason> {
+		let charged = self.charge_gas(RuntimeCosts::DbNext(
+			MAX_LENGTH_DB_KEY.saturating_add(self.ext.max_value_size()),
+		))?;
+		let iterator = self
+			.iterators
+			.get_mut(iterator_id as usize)
+			.ok_or(DispatchError::Other("iterator does not exist"))?;
+		let item = match iterator {
+			StorageIterator::Ascending { cursor, end } => {
+				let (cursor, end) = (cursor.clone(), end.clone());
+				let next = match &cursor {
+					Bound::Included(key) => self.storage_key_from(key),
+					Bound::Excluded(key) => self.ext.next_storage_key_raw(key),
+					Bound::Unbounded => self.storage_key_from(&[]),
+				};
+				match next.filter(|key| end.as_ref().map_or(true, |end| key < end)) {
+					Some(key) => {
+						let value = self.ext.get_storage_raw(&key).unwrap_or_default();
+						if let Some(StorageIterator::Ascending { cursor, .. }) =
+							self.iterators.get_mut(iterator_id as usize)
+						{
+							*cursor = Bound::Excluded(key.clone());
+						}
+						Some((key, value))
+					},
+					None => None,
+				}
+			},
+			StorageIterator::Descending(keys) => {
+				let mut keys = sp_std::mem::take(keys);
+				let mut item = None;
+				// Skip the keys which were removed after the iterator was created.
+				while let Some(key) = keys.pop() {
+					if let Some(value) = self.ext.get_storage_raw(&key) {
+						item = Some((key, value));
+						break
+					}
+				}
+				self.iterators[iterator_id as usize] = StorageIterator::Descending(keys);
+				item
+			},
+		};
+		let (key, value) = item.unwrap_or_default();
+		self.adjust_gas(charged, RuntimeCosts::DbNext((key.len() + value.len()) as u32));
+		Ok(encode_sections(&[key, value]))
+	}
+
+	/// Decode the human readable `address` into the canonical address of an account.
+	fn canonicalize_address(address: &[u8]) -> Result<Vec<u8>, &'static str> {
+		let address = sp_std::str::from_utf8(address).map_err(|_| "Input is not valid UTF-8")?;
+		ensure!(!address.is_empty(), "Input is empty");
+		<E::T as Config>::AddressCodec::decode(address).map(|account| account.encode())
+	}
+
+	/// Encode the canonical `address` of an account into its human readable form.
+	fn humanize_address(address: &[u8]) -> Result<Vec<u8>, &'static str> {
+		let account = <<E::T as frame_system::Config>::AccountId>::decode_all(&mut &address[..])
+			.map_err(|_| "Invalid canonical address length")?;
+		<E::T as Config>::AddressCodec::encode(&account).map(Into::into)
+	}
+
+	/// Signal the outcome of an address conversion to the contract.
+	///
+	/// Returns `0` on success or a pointer to a newly allocated region holding the error
+	/// message otherwise.
+	fn address_result(&mut self, result: Result<(), &'static str>) -> Result<u32, TrapReason> {
+		match result {
+			Ok(()) => Ok(0),
+			Err(message) => Ok(self.write_to_contract(message.as_bytes())?),
+		}
 	}
 
 	/// Write the given buffer to the designated location in the sandbox memory.
@@ -793,6 +1551,7 @@ where
 					gas,
 					callee,
 					value,
+					Vec::new(),
 					input_data,
 					flags.contains(CallFlags::ALLOW_REENTRY),
 				)
@@ -847,7 +1606,17 @@ where
 		let code_hash: CodeHash<<E as Ext>::T> = self.read_sandbox_memory_as(code_hash_ptr)?;
 		let input_data = self.read_sandbox_memory(input_data_ptr, input_data_len)?;
 		let salt = self.read_sandbox_memory(salt_ptr, salt_len)?;
-		let instantiate_outcome = self.ext.instantiate(gas, code_hash, value, input_data, &salt);
+		let instantiate_outcome = self.ext.instantiate(
+			gas,
+			code_hash,
+			value,
+			Vec::new(),
+			input_data,
+			&salt,
+			None,
+			Vec::new(),
+			AddressDerivation::Generator,
+		);
 		if let Ok((address, output)) = &instantiate_outcome {
 			if !output.flags.contains(ReturnFlags::REVERT) {
 				self.write_sandbox_output(
@@ -899,10 +1668,137 @@ const MAX_LENGTH_ED25519_MESSAGE: u32 = 128 * 1024;
 /// This is an arbitrary value, for performance / memory contraints. If you need to batch-verify a
 /// larger number of signatures, let us know.
 const MAX_COUNT_ED25519_BATCH: u32 = 256;
+/// Length of a message hash signed with ECDSA.
+const LENGTH_MESSAGE_HASH: u32 = 32;
+/// Length of a serialized ECDSA signature, without recovery id.
+const LENGTH_ECDSA_SIGNATURE: u32 = 64;
+/// Max length of a serialized ECDSA public key, which is the uncompressed form.
+const MAX_LENGTH_ECDSA_PUBKEY: u32 = 65;
+/// Length of a serialized Ed25519 public key.
+const LENGTH_ED25519_PUBKEY: u32 = 32;
+
+/// Error codes returned to the contract by the CosmWasm crypto functions.
+///
+/// Same values as `cosmwasm_crypto::CryptoError::code`.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[repr(u32)]
+enum CryptoError {
+	InvalidHashFormat = 3,
+	InvalidSignatureFormat = 4,
+	InvalidPubkeyFormat = 5,
+	InvalidRecoveryParam = 6,
+	BatchErr = 7,
+	GenericErr = 10,
+}
+
+/// Verify the secp256k1 ECDSA `signature` of `message_hash` against `public_key`, which can
+/// be given in compressed or uncompressed form.
+fn verify_secp256k1(
+	message_hash: &[u8],
+	signature: &[u8],
+	public_key: &[u8],
+) -> Result<bool, CryptoError> {
+	let message_hash: [u8; 32] =
+		message_hash.try_into().map_err(|_| CryptoError::InvalidHashFormat)?;
+	let signature: [u8; 64] =
+		signature.try_into().map_err(|_| CryptoError::InvalidSignatureFormat)?;
+	match public_key {
+		[0x02 | 0x03, ..] if public_key.len() == 33 => (),
+		[0x04, ..] if public_key.len() == 65 => (),
+		_ => return Err(CryptoError::InvalidPubkeyFormat),
+	}
+	// The signature comes without recovery id: it is valid if any of the candidates matches.
+	Ok((0..=1).any(|recovery_id| {
+		let mut recoverable = [0u8; 65];
+		recoverable[..64].copy_from_slice(&signature);
+		recoverable[64] = recovery_id;
+		if public_key.len() == 33 {
+			sp_io::crypto::secp256k1_ecdsa_recover_compressed(&recoverable, &message_hash)
+				.map_or(false, |recovered| recovered[..] == *public_key)
+		} else {
+			sp_io::crypto::secp256k1_ecdsa_recover(&recoverable, &message_hash)
+				.map_or(false, |recovered| recovered[..] == public_key[1..])
+		}
+	}))
+}
+
+/// Recover the uncompressed public key which produced the secp256k1 ECDSA `signature` of
+/// `message_hash`.
+fn recover_secp256k1_pubkey(
+	message_hash: &[u8],
+	signature: &[u8],
+	recovery_param: u32,
+) -> Result<[u8; 65], CryptoError> {
+	let message_hash: [u8; 32] =
+		message_hash.try_into().map_err(|_| CryptoError::InvalidHashFormat)?;
+	if signature.len() != LENGTH_ECDSA_SIGNATURE as usize {
+		return Err(CryptoError::InvalidSignatureFormat)
+	}
+	let recovery_id = match recovery_param {
+		0 | 1 => recovery_param as u8,
+		_ => return Err(CryptoError::InvalidRecoveryParam),
+	};
+	let mut recoverable = [0u8; 65];
+	recoverable[..64].copy_from_slice(signature);
+	recoverable[64] = recovery_id;
+	let recovered = sp_io::crypto::secp256k1_ecdsa_recover(&recoverable, &message_hash)
+		.map_err(|_| CryptoError::GenericErr)?;
+	let mut public_key = [0x04; 65];
+	public_key[1..].copy_from_slice(&recovered);
+	Ok(public_key)
+}
+
+/// Verify the Ed25519 `signature` of `message` against `public_key`.
+fn verify_ed25519(
+	message: &[u8],
+	signature: &[u8],
+	public_key: &[u8],
+) -> Result<bool, CryptoError> {
+	let signature = sp_core::ed25519::Signature::from_slice(signature)
+		.ok_or(CryptoError::InvalidSignatureFormat)?;
+	let public_key: [u8; 32] =
+		public_key.try_into().map_err(|_| CryptoError::InvalidPubkeyFormat)?;
+	Ok(sp_io::crypto::ed25519_verify(
+		&signature,
+		message,
+		&sp_core::ed25519::Public::from_raw(public_key),
+	))
+}
+
+/// Verify a batch of Ed25519 signatures.
+///
+/// A single message or public key is used for all the signatures. The batch is valid if all
+/// of its signatures are, which is the case of an empty batch.
+fn verify_ed25519_batch(
+	messages: &[&[u8]],
+	signatures: &[&[u8]],
+	public_keys: &[&[u8]],
+) -> Result<bool, CryptoError> {
+	let count = signatures.len();
+	let (messages, public_keys) = if messages.len() == count && public_keys.len() == count {
+		(messages.to_vec(), public_keys.to_vec())
+	} else if messages.len() == 1 && public_keys.len() == count {
+		(vec![messages[0]; count], public_keys.to_vec())
+	} else if public_keys.len() == 1 && messages.len() == count {
+		(messages.to_vec(), vec![public_keys[0]; count])
+	} else {
+		return Err(CryptoError::BatchErr)
+	};
+	let mut valid = true;
+	for ((message, signature), public_key) in messages.iter().zip(signatures).zip(public_keys) {
+		// Validate the format of all the items even if one signature is already invalid.
+		valid &= verify_ed25519(message, signature, public_key)?;
+	}
+	Ok(valid)
+}
 
 /// Max length for a debug message
 const MAX_LENGTH_DEBUG: u32 = 2 * MI;
 
+/// Max length for an abort message
+const MAX_LENGTH_ABORT: u32 = 2 * KI;
+
 // This is the API exposed to contracts.
 //
 // # Note
@@ -2142,72 +3038,264 @@ define_env!(Env, <E: Ext>,
 	},
 
 			// ============ COSMWASM ============
+			// Read the value stored under the key of the region pointed to by `key_ptr`.
+			//
+			// Returns a pointer to a newly allocated region holding the value or `0` if the
+			// key does not exist.
 			[env] db_read(ctx, key_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "DbRead");
-				Ok(0)
+				let charged = ctx.charge_gas(RuntimeCosts::DbRead(ctx.ext.max_value_size()))?;
+				let key = read_region(&ctx.memory, key_ptr, MAX_LENGTH_DB_KEY as usize)?;
+				if let Some(value) = ctx.ext.get_storage_raw(&key) {
+					ctx.adjust_gas(charged, RuntimeCosts::DbRead(value.len() as u32));
+					Ok(ctx.write_to_contract(&value)?)
+				} else {
+					ctx.adjust_gas(charged, RuntimeCosts::DbRead(0));
+					Ok(0)
+				}
 			},
-			[env] db_write(ctx, _key: u32, _value: u32) => {
-				log::debug!(target: "runtime::contracts", "DbWrite");
+
+			// Store the value of the region pointed to by `value_ptr` under the key of the
+			// region pointed to by `key_ptr`.
+			[env] db_write(ctx, key_ptr: u32, value_ptr: u32) => {
+				ensure!(!ctx.read_only, Error::<E::T>::StateChangeDenied);
+				let max_size = ctx.ext.max_value_size();
+				let charged = ctx.charge_gas(
+					RuntimeCosts::DbWrite { new_bytes: max_size, old_bytes: max_size }
+				)?;
+				let key = read_region(&ctx.memory, key_ptr, MAX_LENGTH_DB_KEY as usize)?;
+				let value = read_region(&ctx.memory, value_ptr, MAX_LENGTH_DB_VALUE as usize)?;
+				let value_len = value.len() as u32;
+				if value_len > max_size {
+					Err(Error::<E::T>::ValueTooLarge)?;
+				}
+				let write_outcome = ctx.ext.set_storage_raw(key, Some(value), false)?;
+				ctx.adjust_gas(
+					charged,
+					RuntimeCosts::DbWrite {
+						new_bytes: value_len,
+						old_bytes: write_outcome.old_len(),
+					},
+				);
 				Ok(())
 			},
-			[env] db_remove(ctx, _key: u32) => {
-				log::debug!(target: "runtime::contracts", "DbRemove");
+
+			// Remove the value stored under the key of the region pointed to by `key_ptr`.
+			[env] db_remove(ctx, key_ptr: u32) => {
+				ensure!(!ctx.read_only, Error::<E::T>::StateChangeDenied);
+				let charged = ctx.charge_gas(RuntimeCosts::DbRemove(ctx.ext.max_value_size()))?;
+				let key = read_region(&ctx.memory, key_ptr, MAX_LENGTH_DB_KEY as usize)?;
+				let outcome = ctx.ext.set_storage_raw(key, None, false)?;
+				ctx.adjust_gas(charged, RuntimeCosts::DbRemove(outcome.old_len()));
 				Ok(())
 			},
 
-			[env] db_scan(ctx, _statr_ptr: u32, _end_ptr: u32, _order: i32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "DbScan");
-				Ok(0)
+			// Create an iterator over the keys within the regions pointed to by `start_ptr`
+			// (inclusive) and `end_ptr` (exclusive), a null pointer leaving the bound open.
+			//
+			// `order` is `1` for ascending and `2` for descending keys.
+			//
+			// Returns the identifier of the iterator.
+			[env] db_scan(ctx, start_ptr: u32, end_ptr: u32, order: i32) -> u32 => {
+				ctx.db_scan(start_ptr, end_ptr, order)
 			},
-			[env] db_next(ctx, _iterator_id: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "DbNext");
-				Ok(0)
+
+			// Advance the iterator identified by `iterator_id`.
+			//
+			// Returns a pointer to a newly allocated region holding the key and the value
+			// encoded as sections. Both sections are empty once the iterator is exhausted.
+			[env] db_next(ctx, iterator_id: u32) -> u32 => {
+				let item = ctx.db_next(iterator_id)?;
+				Ok(ctx.write_to_contract(&item)?)
 			},
 
+			// Check that the region pointed to by `source_ptr` holds a valid and normalized
+			// human readable address.
+			//
+			// Returns `0` on success or a pointer to a region holding the error message.
 			[env] addr_validate(ctx, source_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "AddrValidate");
-				Ok(0)
+				ctx.charge_gas(RuntimeCosts::AddrValidate)?;
+				let source = read_region(&ctx.memory, source_ptr, MAX_LENGTH_HUMAN_ADDRESS as usize)?;
+				let result = Runtime::<E>::canonicalize_address(&source)
+					.and_then(|canonical| Runtime::<E>::humanize_address(&canonical))
+					.and_then(|normalized| {
+						ensure!(normalized == source, "Address is not normalized");
+						Ok(())
+					});
+				ctx.address_result(result)
 			},
+
+			// Convert the human readable address of the region pointed to by `source_ptr` into
+			// its canonical form and write it into the region pointed to by `destination_ptr`.
+			//
+			// Returns `0` on success or a pointer to a region holding the error message.
 			[env] addr_canonicalize(ctx, source_ptr: u32, destination_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "AddrCanonicalize");
-				Ok(0)
+				ctx.charge_gas(RuntimeCosts::AddrCanonicalize)?;
+				let source = read_region(&ctx.memory, source_ptr, MAX_LENGTH_HUMAN_ADDRESS as usize)?;
+				match Runtime::<E>::canonicalize_address(&source) {
+					Ok(canonical) => {
+						write_region(&ctx.memory, destination_ptr, &canonical)?;
+						ctx.address_result(Ok(()))
+					},
+					Err(message) => ctx.address_result(Err(message)),
+				}
 			},
+
+			// Convert the canonical address of the region pointed to by `source_ptr` into
+			// its human readable form and write it into the region pointed to by
+			// `destination_ptr`.
+			//
+			// Returns `0` on success or a pointer to a region holding the error message.
 			[env] addr_humanize(ctx, source_ptr: u32, destination_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "AddrHumanize");
-				Ok(0)
+				ctx.charge_gas(RuntimeCosts::AddrHumanize)?;
+				let source =
+					read_region(&ctx.memory, source_ptr, MAX_LENGTH_CANONICAL_ADDRESS as usize)?;
+				match Runtime::<E>::humanize_address(&source) {
+					Ok(human) => {
+						write_region(&ctx.memory, destination_ptr, &human)?;
+						ctx.address_result(Ok(()))
+					},
+					Err(message) => ctx.address_result(Err(message)),
+				}
 			},
 
+			// Verify the secp256k1 ECDSA signature of a message hash.
+			//
+			// The public key can be given in compressed (33 bytes) or uncompressed (65 bytes)
+			// form.
+			//
+			// Returns `0` if the signature is valid, `1` if it is not or an error code if one of
+			// the inputs is malformed.
 			[env] secp256k1_verify(ctx, message_hash_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "Verify1");
-				Ok(0)
+				ctx.charge_gas(RuntimeCosts::Secp256k1Verify)?;
+				let message_hash =
+					read_region(&ctx.memory, message_hash_ptr, LENGTH_MESSAGE_HASH as usize)?;
+				let signature =
+					read_region(&ctx.memory, signature_ptr, LENGTH_ECDSA_SIGNATURE as usize)?;
+				let public_key =
+					read_region(&ctx.memory, public_key_ptr, MAX_LENGTH_ECDSA_PUBKEY as usize)?;
+				Ok(match verify_secp256k1(&message_hash, &signature, &public_key) {
+					Ok(valid) => !valid as u32,
+					Err(code) => code as u32,
+				})
 			},
+
+			// Recover the public key which produced the secp256k1 ECDSA signature of a message
+			// hash.
+			//
+			// Returns a pointer to a region holding the uncompressed public key in the low
+			// half or an error code in the high half.
 			[env] secp256k1_recover_pubkey(ctx, message_hash_ptr: u32, signature_ptr: u32, recovery_param: u32) -> u64 => {
-				log::debug!(target: "runtime::contracts", "Pubkey");
-				Ok(0)
+				ctx.charge_gas(RuntimeCosts::Secp256k1RecoverPubkey)?;
+				let message_hash =
+					read_region(&ctx.memory, message_hash_ptr, LENGTH_MESSAGE_HASH as usize)?;
+				let signature =
+					read_region(&ctx.memory, signature_ptr, LENGTH_ECDSA_SIGNATURE as usize)?;
+				match recover_secp256k1_pubkey(&message_hash, &signature, recovery_param) {
+					Ok(public_key) => Ok(ctx.write_to_contract(&public_key)? as u64),
+					Err(code) => Ok((code as u64) << 32),
+				}
 			},
+
+			// Verify the Ed25519 signature of a message.
+			//
+			// Returns `0` if the signature is valid, `1` if it is not or an error code if one of
+			// the inputs is malformed.
 			[env] ed25519_verify(ctx, message_ptr: u32, signature_ptr: u32, public_key_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "Verify");
-				Ok(0)
+				let charged =
+					ctx.charge_gas(RuntimeCosts::Ed25519Verify(MAX_LENGTH_ED25519_MESSAGE))?;
+				let message =
+					read_region(&ctx.memory, message_ptr, MAX_LENGTH_ED25519_MESSAGE as usize)?;
+				ctx.adjust_gas(charged, RuntimeCosts::Ed25519Verify(message.len() as u32));
+				let signature =
+					read_region(&ctx.memory, signature_ptr, MAX_LENGTH_ED25519_SIGNATURE as usize)?;
+				let public_key =
+					read_region(&ctx.memory, public_key_ptr, LENGTH_ED25519_PUBKEY as usize)?;
+				Ok(match verify_ed25519(&message, &signature, &public_key) {
+					Ok(valid) => !valid as u32,
+					Err(code) => code as u32,
+				})
 			},
+
+			// Verify a batch of Ed25519 signatures.
+			//
+			// The messages, signatures and public keys are given as sections. A single message
+			// or public key is used to verify all the signatures.
+			//
+			// Returns `0` if all the signatures are valid, `1` if any is not or an error code if
+			// one of the inputs is malformed.
 			[env] ed25519_batch_verify(ctx, messages_ptr: u32, signatures_ptr: u32, public_keys_ptr: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "BatchVerify");
-				Ok(0)
+				let charged = ctx.charge_gas(RuntimeCosts::Ed25519BatchVerify {
+					count: MAX_COUNT_ED25519_BATCH,
+					len: MAX_LENGTH_ED25519_MESSAGE * MAX_COUNT_ED25519_BATCH,
+				})?;
+				let messages = read_region(
+					&ctx.memory,
+					messages_ptr,
+					((MAX_LENGTH_ED25519_MESSAGE + 4) * MAX_COUNT_ED25519_BATCH) as usize,
+				)?;
+				let signatures = read_region(
+					&ctx.memory,
+					signatures_ptr,
+					((MAX_LENGTH_ED25519_SIGNATURE + 4) * MAX_COUNT_ED25519_BATCH) as usize,
+				)?;
+				let public_keys = read_region(
+					&ctx.memory,
+					public_keys_ptr,
+					((LENGTH_ED25519_PUBKEY + 4) * MAX_COUNT_ED25519_BATCH) as usize,
+				)?;
+				let messages = decode_sections(&messages)?;
+				let signatures = decode_sections(&signatures)?;
+				let public_keys = decode_sections(&public_keys)?;
+				let len = if messages.len() == 1 {
+					messages[0].len().saturating_mul(signatures.len())
+				} else {
+					messages.iter().map(|message| message.len()).sum()
+				};
+				// The up-front charge only covers batches within the limits.
+				if signatures.len() > MAX_COUNT_ED25519_BATCH as usize ||
+					len > (MAX_LENGTH_ED25519_MESSAGE * MAX_COUNT_ED25519_BATCH) as usize
+				{
+					return Ok(CryptoError::BatchErr as u32)
+				}
+				ctx.adjust_gas(charged, RuntimeCosts::Ed25519BatchVerify {
+					count: signatures.len() as u32,
+					len: len as u32,
+				});
+				Ok(match verify_ed25519_batch(&messages, &signatures, &public_keys) {
+					Ok(valid) => !valid as u32,
+					Err(code) => code as u32,
+				})
 			},
 
+			// Append the UTF-8 message of the region pointed to by `source_ptr` to the debug
+			// buffer, followed by a newline. Invalid UTF-8 sequences are replaced.
+			//
+			// The message is only read when debug message recording is enabled.
 			[env] debug(ctx, source_ptr: u32) => {
-				log::debug!(target: "runtime::contracts", "Debug");
-				// ctx.charge_gas(RuntimeCosts::DebugMessage)?;
-				// if ctx.ext.append_debug_buffer("") {
-				// 	let data = ctx.read_sandbox_memory(source_ptr, MAX_LENGTH_DEBUG)?;
-				// 	let msg = core::str::from_utf8(&data)
-				// 		.map_err(|_| <Error<E::T>>::DebugMessageInvalidUTF8)?;
-				// 	ctx.ext.append_debug_buffer(msg);
-				// }
+				ctx.charge_gas(RuntimeCosts::DebugMessage)?;
+				if ctx.ext.append_debug_buffer("") {
+					let data = read_region(&ctx.memory, source_ptr, MAX_LENGTH_DEBUG as usize)?;
+					ctx.ext.append_debug_buffer(&String::from_utf8_lossy(&data));
+					ctx.ext.append_debug_buffer("\n");
+				}
 				Ok(())
 			},
 
+			// Abort the execution with the UTF-8 message of the region pointed to by
+			// `source_ptr`. Contracts call this when they panic.
+			//
+			// The message is only read when debug message recording is enabled.
+			[env] abort(ctx, source_ptr: u32) => {
+				ctx.charge_gas(RuntimeCosts::DebugMessage)?;
+				if ctx.ext.append_debug_buffer("") {
+					let data = read_region(&ctx.memory, source_ptr, MAX_LENGTH_ABORT as usize)?;
+					ctx.ext.append_debug_buffer(&String::from_utf8_lossy(&data));
+					ctx.ext.append_debug_buffer("\n");
+				}
+				Err(Error::<E::T>::ContractTrapped.into())
+			},
+
 			[env] query_chain(ctx, request: u32) -> u32 => {
-				log::debug!(target: "runtime::contracts", "QueryChain");
-				Ok(0)
+				ctx.query_chain(request)
 			},
 );
@@ -54,6 +54,20 @@ pub trait WeightInfo {
 	fn call() -> Weight;
 	fn upload_code(c: u32, ) -> Weight;
 	fn remove_code() -> Weight;
+	fn migrate() -> Weight;
+	fn update_admin() -> Weight;
+	fn clear_admin() -> Weight;
+	fn ibc_open_channel() -> Weight;
+	fn ibc_relay_packet() -> Weight;
+	fn ibc_close_channel() -> Weight;
+	fn pause_contract() -> Weight;
+	fn unpause_contract() -> Weight;
+	fn pause_code() -> Weight;
+	fn unpause_code() -> Weight;
+	fn freeze_contracts() -> Weight;
+	fn unfreeze_contracts() -> Weight;
+	fn set_code_metadata(c: u32, ) -> Weight;
+	fn call_with_assets(a: u32, ) -> Weight;
 	fn seal_caller(r: u32, ) -> Weight;
 	fn seal_is_contract(r: u32, ) -> Weight;
 	fn seal_code_hash(r: u32, ) -> Weight;
@@ -105,6 +119,25 @@ pub trait WeightInfo {
 	fn seal_ecdsa_recover(r: u32, ) -> Weight;
 	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight;
 	fn seal_set_code_hash(r: u32, ) -> Weight;
+	fn db_scan(r: u32, ) -> Weight;
+	fn db_next(r: u32, ) -> Weight;
+	fn db_next_per_kb(n: u32, ) -> Weight;
+	fn addr_validate(r: u32, ) -> Weight;
+	fn addr_canonicalize(r: u32, ) -> Weight;
+	fn addr_humanize(r: u32, ) -> Weight;
+	fn secp256k1_verify(r: u32, ) -> Weight;
+	fn secp256k1_recover_pubkey(r: u32, ) -> Weight;
+	fn ed25519_verify(r: u32, ) -> Weight;
+	fn ed25519_verify_per_kb(n: u32, ) -> Weight;
+	fn ed25519_batch_verify(r: u32, ) -> Weight;
+	fn query_chain(r: u32, ) -> Weight;
+	fn db_read(r: u32, ) -> Weight;
+	fn db_read_per_kb(n: u32, ) -> Weight;
+	fn db_write(r: u32, ) -> Weight;
+	fn db_write_per_new_kb(n: u32, ) -> Weight;
+	fn db_write_per_old_kb(n: u32, ) -> Weight;
+	fn db_remove(r: u32, ) -> Weight;
+	fn db_remove_per_kb(n: u32, ) -> Weight;
 	fn instr_i64const(r: u32, ) -> Weight;
 	fn instr_i64load(r: u32, ) -> Weight;
 	fn instr_i64store(r: u32, ) -> Weight;
@@ -259,6 +292,143 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeIdToHash (r:1 w:0)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: Contracts OwnerInfoOf (r:2 w:2)
+	// Storage: System EventTopics (r:2 w:2)
+	fn migrate() -> Weight {
+		(326_920_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(12 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	fn update_admin() -> Weight {
+		(18_979_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	fn clear_admin() -> Weight {
+		(10_519_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts NextIbcChannelId (r:1 w:1)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	// Storage: Contracts IbcChannels (r:0 w:2)
+	fn ibc_open_channel() -> Weight {
+		(580_203_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(13 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts IbcChannels (r:2 w:1)
+	// Storage: Contracts IbcPackets (r:1 w:1)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	fn ibc_relay_packet() -> Weight {
+		(329_791_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts IbcChannels (r:2 w:2)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	fn ibc_close_channel() -> Weight {
+		(335_558_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(14 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:0 w:1)
+	fn pause_contract() -> Weight {
+		(16_326_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts PausedContracts (r:1 w:1)
+	fn unpause_contract() -> Weight {
+		(15_645_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts OwnerInfoOf (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:0 w:1)
+	fn pause_code() -> Weight {
+		(13_868_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts PausedCodes (r:1 w:1)
+	fn unpause_code() -> Weight {
+		(13_176_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractsFrozen (r:1 w:1)
+	fn freeze_contracts() -> Weight {
+		(10_720_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractsFrozen (r:1 w:1)
+	fn unfreeze_contracts() -> Weight {
+		(11_641_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts OwnerInfoOf (r:1 w:1)
+	// Storage: Contracts CodeMetadata (r:1 w:1)
+	fn set_code_metadata(c: u32, ) -> Weight {
+		(19_832_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((1_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	fn call_with_assets(a: u32, ) -> Weight {
+		(148_462_000 as Weight)
+			// Standard Error: 729_000
+			.saturating_add((24_067_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+	}
 	// Storage: System Account (r:1 w:0)
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
@@ -825,6 +995,198 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((99 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().writes((99 as Weight).saturating_mul(r as Weight)))
 	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn db_scan(r: u32, ) -> Weight {
+		(118_321_000 as Weight)
+			// Standard Error: 521_000
+			.saturating_add((41_276_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_next(r: u32, ) -> Weight {
+		(121_904_000 as Weight)
+			// Standard Error: 714_000
+			.saturating_add((337_512_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((200 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_next_per_kb(n: u32, ) -> Weight {
+		(571_386_000 as Weight)
+			// Standard Error: 411_000
+			.saturating_add((64_107_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(204 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_validate(r: u32, ) -> Weight {
+		(126_705_000 as Weight)
+			// Standard Error: 1_121_000
+			.saturating_add((96_824_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_canonicalize(r: u32, ) -> Weight {
+		(124_117_000 as Weight)
+			// Standard Error: 986_000
+			.saturating_add((52_390_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_humanize(r: u32, ) -> Weight {
+		(123_649_000 as Weight)
+			// Standard Error: 902_000
+			.saturating_add((46_018_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn secp256k1_verify(r: u32, ) -> Weight {
+		(136_298_000 as Weight)
+			// Standard Error: 8_430_000
+			.saturating_add((7_650_128_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn secp256k1_recover_pubkey(r: u32, ) -> Weight {
+		(133_871_000 as Weight)
+			// Standard Error: 4_417_000
+			.saturating_add((3_849_311_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_verify(r: u32, ) -> Weight {
+		(131_566_000 as Weight)
+			// Standard Error: 3_218_000
+			.saturating_add((2_902_744_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_verify_per_kb(n: u32, ) -> Weight {
+		(3_017_203_000 as Weight)
+			// Standard Error: 41_000
+			.saturating_add((470_912_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_batch_verify(r: u32, ) -> Weight {
+		(129_402_000 as Weight)
+			// Standard Error: 1_944_000
+			.saturating_add((174_337_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn query_chain(r: u32, ) -> Weight {
+		(134_512_000 as Weight)
+			// Standard Error: 3_104_000
+			.saturating_add((1_208_647_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_read(r: u32, ) -> Weight {
+		(118_954_000 as Weight)
+			// Standard Error: 702_000
+			.saturating_add((341_187_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_read_per_kb(n: u32, ) -> Weight {
+		(583_226_000 as Weight)
+			// Standard Error: 417_000
+			.saturating_add((66_051_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(104 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write(r: u32, ) -> Weight {
+		(47_310_000 as Weight)
+			// Standard Error: 1_142_000
+			.saturating_add((418_936_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write_per_new_kb(n: u32, ) -> Weight {
+		(619_842_000 as Weight)
+			// Standard Error: 263_000
+			.saturating_add((30_127_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(105 as Weight))
+			.saturating_add(T::DbWeight::get().writes(103 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write_per_old_kb(n: u32, ) -> Weight {
+		(634_517_000 as Weight)
+			// Standard Error: 316_000
+			.saturating_add((11_604_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(105 as Weight))
+			.saturating_add(T::DbWeight::get().writes(103 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_remove(r: u32, ) -> Weight {
+		(92_765_000 as Weight)
+			// Standard Error: 874_000
+			.saturating_add((393_208_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_remove_per_kb(n: u32, ) -> Weight {
+		(615_029_000 as Weight)
+			// Standard Error: 270_000
+			.saturating_add((10_519_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(105 as Weight))
+			.saturating_add(T::DbWeight::get().writes(103 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
 	fn instr_i64const(r: u32, ) -> Weight {
 		(74_516_000 as Weight)
 			// Standard Error: 1_000
@@ -1182,6 +1544,143 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
 	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeIdToHash (r:1 w:0)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: Contracts OwnerInfoOf (r:2 w:2)
+	// Storage: System EventTopics (r:2 w:2)
+	fn migrate() -> Weight {
+		(326_920_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(12 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	fn update_admin() -> Weight {
+		(18_979_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	fn clear_admin() -> Weight {
+		(10_519_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts NextIbcChannelId (r:1 w:1)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	// Storage: Contracts IbcChannels (r:0 w:2)
+	fn ibc_open_channel() -> Weight {
+		(580_203_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(13 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts IbcChannels (r:2 w:1)
+	// Storage: Contracts IbcPackets (r:1 w:1)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	fn ibc_relay_packet() -> Weight {
+		(329_791_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts IbcChannels (r:2 w:2)
+	// Storage: Contracts ContractInfoOf (r:2 w:2)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:2 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System EventTopics (r:3 w:3)
+	fn ibc_close_channel() -> Weight {
+		(335_558_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(14 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:0 w:1)
+	fn pause_contract() -> Weight {
+		(16_326_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts PausedContracts (r:1 w:1)
+	fn unpause_contract() -> Weight {
+		(15_645_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts OwnerInfoOf (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:0 w:1)
+	fn pause_code() -> Weight {
+		(13_868_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts PausedCodes (r:1 w:1)
+	fn unpause_code() -> Weight {
+		(13_176_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractsFrozen (r:1 w:1)
+	fn freeze_contracts() -> Weight {
+		(10_720_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts ContractsFrozen (r:1 w:1)
+	fn unfreeze_contracts() -> Weight {
+		(11_641_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Contracts OwnerInfoOf (r:1 w:1)
+	// Storage: Contracts CodeMetadata (r:1 w:1)
+	fn set_code_metadata(c: u32, ) -> Weight {
+		(19_832_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((1_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+	// Storage: Contracts ContractsFrozen (r:1 w:0)
+	// Storage: Contracts PausedContracts (r:1 w:0)
+	// Storage: Contracts PausedCodes (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Assets Asset (r:1 w:1)
+	// Storage: Assets Account (r:2 w:2)
+	fn call_with_assets(a: u32, ) -> Weight {
+		(148_462_000 as Weight)
+			// Standard Error: 729_000
+			.saturating_add((24_067_000 as Weight).saturating_mul(a as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(a as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+	}
 	// Storage: System Account (r:1 w:0)
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
@@ -1748,6 +2247,198 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((99 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((99 as Weight).saturating_mul(r as Weight)))
 	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn db_scan(r: u32, ) -> Weight {
+		(118_321_000 as Weight)
+			// Standard Error: 521_000
+			.saturating_add((41_276_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_next(r: u32, ) -> Weight {
+		(121_904_000 as Weight)
+			// Standard Error: 714_000
+			.saturating_add((337_512_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((200 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_next_per_kb(n: u32, ) -> Weight {
+		(571_386_000 as Weight)
+			// Standard Error: 411_000
+			.saturating_add((64_107_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(204 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_validate(r: u32, ) -> Weight {
+		(126_705_000 as Weight)
+			// Standard Error: 1_121_000
+			.saturating_add((96_824_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_canonicalize(r: u32, ) -> Weight {
+		(124_117_000 as Weight)
+			// Standard Error: 986_000
+			.saturating_add((52_390_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn addr_humanize(r: u32, ) -> Weight {
+		(123_649_000 as Weight)
+			// Standard Error: 902_000
+			.saturating_add((46_018_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn secp256k1_verify(r: u32, ) -> Weight {
+		(136_298_000 as Weight)
+			// Standard Error: 8_430_000
+			.saturating_add((7_650_128_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn secp256k1_recover_pubkey(r: u32, ) -> Weight {
+		(133_871_000 as Weight)
+			// Standard Error: 4_417_000
+			.saturating_add((3_849_311_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_verify(r: u32, ) -> Weight {
+		(131_566_000 as Weight)
+			// Standard Error: 3_218_000
+			.saturating_add((2_902_744_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_verify_per_kb(n: u32, ) -> Weight {
+		(3_017_203_000 as Weight)
+			// Standard Error: 41_000
+			.saturating_add((470_912_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn ed25519_batch_verify(r: u32, ) -> Weight {
+		(129_402_000 as Weight)
+			// Standard Error: 1_944_000
+			.saturating_add((174_337_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn query_chain(r: u32, ) -> Weight {
+		(134_512_000 as Weight)
+			// Standard Error: 3_104_000
+			.saturating_add((1_208_647_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_read(r: u32, ) -> Weight {
+		(118_954_000 as Weight)
+			// Standard Error: 702_000
+			.saturating_add((341_187_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_read_per_kb(n: u32, ) -> Weight {
+		(583_226_000 as Weight)
+			// Standard Error: 417_000
+			.saturating_add((66_051_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(104 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write(r: u32, ) -> Weight {
+		(47_310_000 as Weight)
+			// Standard Error: 1_142_000
+			.saturating_add((418_936_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write_per_new_kb(n: u32, ) -> Weight {
+		(619_842_000 as Weight)
+			// Standard Error: 263_000
+			.saturating_add((30_127_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(105 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(103 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_write_per_old_kb(n: u32, ) -> Weight {
+		(634_517_000 as Weight)
+			// Standard Error: 316_000
+			.saturating_add((11_604_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(105 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(103 as Weight))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_remove(r: u32, ) -> Weight {
+		(92_765_000 as Weight)
+			// Standard Error: 874_000
+			.saturating_add((393_208_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(r as Weight)))
+	}
+	// Storage: Skipped Metadata (r:0 w:0)
+	fn db_remove_per_kb(n: u32, ) -> Weight {
+		(615_029_000 as Weight)
+			// Standard Error: 270_000
+			.saturating_add((10_519_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(105 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(103 as Weight))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
 	fn instr_i64const(r: u32, ) -> Weight {
 		(74_516_000 as Weight)
 			// Standard Error: 1_000
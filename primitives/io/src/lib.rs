@@ -1575,6 +1575,20 @@ pub trait Sandbox {
 			.get_global_val(instance_idx, name)
 			.expect("Failed to get global from sandbox")
 	}
+
+	/// Get the memory exported under the given `name` by the sandbox instance `instance_idx`.
+	///
+	/// Returns the index of a new memory handle referring to it, or `None` when the instance
+	/// doesn't export such a memory.
+	///
+	/// This host function is newer than the rest of the interface: nodes need to be upgraded
+	/// before a runtime calling it is enacted. The host executor of `sp-sandbox` only calls it
+	/// with its `instance-memory` feature.
+	fn instance_memory(&mut self, instance_idx: u32, name: &str) -> Option<u32> {
+		self.sandbox()
+			.instance_memory(instance_idx, name)
+			.expect("Failed to get memory export from sandbox")
+	}
 }
 
 /// Wasm host functions for managing tasks.
@@ -37,39 +37,6 @@ pub struct Memory {
 	pub memref: MemoryRef,
 }
 
-impl Memory {
-	pub fn typed_get<T>(&self, ptr: u32) -> Result<T, Error> {
-		if core::mem::size_of::<T>() == 0 {
-			Err(Error::OutOfBounds)
-		} else {
-			let mut t: T = unsafe { core::mem::zeroed() };
-			let b = unsafe {
-				core::slice::from_raw_parts_mut(
-					&mut t as *mut T as *mut u8,
-					core::mem::size_of::<T>(),
-				)
-			};
-			<Self as super::SandboxMemory>::get(self, ptr, b)?;
-			Ok(t)
-		}
-	}
-
-	pub fn typed_set<T>(&self, ptr: u32, t: &T) -> Result<(), Error> {
-		if core::mem::size_of::<T>() == 0 {
-			Err(Error::OutOfBounds)
-		} else {
-			let b = unsafe {
-				core::slice::from_raw_parts(
-					t as *const T as *const u8,
-					core::mem::size_of::<T>(),
-				)
-			};
-			<Self as super::SandboxMemory>::set(self, ptr, b)?;
-			Ok(())
-		}
-	}
-}
-
 impl super::SandboxMemory for Memory {
 	fn new(initial: u32, maximum: Option<u32>) -> Result<Memory, Error> {
 		Ok(Memory {
@@ -272,6 +239,18 @@ pub struct Instance<T> {
 	_marker: PhantomData<T>,
 }
 
+/// Cloning an instance is cheap: the clone refers to the same module instance and
+/// thereby shares its memory and globals with the original.
+impl<T> Clone for Instance<T> {
+	fn clone(&self) -> Self {
+		Instance {
+			instance: self.instance.clone(),
+			defined_host_functions: self.defined_host_functions.clone(),
+			_marker: PhantomData::<T>,
+		}
+	}
+}
+
 impl<T> super::SandboxInstance<T> for Instance<T> {
 	type Memory = Memory;
 	type EnvironmentBuilder = EnvironmentDefinitionBuilder<T>;
@@ -320,8 +299,10 @@ impl<T> super::SandboxInstance<T> for Instance<T> {
 		Some(to_interface(global))
 	}
 
-	fn get_export(&self, name: &str) -> Option<wasmi::ExternVal> {
-		self.instance.export_by_name(name)
+	fn get_memory(&self, name: &str) -> Option<Memory> {
+		let memref = self.instance.export_by_name(name)?.as_memory()?.clone();
+
+		Some(Memory { memref })
 	}
 }
 
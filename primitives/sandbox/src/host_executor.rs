@@ -155,13 +155,37 @@ impl<T> super::SandboxEnvironmentBuilder<T, Memory> for EnvironmentDefinitionBui
 	}
 }
 
+struct InstanceHandle {
+	instance_idx: u32,
+}
+
+impl Drop for InstanceHandle {
+	fn drop(&mut self) {
+		sandbox::instance_teardown(self.instance_idx);
+	}
+}
+
 /// Sandboxed instance of a WASM module.
 pub struct Instance<T> {
-	instance_idx: u32,
+	// Handle to the instance is wrapped to add reference-counting semantics
+	// to `Instance`.
+	handle: Rc<InstanceHandle>,
 	_retained_memories: Vec<Memory>,
 	_marker: marker::PhantomData<T>,
 }
 
+/// Cloning an instance is cheap: the clone refers to the same module instance and
+/// thereby shares its memory and globals with the original.
+impl<T> Clone for Instance<T> {
+	fn clone(&self) -> Self {
+		Instance {
+			handle: self.handle.clone(),
+			_retained_memories: self._retained_memories.clone(),
+			_marker: marker::PhantomData::<T>,
+		}
+	}
+}
+
 /// The primary responsibility of this thunk is to deserialize arguments and
 /// call the original function, specified by the index.
 extern "C" fn dispatch_thunk<T>(
@@ -232,7 +256,7 @@ impl<T> super::SandboxInstance<T> for Instance<T> {
 		// We need to retain memories to keep them alive while the Instance is alive.
 		let retained_memories = env_def_builder.retained_memories.clone();
 		Ok(Instance {
-			instance_idx,
+			handle: Rc::new(InstanceHandle { instance_idx }),
 			_retained_memories: retained_memories,
 			_marker: marker::PhantomData::<T>,
 		})
@@ -243,7 +267,7 @@ impl<T> super::SandboxInstance<T> for Instance<T> {
 		let mut return_val = vec![0u8; ReturnValue::ENCODED_MAX_SIZE];
 
 		let result = sandbox::invoke(
-			self.instance_idx,
+			self.handle.instance_idx,
 			name,
 			&serialized_args,
 			return_val.as_mut_ptr() as _,
@@ -263,16 +287,20 @@ impl<T> super::SandboxInstance<T> for Instance<T> {
 	}
 
 	fn get_global_val(&self, name: &str) -> Option<Value> {
-		sandbox::get_global_val(self.instance_idx, name)
+		sandbox::get_global_val(self.handle.instance_idx, name)
 	}
 
-	fn get_export(&self, name: &str) -> Option<wasmi::ExternVal> {
-		None
-	}
-}
+	fn get_memory(&self, name: &str) -> Option<Memory> {
+		#[cfg(feature = "instance-memory")]
+		{
+			let memory_idx = sandbox::instance_memory(self.handle.instance_idx, name)?;
 
-impl<T> Drop for Instance<T> {
-	fn drop(&mut self) {
-		sandbox::instance_teardown(self.instance_idx);
+			Some(Memory { handle: Rc::new(MemoryHandle { memory_idx }) })
+		}
+		#[cfg(not(feature = "instance-memory"))]
+		{
+			let _ = name;
+			None
+		}
 	}
 }
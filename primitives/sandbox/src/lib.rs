@@ -49,12 +49,17 @@ use sp_core::RuntimeDebug;
 use sp_std::prelude::*;
 
 pub use sp_wasm_interface::{ReturnValue, Value};
-use wasmi::ExternVal;
 
-#[cfg(not(all(feature = "wasmer-sandbox", not(feature = "std"))))]
+#[cfg(not(all(
+	any(feature = "wasmer-sandbox", feature = "wasmtime-sandbox"),
+	not(feature = "std")
+)))]
 pub use self::embedded_executor as default_executor;
 pub use self::env::HostError;
-#[cfg(all(feature = "wasmer-sandbox", not(feature = "std")))]
+#[cfg(all(
+	any(feature = "wasmer-sandbox", feature = "wasmtime-sandbox"),
+	not(feature = "std")
+))]
 pub use self::host_executor as default_executor;
 
 /// The target used for logging.
@@ -114,6 +119,41 @@ pub trait SandboxMemory: Sized + Clone {
 	///
 	/// Returns `Err` if the range is out-of-bounds.
 	fn set(&self, ptr: u32, value: &[u8]) -> Result<(), Error>;
+
+	/// Read a value of type `T` at the address `ptr`, reinterpreting its bytes.
+	///
+	/// `T` must be valid for any bit pattern, such as a `#[repr(C)]` struct of integers.
+	///
+	/// Returns `Err` if the range is out-of-bounds or `T` is zero-sized.
+	fn typed_get<T>(&self, ptr: u32) -> Result<T, Error> {
+		if core::mem::size_of::<T>() == 0 {
+			Err(Error::OutOfBounds)
+		} else {
+			let mut t: T = unsafe { core::mem::zeroed() };
+			let b = unsafe {
+				core::slice::from_raw_parts_mut(
+					&mut t as *mut T as *mut u8,
+					core::mem::size_of::<T>(),
+				)
+			};
+			self.get(ptr, b)?;
+			Ok(t)
+		}
+	}
+
+	/// Write the bytes of the value `t` at the address `ptr`.
+	///
+	/// Returns `Err` if the range is out-of-bounds or `T` is zero-sized.
+	fn typed_set<T>(&self, ptr: u32, t: &T) -> Result<(), Error> {
+		if core::mem::size_of::<T>() == 0 {
+			Err(Error::OutOfBounds)
+		} else {
+			let b = unsafe {
+				core::slice::from_raw_parts(t as *const T as *const u8, core::mem::size_of::<T>())
+			};
+			self.set(ptr, b)
+		}
+	}
 }
 
 /// Struct that can be used for defining an environment for a sandboxed module.
@@ -189,6 +229,8 @@ pub trait SandboxInstance<State>: Sized {
 	/// Returns `Some(_)` if the global could be found.
 	fn get_global_val(&self, name: &str) -> Option<Value>;
 
-	/// Get export
-	fn get_export(&self, name: &str) -> Option<ExternVal>;
+	/// Get the linear memory exported under the given `name`.
+	///
+	/// Returns `Some(_)` if the memory could be found.
+	fn get_memory(&self, name: &str) -> Option<Self::Memory>;
 }
@@ -382,6 +382,12 @@ pub trait Sandbox {
 	///
 	/// Returns `Some(_)` when the requested global variable could be found.
 	fn get_global_val(&self, instance_idx: u32, name: &str) -> Result<Option<Value>>;
+
+	/// Get the memory exported under the given `name` by the sandbox instance `instance_idx`.
+	///
+	/// Returns the index of a new memory handle referring to it, or `None` when the instance
+	/// doesn't export such a memory.
+	fn instance_memory(&mut self, instance_idx: u32, name: &str) -> Result<Option<MemoryId>>;
 }
 
 if_wasmtime_is_enabled! {